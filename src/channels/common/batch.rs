@@ -0,0 +1,97 @@
+//! Pipelined batch delivery for channel operations.
+//!
+//! Messages to different recipients are sent concurrently, while messages to
+//! the same recipient stay sequential so they arrive in order.
+
+use std::collections::HashMap;
+use std::future::Future;
+use futures::future::join_all;
+use crate::error::ChannelError;
+use crate::types::{OutgoingMessage, ProofCarryingAction};
+
+/// Send a batch of messages, pipelining across recipients.
+///
+/// Results are returned in the same order as `messages`. Rate limiting is
+/// left to `send`, so it is applied per message.
+pub async fn send_pipelined<F, Fut>(
+    messages: Vec<OutgoingMessage>,
+    send: F,
+) -> Vec<Result<ProofCarryingAction, ChannelError>>
+where
+    F: Fn(OutgoingMessage) -> Fut,
+    Fut: Future<Output = Result<ProofCarryingAction, ChannelError>>,
+{
+    let count = messages.len();
+
+    // Group by recipient, remembering each message's original position
+    let mut groups: Vec<Vec<(usize, OutgoingMessage)>> = Vec::new();
+    let mut group_index: HashMap<String, usize> = HashMap::new();
+    for (position, message) in messages.into_iter().enumerate() {
+        let index = *group_index
+            .entry(message.recipient_id.clone())
+            .or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+        groups[index].push((position, message));
+    }
+
+    let send = &send;
+    let group_results = join_all(groups.into_iter().map(|group| async move {
+        let mut results = Vec::with_capacity(group.len());
+        for (position, message) in group {
+            results.push((position, send(message).await));
+        }
+        results
+    }))
+    .await;
+
+    let mut ordered: Vec<Option<Result<ProofCarryingAction, ChannelError>>> =
+        (0..count).map(|_| None).collect();
+    for (position, result) in group_results.into_iter().flatten() {
+        ordered[position] = Some(result);
+    }
+    ordered.into_iter().flatten().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_results_follow_input_order() {
+        let delivered = Mutex::new(Vec::new());
+        let messages = vec![
+            OutgoingMessage::new("test", "slow", "a1"),
+            OutgoingMessage::new("test", "fast", "b1"),
+            OutgoingMessage::new("test", "slow", ""),
+            OutgoingMessage::new("test", "slow", "a3"),
+        ];
+
+        let results = send_pipelined(messages, |message| {
+            let delivered = &delivered;
+            async move {
+                if message.recipient_id == "slow" {
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                }
+                if message.content.is_empty() {
+                    return Err(ChannelError::InvalidMessage("empty".to_string()));
+                }
+                delivered.lock().unwrap().push(message.content);
+                Ok(ProofCarryingAction::pending())
+            }
+        })
+        .await;
+
+        assert_eq!(results.len(), 4);
+        assert!(results[0].is_ok());
+        assert!(results[1].is_ok());
+        assert!(matches!(results[2], Err(ChannelError::InvalidMessage(_))));
+        assert!(results[3].is_ok());
+
+        // The fast recipient was not held up, and per-recipient order held
+        assert_eq!(*delivered.lock().unwrap(), vec!["b1", "a1", "a3"]);
+    }
+}
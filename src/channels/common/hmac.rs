@@ -0,0 +1,53 @@
+//! HMAC-SHA256 for signing and verifying webhook payloads.
+
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+const BLOCK_SIZE: usize = 64;
+
+/// Compute the HMAC-SHA256 of `data` under `key` (RFC 2104).
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(block.map(|b| b ^ 0x36));
+    inner.update(data);
+    let mut outer = Sha256::new();
+    outer.update(block.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+/// Check `mac` against the HMAC-SHA256 of `data`, in constant time.
+pub fn verify_hmac_sha256(key: &[u8], data: &[u8], mac: &[u8]) -> bool {
+    hmac_sha256(key, data).ct_eq(mac).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rfc4231_vectors() {
+        // Test case 2
+        assert_eq!(
+            hex::encode(hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        // Test case 6: key longer than the block size
+        assert_eq!(
+            hex::encode(hmac_sha256(&[0xaa; 131], b"Test Using Larger Than Block-Size Key - Hash Key First")),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+
+        let mac = hmac_sha256(b"key", b"data");
+        assert!(verify_hmac_sha256(b"key", b"data", &mac));
+        assert!(!verify_hmac_sha256(b"key", b"datum", &mac));
+        assert!(!verify_hmac_sha256(b"key", b"data", &mac[..31]));
+    }
+}
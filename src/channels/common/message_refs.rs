@@ -0,0 +1,66 @@
+//! Mapping from internal message hashes to platform message ids.
+//!
+//! Replies reference the message they answer by `ContentHash`. Channels
+//! record the platform id of every message they receive or send, so `send`
+//! can translate `reply_to` back into the platform's reply reference.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use crate::types::{ContentHash, OutgoingMessage};
+
+/// Number of message ids remembered by default.
+pub const DEFAULT_MESSAGE_REF_CAPACITY: usize = 4096;
+
+/// Bounded map of recent message hashes to platform message ids.
+///
+/// The oldest entries are evicted once `capacity` is reached.
+pub struct MessageRefs {
+    capacity: usize,
+    inner: Mutex<(HashMap<ContentHash, String>, VecDeque<ContentHash>)>,
+}
+
+impl MessageRefs {
+    /// Create a map remembering up to `capacity` messages.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            inner: Mutex::new((HashMap::new(), VecDeque::new())),
+        }
+    }
+
+    /// Remember the platform id of the message with hash `id`.
+    pub fn record(&self, id: ContentHash, platform_id: impl Into<String>) {
+        let mut guard = self.inner.lock().unwrap();
+        let (ids, order) = &mut *guard;
+        if ids.insert(id, platform_id.into()).is_none() {
+            order.push_back(id);
+        }
+        while order.len() > self.capacity {
+            if let Some(oldest) = order.pop_front() {
+                ids.remove(&oldest);
+            }
+        }
+    }
+
+    /// Remember the platform id of a sent message, keyed by its receipt id.
+    pub fn record_sent(&self, message: &OutgoingMessage, platform_id: impl Into<String>) {
+        self.record(message.receipt_id(), platform_id);
+    }
+
+    /// Platform id of the message with hash `id`, if still remembered.
+    pub fn resolve(&self, id: &ContentHash) -> Option<String> {
+        self.inner.lock().unwrap().0.get(id).cloned()
+    }
+
+    /// Platform id of the message `message` replies to, if known.
+    pub fn reply_target(&self, message: &OutgoingMessage) -> Option<String> {
+        message.reply_to.as_ref().and_then(|id| self.resolve(id))
+    }
+}
+
+impl Default for MessageRefs {
+    fn default() -> Self {
+        Self::new(DEFAULT_MESSAGE_REF_CAPACITY)
+    }
+}
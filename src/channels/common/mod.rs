@@ -3,8 +3,14 @@
 //! This module provides shared functionality used across all channel implementations,
 //! including rate limiting, retry logic, and message conversion utilities.
 
+pub mod batch;
+pub mod hmac;
+pub mod message_refs;
 pub mod rate_limit;
 pub mod retry;
 
+pub use batch::send_pipelined;
+pub use hmac::{hmac_sha256, verify_hmac_sha256};
+pub use message_refs::MessageRefs;
 pub use rate_limit::{RateLimiter, RateLimitConfig};
 pub use retry::{RetryPolicy, RetryResult, with_retry};
@@ -172,6 +172,7 @@ fn should_retry(error: &ChannelError) -> bool {
         ChannelError::InvalidMessage(_) => false,
         ChannelError::ChannelClosed => false,
         ChannelError::AuthenticationFailed(_) => false,
+        ChannelError::Unsupported(_) => false,
     }
 }
 
@@ -27,6 +27,13 @@ pub struct DiscordConfig {
     /// Command prefix for text commands (optional).
     #[serde(default)]
     pub command_prefix: Option<String>,
+
+    /// Request the privileged presence intent to track user presence.
+    ///
+    /// Off by default: it must also be enabled in the Developer Portal and
+    /// streams presence updates for every guild member.
+    #[serde(default)]
+    pub track_presence: bool,
 }
 
 impl Default for DiscordConfig {
@@ -38,6 +45,7 @@ impl Default for DiscordConfig {
             guild_allowlist: Vec::new(),
             register_commands: true,
             command_prefix: None,
+            track_presence: false,
         }
     }
 }
@@ -81,6 +89,12 @@ impl DiscordConfig {
         self
     }
 
+    /// Enable or disable presence tracking.
+    pub fn with_presence_tracking(mut self, track: bool) -> Self {
+        self.track_presence = track;
+        self
+    }
+
     /// Validate the configuration.
     pub fn validate(&self) -> Result<(), String> {
         if self.token.is_empty() {
@@ -110,12 +124,15 @@ mod tests {
             .with_dm_allowlist(vec!["user1".to_string()])
             .with_guild_allowlist(vec![111, 222])
             .with_register_commands(true)
-            .with_command_prefix("!");
+            .with_command_prefix("!")
+            .with_presence_tracking(true);
 
         assert_eq!(config.application_id, 123456789);
         assert_eq!(config.dm_allowlist.len(), 1);
         assert_eq!(config.guild_allowlist.len(), 2);
         assert_eq!(config.command_prefix, Some("!".to_string()));
+        assert!(config.track_presence);
+        assert!(!DiscordConfig::new("token").track_presence);
     }
 
     #[test]
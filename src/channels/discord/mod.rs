@@ -15,6 +15,20 @@ pub use implementation::DiscordChannel;
 #[cfg(not(feature = "discord"))]
 pub use stub::DiscordChannel;
 
+use crate::channels::Presence;
+
+/// Map a Discord status (`online`, `idle`, `dnd`, `invisible`, `offline`) to a presence.
+///
+/// Idle and do-not-disturb users are connected, so they count as online;
+/// invisible users are indistinguishable from offline ones.
+pub fn presence_from_status(status: &str) -> Presence {
+    match status {
+        "online" | "idle" | "dnd" => Presence::Online,
+        "invisible" | "offline" => Presence::Offline,
+        _ => Presence::Unknown,
+    }
+}
+
 /// Stub implementation when discord feature is disabled.
 #[cfg(not(feature = "discord"))]
 mod stub {
@@ -49,7 +63,8 @@ mod stub {
             ))
         }
 
-        async fn send(&self, _message: OutgoingMessage) -> Result<ProofCarryingAction, ChannelError> {
+        async fn send(&self, message: OutgoingMessage) -> Result<ProofCarryingAction, ChannelError> {
+            crate::channels::check_attachments(self, &message)?;
             Err(ChannelError::ConnectionFailed(
                 "Discord feature not enabled. Compile with --features discord".to_string()
             ))
@@ -77,16 +92,18 @@ mod stub {
 mod implementation {
     use async_trait::async_trait;
     use std::sync::Arc;
+    use std::collections::HashMap;
     use tokio::sync::{mpsc, Mutex, RwLock};
     use serenity::prelude::*;
     use serenity::model::prelude::*;
     use serenity::model::application::Interaction;
     
-    use crate::channels::{Channel, ChannelFeature};
-    use crate::channels::common::{RateLimiter, RateLimitConfig};
+    use crate::channels::{Channel, ChannelFeature, Presence};
+    use crate::channels::common::{MessageRefs, RateLimiter, RateLimitConfig};
     use crate::error::ChannelError;
     use crate::types::{
-        Action, Confidence, ContentHash, IncomingMessage, OutgoingMessage, ProofCarryingAction,
+        Action, Confidence, ContentHash, IncomingMessage, OutgoingMessage,
+        ProofCarryingAction,
     };
     use super::DiscordConfig;
 
@@ -96,12 +113,16 @@ mod implementation {
         config: DiscordConfig,
         message_rx: Arc<Mutex<mpsc::Receiver<IncomingMessage>>>,
         rate_limiter: RateLimiter,
+        presence: Arc<RwLock<HashMap<String, Presence>>>,
+        message_refs: Arc<MessageRefs>,
     }
 
     /// Event handler for Discord events.
     struct Handler {
         tx: mpsc::Sender<IncomingMessage>,
         config: DiscordConfig,
+        presence: Arc<RwLock<HashMap<String, Presence>>>,
+        message_refs: Arc<MessageRefs>,
     }
 
     #[async_trait]
@@ -118,6 +139,7 @@ mod implementation {
             }
 
             let incoming = self.convert_message(&msg);
+            self.message_refs.record(incoming.id, msg.id.get().to_string());
             if self.tx.send(incoming).await.is_err() {
                 tracing::error!("Failed to send Discord message to channel queue");
             }
@@ -159,6 +181,7 @@ mod implementation {
                     sender_id: user_id,
                     content,
                     timestamp: chrono::Utc::now().timestamp_millis() as u64,
+                    attachments: Vec::new(),
                     metadata: serde_json::json!({
                         "type": "slash_command",
                         "command": command.data.name,
@@ -179,6 +202,13 @@ mod implementation {
             }
         }
 
+        async fn presence_update(&self, _ctx: Context, new_data: serenity::model::gateway::Presence) {
+            self.presence.write().await.insert(
+                new_data.user.id.to_string(),
+                super::presence_from_status(new_data.status.name()),
+            );
+        }
+
         async fn ready(&self, _ctx: Context, ready: Ready) {
             tracing::info!("Discord bot ready as {}", ready.user.name);
         }
@@ -218,6 +248,7 @@ mod implementation {
                 sender_id: msg.author.id.to_string(),
                 content: msg.content.clone(),
                 timestamp: msg.timestamp.timestamp_millis() as u64,
+                attachments: Vec::new(),
                 metadata: serde_json::json!({
                     "channel_id": msg.channel_id.get().to_string(),
                     "guild_id": msg.guild_id.map(|g| g.get().to_string()),
@@ -235,13 +266,20 @@ mod implementation {
         pub async fn new(config: DiscordConfig) -> Result<Self, ChannelError> {
             let (tx, rx) = mpsc::channel(100);
 
-            let intents = GatewayIntents::GUILD_MESSAGES
+            let mut intents = GatewayIntents::GUILD_MESSAGES
                 | GatewayIntents::DIRECT_MESSAGES
                 | GatewayIntents::MESSAGE_CONTENT;
+            if config.track_presence {
+                intents |= GatewayIntents::GUILD_PRESENCES;
+            }
 
+            let presence = Arc::new(RwLock::new(HashMap::new()));
+            let message_refs = Arc::new(MessageRefs::default());
             let handler = Handler {
                 tx,
                 config: config.clone(),
+                presence: presence.clone(),
+                message_refs: message_refs.clone(),
             };
 
             let mut client = Client::builder(&config.token, intents)
@@ -263,6 +301,8 @@ mod implementation {
                 config,
                 message_rx: Arc::new(Mutex::new(rx)),
                 rate_limiter: RateLimiter::new(RateLimitConfig::discord()),
+                presence,
+                message_refs,
             })
         }
     }
@@ -290,10 +330,19 @@ mod implementation {
                 .parse()
                 .map_err(|e| ChannelError::InvalidMessage(format!("Invalid channel_id: {}", e)))?;
 
-            // Send the message
+            // Send the message, referencing the message it replies to
             let channel = ChannelId::new(channel_id);
-            channel
-                .send_message(&self.http, serenity::builder::CreateMessage::new().content(&message.content))
+            let mut create = serenity::builder::CreateMessage::new().content(&message.content);
+            for attachment in &message.attachments {
+                // Fetched here rather than by serenity, so downloads stay bounded
+                let bytes = attachment.fetch().await?;
+                create = create.add_file(serenity::builder::CreateAttachment::bytes(bytes, attachment.file_name.clone()));
+            }
+            if let Some(reply_id) = self.message_refs.reply_target(&message).and_then(|id| id.parse().ok()) {
+                create = create.reference_message((channel, MessageId::new(reply_id)));
+            }
+            let sent = channel
+                .send_message(&self.http, create)
                 .await
                 .map_err(|e| {
                     let error_str = e.to_string();
@@ -303,10 +352,18 @@ mod implementation {
                         ChannelError::SendFailed(e.to_string())
                     }
                 })?;
+            self.message_refs.record_sent(&message, sent.id.get().to_string());
 
             Ok(ProofCarryingAction::pending())
         }
 
+        async fn send_batch(
+            &self,
+            messages: Vec<OutgoingMessage>,
+        ) -> Vec<Result<ProofCarryingAction, ChannelError>> {
+            crate::channels::common::send_pipelined(messages, |message| self.send(message)).await
+        }
+
         fn evaluate_permission(&self, _action: &Action, sender: &str) -> Confidence {
             if self.config.dm_allowlist.contains(&sender.to_string()) {
                 Confidence::new(0.95)
@@ -329,5 +386,25 @@ mod implementation {
                 ChannelFeature::Voice => false,  // Not implemented yet
             }
         }
+
+        async fn user_presence(&self, user_id: &str) -> Result<Presence, ChannelError> {
+            // Presence arrives via gateway events for users sharing a guild,
+            // and only when `track_presence` is enabled
+            Ok(self.presence.read().await.get(user_id).copied().unwrap_or(Presence::Unknown))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presence_from_status() {
+        assert_eq!(presence_from_status("online"), Presence::Online);
+        assert_eq!(presence_from_status("dnd"), Presence::Online);
+        assert_eq!(presence_from_status("invisible"), Presence::Offline);
+        assert_eq!(presence_from_status("offline"), Presence::Offline);
+        assert_eq!(presence_from_status("streaming"), Presence::Unknown);
     }
 }
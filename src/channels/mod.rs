@@ -27,6 +27,7 @@
 //! | Telegram | Implemented | `telegram`   | `telegram.0`        |
 //! | Discord  | Implemented | `discord`    | `discord.0`         |
 //! | Slack    | Implemented | -            | `slack.0`           |
+//! | Webhook  | Implemented | -            | -                   |
 //!
 //! ## Usage
 //!
@@ -53,6 +54,7 @@
 //! See: `AGENT-8-0OPENCLAW-CHANNELS.md`
 
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use crate::types::{Action, Confidence, IncomingMessage, OutgoingMessage, ProofCarryingAction};
 use crate::error::ChannelError;
 
@@ -61,15 +63,18 @@ pub mod common;
 pub mod telegram;
 pub mod discord;
 pub mod slack;
+pub mod webhook;
 
 // Re-export commonly used types
 pub use telegram::{TelegramChannel, TelegramConfig, DmPolicy, GroupPolicy};
 pub use discord::{DiscordChannel, DiscordConfig};
 pub use slack::{SlackChannel, SlackConfig, SlackEvent};
+pub use webhook::{WebhookChannel, WebhookConfig, WebhookFieldMapping};
 pub use common::{RateLimiter, RateLimitConfig, RetryPolicy};
 
 /// Channel features that may or may not be supported.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ChannelFeature {
     /// Slash/bot commands
     Commands,
@@ -85,6 +90,18 @@ pub enum ChannelFeature {
     Voice,
 }
 
+/// Online status of a user on a channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Presence {
+    /// The user is active
+    Online,
+    /// The user is away or offline
+    Offline,
+    /// The platform does not expose presence
+    Unknown,
+}
+
 /// Trait that all channel connectors must implement.
 #[async_trait]
 pub trait Channel: Send + Sync {
@@ -93,7 +110,9 @@ pub trait Channel: Send + Sync {
 
     /// Receive the next message from the channel.
     ///
-    /// This is a blocking call that waits for the next message.
+    /// This is a blocking call that waits for the next message. The gateway
+    /// drops a pending call when the channel is disconnected, so it must not
+    /// lose a message it has not returned yet.
     async fn receive(&self) -> Result<IncomingMessage, ChannelError>;
 
     /// Send a message to the channel.
@@ -101,22 +120,82 @@ pub trait Channel: Send + Sync {
     /// Returns a Proof-Carrying Action indicating the result.
     async fn send(&self, message: OutgoingMessage) -> Result<ProofCarryingAction, ChannelError>;
 
+    /// Send several messages to the channel.
+    ///
+    /// Returns one result per message, in the same order. The default sends
+    /// sequentially through `send`; platforms with a bulk API may override it.
+    async fn send_batch(
+        &self,
+        messages: Vec<OutgoingMessage>,
+    ) -> Vec<Result<ProofCarryingAction, ChannelError>> {
+        let mut results = Vec::with_capacity(messages.len());
+        for message in messages {
+            results.push(self.send(message).await);
+        }
+        results
+    }
+
+    /// Send a message that may be edited later.
+    ///
+    /// Returns the platform's id for the sent message, or `None` when the
+    /// platform cannot edit messages. The default sends through `send`.
+    async fn send_editable(&self, message: OutgoingMessage) -> Result<Option<String>, ChannelError> {
+        self.send(message).await.map(|_| None)
+    }
+
+    /// Replace the content of a message sent with `send_editable`.
+    ///
+    /// The default rejects the edit; callers fall back to sending a new message.
+    async fn edit(
+        &self,
+        _message_id: &str,
+        _message: OutgoingMessage,
+    ) -> Result<ProofCarryingAction, ChannelError> {
+        Err(ChannelError::SendFailed(format!("{} does not support editing messages", self.name())))
+    }
+
+    /// Delete a message sent with `send_editable` to `recipient_id`.
+    ///
+    /// The default rejects the deletion; callers fall back to editing it.
+    async fn delete(&self, _message_id: &str, _recipient_id: &str) -> Result<(), ChannelError> {
+        Err(ChannelError::Unsupported(format!("{} does not support deleting messages", self.name())))
+    }
+
     /// Evaluate permission for an action.
     ///
     /// Returns a confidence score based on the sender and action.
     fn evaluate_permission(&self, action: &Action, sender: &str) -> Confidence;
 
+    /// Evaluate permission for an action triggered by a specific message.
+    ///
+    /// Platforms can use message metadata (workspace, guild, ...) here. The
+    /// default defers to `evaluate_permission` with the message's sender.
+    fn evaluate_message_permission(&self, action: &Action, message: &IncomingMessage) -> Confidence {
+        self.evaluate_permission(action, &message.sender_id)
+    }
+
     /// Get the channel's allowlist.
     fn allowlist(&self) -> &[String];
 
     /// Check if the channel supports a feature.
     fn supports(&self, feature: ChannelFeature) -> bool;
+
+    /// Query whether a user is currently online.
+    ///
+    /// Defaults to `Presence::Unknown` for platforms without a presence API.
+    async fn user_presence(&self, _user_id: &str) -> Result<Presence, ChannelError> {
+        Ok(Presence::Unknown)
+    }
 }
 
 /// Placeholder channel for testing.
 pub struct TestChannel {
     name: String,
     allowlist: Vec<String>,
+    features: Vec<ChannelFeature>,
+    presence: std::collections::HashMap<String, Presence>,
+    incoming: std::sync::Mutex<std::collections::VecDeque<IncomingMessage>>,
+    sent: std::sync::Mutex<Vec<OutgoingMessage>>,
 }
 
 impl TestChannel {
@@ -125,14 +204,41 @@ impl TestChannel {
         Self {
             name: name.to_string(),
             allowlist: Vec::new(),
+            features: Vec::new(),
+            presence: std::collections::HashMap::new(),
+            incoming: std::sync::Mutex::new(std::collections::VecDeque::new()),
+            sent: std::sync::Mutex::new(Vec::new()),
         }
     }
 
+    /// Queue a message to be returned by `receive`.
+    pub fn with_incoming(self, message: IncomingMessage) -> Self {
+        self.incoming.lock().unwrap().push_back(message);
+        self
+    }
+
+    /// Messages sent through this channel so far.
+    pub fn sent(&self) -> Vec<OutgoingMessage> {
+        self.sent.lock().unwrap().clone()
+    }
+
     /// Add users to the allowlist.
     pub fn with_allowlist(mut self, users: Vec<String>) -> Self {
         self.allowlist = users;
         self
     }
+
+    /// Declare the features this channel supports.
+    pub fn with_features(mut self, features: Vec<ChannelFeature>) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Script the presence reported for a user.
+    pub fn with_presence(mut self, user_id: &str, presence: Presence) -> Self {
+        self.presence.insert(user_id.to_string(), presence);
+        self
+    }
 }
 
 #[async_trait]
@@ -142,10 +248,12 @@ impl Channel for TestChannel {
     }
 
     async fn receive(&self) -> Result<IncomingMessage, ChannelError> {
-        Err(ChannelError::ChannelClosed)
+        self.incoming.lock().unwrap().pop_front().ok_or(ChannelError::ChannelClosed)
     }
 
-    async fn send(&self, _message: OutgoingMessage) -> Result<ProofCarryingAction, ChannelError> {
+    async fn send(&self, message: OutgoingMessage) -> Result<ProofCarryingAction, ChannelError> {
+        check_attachments(self, &message)?;
+        self.sent.lock().unwrap().push(message);
         Ok(ProofCarryingAction::pending())
     }
 
@@ -157,14 +265,62 @@ impl Channel for TestChannel {
         &self.allowlist
     }
 
-    fn supports(&self, _feature: ChannelFeature) -> bool {
-        false
+    fn supports(&self, feature: ChannelFeature) -> bool {
+        self.features.contains(&feature)
     }
+
+    async fn user_presence(&self, user_id: &str) -> Result<Presence, ChannelError> {
+        Ok(self.presence.get(user_id).copied().unwrap_or(Presence::Unknown))
+    }
+}
+
+/// Reject `message` if it carries attachments and `channel` cannot send files.
+pub fn check_attachments(channel: &(impl Channel + ?Sized), message: &OutgoingMessage) -> Result<(), ChannelError> {
+    if message.attachments.is_empty() || channel.supports(ChannelFeature::Files) {
+        return Ok(());
+    }
+    Err(ChannelError::Unsupported(format!("{} cannot send attachments", channel.name())))
+}
+
+/// Connection state of a registered channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelState {
+    /// Messages are received and replies delivered
+    Connected,
+    /// The channel is neither polled nor sent to
+    Disconnected,
 }
 
-/// Registry for managing multiple channels.
+/// Lifecycle status of a registered channel.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelStatus {
+    /// Channel name
+    pub name: String,
+
+    /// Current connection state
+    pub state: ChannelState,
+
+    /// Times the channel was connected again after a disconnect
+    pub reconnects: u32,
+
+    /// Why the channel was last disconnected
+    pub last_disconnect_reason: Option<String>,
+
+    /// Unix timestamp in milliseconds of the last state change
+    pub changed_at: u64,
+}
+
+struct RegisteredChannel {
+    channel: std::sync::Arc<dyn Channel>,
+    status: ChannelStatus,
+    /// Bumped on every connect, so listeners of an earlier connection stop
+    generation: u64,
+}
+
+/// Registry for managing multiple channels and their connection state.
 pub struct ChannelRegistry {
-    channels: std::collections::HashMap<String, Box<dyn Channel>>,
+    channels: std::collections::HashMap<String, RegisteredChannel>,
 }
 
 impl ChannelRegistry {
@@ -177,12 +333,104 @@ impl ChannelRegistry {
 
     /// Register a channel.
     pub fn register<C: Channel + 'static>(&mut self, channel: C) {
-        self.channels.insert(channel.name().to_string(), Box::new(channel));
+        self.register_shared(std::sync::Arc::new(channel));
+    }
+
+    /// Register a shared channel as connected, replacing any channel with its name.
+    ///
+    /// Replacing a channel counts as a reconnect.
+    pub fn register_shared(&mut self, channel: std::sync::Arc<dyn Channel>) {
+        let name = channel.name().to_string();
+        let (reconnects, generation) = self
+            .channels
+            .get(&name)
+            .map_or((0, 0), |entry| (entry.status.reconnects + 1, entry.generation + 1));
+        let status = ChannelStatus {
+            name: name.clone(),
+            state: ChannelState::Connected,
+            reconnects,
+            last_disconnect_reason: None,
+            changed_at: chrono::Utc::now().timestamp_millis() as u64,
+        };
+        self.channels.insert(name, RegisteredChannel { channel, status, generation });
     }
 
-    /// Get a channel by name.
+    /// Get a channel by name, whatever its state.
     pub fn get(&self, name: &str) -> Option<&dyn Channel> {
-        self.channels.get(name).map(|c| c.as_ref())
+        self.channels.get(name).map(|entry| entry.channel.as_ref())
+    }
+
+    /// Get a channel by name if it is connected.
+    pub fn connected(&self, name: &str) -> Option<std::sync::Arc<dyn Channel>> {
+        self.channels
+            .get(name)
+            .filter(|entry| entry.status.state == ChannelState::Connected)
+            .map(|entry| entry.channel.clone())
+    }
+
+    /// All connected channels.
+    pub fn connected_channels(&self) -> Vec<std::sync::Arc<dyn Channel>> {
+        self.channels
+            .values()
+            .filter(|entry| entry.status.state == ChannelState::Connected)
+            .map(|entry| entry.channel.clone())
+            .collect()
+    }
+
+    /// Mark a channel disconnected. Returns whether it was connected.
+    pub fn disconnect(&mut self, name: &str, reason: &str) -> bool {
+        let Some(entry) = self.channels.get_mut(name) else {
+            return false;
+        };
+        if entry.status.state == ChannelState::Disconnected {
+            return false;
+        }
+        entry.status.state = ChannelState::Disconnected;
+        entry.status.last_disconnect_reason = Some(reason.to_string());
+        entry.status.changed_at = chrono::Utc::now().timestamp_millis() as u64;
+        true
+    }
+
+    /// Connect a registered, disconnected channel again. Returns whether it
+    /// was reconnected.
+    pub fn connect(&mut self, name: &str) -> bool {
+        let Some(entry) = self.channels.get_mut(name) else {
+            return false;
+        };
+        if entry.status.state == ChannelState::Connected {
+            return false;
+        }
+        entry.status.state = ChannelState::Connected;
+        entry.status.reconnects += 1;
+        entry.status.changed_at = chrono::Utc::now().timestamp_millis() as u64;
+        entry.generation += 1;
+        true
+    }
+
+    /// Connection state of a channel.
+    pub fn state(&self, name: &str) -> Option<ChannelState> {
+        self.channels.get(name).map(|entry| entry.status.state)
+    }
+
+    /// Lifecycle status of a channel.
+    pub fn status(&self, name: &str) -> Option<ChannelStatus> {
+        self.channels.get(name).map(|entry| entry.status.clone())
+    }
+
+    /// Lifecycle status of every registered channel, by name.
+    pub fn statuses(&self) -> Vec<ChannelStatus> {
+        let mut statuses: Vec<ChannelStatus> = self.channels.values().map(|entry| entry.status.clone()).collect();
+        statuses.sort_by(|a, b| a.name.cmp(&b.name));
+        statuses
+    }
+
+    /// Connection generation of a connected channel, for listeners to tell
+    /// whether the connection they poll is still current.
+    pub fn generation(&self, name: &str) -> Option<u64> {
+        self.channels
+            .get(name)
+            .filter(|entry| entry.status.state == ChannelState::Connected)
+            .map(|entry| entry.generation)
     }
 
     /// List all registered channel names.
@@ -194,6 +442,14 @@ impl ChannelRegistry {
     pub fn has(&self, name: &str) -> bool {
         self.channels.contains_key(name)
     }
+
+    /// Number of connected channels.
+    pub fn connected_count(&self) -> usize {
+        self.channels
+            .values()
+            .filter(|entry| entry.status.state == ChannelState::Connected)
+            .count()
+    }
 }
 
 impl Default for ChannelRegistry {
@@ -201,3 +457,112 @@ impl Default for ChannelRegistry {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Channel that records sends and rejects empty messages.
+    struct RecordingChannel {
+        sent: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl Channel for RecordingChannel {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        async fn receive(&self) -> Result<IncomingMessage, ChannelError> {
+            Err(ChannelError::ChannelClosed)
+        }
+
+        async fn send(&self, message: OutgoingMessage) -> Result<ProofCarryingAction, ChannelError> {
+            if message.content.is_empty() {
+                return Err(ChannelError::InvalidMessage("empty".to_string()));
+            }
+            self.sent.lock().unwrap().push(message.content);
+            Ok(ProofCarryingAction::pending())
+        }
+
+        fn evaluate_permission(&self, _action: &Action, _sender: &str) -> Confidence {
+            Confidence::neutral()
+        }
+
+        fn allowlist(&self) -> &[String] {
+            &[]
+        }
+
+        fn supports(&self, _feature: ChannelFeature) -> bool {
+            false
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_send_batch_preserves_order_and_results() {
+        let channel = RecordingChannel { sent: Mutex::new(Vec::new()) };
+        let messages = vec![
+            OutgoingMessage::new("recording", "a", "first"),
+            OutgoingMessage::new("recording", "b", ""),
+            OutgoingMessage::new("recording", "c", "third"),
+        ];
+
+        let results = channel.send_batch(messages).await;
+
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(ChannelError::InvalidMessage(_))));
+        assert!(results[2].is_ok());
+        assert_eq!(*channel.sent.lock().unwrap(), vec!["first", "third"]);
+    }
+
+    #[tokio::test]
+    async fn test_presence_defaults_to_unknown() {
+        let channel = TestChannel::new("test").with_presence("alice", Presence::Online);
+        assert_eq!(channel.user_presence("alice").await.unwrap(), Presence::Online);
+        assert_eq!(channel.user_presence("bob").await.unwrap(), Presence::Unknown);
+
+        let recording = RecordingChannel { sent: Mutex::new(Vec::new()) };
+        assert_eq!(recording.user_presence("alice").await.unwrap(), Presence::Unknown);
+    }
+
+    #[test]
+    fn test_registry_tracks_lifecycle() {
+        let mut registry = ChannelRegistry::new();
+        registry.register(TestChannel::new("test"));
+        assert_eq!(registry.state("test"), Some(ChannelState::Connected));
+        assert_eq!(registry.generation("test"), Some(0));
+
+        assert!(registry.disconnect("test", "maintenance"));
+        assert!(!registry.disconnect("test", "again"));
+        assert!(registry.connected("test").is_none());
+        assert!(registry.get("test").is_some());
+        assert_eq!(registry.generation("test"), None);
+        let status = registry.status("test").unwrap();
+        assert_eq!(status.state, ChannelState::Disconnected);
+        assert_eq!(status.last_disconnect_reason.as_deref(), Some("maintenance"));
+
+        assert!(registry.connect("test"));
+        assert!(!registry.connect("test"));
+        assert!(!registry.connect("missing"));
+        assert_eq!(registry.generation("test"), Some(1));
+        assert_eq!(registry.statuses()[0].reconnects, 1);
+        assert_eq!(registry.connected_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_channel_without_files_rejects_attachments() {
+        let attachment = crate::types::OutgoingAttachment::bytes("notes.txt", "text/plain", b"hi".to_vec());
+        let message = OutgoingMessage::new("test", "user", "see attached").with_attachment(attachment);
+
+        let plain = TestChannel::new("plain");
+        assert!(matches!(plain.send(message.clone()).await, Err(ChannelError::Unsupported(_))));
+        assert!(plain.sent().is_empty());
+        assert!(plain.send(OutgoingMessage::new("test", "user", "text only")).await.is_ok());
+
+        let files = TestChannel::new("files").with_features(vec![ChannelFeature::Files]);
+        files.send(message).await.unwrap();
+        assert_eq!(files.sent()[0].attachments.len(), 1);
+    }
+}
@@ -11,11 +11,12 @@ use async_trait::async_trait;
 use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex};
 
-use crate::channels::{Channel, ChannelFeature};
-use crate::channels::common::{RateLimiter, RateLimitConfig};
+use crate::channels::{Channel, ChannelFeature, Presence};
+use crate::channels::common::{MessageRefs, RateLimiter, RateLimitConfig};
 use crate::error::ChannelError;
 use crate::types::{
-    Action, Confidence, ContentHash, IncomingMessage, OutgoingMessage, ProofCarryingAction,
+    Action, Confidence, ContentHash, IncomingMessage, OutgoingAttachment, OutgoingMessage,
+    ProofCarryingAction,
 };
 
 /// Slack channel implementation.
@@ -27,6 +28,7 @@ pub struct SlackChannel {
     message_rx: Arc<Mutex<mpsc::Receiver<IncomingMessage>>>,
     message_tx: mpsc::Sender<IncomingMessage>,
     rate_limiter: RateLimiter,
+    thread_refs: MessageRefs,
 }
 
 impl SlackChannel {
@@ -41,6 +43,7 @@ impl SlackChannel {
             message_rx: Arc::new(Mutex::new(rx)),
             message_tx: tx,
             rate_limiter: RateLimiter::new(RateLimitConfig::slack()),
+            thread_refs: MessageRefs::default(),
         };
 
         // Note: Full implementation would start an HTTP server for Events API
@@ -70,6 +73,7 @@ impl SlackChannel {
                 text,
                 ts,
                 thread_ts,
+                team_id,
             } => {
                 // Check allowlists
                 if !self.config.channel_allowlist.is_empty()
@@ -80,16 +84,21 @@ impl SlackChannel {
                     ));
                 }
 
+                let id = ContentHash::from_bytes(format!("slack:{}:{}", channel, ts).as_bytes());
+                // Replies go to the thread root, or start a thread on this message
+                self.thread_refs.record(id, thread_ts.clone().unwrap_or_else(|| ts.clone()));
                 Ok(IncomingMessage {
-                    id: ContentHash::from_bytes(format!("slack:{}:{}", channel, ts).as_bytes()),
+                    id,
                     channel_id: "slack".to_string(),
                     sender_id: user,
                     content: text,
                     timestamp: parse_slack_ts(&ts),
+                    attachments: Vec::new(),
                     metadata: serde_json::json!({
                         "channel": channel,
                         "ts": ts,
                         "thread_ts": thread_ts,
+                        "team_id": team_id,
                     }),
                 })
             }
@@ -99,6 +108,7 @@ impl SlackChannel {
                 user_id,
                 channel_id,
                 trigger_id,
+                team_id,
             } => Ok(IncomingMessage {
                 id: ContentHash::from_bytes(
                     format!("slack:cmd:{}:{}", trigger_id, command).as_bytes(),
@@ -107,11 +117,13 @@ impl SlackChannel {
                 sender_id: user_id,
                 content: format!("{} {}", command, text),
                 timestamp: chrono::Utc::now().timestamp_millis() as u64,
+                attachments: Vec::new(),
                 metadata: serde_json::json!({
                     "type": "slash_command",
                     "command": command,
                     "channel": channel_id,
                     "trigger_id": trigger_id,
+                    "team_id": team_id,
                 }),
             }),
             SlackEvent::AppMention {
@@ -119,28 +131,36 @@ impl SlackChannel {
                 user,
                 text,
                 ts,
-            } => Ok(IncomingMessage {
-                id: ContentHash::from_bytes(format!("slack:mention:{}:{}", channel, ts).as_bytes()),
-                channel_id: "slack".to_string(),
-                sender_id: user,
-                content: text,
-                timestamp: parse_slack_ts(&ts),
-                metadata: serde_json::json!({
-                    "type": "app_mention",
-                    "channel": channel,
-                    "ts": ts,
-                }),
-            }),
+                team_id,
+            } => {
+                let id = ContentHash::from_bytes(format!("slack:mention:{}:{}", channel, ts).as_bytes());
+                self.thread_refs.record(id, ts.clone());
+                Ok(IncomingMessage {
+                    id,
+                    channel_id: "slack".to_string(),
+                    sender_id: user,
+                    content: text,
+                    timestamp: parse_slack_ts(&ts),
+                    attachments: Vec::new(),
+                    metadata: serde_json::json!({
+                        "type": "app_mention",
+                        "channel": channel,
+                        "ts": ts,
+                        "team_id": team_id,
+                    }),
+                })
+            }
         }
     }
 
-    /// Send a message to Slack.
+    /// Send a message to Slack, returning the posted message's `ts`.
+    ///
+    /// Replies to a known message are posted in its thread.
     /// 
     /// Note: Full implementation would use slack-morphism's WebAPI client.
-    async fn send_message_impl(&self, channel: &str, text: &str) -> Result<(), ChannelError> {
-        // Apply rate limiting
-        self.rate_limiter.acquire().await;
-
+    async fn send_message_impl(&self, message: &OutgoingMessage) -> Result<Option<String>, ChannelError> {
+        let channel = &message.recipient_id;
+        let text = &message.content;
         // In a full implementation, this would use the Slack Web API:
         // self.client.chat_postMessage(channel, text).await
 
@@ -151,53 +171,128 @@ impl SlackChannel {
             text
         );
 
-        // Placeholder for actual HTTP call
-        let client = reqwest::Client::new();
-        let response = client
-            .post("https://slack.com/api/chat.postMessage")
-            .header("Authorization", format!("Bearer {}", self.config.bot_token))
-            .json(&serde_json::json!({
+        let thread_ts = self.thread_refs.reply_target(message);
+        let mut ts = None;
+        if !text.is_empty() || message.attachments.is_empty() {
+            let mut payload = serde_json::json!({
                 "channel": channel,
                 "text": text,
-            }))
+            });
+            if let Some(thread_ts) = &thread_ts {
+                payload["thread_ts"] = serde_json::Value::String(thread_ts.clone());
+            }
+            let body = self.call_chat_api("chat.postMessage", payload).await?;
+            ts = body.get("ts").and_then(|ts| ts.as_str()).map(|ts| ts.to_string());
+        }
+        for attachment in &message.attachments {
+            self.upload_file(channel, thread_ts.as_deref(), attachment).await?;
+        }
+        Ok(ts)
+    }
+
+    /// Share a file in `channel` through Slack's external upload flow.
+    async fn upload_file(
+        &self,
+        channel: &str,
+        thread_ts: Option<&str>,
+        attachment: &OutgoingAttachment,
+    ) -> Result<(), ChannelError> {
+        let bytes = attachment.fetch().await?;
+
+        // Reserve an upload URL for the file
+        self.rate_limiter.acquire().await;
+        let response = reqwest::Client::new()
+            .post("https://slack.com/api/files.getUploadURLExternal")
+            .header("Authorization", format!("Bearer {}", self.config.bot_token))
+            .form(&[("filename", attachment.file_name.clone()), ("length", bytes.len().to_string())])
             .send()
             .await
             .map_err(|e| ChannelError::SendFailed(e.to_string()))?;
+        let body = check_api_response(response).await?;
+        let (Some(upload_url), Some(file_id)) = (
+            body.get("upload_url").and_then(|u| u.as_str()),
+            body.get("file_id").and_then(|f| f.as_str()),
+        ) else {
+            return Err(ChannelError::SendFailed("Slack returned no upload URL".to_string()));
+        };
 
-        if !response.status().is_success() {
-            return Err(ChannelError::SendFailed(format!(
-                "Slack API error: {}",
-                response.status()
-            )));
+        reqwest::Client::new()
+            .post(upload_url)
+            .header("Content-Type", attachment.mime_type.as_str())
+            .body(bytes)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| ChannelError::SendFailed(e.to_string()))?;
+
+        let mut payload = serde_json::json!({
+            "files": [{ "id": file_id, "title": attachment.file_name }],
+            "channel_id": channel,
+        });
+        if let Some(thread_ts) = thread_ts {
+            payload["thread_ts"] = serde_json::Value::String(thread_ts.to_string());
         }
+        self.call_chat_api("files.completeUploadExternal", payload).await?;
+        Ok(())
+    }
 
-        // Check for rate limiting
-        let body: serde_json::Value = response
-            .json()
+    /// Call a `chat.*` Web API method, mapping Slack errors to `ChannelError`.
+    async fn call_chat_api(
+        &self,
+        method: &str,
+        payload: serde_json::Value,
+    ) -> Result<serde_json::Value, ChannelError> {
+        // Apply rate limiting
+        self.rate_limiter.acquire().await;
+
+        let client = reqwest::Client::new();
+        let response = client
+            .post(format!("https://slack.com/api/{}", method))
+            .header("Authorization", format!("Bearer {}", self.config.bot_token))
+            .json(&payload)
+            .send()
             .await
             .map_err(|e| ChannelError::SendFailed(e.to_string()))?;
 
-        if body.get("ok") == Some(&serde_json::Value::Bool(false)) {
-            let error = body
-                .get("error")
-                .and_then(|e| e.as_str())
-                .unwrap_or("unknown");
-
-            if error == "rate_limited" {
-                let retry_after = body
-                    .get("retry_after")
-                    .and_then(|r| r.as_u64())
-                    .unwrap_or(1);
-                return Err(ChannelError::RateLimited {
-                    retry_after: retry_after * 1000,
-                });
-            }
+        check_api_response(response).await
+    }
+}
 
-            return Err(ChannelError::SendFailed(format!("Slack error: {}", error)));
+/// Read a Web API response body, mapping Slack errors to `ChannelError`.
+async fn check_api_response(response: reqwest::Response) -> Result<serde_json::Value, ChannelError> {
+    if !response.status().is_success() {
+        return Err(ChannelError::SendFailed(format!(
+            "Slack API error: {}",
+            response.status()
+        )));
+    }
+
+    // Check for rate limiting
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| ChannelError::SendFailed(e.to_string()))?;
+
+    if body.get("ok") == Some(&serde_json::Value::Bool(false)) {
+        let error = body
+            .get("error")
+            .and_then(|e| e.as_str())
+            .unwrap_or("unknown");
+
+        if error == "rate_limited" {
+            let retry_after = body
+                .get("retry_after")
+                .and_then(|r| r.as_u64())
+                .unwrap_or(1);
+            return Err(ChannelError::RateLimited {
+                retry_after: retry_after * 1000,
+            });
         }
 
-        Ok(())
+        return Err(ChannelError::SendFailed(format!("Slack error: {}", error)));
     }
+
+    Ok(body)
 }
 
 #[async_trait]
@@ -214,22 +309,68 @@ impl Channel for SlackChannel {
     }
 
     async fn send(&self, message: OutgoingMessage) -> Result<ProofCarryingAction, ChannelError> {
-        self.send_message_impl(&message.recipient_id, &message.content)
-            .await?;
+        self.send_message_impl(&message).await?;
         Ok(ProofCarryingAction::pending())
     }
 
+    async fn send_editable(&self, message: OutgoingMessage) -> Result<Option<String>, ChannelError> {
+        self.send_message_impl(&message).await
+    }
+
+    async fn edit(
+        &self,
+        message_id: &str,
+        message: OutgoingMessage,
+    ) -> Result<ProofCarryingAction, ChannelError> {
+        self.call_chat_api(
+            "chat.update",
+            serde_json::json!({
+                "channel": message.recipient_id,
+                "ts": message_id,
+                "text": message.content,
+            }),
+        )
+        .await?;
+        Ok(ProofCarryingAction::pending())
+    }
+
+    async fn delete(&self, message_id: &str, recipient_id: &str) -> Result<(), ChannelError> {
+        self.call_chat_api("chat.delete", serde_json::json!({ "channel": recipient_id, "ts": message_id }))
+            .await
+            .map(|_| ())
+    }
+
+    async fn send_batch(
+        &self,
+        messages: Vec<OutgoingMessage>,
+    ) -> Vec<Result<ProofCarryingAction, ChannelError>> {
+        // Slack's Web API handles concurrent posts to different channels
+        crate::channels::common::send_pipelined(messages, |message| self.send(message)).await
+    }
+
     fn evaluate_permission(&self, _action: &Action, _sender: &str) -> Confidence {
-        // Check if sender's workspace is allowlisted
-        // In Slack, we'd typically check workspace membership
+        // Without a message the sender's workspace is unknown
         if !self.config.workspace_allowlist.is_empty() {
-            // For simplicity, check user against allowlist
             Confidence::new(0.5)
         } else {
             Confidence::new(0.3)
         }
     }
 
+    fn evaluate_message_permission(&self, _action: &Action, message: &IncomingMessage) -> Confidence {
+        if self.config.workspace_allowlist.is_empty() {
+            return Confidence::new(0.3);
+        }
+
+        let team_id = message.metadata.get("team_id").and_then(|t| t.as_str());
+        match team_id {
+            Some(team) if self.config.workspace_allowlist.iter().any(|w| w == team) => {
+                Confidence::new(0.9)
+            }
+            _ => Confidence::none(),
+        }
+    }
+
     fn allowlist(&self) -> &[String] {
         &self.config.channel_allowlist
     }
@@ -244,6 +385,36 @@ impl Channel for SlackChannel {
             ChannelFeature::Voice => false,    // Huddles not supported via API
         }
     }
+
+    async fn user_presence(&self, user_id: &str) -> Result<Presence, ChannelError> {
+        self.rate_limiter.acquire().await;
+
+        let body: serde_json::Value = reqwest::Client::new()
+            .get("https://slack.com/api/users.getPresence")
+            .header("Authorization", format!("Bearer {}", self.config.bot_token))
+            .query(&[("user", user_id)])
+            .send()
+            .await
+            .map_err(|e| ChannelError::ConnectionFailed(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| ChannelError::ConnectionFailed(e.to_string()))?;
+
+        parse_presence_response(&body)
+    }
+}
+
+/// Interpret a `users.getPresence` response.
+fn parse_presence_response(body: &serde_json::Value) -> Result<Presence, ChannelError> {
+    if body.get("ok") != Some(&serde_json::Value::Bool(true)) {
+        let error = body.get("error").and_then(|e| e.as_str()).unwrap_or("unknown");
+        return Err(ChannelError::ConnectionFailed(format!("Slack error: {}", error)));
+    }
+    Ok(match body.get("presence").and_then(|p| p.as_str()) {
+        Some("active") => Presence::Online,
+        Some("away") => Presence::Offline,
+        _ => Presence::Unknown,
+    })
 }
 
 /// Slack event types that can be received from the Events API.
@@ -256,6 +427,8 @@ pub enum SlackEvent {
         text: String,
         ts: String,
         thread_ts: Option<String>,
+        /// Workspace the message was posted in
+        team_id: Option<String>,
     },
     /// A slash command was invoked.
     SlashCommand {
@@ -264,6 +437,8 @@ pub enum SlackEvent {
         user_id: String,
         channel_id: String,
         trigger_id: String,
+        /// Workspace the command was invoked in
+        team_id: Option<String>,
     },
     /// The app was mentioned.
     AppMention {
@@ -271,6 +446,8 @@ pub enum SlackEvent {
         user: String,
         text: String,
         ts: String,
+        /// Workspace the mention was posted in
+        team_id: Option<String>,
     },
 }
 
@@ -293,4 +470,42 @@ mod tests {
         // Should be approximately 1234567890123 (ms)
         assert!(millis > 1234567890000);
     }
+
+    #[test]
+    fn test_parse_presence_response() {
+        let active = serde_json::json!({"ok": true, "presence": "active"});
+        let away = serde_json::json!({"ok": true, "presence": "away"});
+        let failed = serde_json::json!({"ok": false, "error": "user_not_found"});
+
+        assert_eq!(parse_presence_response(&active).unwrap(), Presence::Online);
+        assert_eq!(parse_presence_response(&away).unwrap(), Presence::Offline);
+        assert!(parse_presence_response(&failed).is_err());
+    }
+
+    async fn message_from_team(channel: &SlackChannel, team_id: &str) -> IncomingMessage {
+        channel
+            .convert_event(SlackEvent::Message {
+                channel: "C1".to_string(),
+                user: "U1".to_string(),
+                text: "hi".to_string(),
+                ts: "1234567890.123456".to_string(),
+                thread_ts: None,
+                team_id: Some(team_id.to_string()),
+            })
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_workspace_allowlist_permission() {
+        let config = SlackConfig::new("test_token").with_workspace_allowlist(vec!["T123".to_string()]);
+        let channel = SlackChannel::new(config).await.unwrap();
+        let action = Action::NoOp { reason: "test".to_string() };
+
+        let allowed = message_from_team(&channel, "T123").await;
+        assert_eq!(allowed.metadata["team_id"], "T123");
+        assert!(channel.evaluate_message_permission(&action, &allowed).meets_threshold(0.8));
+
+        let denied = message_from_team(&channel, "T999").await;
+        assert_eq!(channel.evaluate_message_permission(&action, &denied).value(), 0.0);
+    }
 }
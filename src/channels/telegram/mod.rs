@@ -9,6 +9,14 @@ mod config;
 
 pub use config::{TelegramConfig, DmPolicy, GroupPolicy};
 
+use crate::channels::common::MessageRefs;
+use crate::types::OutgoingMessage;
+
+/// Telegram message id to pass as `reply_to_message_id` for `message`.
+pub fn reply_to_message_id(message: &OutgoingMessage, refs: &MessageRefs) -> Option<i32> {
+    refs.reply_target(message).and_then(|id| id.parse().ok())
+}
+
 #[cfg(feature = "telegram")]
 pub use implementation::TelegramChannel;
 
@@ -49,7 +57,8 @@ mod stub {
             ))
         }
 
-        async fn send(&self, _message: OutgoingMessage) -> Result<ProofCarryingAction, ChannelError> {
+        async fn send(&self, message: OutgoingMessage) -> Result<ProofCarryingAction, ChannelError> {
+            crate::channels::check_attachments(self, &message)?;
             Err(ChannelError::ConnectionFailed(
                 "Telegram feature not enabled. Compile with --features telegram".to_string()
             ))
@@ -79,22 +88,34 @@ mod implementation {
     use std::sync::Arc;
     use tokio::sync::{mpsc, Mutex};
     use teloxide::prelude::*;
-    use teloxide::types::ChatId;
+    use teloxide::types::{ChatId, InputFile};
     
-    use crate::channels::{Channel, ChannelFeature};
-    use crate::channels::common::{RateLimiter, RateLimitConfig};
+    use crate::channels::{Channel, ChannelFeature, Presence};
+    use crate::channels::common::{MessageRefs, RateLimiter, RateLimitConfig};
     use crate::error::ChannelError;
     use crate::types::{
-        Action, Confidence, ContentHash, IncomingMessage, OutgoingMessage, ProofCarryingAction,
+        Action, AttachmentSource, Confidence, ContentHash, IncomingMessage, OutgoingMessage,
+        ProofCarryingAction,
     };
     use super::{TelegramConfig, DmPolicy, GroupPolicy};
 
+    /// Map a Bot API error, recognizing rate limiting.
+    fn send_error(e: teloxide::RequestError) -> ChannelError {
+        let error_str = e.to_string();
+        if error_str.contains("429") || error_str.contains("Too Many Requests") {
+            ChannelError::RateLimited { retry_after: 1000 }
+        } else {
+            ChannelError::SendFailed(error_str)
+        }
+    }
+
     /// Telegram channel implementation using teloxide.
     pub struct TelegramChannel {
         bot: Bot,
         config: TelegramConfig,
         message_rx: Arc<Mutex<mpsc::Receiver<IncomingMessage>>>,
         rate_limiter: RateLimiter,
+        message_refs: Arc<MessageRefs>,
     }
 
     impl TelegramChannel {
@@ -102,16 +123,18 @@ mod implementation {
         pub async fn new(config: TelegramConfig) -> Result<Self, ChannelError> {
             let bot = Bot::new(&config.token);
             let (tx, rx) = mpsc::channel(100);
+            let message_refs = Arc::new(MessageRefs::default());
 
             let channel = Self {
                 bot: bot.clone(),
                 config: config.clone(),
                 message_rx: Arc::new(Mutex::new(rx)),
                 rate_limiter: RateLimiter::new(RateLimitConfig::telegram()),
+                message_refs: message_refs.clone(),
             };
 
             // Start the message listener in a background task
-            Self::start_listener(bot, tx, config);
+            Self::start_listener(bot, tx, config, message_refs);
 
             Ok(channel)
         }
@@ -119,12 +142,14 @@ mod implementation {
         fn start_listener(
             bot: Bot, 
             tx: mpsc::Sender<IncomingMessage>, 
-            config: TelegramConfig
+            config: TelegramConfig,
+            message_refs: Arc<MessageRefs>,
         ) {
             tokio::spawn(async move {
                 teloxide::repl(bot, move |bot: Bot, msg: Message| {
                     let tx = tx.clone();
                     let config = config.clone();
+                    let message_refs = message_refs.clone();
 
                     async move {
                         // Check permissions based on policy
@@ -138,6 +163,7 @@ mod implementation {
 
                         // Convert to IncomingMessage
                         let incoming = Self::convert_message(&msg);
+                        message_refs.record(incoming.id, msg.id.0.to_string());
 
                         // Send to channel
                         if tx.send(incoming).await.is_err() {
@@ -215,6 +241,7 @@ mod implementation {
                     .unwrap_or_default(),
                 content,
                 timestamp: msg.date.timestamp_millis() as u64,
+                attachments: Vec::new(),
                 metadata: serde_json::json!({
                     "chat_id": msg.chat.id.0,
                     "message_id": msg.id.0,
@@ -250,25 +277,41 @@ mod implementation {
                 .parse()
                 .map_err(|e| ChannelError::InvalidMessage(format!("Invalid chat_id: {}", e)))?;
 
-            // Send the message
-            self.bot
-                .send_message(ChatId(chat_id), &message.content)
-                .await
-                .map_err(|e| {
-                    // Check for rate limiting
-                    let error_str = e.to_string();
-                    if error_str.contains("429") || error_str.contains("Too Many Requests") {
-                        // Extract retry_after if possible
-                        ChannelError::RateLimited { retry_after: 1000 }
-                    } else {
-                        ChannelError::SendFailed(e.to_string())
-                    }
-                })?;
+            // Send the message, quoting the message it replies to
+            if !message.content.is_empty() || message.attachments.is_empty() {
+                let mut request = self.bot.send_message(ChatId(chat_id), &message.content);
+                if let Some(reply_id) = super::reply_to_message_id(&message, &self.message_refs) {
+                    request = request.reply_to_message_id(teloxide::types::MessageId(reply_id));
+                }
+                let sent = request.await.map_err(send_error)?;
+                self.message_refs.record_sent(&message, sent.id.0.to_string());
+            }
+
+            // Each attachment follows as a document
+            for attachment in &message.attachments {
+                let file = match &attachment.source {
+                    AttachmentSource::Bytes(bytes) => InputFile::memory(bytes.clone()),
+                    AttachmentSource::Url(url) => InputFile::url(url.parse().map_err(|e| {
+                        ChannelError::InvalidMessage(format!("Invalid attachment URL: {}", e))
+                    })?),
+                };
+                self.bot
+                    .send_document(ChatId(chat_id), file.file_name(attachment.file_name.clone()))
+                    .await
+                    .map_err(send_error)?;
+            }
 
             // Return a pending PCA (actual proof is generated by Gateway)
             Ok(ProofCarryingAction::pending())
         }
 
+        async fn send_batch(
+            &self,
+            messages: Vec<OutgoingMessage>,
+        ) -> Vec<Result<ProofCarryingAction, ChannelError>> {
+            crate::channels::common::send_pipelined(messages, |message| self.send(message)).await
+        }
+
         fn evaluate_permission(&self, _action: &Action, sender: &str) -> Confidence {
             if self.config.allowlist.contains(&sender.to_string()) {
                 Confidence::new(0.95)
@@ -291,5 +334,30 @@ mod implementation {
                 ChannelFeature::Voice => true,
             }
         }
+
+        async fn user_presence(&self, _user_id: &str) -> Result<Presence, ChannelError> {
+            // The Bot API has no presence endpoint
+            Ok(Presence::Unknown)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ContentHash;
+
+    #[test]
+    fn test_reply_carries_telegram_message_id() {
+        let refs = MessageRefs::default();
+        let incoming = ContentHash::from_bytes(b"telegram:100:42");
+        refs.record(incoming, "42");
+
+        let reply = OutgoingMessage::new("telegram", "100", "hi").reply_to(incoming);
+        assert_eq!(reply_to_message_id(&reply, &refs), Some(42));
+
+        let unknown = OutgoingMessage::new("telegram", "100", "hi").reply_to(ContentHash::from_bytes(b"other"));
+        assert_eq!(reply_to_message_id(&unknown, &refs), None);
+        assert_eq!(reply_to_message_id(&OutgoingMessage::new("telegram", "100", "hi"), &refs), None);
     }
 }
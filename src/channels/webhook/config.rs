@@ -0,0 +1,215 @@
+//! Webhook channel configuration.
+
+use serde::{Deserialize, Serialize};
+
+/// Which JSON fields of an inbound payload become message fields.
+///
+/// Fields are dotted paths into the payload, e.g. `"user.id"`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookFieldMapping {
+    /// Field holding the sender id.
+    #[serde(default = "default_sender_field")]
+    pub sender_id: String,
+
+    /// Field holding the message text.
+    #[serde(default = "default_content_field")]
+    pub content: String,
+
+    /// Field holding the platform's message id, used for deduplication.
+    #[serde(default)]
+    pub message_id: Option<String>,
+
+    /// Field holding the message time in Unix milliseconds.
+    #[serde(default)]
+    pub timestamp: Option<String>,
+}
+
+fn default_sender_field() -> String {
+    "sender".to_string()
+}
+
+fn default_content_field() -> String {
+    "text".to_string()
+}
+
+impl Default for WebhookFieldMapping {
+    fn default() -> Self {
+        Self {
+            sender_id: default_sender_field(),
+            content: default_content_field(),
+            message_id: None,
+            timestamp: None,
+        }
+    }
+}
+
+/// Configuration for the webhook channel.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// Channel name messages are tagged with.
+    #[serde(default = "default_name")]
+    pub name: String,
+
+    /// Address the inbound HTTP server binds to.
+    #[serde(default = "default_host")]
+    pub host: String,
+
+    /// Port the inbound HTTP server listens on (0 picks a free port).
+    #[serde(default = "default_port")]
+    pub port: u16,
+
+    /// Path inbound POSTs are accepted on.
+    #[serde(default = "default_path")]
+    pub path: String,
+
+    /// Shared secret for HMAC-SHA256 signatures in both directions.
+    pub secret: String,
+
+    /// Header carrying the `sha256=<hex>` signature.
+    #[serde(default = "default_signature_header")]
+    pub signature_header: String,
+
+    /// Header carrying the request time in Unix seconds, covered by the signature.
+    #[serde(default = "default_timestamp_header")]
+    pub timestamp_header: String,
+
+    /// How far a request's timestamp may be from now, in seconds.
+    /// Older requests are rejected as replays.
+    #[serde(default = "default_max_skew_secs")]
+    pub max_skew_secs: u64,
+
+    /// URL outgoing messages are POSTed to.
+    #[serde(default)]
+    pub callback_url: Option<String>,
+
+    /// Mapping from payload fields to message fields.
+    #[serde(default)]
+    pub fields: WebhookFieldMapping,
+
+    /// Allowlisted sender ids.
+    #[serde(default)]
+    pub allowlist: Vec<String>,
+}
+
+fn default_name() -> String {
+    "webhook".to_string()
+}
+
+fn default_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_port() -> u16 {
+    8090
+}
+
+fn default_path() -> String {
+    "/webhook".to_string()
+}
+
+fn default_signature_header() -> String {
+    "x-signature-256".to_string()
+}
+
+fn default_timestamp_header() -> String {
+    "x-webhook-timestamp".to_string()
+}
+
+fn default_max_skew_secs() -> u64 {
+    300
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            name: default_name(),
+            host: default_host(),
+            port: default_port(),
+            path: default_path(),
+            secret: String::new(),
+            signature_header: default_signature_header(),
+            timestamp_header: default_timestamp_header(),
+            max_skew_secs: default_max_skew_secs(),
+            callback_url: None,
+            fields: WebhookFieldMapping::default(),
+            allowlist: Vec::new(),
+        }
+    }
+}
+
+impl WebhookConfig {
+    /// Create a new config with the given shared secret.
+    pub fn new(secret: &str) -> Self {
+        Self {
+            secret: secret.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the channel name.
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Set the inbound server port.
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// Set the URL outgoing messages are POSTed to.
+    pub fn with_callback_url(mut self, url: &str) -> Self {
+        self.callback_url = Some(url.to_string());
+        self
+    }
+
+    /// Set the payload field mapping.
+    pub fn with_fields(mut self, fields: WebhookFieldMapping) -> Self {
+        self.fields = fields;
+        self
+    }
+
+    /// Set the sender allowlist.
+    pub fn with_allowlist(mut self, allowlist: Vec<String>) -> Self {
+        self.allowlist = allowlist;
+        self
+    }
+
+    /// Validate the configuration.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.secret.is_empty() {
+            return Err("Webhook secret is required".to_string());
+        }
+        if !self.path.starts_with('/') {
+            return Err("Webhook path must start with '/'".to_string());
+        }
+        if self.fields.sender_id.is_empty() || self.fields.content.is_empty() {
+            return Err("Webhook sender_id and content fields are required".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_from_json() {
+        let config: WebhookConfig = serde_json::from_value(serde_json::json!({
+            "secret": "s3cret",
+            "callback_url": "https://example.com/hook",
+            "fields": { "sender_id": "user.id", "content": "body" }
+        }))
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.name, "webhook");
+        assert_eq!(config.fields.sender_id, "user.id");
+        assert_eq!(config.fields.message_id, None);
+        assert_eq!(config.timestamp_header, "x-webhook-timestamp");
+        assert_eq!(config.max_skew_secs, 300);
+
+        assert!(WebhookConfig::default().validate().is_err());
+    }
+}
@@ -0,0 +1,433 @@
+//! Generic webhook channel for platforms without a dedicated connector.
+//!
+//! Inbound: an HTTP server accepts signed JSON POSTs and maps their fields
+//! to `IncomingMessage`s. Outbound: messages are POSTed as JSON to a
+//! callback URL, signed with the same shared secret. Signatures are
+//! HMAC-SHA256 over `<timestamp>.<raw body>`, sent as `sha256=<hex>`, with
+//! the Unix-seconds timestamp in its own header. Requests outside the
+//! configured window, or repeating a message id already seen, are rejected.
+
+mod config;
+
+pub use config::{WebhookConfig, WebhookFieldMapping};
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+
+use crate::channels::{Channel, ChannelFeature};
+use crate::channels::common::{hmac_sha256, verify_hmac_sha256};
+use crate::error::ChannelError;
+use crate::types::{
+    Action, Confidence, ContentHash, IncomingMessage, OutgoingMessage, ProofCarryingAction,
+};
+
+/// State shared with the inbound HTTP handler.
+struct WebhookInner {
+    config: WebhookConfig,
+    message_tx: mpsc::Sender<IncomingMessage>,
+    /// Message ids accepted within the replay window, with their request time.
+    seen: std::sync::Mutex<HashMap<String, i64>>,
+}
+
+/// Webhook channel implementation.
+pub struct WebhookChannel {
+    inner: Arc<WebhookInner>,
+    message_rx: Arc<Mutex<mpsc::Receiver<IncomingMessage>>>,
+}
+
+impl WebhookChannel {
+    /// Create a new webhook channel. Call [`Self::start`] to accept requests.
+    pub fn new(config: WebhookConfig) -> Result<Self, ChannelError> {
+        config.validate().map_err(ChannelError::ConnectionFailed)?;
+        let (tx, rx) = mpsc::channel(100);
+        Ok(Self {
+            inner: Arc::new(WebhookInner {
+                config,
+                message_tx: tx,
+                seen: std::sync::Mutex::new(HashMap::new()),
+            }),
+            message_rx: Arc::new(Mutex::new(rx)),
+        })
+    }
+
+    /// Start the inbound HTTP server, returning the address it listens on.
+    pub async fn start(&self) -> Result<SocketAddr, ChannelError> {
+        let config = &self.inner.config;
+        let listener = tokio::net::TcpListener::bind((config.host.as_str(), config.port))
+            .await
+            .map_err(|e| ChannelError::ConnectionFailed(e.to_string()))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|e| ChannelError::ConnectionFailed(e.to_string()))?;
+
+        let app = axum::Router::new()
+            .route(&config.path, axum::routing::post(handle_post))
+            .with_state(self.inner.clone());
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                tracing::error!("Webhook server stopped: {}", e);
+            }
+        });
+        tracing::info!("Webhook channel {} listening on {}", config.name, addr);
+        Ok(addr)
+    }
+
+    /// Verify and enqueue a raw inbound request body sent at `timestamp`
+    /// (Unix seconds, from the timestamp header).
+    pub async fn process_request(
+        &self,
+        body: &[u8],
+        timestamp: Option<&str>,
+        signature: Option<&str>,
+    ) -> Result<(), ChannelError> {
+        self.inner.process_request(body, timestamp, signature).await
+    }
+
+    /// Convert an inbound JSON payload to a message using the field mapping.
+    pub fn convert_event(&self, payload: &serde_json::Value) -> Result<IncomingMessage, ChannelError> {
+        self.inner.convert_event(payload)
+    }
+
+    /// The `sha256=<hex>` signature of `body` sent at `timestamp` under the
+    /// shared secret.
+    pub fn sign(&self, timestamp: i64, body: &[u8]) -> String {
+        let message = signed_message(&timestamp.to_string(), body);
+        format!("sha256={}", hex::encode(hmac_sha256(self.inner.config.secret.as_bytes(), &message)))
+    }
+}
+
+/// The bytes a signature covers: the timestamp header, a dot, then the body.
+fn signed_message(timestamp: &str, body: &[u8]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(timestamp.len() + 1 + body.len());
+    message.extend_from_slice(timestamp.as_bytes());
+    message.push(b'.');
+    message.extend_from_slice(body);
+    message
+}
+
+impl WebhookInner {
+    async fn process_request(
+        &self,
+        body: &[u8],
+        timestamp: Option<&str>,
+        signature: Option<&str>,
+    ) -> Result<(), ChannelError> {
+        let mac = self.verify_signature(body, timestamp, signature)?;
+        let sent_at = self.check_timestamp(timestamp)?;
+        let payload: serde_json::Value = serde_json::from_slice(body)
+            .map_err(|e| ChannelError::InvalidMessage(format!("Malformed webhook JSON: {}", e)))?;
+        let incoming = self.convert_event(&payload)?;
+
+        // Payloads without a message id are deduplicated by their signature
+        let replay_key = incoming.metadata["message_id"]
+            .as_str()
+            .map(|id| format!("id:{}", id))
+            .unwrap_or_else(|| format!("mac:{}", hex::encode(&mac)));
+        self.remember(replay_key, sent_at)?;
+
+        self.message_tx
+            .send(incoming)
+            .await
+            .map_err(|_| ChannelError::ChannelClosed)
+    }
+
+    /// Check the signature over timestamp and body, returning the MAC.
+    fn verify_signature(
+        &self,
+        body: &[u8],
+        timestamp: Option<&str>,
+        signature: Option<&str>,
+    ) -> Result<Vec<u8>, ChannelError> {
+        let timestamp = timestamp
+            .map(str::trim)
+            .ok_or_else(|| ChannelError::PermissionDenied("Missing webhook timestamp".to_string()))?;
+        let mac = signature
+            .and_then(|s| s.trim().strip_prefix("sha256="))
+            .and_then(|s| hex::decode(s).ok())
+            .ok_or_else(|| ChannelError::PermissionDenied("Missing or malformed webhook signature".to_string()))?;
+        if !verify_hmac_sha256(self.config.secret.as_bytes(), &signed_message(timestamp, body), &mac) {
+            return Err(ChannelError::PermissionDenied("Invalid webhook signature".to_string()));
+        }
+        Ok(mac)
+    }
+
+    /// Parse the signed timestamp and reject it if outside the replay window.
+    fn check_timestamp(&self, timestamp: Option<&str>) -> Result<i64, ChannelError> {
+        let sent_at: i64 = timestamp
+            .and_then(|t| t.trim().parse().ok())
+            .ok_or_else(|| ChannelError::PermissionDenied("Malformed webhook timestamp".to_string()))?;
+        let skew = chrono::Utc::now().timestamp().abs_diff(sent_at);
+        if skew > self.config.max_skew_secs {
+            return Err(ChannelError::PermissionDenied(format!(
+                "Webhook timestamp is {}s from now, outside the {}s window",
+                skew, self.config.max_skew_secs
+            )));
+        }
+        Ok(sent_at)
+    }
+
+    /// Record a request as seen, rejecting it if it already was. Entries
+    /// older than the window are forgotten; their timestamps fail anyway.
+    fn remember(&self, key: String, sent_at: i64) -> Result<(), ChannelError> {
+        let cutoff = chrono::Utc::now().timestamp() - self.config.max_skew_secs as i64;
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        seen.retain(|_, at| *at >= cutoff);
+        if seen.contains_key(&key) {
+            return Err(ChannelError::PermissionDenied("Replayed webhook request".to_string()));
+        }
+        seen.insert(key, sent_at);
+        Ok(())
+    }
+
+    fn convert_event(&self, payload: &serde_json::Value) -> Result<IncomingMessage, ChannelError> {
+        let fields = &self.config.fields;
+        let sender_id = field_string(payload, &fields.sender_id).ok_or_else(|| {
+            ChannelError::InvalidMessage(format!("Webhook payload missing '{}'", fields.sender_id))
+        })?;
+        let content = field_string(payload, &fields.content).ok_or_else(|| {
+            ChannelError::InvalidMessage(format!("Webhook payload missing '{}'", fields.content))
+        })?;
+
+        if !self.config.allowlist.is_empty() && !self.config.allowlist.contains(&sender_id) {
+            return Err(ChannelError::PermissionDenied("Sender not in allowlist".to_string()));
+        }
+
+        let message_id = fields.message_id.as_deref().and_then(|path| field_string(payload, path));
+        let id = match &message_id {
+            Some(message_id) => ContentHash::from_string(&format!("{}:{}", self.config.name, message_id)),
+            None => ContentHash::from_bytes(&serde_json::to_vec(payload).unwrap_or_default()),
+        };
+        let timestamp = fields
+            .timestamp
+            .as_deref()
+            .and_then(|path| field(payload, path))
+            .and_then(|value| value.as_u64())
+            .unwrap_or_else(|| chrono::Utc::now().timestamp_millis() as u64);
+
+        Ok(IncomingMessage {
+            id,
+            channel_id: self.config.name.clone(),
+            sender_id,
+            content,
+            timestamp,
+            attachments: Vec::new(),
+            metadata: serde_json::json!({
+                "message_id": message_id,
+                "payload": payload,
+            }),
+        })
+    }
+}
+
+/// Look up a dotted path such as `user.id` in a JSON payload.
+fn field<'a>(payload: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    path.split('.').try_fold(payload, |value, key| value.get(key))
+}
+
+/// A field as a string; numbers are accepted and formatted.
+fn field_string(payload: &serde_json::Value, path: &str) -> Option<String> {
+    match field(payload, path)? {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+async fn handle_post(
+    axum::extract::State(inner): axum::extract::State<Arc<WebhookInner>>,
+    headers: axum::http::HeaderMap,
+    body: axum::body::Bytes,
+) -> axum::http::StatusCode {
+    use axum::http::StatusCode;
+
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    let timestamp = header(&inner.config.timestamp_header);
+    let signature = header(&inner.config.signature_header);
+    match inner.process_request(&body, timestamp, signature).await {
+        Ok(()) => StatusCode::ACCEPTED,
+        Err(ChannelError::PermissionDenied(reason)) => {
+            tracing::warn!("Rejected webhook request: {}", reason);
+            StatusCode::UNAUTHORIZED
+        }
+        Err(ChannelError::InvalidMessage(reason)) => {
+            tracing::warn!("Rejected webhook request: {}", reason);
+            StatusCode::BAD_REQUEST
+        }
+        Err(_) => StatusCode::SERVICE_UNAVAILABLE,
+    }
+}
+
+#[async_trait]
+impl Channel for WebhookChannel {
+    fn name(&self) -> &str {
+        &self.inner.config.name
+    }
+
+    async fn receive(&self) -> Result<IncomingMessage, ChannelError> {
+        let mut rx = self.message_rx.lock().await;
+        rx.recv().await.ok_or(ChannelError::ChannelClosed)
+    }
+
+    async fn send(&self, message: OutgoingMessage) -> Result<ProofCarryingAction, ChannelError> {
+        crate::channels::check_attachments(self, &message)?;
+        let url = self.inner.config.callback_url.as_deref().ok_or_else(|| {
+            ChannelError::SendFailed("Webhook channel has no callback_url".to_string())
+        })?;
+        let body = serde_json::to_vec(&message).map_err(|e| ChannelError::SendFailed(e.to_string()))?;
+        let timestamp = chrono::Utc::now().timestamp();
+
+        let response = reqwest::Client::new()
+            .post(url)
+            .header("Content-Type", "application/json")
+            .header(self.inner.config.timestamp_header.as_str(), timestamp.to_string())
+            .header(self.inner.config.signature_header.as_str(), self.sign(timestamp, &body))
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| ChannelError::SendFailed(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(ChannelError::SendFailed(format!("Webhook callback error: {}", response.status())));
+        }
+        Ok(ProofCarryingAction::pending())
+    }
+
+    fn evaluate_permission(&self, _action: &Action, sender: &str) -> Confidence {
+        // Requests are authenticated by signature; the allowlist narrows senders
+        if self.inner.config.allowlist.iter().any(|allowed| allowed == sender) {
+            Confidence::new(0.9)
+        } else if self.inner.config.allowlist.is_empty() {
+            Confidence::new(0.5)
+        } else {
+            Confidence::none()
+        }
+    }
+
+    fn allowlist(&self) -> &[String] {
+        &self.inner.config.allowlist
+    }
+
+    fn supports(&self, _feature: ChannelFeature) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel() -> WebhookChannel {
+        let fields = WebhookFieldMapping {
+            sender_id: "user.id".to_string(),
+            content: "body".to_string(),
+            message_id: Some("event_id".to_string()),
+            timestamp: Some("sent_at".to_string()),
+        };
+        WebhookChannel::new(WebhookConfig::new("s3cret").with_name("forms").with_fields(fields)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_convert_signed_payload() {
+        let channel = channel();
+        let payload = serde_json::json!({
+            "event_id": 42,
+            "sent_at": 1700000000000u64,
+            "user": { "id": "u-7" },
+            "body": "/status",
+        });
+
+        let message = channel.convert_event(&payload).unwrap();
+        assert_eq!(message.channel_id, "forms");
+        assert_eq!(message.sender_id, "u-7");
+        assert_eq!(message.content, "/status");
+        assert_eq!(message.timestamp, 1700000000000);
+        assert_eq!(message.id, ContentHash::from_string("forms:42"));
+        assert!(channel.convert_event(&serde_json::json!({ "body": "hi" })).is_err());
+
+        let body = serde_json::to_vec(&payload).unwrap();
+        let now = chrono::Utc::now().timestamp();
+        let ts = now.to_string();
+        let signature = channel.sign(now, &body);
+        assert!(matches!(
+            channel.process_request(&body, Some(&ts), None).await,
+            Err(ChannelError::PermissionDenied(_))
+        ));
+        assert!(matches!(
+            channel.process_request(b"{}", Some(&ts), Some(&signature)).await,
+            Err(ChannelError::PermissionDenied(_))
+        ));
+        channel.process_request(&body, Some(&ts), Some(&signature)).await.unwrap();
+        assert_eq!(channel.receive().await.unwrap().content, "/status");
+    }
+
+    #[tokio::test]
+    async fn test_replayed_requests_rejected() {
+        let channel = channel();
+        let body = br#"{"event_id":"e-1","user":{"id":"u-7"},"body":"/deploy"}"#;
+        let now = chrono::Utc::now().timestamp();
+        let ts = now.to_string();
+        let signature = channel.sign(now, body);
+
+        channel.process_request(body, Some(&ts), Some(&signature)).await.unwrap();
+        assert_eq!(channel.receive().await.unwrap().content, "/deploy");
+
+        // The same signed request again
+        assert!(matches!(
+            channel.process_request(body, Some(&ts), Some(&signature)).await,
+            Err(ChannelError::PermissionDenied(_))
+        ));
+
+        // Re-signed under a new timestamp, but the message id was seen
+        let later = (now + 1).to_string();
+        assert!(matches!(
+            channel.process_request(body, Some(&later), Some(&channel.sign(now + 1, body))).await,
+            Err(ChannelError::PermissionDenied(_))
+        ));
+
+        // The signature binds the timestamp, so it cannot be swapped
+        let other = br#"{"event_id":"e-2","user":{"id":"u-7"},"body":"/deploy"}"#;
+        assert!(matches!(
+            channel.process_request(other, Some(&later), Some(&channel.sign(now, other))).await,
+            Err(ChannelError::PermissionDenied(_))
+        ));
+
+        // A request captured outside the window
+        let stale = now - 600;
+        assert!(matches!(
+            channel.process_request(other, Some(&stale.to_string()), Some(&channel.sign(stale, other))).await,
+            Err(ChannelError::PermissionDenied(_))
+        ));
+
+        // Without a message id, the exact signed request is still not accepted twice
+        let anonymous = br#"{"user":{"id":"u-7"},"body":"hi"}"#;
+        let signature = channel.sign(now, anonymous);
+        channel.process_request(anonymous, Some(&ts), Some(&signature)).await.unwrap();
+        assert!(channel.process_request(anonymous, Some(&ts), Some(&signature)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_http_server_accepts_only_signed_posts() {
+        let channel = WebhookChannel::new(WebhookConfig::new("s3cret").with_port(0)).unwrap();
+        let addr = channel.start().await.unwrap();
+        let url = format!("http://{}/webhook", addr);
+        let body = br#"{"sender":"alice","text":"hello"}"#.to_vec();
+        let client = reqwest::Client::new();
+
+        let unsigned = client.post(&url).body(body.clone()).send().await.unwrap();
+        assert_eq!(unsigned.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+        let now = chrono::Utc::now().timestamp();
+        let signed = client
+            .post(&url)
+            .header("x-webhook-timestamp", now.to_string())
+            .header("x-signature-256", channel.sign(now, &body))
+            .body(body)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(signed.status(), reqwest::StatusCode::ACCEPTED);
+        assert_eq!(channel.receive().await.unwrap().sender_id, "alice");
+    }
+}
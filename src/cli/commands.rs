@@ -0,0 +1,1024 @@
+//! CLI command implementations.
+//!
+//! Each command returns its rendered output as a `String` so that the
+//! binary only has to print it, and tests can assert on it directly.
+
+use std::path::{Path, PathBuf};
+use crate::channels::{ChannelState, ChannelStatus};
+use crate::error::{ConfigError, Error, GatewayError, ProofError, SkillError};
+use crate::gateway::{Gateway, ProofGenerator};
+use crate::skills::{
+    self, Op, SkillEntry, SkillGraph, SkillLoader, SkillRegistry, SkillVerifier, VerificationResult,
+};
+use crate::types::{Action, IncomingMessage, ProofCarryingAction};
+use super::{expand_path, ChannelConfig, Config};
+
+/// Output format for CLI commands.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text.
+    #[default]
+    Text,
+    /// Machine-readable JSON.
+    Json,
+}
+
+/// Get the installation directory that holds the config file.
+fn base_dir(config_path: &Path) -> PathBuf {
+    expand_path(config_path)
+        .parent()
+        .map(|p| p.to_path_buf())
+        .unwrap_or_default()
+}
+
+/// Get the skills directory for a config file.
+pub fn skills_dir(config_path: &Path) -> PathBuf {
+    base_dir(config_path).join("skills")
+}
+
+/// Open a skill registry with built-in skills and the skills listed in the config.
+///
+/// Skills that fail to load are logged and skipped. A missing config file
+/// means the default skills; an unreadable one is an error.
+pub fn open_registry(config_path: &Path) -> Result<SkillRegistry, Error> {
+    let config = match Config::load(config_path) {
+        Ok(config) => config,
+        Err(ConfigError::FileNotFound(_)) => Config::default(),
+        Err(e) => return Err(e.into()),
+    };
+    let base_dir = base_dir(config_path);
+
+    let mut registry = skills::create_registry(skills_dir(config_path))?;
+    let mut loader = SkillLoader::new(&base_dir);
+
+    for path in &config.skills {
+        let result = loader
+            .load_file(path)
+            .and_then(|graph| registry.install_graph(&graph.name.clone(), graph, false));
+        if let Err(e) = result {
+            tracing::warn!("Failed to load skill '{}': {}", path, e);
+        }
+    }
+
+    Ok(registry)
+}
+
+/// Render the configured channels and whether each is enabled.
+pub fn channel_list(config_path: &Path) -> Result<String, ConfigError> {
+    let config = Config::load(config_path)?;
+    if config.channels.is_empty() {
+        return Ok("No channels configured.\n".to_string());
+    }
+    Ok(config
+        .channels
+        .iter()
+        .map(|channel| format!("{:<12} {}\n", channel.channel_type, channel_setting(channel)))
+        .collect())
+}
+
+/// Render the state of one channel: its runtime state in the running
+/// gateway, or its configured setting when no gateway is running.
+pub async fn channel_status(config_path: &Path, name: &str) -> Result<String, Error> {
+    let config = Config::load(config_path)?;
+    let channel = find_channel(&config, name)?;
+    let state = match admin_request(&config, reqwest::Method::GET, "/channels").await? {
+        AdminResponse::Ok(statuses) => {
+            let statuses: Vec<ChannelStatus> = serde_json::from_value(statuses)?;
+            match statuses.into_iter().find(|status| status.name == name) {
+                Some(status) => {
+                    let mut state = match status.state {
+                        ChannelState::Connected => "connected".to_string(),
+                        ChannelState::Disconnected => "disconnected".to_string(),
+                    };
+                    if let Some(reason) = status.last_disconnect_reason {
+                        state.push_str(&format!(" (last disconnect: {})", reason));
+                    }
+                    state
+                }
+                None => format!("{}, not loaded by the running gateway", channel_setting(channel)),
+            }
+        }
+        AdminResponse::Rejected(_, message) => return Err(GatewayError::ServerError(message).into()),
+        AdminResponse::NotRunning => format!("{}, gateway not running", channel_setting(channel)),
+    };
+    Ok(format!(
+        "Channel: {}\nState: {}\nAllowlist: {} entries\n",
+        channel.channel_type,
+        state,
+        channel.allowlist.len()
+    ))
+}
+
+fn channel_setting(channel: &ChannelConfig) -> &'static str {
+    if channel.enabled { "enabled" } else { "disabled" }
+}
+
+fn find_channel<'a>(config: &'a Config, name: &str) -> Result<&'a ChannelConfig, ConfigError> {
+    config
+        .channels
+        .iter()
+        .find(|channel| channel.channel_type == name)
+        .ok_or_else(|| ConfigError::InvalidValue {
+            key: "channels".to_string(),
+            reason: format!("channel '{}' is not configured", name),
+        })
+}
+
+/// Reply of the running gateway's admin API.
+enum AdminResponse {
+    /// The request succeeded, with the JSON body
+    Ok(serde_json::Value),
+    /// The gateway refused the request
+    Rejected(reqwest::StatusCode, String),
+    /// No gateway is listening at the configured address
+    NotRunning,
+}
+
+/// Call the admin API of the gateway at the configured address.
+async fn admin_request(config: &Config, method: reqwest::Method, path: &str) -> Result<AdminResponse, Error> {
+    let url = format!("http://{}:{}{}", config.gateway.bind, config.gateway.port, path);
+    let mut request = reqwest::Client::new()
+        .request(method, &url)
+        .timeout(std::time::Duration::from_secs(10));
+    if let Some(token) = &config.gateway.admin_token {
+        request = request.bearer_auth(token);
+    }
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) if e.is_connect() => return Ok(AdminResponse::NotRunning),
+        Err(e) => return Err(GatewayError::ServerError(format!("{}: {}", url, e)).into()),
+    };
+    let status = response.status();
+    if !status.is_success() {
+        let message = response.text().await.unwrap_or_default();
+        return Ok(AdminResponse::Rejected(status, format!("{} {}: {}", status, url, message)));
+    }
+    let body = response
+        .json()
+        .await
+        .map_err(|e| GatewayError::ServerError(format!("{}: {}", url, e)))?;
+    Ok(AdminResponse::Ok(body))
+}
+
+/// Enable a channel in the config, adding it if needed, and connect it in
+/// the running gateway.
+///
+/// Without a running gateway, the channel connects when the gateway starts.
+pub async fn channel_connect(config_path: &Path, channel_type: &str) -> Result<String, Error> {
+    let mut config = match Config::load(config_path) {
+        Ok(config) => config,
+        Err(ConfigError::FileNotFound(_)) => Config::default(),
+        Err(e) => return Err(e.into()),
+    };
+    match config.channels.iter_mut().find(|channel| channel.channel_type == channel_type) {
+        Some(channel) => channel.enabled = true,
+        None => config.channels.push(ChannelConfig {
+            channel_type: channel_type.to_string(),
+            enabled: true,
+            token: None,
+            allowlist: Vec::new(),
+        }),
+    }
+    if let Some(parent) = expand_path(config_path).parent() {
+        std::fs::create_dir_all(parent).map_err(|e| ConfigError::ParseError(e.to_string()))?;
+    }
+    config.save(config_path)?;
+
+    let path = format!("/channels/{}/connect", channel_type);
+    Ok(match admin_request(&config, reqwest::Method::POST, &path).await? {
+        AdminResponse::Ok(_) => format!("Channel {} connected.\n", channel_type),
+        AdminResponse::Rejected(reqwest::StatusCode::CONFLICT, _) => {
+            format!("Channel {} is already connected.\n", channel_type)
+        }
+        AdminResponse::Rejected(reqwest::StatusCode::NOT_FOUND, _) => format!(
+            "Channel {} enabled; the running gateway has not loaded it and connects it on restart.\n",
+            channel_type
+        ),
+        AdminResponse::Rejected(_, message) => return Err(GatewayError::ServerError(message).into()),
+        AdminResponse::NotRunning => {
+            format!("Channel {} enabled; it connects when the gateway starts.\n", channel_type)
+        }
+    })
+}
+
+/// Disable a configured channel, keeping its settings, and disconnect it
+/// in the running gateway.
+pub async fn channel_disconnect(config_path: &Path, name: &str) -> Result<String, Error> {
+    let mut config = Config::load(config_path)?;
+    find_channel(&config, name)?;
+    if let Some(channel) = config.channels.iter_mut().find(|channel| channel.channel_type == name) {
+        channel.enabled = false;
+    }
+    config.save(config_path)?;
+
+    let path = format!("/channels/{}/disconnect", name);
+    Ok(match admin_request(&config, reqwest::Method::POST, &path).await? {
+        AdminResponse::Ok(_) => format!("Channel {} disconnected.\n", name),
+        AdminResponse::Rejected(reqwest::StatusCode::CONFLICT | reqwest::StatusCode::NOT_FOUND, _) => {
+            format!("Channel {} disabled; it was not connected.\n", name)
+        }
+        AdminResponse::Rejected(_, message) => return Err(GatewayError::ServerError(message).into()),
+        AdminResponse::NotRunning => format!("Channel {} disabled.\n", name),
+    })
+}
+
+/// Render detailed information about a skill.
+///
+/// The skill is resolved by name, full content hash, or hash prefix.
+pub fn skill_info(
+    registry: &SkillRegistry,
+    skill: &str,
+    format: OutputFormat,
+) -> Result<String, SkillError> {
+    let entry = registry
+        .resolve(skill)
+        .ok_or_else(|| SkillError::NotFound(skill.to_string()))?;
+
+    let verification = SkillVerifier::verify(&entry.graph)?;
+    let warnings: Vec<String> = verification.warnings.iter().map(|w| w.to_string()).collect();
+    let complexity = entry.graph.complexity();
+    let metadata = &entry.metadata;
+
+    if format == OutputFormat::Json {
+        let info = serde_json::json!({
+            "name": metadata.name,
+            "version": metadata.version,
+            "author": metadata.author,
+            "description": metadata.description,
+            "permissions": metadata.permissions,
+            "inputs": metadata.inputs,
+            "outputs": metadata.outputs,
+            "timeout_ms": metadata.timeout_ms,
+            "hash": entry.hash.to_hex(),
+            "verified": entry.verified,
+            "builtin": entry.builtin,
+            "warnings": warnings,
+            "complexity": complexity,
+        });
+        return serde_json::to_string_pretty(&info)
+            .map_err(|e| SkillError::ExecutionFailed(e.to_string()));
+    }
+
+    let mut out = String::new();
+    out.push_str(&format!("Skill:       {}\n", metadata.name));
+    out.push_str(&format!("Version:     {}\n", metadata.version));
+    out.push_str(&format!(
+        "Author:      {}\n",
+        metadata.author.as_deref().unwrap_or("unknown")
+    ));
+    out.push_str(&format!("Description: {}\n", metadata.description));
+    if let Some(timeout_ms) = metadata.timeout_ms {
+        out.push_str(&format!("Timeout:     {}ms\n", timeout_ms));
+    }
+    out.push_str(&format!("Hash:        {}\n", entry.hash.to_hex()));
+    out.push_str(&format!(
+        "Status:      {}{}\n",
+        if entry.verified { "verified" } else { "unverified" },
+        if entry.builtin { " (built-in)" } else { "" }
+    ));
+
+    out.push_str("\nPermissions:\n");
+    if metadata.permissions.is_empty() {
+        out.push_str("  (none)\n");
+    }
+    for permission in &metadata.permissions {
+        out.push_str(&format!("  - {}\n", permission));
+    }
+
+    out.push_str("\nInputs:\n");
+    if metadata.inputs.is_empty() {
+        out.push_str("  (none)\n");
+    }
+    for input in &metadata.inputs {
+        out.push_str(&format!(
+            "  - {}: {}{}",
+            input.name,
+            input.tensor_type,
+            if input.required { "" } else { " (optional)" }
+        ));
+        if !input.description.is_empty() {
+            out.push_str(&format!(" - {}", input.description));
+        }
+        out.push('\n');
+    }
+
+    out.push_str("\nOutputs:\n");
+    if metadata.outputs.is_empty() {
+        out.push_str("  (none)\n");
+    }
+    for output in &metadata.outputs {
+        out.push_str(&format!("  - {}: {}", output.name, output.tensor_type));
+        if !output.description.is_empty() {
+            out.push_str(&format!(" - {}", output.description));
+        }
+        out.push('\n');
+    }
+
+    out.push_str("\nComplexity:\n");
+    out.push_str(&format!("  Nodes:       {}\n", complexity.node_count));
+    out.push_str(&format!("  Inputs:      {}\n", complexity.input_count));
+    out.push_str(&format!("  Operations:  {}\n", complexity.operation_count));
+    out.push_str(&format!("  Externals:   {}\n", complexity.external_count));
+    out.push_str(&format!("  Edges:       {}\n", complexity.edge_count));
+    out.push_str(&format!("  Max depth:   {}\n", complexity.max_depth));
+
+    if !warnings.is_empty() {
+        out.push_str("\nWarnings:\n");
+        for warning in &warnings {
+            out.push_str(&format!("  - {}\n", warning));
+        }
+    }
+
+    Ok(out)
+}
+
+/// List installed skills, ranked by relevance when a query is given.
+pub fn skill_list(registry: &SkillRegistry, query: Option<&str>) -> String {
+    let entries: Vec<&SkillEntry> = match query {
+        Some(query) => registry.search(query).into_iter().map(|(entry, _)| entry).collect(),
+        None => {
+            let mut entries = registry.list();
+            entries.sort_by(|a, b| a.metadata.name.cmp(&b.metadata.name));
+            entries
+        }
+    };
+
+    if entries.is_empty() {
+        return match query {
+            Some(query) => format!("No skills match '{}'\n", query),
+            None => "No skills installed\n".to_string(),
+        };
+    }
+
+    let width = entries.iter().map(|e| e.metadata.name.len()).max().unwrap_or(0);
+    entries
+        .iter()
+        .map(|e| format!("{:width$}  {}\n", e.metadata.name, e.metadata.description, width = width))
+        .collect()
+}
+
+/// Process one message through an ephemeral gateway and render the reply.
+///
+/// Non-message actions render as their action type.
+pub async fn ask(message: &str) -> Result<String, GatewayError> {
+    let gateway = Gateway::new()?;
+    let pca = gateway.process_message(IncomingMessage::new("cli", "cli", message)).await?;
+    Ok(match pca.action {
+        Action::SendMessage(reply) => reply.content,
+        other => other.action_type().to_string(),
+    })
+}
+
+/// Verify a skill file, or an installed skill by name or hash.
+pub fn skill_verify(registry: &SkillRegistry, skill: &str) -> Result<VerificationResult, SkillError> {
+    let path = Path::new(skill);
+    let graph = if path.is_file() {
+        SkillLoader::new(".").with_verify(false).load_file(path)?
+    } else {
+        registry
+            .resolve(skill)
+            .ok_or_else(|| SkillError::NotFound(skill.to_string()))?
+            .graph
+            .clone()
+    };
+    SkillVerifier::verify(&graph)
+}
+
+/// Check whether a skill file could be installed, without installing it.
+pub fn skill_check(registry: &SkillRegistry, source: &str) -> Result<VerificationResult, SkillError> {
+    let graph = SkillLoader::new(".").with_verify(false).load_file(Path::new(source))?;
+    registry.check_install(&graph.name, &graph)
+}
+
+/// Render a verification result.
+pub fn render_verification(result: &VerificationResult, format: OutputFormat) -> Result<String, SkillError> {
+    if format == OutputFormat::Json {
+        return serde_json::to_string_pretty(result)
+            .map(|json| json + "\n")
+            .map_err(|e| SkillError::ExecutionFailed(e.to_string()));
+    }
+
+    let mut out = format!("Verification: {}\n", if result.safe { "safe" } else { "unsafe" });
+    for error in &result.errors {
+        out.push_str(&format!("  error:   {}\n", error));
+    }
+    for warning in &result.warnings {
+        out.push_str(&format!("  warning: {}\n", warning));
+    }
+    Ok(out)
+}
+
+/// Outcome of verifying a serialized PCA.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PcaVerification {
+    /// Whether the signature verified under a trusted key
+    pub passed: bool,
+    /// Type of the action the PCA carries
+    pub action_type: String,
+    /// Confidence recorded in the PCA
+    pub confidence: f32,
+    /// When the PCA was created (Unix timestamp ms)
+    pub timestamp: u64,
+    /// Number of entries in the decision trace
+    pub trace_len: usize,
+    /// Where the verifying public key came from
+    pub key_source: KeySource,
+    /// SHA-256 fingerprint of the verifying public key
+    pub key_fingerprint: String,
+    /// Why verification failed, if it did
+    pub failure: Option<String>,
+}
+
+impl PcaVerification {
+    /// Whether the signature verified under a trusted key.
+    pub fn passed(&self) -> bool {
+        self.passed
+    }
+}
+
+/// Where `pca_verify` found the public key.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case", tag = "kind", content = "path")]
+pub enum KeySource {
+    /// `--public-key` or `--pubkey`
+    Argument,
+    /// The keypair configured for the gateway
+    Configured(PathBuf),
+    /// A `<pca_file>.pub` next to the PCA
+    Sibling(PathBuf),
+    /// A `public_key` field in the PCA itself
+    Embedded,
+}
+
+impl KeySource {
+    /// Whether the key was chosen by the verifier rather than shipped with
+    /// the PCA. Anyone can sign a forged PCA and ship their own key with it.
+    pub fn is_trusted(&self) -> bool {
+        matches!(self, Self::Argument | Self::Configured(_))
+    }
+}
+
+impl std::fmt::Display for KeySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Argument => write!(f, "command line"),
+            Self::Configured(path) => write!(f, "configured keypair {}", path.display()),
+            Self::Sibling(path) => write!(f, "{} (untrusted)", path.display()),
+            Self::Embedded => write!(f, "embedded in the PCA (untrusted)"),
+        }
+    }
+}
+
+/// A public key given on the command line.
+#[derive(Debug, Clone, Copy)]
+pub enum PublicKeyArg<'a> {
+    /// File holding the key as hex or as 32 raw bytes
+    File(&'a Path),
+    /// Hex-encoded key
+    Hex(&'a str),
+}
+
+/// Verify a PCA JSON file.
+///
+/// The public key is taken from `public_key` if given, else from the
+/// keypair configured in `config_path`, else from a sibling
+/// `<pca_file>.pub`, else from a `public_key` hex field embedded in the
+/// file. Only the first two can make the result pass: a key shipped with
+/// the PCA proves nothing about who signed it. Unreadable input or config
+/// is an error; a bad signature is a failed verification.
+pub fn pca_verify(
+    pca_file: &Path,
+    public_key: Option<PublicKeyArg>,
+    config_path: Option<&Path>,
+) -> Result<PcaVerification, ProofError> {
+    let content = std::fs::read_to_string(pca_file).map_err(|e| {
+        ProofError::VerificationFailed(format!("Cannot read {}: {}", pca_file.display(), e))
+    })?;
+    let mut json: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| ProofError::VerificationFailed(format!("Malformed PCA JSON: {}", e)))?;
+    let embedded_key = json
+        .as_object_mut()
+        .and_then(|fields| fields.remove("public_key"))
+        .and_then(|key| key.as_str().map(str::to_string));
+    let pca = ProofCarryingAction::from_json(&json.to_string())?;
+
+    let mut sibling = pca_file.as_os_str().to_owned();
+    sibling.push(".pub");
+    let sibling = PathBuf::from(sibling);
+    let (key, key_source) = match public_key {
+        Some(PublicKeyArg::File(path)) => (read_public_key(path)?, KeySource::Argument),
+        Some(PublicKeyArg::Hex(hex_key)) => (decode_public_key(hex_key)?, KeySource::Argument),
+        None => match configured_keypair(config_path)? {
+            Some(path) => (ProofGenerator::from_file(&path)?.public_key_bytes(), KeySource::Configured(path)),
+            None if sibling.is_file() => (read_public_key(&sibling)?, KeySource::Sibling(sibling)),
+            None => match embedded_key {
+                Some(hex_key) => (decode_public_key(&hex_key)?, KeySource::Embedded),
+                None => {
+                    return Err(ProofError::VerificationFailed(format!(
+                        "No public key: pass --public-key or --pubkey, or configure a keypair \
+                         (a key in {} or embedded in the PCA is checked but not trusted)",
+                        sibling.display()
+                    )))
+                }
+            },
+        },
+    };
+
+    let failure = ProofGenerator::verify_with_public_key(&pca, &key).err().map(|e| e.to_string());
+    Ok(PcaVerification {
+        passed: failure.is_none() && key_source.is_trusted(),
+        action_type: pca.action.action_type().to_string(),
+        confidence: pca.confidence.value(),
+        timestamp: pca.timestamp,
+        trace_len: pca.decision_trace.len(),
+        key_source,
+        key_fingerprint: key_fingerprint(&key),
+        failure,
+    })
+}
+
+/// The keypair file configured in `config_path`, if it exists. A missing
+/// config file means no keypair; an unparsable one is an error.
+fn configured_keypair(config_path: Option<&Path>) -> Result<Option<PathBuf>, ProofError> {
+    let Some(config_path) = config_path else {
+        return Ok(None);
+    };
+    let config = match Config::load(config_path) {
+        Ok(config) => config,
+        Err(ConfigError::FileNotFound(_)) => return Ok(None),
+        Err(e) => {
+            return Err(ProofError::VerificationFailed(format!(
+                "Cannot load config {}: {}",
+                config_path.display(),
+                e
+            )))
+        }
+    };
+    let keypair = expand_path(Path::new(&config.gateway.keypair_path));
+    Ok(keypair.is_file().then_some(keypair))
+}
+
+/// `sha256:` and the first 16 bytes of the key's SHA-256, in hex.
+fn key_fingerprint(key: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    format!("sha256:{}", hex::encode(&Sha256::digest(key)[..16]))
+}
+
+/// Read a public key file holding hex or raw key bytes.
+fn read_public_key(path: &Path) -> Result<Vec<u8>, ProofError> {
+    let bytes = std::fs::read(path).map_err(|e| {
+        ProofError::VerificationFailed(format!("Cannot read public key {}: {}", path.display(), e))
+    })?;
+    if bytes.len() == 32 {
+        return Ok(bytes);
+    }
+    decode_public_key(String::from_utf8_lossy(&bytes).trim())
+}
+
+fn decode_public_key(hex_key: &str) -> Result<Vec<u8>, ProofError> {
+    hex::decode(hex_key.trim())
+        .map_err(|e| ProofError::VerificationFailed(format!("Public key is not valid hex: {}", e)))
+}
+
+/// Render a PCA verification as `PASS`/`FAIL` with the action type, confidence,
+/// timestamp and trace length.
+pub fn render_pca_verification(result: &PcaVerification, format: OutputFormat) -> Result<String, ProofError> {
+    if format == OutputFormat::Json {
+        return serde_json::to_string_pretty(result)
+            .map(|json| json + "\n")
+            .map_err(|e| ProofError::VerificationFailed(e.to_string()));
+    }
+
+    let verdict = match (&result.failure, result.passed()) {
+        (_, true) => "PASS",
+        (None, false) => "UNTRUSTED",
+        (Some(_), false) => "FAIL",
+    };
+    let mut out = format!("{}\n", verdict);
+    out.push_str(&format!("  action:     {}\n", result.action_type));
+    out.push_str(&format!("  confidence: {:.2}\n", result.confidence));
+    let timestamp = chrono::DateTime::from_timestamp_millis(result.timestamp as i64)
+        .map(|time| time.to_rfc3339())
+        .unwrap_or_else(|| result.timestamp.to_string());
+    out.push_str(&format!("  timestamp:  {}\n", timestamp));
+    out.push_str(&format!("  trace:      {} entries\n", result.trace_len));
+    out.push_str(&format!("  key:        {}\n", result.key_source));
+    out.push_str(&format!("  key id:     {}\n", result.key_fingerprint));
+    if let Some(failure) = &result.failure {
+        out.push_str(&format!("  reason:     {}\n", failure));
+    } else if !result.key_source.is_trusted() {
+        out.push_str("  reason:     signature matches a key shipped with the PCA; pass --pubkey or configure a keypair\n");
+    }
+    Ok(out)
+}
+
+/// Build a minimal skill graph: the given inputs, one Identity operation
+/// per output reading the first input, and the declared outputs.
+///
+/// Inputs are given as `name` or `name:type` (type defaults to `string`).
+pub fn scaffold_skill(name: &str, inputs: &[String], outputs: &[String]) -> Result<SkillGraph, SkillError> {
+    let is_identifier = |s: &str| {
+        !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    };
+    if !is_identifier(name) {
+        return Err(SkillError::InvalidGraph(format!("Invalid skill name '{}'", name)));
+    }
+
+    let mut inputs: Vec<(String, String)> = inputs
+        .iter()
+        .map(|spec| {
+            let (input, tensor_type) = spec.split_once(':').unwrap_or((spec, "string"));
+            if !is_identifier(input) || !is_identifier(tensor_type) {
+                return Err(SkillError::InvalidGraph(format!("Invalid input spec '{}'", spec)));
+            }
+            Ok((input.to_string(), tensor_type.to_string()))
+        })
+        .collect::<Result<_, _>>()?;
+    if inputs.is_empty() {
+        inputs.push(("message".to_string(), "string".to_string()));
+    }
+
+    let outputs: Vec<String> = if outputs.is_empty() {
+        vec!["output".to_string()]
+    } else {
+        outputs.to_vec()
+    };
+
+    let mut builder = SkillGraph::builder(name)
+        .description(&format!("{} skill", name));
+    for (input, tensor_type) in &inputs {
+        builder = builder.add_input(input, tensor_type);
+    }
+    for output in &outputs {
+        if !is_identifier(output) || inputs.iter().any(|(input, _)| input == output) {
+            return Err(SkillError::InvalidGraph(format!("Invalid output name '{}'", output)));
+        }
+        builder = builder
+            .add_operation(output, Op::Identity, vec![inputs[0].0.as_str()])
+            .output(output);
+    }
+
+    Ok(builder.entry_point(&inputs[0].0).build())
+}
+
+/// Render a scaffolded skill graph in `.0` format.
+fn render_scaffold(graph: &SkillGraph) -> Result<String, SkillError> {
+    let mut out = String::new();
+    out.push_str(&format!("# {} skill\n#\n", graph.name));
+    out.push_str("# Scaffolded by `zero-openclaw skill new`. Replace the Identity\n");
+    out.push_str("# operations with the skill's logic, then install it.\n\n");
+    out.push_str(&graph.to_zero_source()?);
+    Ok(out)
+}
+
+/// Scaffold a new skill and write it to `<skills_dir>/<name>.0`.
+///
+/// The scaffold is verified before it is written. Existing files are not
+/// overwritten.
+pub fn skill_new(
+    skills_dir: &Path,
+    name: &str,
+    inputs: &[String],
+    outputs: &[String],
+) -> Result<PathBuf, SkillError> {
+    let graph = scaffold_skill(name, inputs, outputs)?;
+
+    let result = SkillVerifier::verify(&graph)?;
+    if !result.safe {
+        return Err(SkillError::VerificationFailed(
+            result.errors.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("; ")
+        ));
+    }
+
+    let path = skills_dir.join(format!("{}.0", name));
+    if path.exists() {
+        return Err(SkillError::AlreadyInstalled(path.display().to_string()));
+    }
+
+    let source = render_scaffold(&graph)?;
+    std::fs::create_dir_all(skills_dir)
+        .and_then(|_| std::fs::write(&path, source))
+        .map_err(|e| SkillError::ExecutionFailed(format!("{}: {}", path.display(), e)))?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::skills::{SkillMetadata, SkillNode};
+
+    fn rich_registry() -> SkillRegistry {
+        let graph = SkillGraph::builder("weather")
+            .description("Look up the weather")
+            .add_input("city", "string")
+            .add_input("units", "string")
+            .add_operation("result", Op::StringConcat, vec!["city", "units"])
+            .output("result")
+            .permission("network")
+            .build();
+
+        let metadata = SkillMetadata::new("weather", "Look up the weather")
+            .with_version("1.2.0")
+            .with_author("Weather Team")
+            .with_permission("network")
+            .with_permission("location")
+            .with_input("city", "City to look up", "string", true)
+            .with_input("units", "Metric or imperial", "string", false)
+            .with_output("result", "Forecast summary", "string");
+
+        let mut registry = SkillRegistry::new("/tmp/skills");
+        registry.install_with_metadata(graph, metadata, false).unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_skill_info_text() {
+        let registry = rich_registry();
+        let info = skill_info(&registry, "weather", OutputFormat::Text).unwrap();
+
+        assert!(info.contains("Weather Team"));
+        assert!(info.contains("network"));
+        assert!(info.contains("location"));
+        assert!(info.contains("city: string"));
+        assert!(info.contains("units: string (optional)"));
+        assert!(info.contains("Max depth:   1"));
+    }
+
+    #[test]
+    fn test_skill_info_json_by_hash_prefix() {
+        let registry = rich_registry();
+        let hash = registry.get_by_name("weather").unwrap().hash.to_hex();
+        let info = skill_info(&registry, &hash[..12], OutputFormat::Json).unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&info).unwrap();
+        assert_eq!(json["hash"], hash);
+        assert_eq!(json["permissions"], serde_json::json!(["network", "location"]));
+        assert_eq!(json["inputs"][0]["name"], "city");
+        assert_eq!(json["inputs"][1]["name"], "units");
+        assert_eq!(json["complexity"]["operation_count"], 1);
+    }
+
+    #[test]
+    fn test_skill_verify_json() {
+        let registry = rich_registry();
+        let result = skill_verify(&registry, "weather").unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&render_verification(&result, OutputFormat::Json).unwrap()).unwrap();
+
+        assert_eq!(json["safe"], result.safe);
+        assert_eq!(json["warnings"].as_array().unwrap().len(), result.warnings.len());
+        assert!(render_verification(&result, OutputFormat::Text).unwrap().starts_with("Verification:"));
+    }
+
+    #[test]
+    fn test_skill_list_query() {
+        let registry = rich_registry();
+        assert!(skill_list(&registry, None).starts_with("weather  Look up the weather"));
+        assert!(skill_list(&registry, Some("wthr")).contains("weather"));
+        assert_eq!(skill_list(&registry, Some("zzz")), "No skills match 'zzz'\n");
+    }
+
+    #[test]
+    fn test_skill_info_not_found() {
+        let registry = rich_registry();
+        assert!(matches!(
+            skill_info(&registry, "missing", OutputFormat::Text),
+            Err(SkillError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_skill_new_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let inputs = vec!["query:string".to_string(), "limit:i64".to_string()];
+        let outputs = vec!["result".to_string()];
+
+        let path = skill_new(dir.path(), "lookup", &inputs, &outputs).unwrap();
+        assert_eq!(path, dir.path().join("lookup.0"));
+
+        let mut loader = SkillLoader::new(dir.path());
+        let graph = loader.load_file(&path).unwrap();
+        assert_eq!(graph.name, "lookup");
+        assert_eq!(graph.outputs, vec!["result"]);
+        assert!(graph.nodes.iter().any(|n| matches!(
+            n,
+            SkillNode::Input { name, tensor_type, .. } if name == "limit" && tensor_type == "i64"
+        )));
+        assert!(SkillVerifier::verify(&graph).unwrap().safe);
+
+        // Refuses to overwrite
+        assert!(matches!(
+            skill_new(dir.path(), "lookup", &inputs, &outputs),
+            Err(SkillError::AlreadyInstalled(_))
+        ));
+    }
+
+    #[test]
+    fn test_scaffold_rejects_invalid_specs() {
+        assert!(scaffold_skill("bad name", &[], &[]).is_err());
+        assert!(scaffold_skill("ok", &["in:".to_string()], &[]).is_err());
+        assert!(scaffold_skill("ok", &["message".to_string()], &["message".to_string()]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ask_help() {
+        let reply = ask("/help").await.unwrap();
+        assert!(reply.starts_with("Available commands:"));
+        assert!(reply.contains("/help - Show this help message"));
+    }
+
+    async fn signed_pca(generator: &ProofGenerator) -> ProofCarryingAction {
+        let action = Action::SendMessage(crate::types::OutgoingMessage::new("test", "user", "hi"));
+        let mut trace = crate::gateway::ExecutionTrace::new().with_confidence(0.9);
+        trace.add_node(crate::types::ContentHash::from_string("skill:test"));
+        generator
+            .generate(action, crate::types::ContentHash::zero(), crate::types::ContentHash::zero(), vec![trace])
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_pca_verify_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let generator = ProofGenerator::new_random();
+        let pca = signed_pca(&generator).await;
+        let path = dir.path().join("action.json");
+        std::fs::write(&path, serde_json::to_string(&pca).unwrap()).unwrap();
+
+        // No key anywhere
+        let err = pca_verify(&path, None, None).unwrap_err();
+        assert!(err.to_string().contains("No public key"));
+
+        // An explicit key
+        let public_key = hex::encode(generator.public_key_bytes());
+        let result = pca_verify(&path, Some(PublicKeyArg::Hex(&public_key)), None).unwrap();
+        assert!(result.passed());
+        let out = render_pca_verification(&result, OutputFormat::Text).unwrap();
+        assert!(out.starts_with("PASS\n"));
+        assert!(out.contains("action:     SendMessage"));
+        assert!(out.contains("key:        command line"));
+        assert!(out.contains(&format!("key id:     {}", key_fingerprint(&generator.public_key_bytes()))));
+        let json: serde_json::Value =
+            serde_json::from_str(&render_pca_verification(&result, OutputFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["passed"], true);
+
+        // A sibling key file verifies the signature but is not trusted
+        std::fs::write(dir.path().join("action.json.pub"), &public_key).unwrap();
+        let result = pca_verify(&path, None, None).unwrap();
+        assert!(result.failure.is_none());
+        assert!(!result.passed());
+        assert!(render_pca_verification(&result, OutputFormat::Text).unwrap().starts_with("UNTRUSTED\n"));
+        let json: serde_json::Value =
+            serde_json::from_str(&render_pca_verification(&result, OutputFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["passed"], false);
+        std::fs::remove_file(dir.path().join("action.json.pub")).unwrap();
+
+        // A forged PCA signed with the key it embeds does not pass
+        let forger = ProofGenerator::new_random();
+        let mut json = serde_json::to_value(signed_pca(&forger).await).unwrap();
+        json["public_key"] = hex::encode(forger.public_key_bytes()).into();
+        std::fs::write(&path, json.to_string()).unwrap();
+        let result = pca_verify(&path, None, None).unwrap();
+        assert_eq!(result.key_source, KeySource::Embedded);
+        assert!(!result.passed());
+        let out = render_pca_verification(&result, OutputFormat::Text).unwrap();
+        assert!(out.starts_with("UNTRUSTED\n"));
+        assert!(out.contains("key:        embedded in the PCA (untrusted)"));
+
+        // ...and an explicit key takes precedence over the embedded one
+        let result = pca_verify(&path, Some(PublicKeyArg::Hex(&public_key)), None).unwrap();
+        assert!(result.failure.is_some());
+        assert!(render_pca_verification(&result, OutputFormat::Text).unwrap().starts_with("FAIL\n"));
+
+        // Truncated signature
+        let mut json = serde_json::to_value(&pca).unwrap();
+        json["signature"] = hex::encode(&pca.signature[..10]).into();
+        std::fs::write(&path, json.to_string()).unwrap();
+        let result = pca_verify(&path, Some(PublicKeyArg::Hex(&public_key)), None).unwrap();
+        assert!(result.failure.unwrap().contains("expected 64 bytes, found 10"));
+
+        // Malformed JSON
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(pca_verify(&path, None, None).unwrap_err().to_string().contains("Malformed PCA JSON"));
+    }
+
+    /// Config at `dir` pointing the CLI at `port`, with `admin_token`.
+    fn channel_config(dir: &Path, port: u16, admin_token: Option<&str>) -> PathBuf {
+        let mut config = Config::default_config();
+        config.gateway.port = port;
+        config.gateway.admin_token = admin_token.map(str::to_string);
+        let config_path = dir.join("config.json");
+        config.save(&config_path).unwrap();
+        config_path
+    }
+
+    #[tokio::test]
+    async fn test_channel_connect_and_disconnect() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(channel_list(&dir.path().join("config.json")).is_err());
+
+        // Nothing listens on a port that was just released
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let config_path = channel_config(dir.path(), port, None);
+        assert_eq!(channel_list(&config_path).unwrap(), "No channels configured.\n");
+        assert!(channel_disconnect(&config_path, "slack").await.is_err());
+
+        let out = channel_connect(&config_path, "slack").await.unwrap();
+        assert!(out.contains("connects when the gateway starts"), "{}", out);
+        let status = channel_status(&config_path, "slack").await.unwrap();
+        assert!(status.contains("State: enabled, gateway not running"), "{}", status);
+
+        channel_disconnect(&config_path, "slack").await.unwrap();
+        assert!(channel_list(&config_path).unwrap().contains("slack        disabled"));
+
+        channel_connect(&config_path, "slack").await.unwrap();
+        assert_eq!(Config::load(&config_path).unwrap().channels.len(), 1);
+        assert!(channel_status(&config_path, "discord").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_channel_commands_keep_unparsable_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("config.json");
+        std::fs::write(&config_path, "{ not json").unwrap();
+
+        assert!(channel_list(&config_path).is_err());
+        assert!(channel_status(&config_path, "slack").await.is_err());
+        assert!(channel_connect(&config_path, "slack").await.is_err());
+        assert!(open_registry(&config_path).is_err());
+        assert_eq!(std::fs::read_to_string(&config_path).unwrap(), "{ not json");
+    }
+
+    #[tokio::test]
+    async fn test_channel_commands_drive_running_gateway() {
+        use crate::channels::TestChannel;
+        use crate::gateway::{GatewayConfig, GatewayServer};
+
+        let mut gateway = Gateway::with_config(GatewayConfig::for_testing()).unwrap();
+        gateway.register_channel(std::sync::Arc::new(TestChannel::new("slack")));
+        let server = GatewayServer::new(gateway.event_bus().clone(), "127.0.0.1", 0)
+            .with_channel_control(std::sync::Arc::new(gateway.clone()), "secret");
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let serving = tokio::spawn(async move { server.serve(listener).await });
+
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = channel_config(dir.path(), port, Some("secret"));
+        channel_connect(&config_path, "slack").await.unwrap();
+        let status = channel_status(&config_path, "slack").await.unwrap();
+        assert!(status.contains("State: connected"), "{}", status);
+
+        assert_eq!(channel_disconnect(&config_path, "slack").await.unwrap(), "Channel slack disconnected.\n");
+        assert_eq!(gateway.channel_status("slack").unwrap().state, ChannelState::Disconnected);
+        let status = channel_status(&config_path, "slack").await.unwrap();
+        assert!(status.contains("State: disconnected (last disconnect:"), "{}", status);
+
+        assert_eq!(channel_connect(&config_path, "slack").await.unwrap(), "Channel slack connected.\n");
+        assert_eq!(gateway.channel_status("slack").unwrap().state, ChannelState::Connected);
+
+        // The admin routes refuse a wrong token
+        let config_path = channel_config(dir.path(), port, Some("guess"));
+        assert!(channel_status(&config_path, "slack").await.is_err());
+        serving.abort();
+    }
+
+    #[tokio::test]
+    async fn test_pca_verify_round_trip_with_configured_keypair() {
+        let dir = tempfile::tempdir().unwrap();
+        let generator = ProofGenerator::new_random();
+        let keypair_path = dir.path().join("keypair");
+        generator.save_to_file(&keypair_path).unwrap();
+        let mut config = Config::default_config();
+        config.gateway.keypair_path = keypair_path.display().to_string();
+        let config_path = dir.path().join("config.json");
+        config.save(&config_path).unwrap();
+
+        let pca = signed_pca(&generator).await;
+        let path = dir.path().join("action.json");
+        std::fs::write(&path, serde_json::to_string(&pca).unwrap()).unwrap();
+
+        // The configured keypair beats a key embedded in the file
+        let mut json = serde_json::to_value(&pca).unwrap();
+        json["public_key"] = hex::encode(ProofGenerator::new_random().public_key_bytes()).into();
+        std::fs::write(&path, json.to_string()).unwrap();
+        let result = pca_verify(&path, None, Some(&config_path)).unwrap();
+        assert!(result.passed());
+        assert_eq!(result.key_source, KeySource::Configured(keypair_path.clone()));
+        assert_eq!(result.timestamp, pca.timestamp);
+        assert_eq!(result.trace_len, pca.decision_trace.len());
+        let out = render_pca_verification(&result, OutputFormat::Text).unwrap();
+        assert!(out.contains(&format!("trace:      {} entries", pca.decision_trace.len())));
+
+        // An explicit key overrides the configured keypair
+        let other = hex::encode(ProofGenerator::new_random().public_key_bytes());
+        let result = pca_verify(&path, Some(PublicKeyArg::Hex(&other)), Some(&config_path)).unwrap();
+        assert!(!result.passed());
+
+        // A broken config is reported, not replaced by defaults
+        std::fs::write(&config_path, "{ not json").unwrap();
+        let err = pca_verify(&path, None, Some(&config_path)).unwrap_err();
+        assert!(err.to_string().contains("Cannot load config"));
+        assert!(pca_verify(&path, None, Some(&dir.path().join("missing.json"))).is_ok());
+    }
+}
@@ -12,7 +12,7 @@ use std::path::{Path, PathBuf};
 use crate::error::ConfigError;
 
 // Submodules to be implemented by Agent #10
-// pub mod commands;
+pub mod commands;
 // pub mod config;
 
 /// Expand tilde (~) in paths.
@@ -46,6 +46,9 @@ pub struct GatewayConfig {
     pub bind: String,
     /// Path to keypair file
     pub keypair_path: String,
+    /// Bearer token for the running gateway's channel admin routes
+    #[serde(default)]
+    pub admin_token: Option<String>,
 }
 
 /// Channel configuration.
@@ -92,6 +95,7 @@ impl Config {
                 port: 18789,
                 bind: "127.0.0.1".to_string(),
                 keypair_path: "~/.0-openclaw/keypair".to_string(),
+                admin_token: None,
             },
             channels: Vec::new(),
             skills: vec!["graphs/skills/echo.0".to_string()],
@@ -81,8 +81,26 @@ pub enum GatewayError {
     #[error("Execution error: {0}")]
     ExecutionError(String),
 
+    #[error("Execution timed out after {timeout_ms}ms")]
+    Timeout { timeout_ms: u64 },
+
+    #[error("Graph execution exceeded its time budget after {elapsed_ms}ms (trace: {trace:?})")]
+    ExecutionTimeout { elapsed_ms: u64, trace: Vec<String> },
+
     #[error("Config error: {0}")]
     ConfigError(String),
+
+    #[error("Circuit open for {key}")]
+    CircuitOpen { key: String },
+
+    #[error("Dead letter not found: {0}")]
+    DeadLetterNotFound(ContentHash),
+
+    #[error("Peer error: {0}")]
+    PeerError(String),
+
+    #[error("Audit sink error: {0}")]
+    AuditError(String),
 }
 
 impl From<SessionError> for GatewayError {
@@ -117,6 +135,9 @@ pub enum ChannelError {
 
     #[error("Authentication failed: {0}")]
     AuthenticationFailed(String),
+
+    #[error("Unsupported: {0}")]
+    Unsupported(String),
 }
 
 /// Errors related to Skills.
@@ -142,6 +163,9 @@ pub enum SkillError {
 
     #[error("Unsafe operation detected: {op} - {reason}")]
     UnsafeOperation { op: String, reason: String },
+
+    #[error("Skill quota exceeded: {used} would exceed limit of {limit}")]
+    QuotaExceeded { used: u64, limit: u64 },
 }
 
 /// Errors related to Sessions.
@@ -158,6 +182,12 @@ pub enum SessionError {
 
     #[error("Session update failed: {0}")]
     UpdateFailed(String),
+
+    #[error("Session version conflict: expected {expected}, found {actual}")]
+    VersionConflict { expected: u64, actual: u64 },
+
+    #[error("Too many sessions for user (limit {limit})")]
+    TooManySessions { limit: usize },
 }
 
 /// Errors related to Proofs.
@@ -183,6 +213,12 @@ pub enum ProofError {
 
     #[error("Verification failed: {0}")]
     VerificationFailed(String),
+
+    #[error("PCA timestamp {timestamp} outside accepted range (now {now})")]
+    TimestampOutOfRange { timestamp: u64, now: u64 },
+
+    #[error("Invalid PCA format: {0}")]
+    InvalidFormat(String),
 }
 
 /// Errors related to Configuration.
@@ -0,0 +1,76 @@
+//! Screening of inbound attachments.
+//!
+//! Attachments are checked against the configured [`AttachmentPolicy`] and
+//! an optional [`AttachmentScanner`] (e.g. a malware hook) before any skill
+//! sees the message. Failing attachments are stripped, or the whole message
+//! is rejected, depending on the policy.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::types::Attachment;
+
+/// What happens to a message carrying an attachment that fails screening.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttachmentViolation {
+    /// Remove the attachment and process the rest of the message.
+    #[default]
+    Strip,
+    /// Deny the whole message.
+    Reject,
+}
+
+/// Limits on the attachments skills may receive.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AttachmentPolicy {
+    /// Largest attachment accepted (unset allows any size)
+    #[serde(default)]
+    pub max_bytes: Option<u64>,
+
+    /// Accepted MIME types such as `application/pdf` or `image/*` (empty allows all)
+    #[serde(default)]
+    pub allowed_mime_types: Vec<String>,
+
+    /// Whether failing attachments are stripped or reject the message
+    #[serde(default)]
+    pub on_violation: AttachmentViolation,
+}
+
+impl AttachmentPolicy {
+    /// Check an attachment against the policy, returning why it fails.
+    pub fn check(&self, attachment: &Attachment) -> Result<(), String> {
+        if let Some(max_bytes) = self.max_bytes {
+            if attachment.size_bytes > max_bytes {
+                return Err(format!(
+                    "{} bytes exceeds the {} byte limit",
+                    attachment.size_bytes, max_bytes
+                ));
+            }
+        }
+        if !self.allowed_mime_types.is_empty()
+            && !self.allowed_mime_types.iter().any(|allowed| mime_matches(allowed, &attachment.mime_type))
+        {
+            return Err(format!("type {} is not allowed", attachment.mime_type));
+        }
+        Ok(())
+    }
+}
+
+/// Match a MIME type against an allowlist entry, which may end in `/*`.
+fn mime_matches(allowed: &str, mime_type: &str) -> bool {
+    let mime_type = mime_type.to_ascii_lowercase();
+    match allowed.strip_suffix("/*") {
+        Some(family) => mime_type
+            .split_once('/')
+            .is_some_and(|(kind, _)| kind.eq_ignore_ascii_case(family)),
+        None => allowed.eq_ignore_ascii_case(&mime_type),
+    }
+}
+
+/// Hook inspecting attachments that passed the policy, e.g. a malware scanner.
+#[async_trait]
+pub trait AttachmentScanner: Send + Sync {
+    /// Inspect an attachment, returning why it must not reach skills.
+    async fn scan(&self, attachment: &Attachment) -> Result<(), String>;
+}
@@ -0,0 +1,222 @@
+//! Export of executed actions to external audit systems.
+//!
+//! Every verified PCA the gateway executes is handed to the registered
+//! [`AuditSink`]s. Sinks run on spawned tasks, so a slow SIEM or broker
+//! never delays message processing.
+
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{mpsc, Mutex};
+
+use crate::error::GatewayError;
+use crate::types::ProofCarryingAction;
+use super::privacy::UserScope;
+
+/// Destination for the proofs of executed actions.
+#[async_trait]
+pub trait AuditSink: Send + Sync {
+    /// Record an executed action. Does nothing by default.
+    async fn record(&self, _pca: &ProofCarryingAction) -> Result<(), GatewayError> {
+        Ok(())
+    }
+
+    /// Recorded actions concerning `scope`'s user. Sinks that cannot read
+    /// back what they recorded return none.
+    async fn export_user(&self, _scope: &UserScope) -> Result<Vec<ProofCarryingAction>, GatewayError> {
+        Ok(Vec::new())
+    }
+
+    /// Erase recorded actions concerning `scope`'s user, returning how many
+    /// were removed. Sinks that cannot erase what they recorded fail, so
+    /// data they keep is never reported as erased.
+    async fn erase_user(&self, _scope: &UserScope) -> Result<usize, GatewayError> {
+        Err(GatewayError::AuditError("This audit sink cannot erase recorded actions".to_string()))
+    }
+}
+
+/// Sink that discards every record.
+#[derive(Debug, Default)]
+pub struct NoopAuditSink;
+
+#[async_trait]
+impl AuditSink for NoopAuditSink {
+    async fn erase_user(&self, _scope: &UserScope) -> Result<usize, GatewayError> {
+        Ok(0)
+    }
+}
+
+/// Sink appending one JSON-encoded PCA per line to a file.
+#[derive(Debug)]
+pub struct JsonlAuditSink {
+    path: PathBuf,
+    /// Serializes appends so concurrent records never interleave.
+    lock: Mutex<()>,
+}
+
+impl JsonlAuditSink {
+    /// Append to the JSONL file at `path`, creating it if needed.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Replace the file's contents through a synced temporary file, so a
+    /// crash leaves either the old log or the new one, never a truncated one.
+    async fn replace_contents(&self, contents: &[u8]) -> std::io::Result<()> {
+        let tmp = PathBuf::from(format!("{}.tmp", self.path.display()));
+        let mut file = tokio::fs::File::create(&tmp).await?;
+        file.write_all(contents).await?;
+        file.sync_all().await?;
+        drop(file);
+        tokio::fs::rename(&tmp, &self.path).await
+    }
+
+    /// Read the recorded lines, with the PCA each one parses to.
+    async fn read_lines(&self) -> Result<Vec<(String, Option<ProofCarryingAction>)>, GatewayError> {
+        let contents = match tokio::fs::read_to_string(&self.path).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(GatewayError::AuditError(format!("{}: {}", self.path.display(), e))),
+        };
+        Ok(contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| (line.to_string(), serde_json::from_str(line).ok()))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl AuditSink for JsonlAuditSink {
+    async fn record(&self, pca: &ProofCarryingAction) -> Result<(), GatewayError> {
+        let mut line = serde_json::to_vec(pca).map_err(|e| GatewayError::AuditError(e.to_string()))?;
+        line.push(b'\n');
+
+        let io_error = |e: std::io::Error| GatewayError::AuditError(format!("{}: {}", self.path.display(), e));
+        let _guard = self.lock.lock().await;
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .map_err(io_error)?;
+        file.write_all(&line).await.map_err(io_error)?;
+        // tokio completes file writes in the background; wait for them
+        file.flush().await.map_err(io_error)
+    }
+
+    async fn export_user(&self, scope: &UserScope) -> Result<Vec<ProofCarryingAction>, GatewayError> {
+        let _guard = self.lock.lock().await;
+        Ok(self
+            .read_lines()
+            .await?
+            .into_iter()
+            .filter_map(|(_, pca)| pca)
+            .filter(|pca| scope.matches_pca(pca))
+            .collect())
+    }
+
+    async fn erase_user(&self, scope: &UserScope) -> Result<usize, GatewayError> {
+        let _guard = self.lock.lock().await;
+        let lines = self.read_lines().await?;
+        let total = lines.len();
+        // Lines that do not parse are kept: they cannot be attributed to anyone
+        let kept: Vec<String> = lines
+            .into_iter()
+            .filter(|(_, pca)| !pca.as_ref().is_some_and(|pca| scope.matches_pca(pca)))
+            .map(|(line, _)| line + "\n")
+            .collect();
+        let removed = total - kept.len();
+        if removed > 0 {
+            self.replace_contents(kept.concat().as_bytes())
+                .await
+                .map_err(|e| GatewayError::AuditError(format!("{}: {}", self.path.display(), e)))?;
+        }
+        Ok(removed)
+    }
+}
+
+/// Sink handing serialized PCAs to a message-queue producer.
+///
+/// A stub for broker integrations (Kafka, syslog relays, ...): records are
+/// queued as `(topic, payload)` pairs for a producer task to drain.
+#[derive(Debug, Clone)]
+pub struct MessageQueueAuditSink {
+    topic: String,
+    sender: mpsc::Sender<(String, Vec<u8>)>,
+}
+
+impl MessageQueueAuditSink {
+    /// Create a sink for `topic` and the receiver its producer drains.
+    pub fn new(topic: &str, capacity: usize) -> (Self, mpsc::Receiver<(String, Vec<u8>)>) {
+        let (sender, receiver) = mpsc::channel(capacity);
+        (Self { topic: topic.to_string(), sender }, receiver)
+    }
+}
+
+#[async_trait]
+impl AuditSink for MessageQueueAuditSink {
+    async fn record(&self, pca: &ProofCarryingAction) -> Result<(), GatewayError> {
+        let payload = serde_json::to_vec(pca).map_err(|e| GatewayError::AuditError(e.to_string()))?;
+        self.sender
+            .send((self.topic.clone(), payload))
+            .await
+            .map_err(|_| GatewayError::AuditError(format!("Audit queue '{}' closed", self.topic)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_jsonl_and_queue_sinks_record_pcas() {
+        let pca = ProofCarryingAction::pending();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let sink = JsonlAuditSink::new(&path);
+        sink.record(&pca).await.unwrap();
+        sink.record(&pca).await.unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.lines().count(), 2);
+        let line: ProofCarryingAction = serde_json::from_str(contents.lines().next().unwrap()).unwrap();
+        assert_eq!(line.timestamp, pca.timestamp);
+
+        let (sink, mut receiver) = MessageQueueAuditSink::new("proofs", 4);
+        sink.record(&pca).await.unwrap();
+        let (topic, payload) = receiver.recv().await.unwrap();
+        assert_eq!(topic, "proofs");
+        assert!(serde_json::from_slice::<ProofCarryingAction>(&payload).is_ok());
+
+        NoopAuditSink.record(&pca).await.unwrap();
+        let scope = UserScope::new(crate::types::UserRef::new("test", "alice"), []);
+        assert_eq!(NoopAuditSink.erase_user(&scope).await.unwrap(), 0);
+        assert!(sink.erase_user(&scope).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_jsonl_sink_exports_and_erases_user() {
+        let dir = tempfile::tempdir().unwrap();
+        let sink = JsonlAuditSink::new(dir.path().join("audit.jsonl"));
+        let scope = UserScope::new(crate::types::UserRef::new("test", "alice"), []);
+        assert_eq!(sink.erase_user(&scope).await.unwrap(), 0);
+
+        let mut to_alice = ProofCarryingAction::pending();
+        to_alice.action = crate::types::Action::SendMessage(crate::types::OutgoingMessage::new("test", "alice", "hi"));
+        let mut to_bob = ProofCarryingAction::pending();
+        to_bob.action = crate::types::Action::SendMessage(crate::types::OutgoingMessage::new("test", "bob", "hi"));
+        sink.record(&to_alice).await.unwrap();
+        sink.record(&to_bob).await.unwrap();
+
+        assert_eq!(sink.export_user(&scope).await.unwrap().len(), 1);
+        assert_eq!(sink.erase_user(&scope).await.unwrap(), 1);
+        assert!(sink.export_user(&scope).await.unwrap().is_empty());
+        assert_eq!(sink.read_lines().await.unwrap().len(), 1);
+        assert!(!dir.path().join("audit.jsonl.tmp").exists());
+    }
+}
@@ -4,8 +4,14 @@
 //! including server settings, paths, and runtime options.
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use crate::channels::ChannelFeature;
 use crate::error::ConfigError;
+use super::attachments::AttachmentPolicy;
+use super::identity::SenderIdConfig;
+use super::quiet_hours::QuietHours;
+use crate::skills::{SkillLoadMode, SkillQuota};
 
 /// Gateway configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,6 +43,157 @@ pub struct GatewayConfig {
     /// Logging configuration
     #[serde(default)]
     pub logging: LoggingConfig,
+
+    /// Skill execution configuration
+    #[serde(default)]
+    pub execution: ExecutionConfig,
+
+    /// Sign every PCA and verify it before execution
+    ///
+    /// Disable only in trusted deployments that do not need verifiable
+    /// actions; PCAs are then flagged `unsigned`.
+    #[serde(default = "default_true")]
+    pub proofs_enabled: bool,
+
+    /// How per-trace confidences combine into a PCA's confidence
+    #[serde(default)]
+    pub confidence: ConfidenceConfig,
+
+    /// Maximum decision trace length stored in a PCA
+    ///
+    /// Longer traces are truncated and committed to via a Merkle root.
+    #[serde(default = "default_max_trace_len")]
+    pub max_trace_len: usize,
+
+    /// Dead-letter queue configuration
+    #[serde(default)]
+    pub dead_letter: DeadLetterConfig,
+
+    /// Channel features required by skills, keyed by skill name
+    ///
+    /// Merged with the features declared in each skill's metadata.
+    #[serde(default)]
+    pub skill_features: HashMap<String, Vec<ChannelFeature>>,
+
+    /// What to do with replies addressed to a disconnected channel
+    #[serde(default)]
+    pub undeliverable_policy: UndeliverablePolicy,
+
+    /// Window during which non-urgent outbound messages are held
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
+
+    /// Route parameter to skill input bindings, keyed by route target
+    ///
+    /// For example `"skill:search": { "arg0": "query" }` binds the first
+    /// command argument to the search skill's `query` input.
+    #[serde(default)]
+    pub input_mappings: HashMap<String, HashMap<String, String>>,
+
+    /// Skills run in order on every message before routing
+    ///
+    /// Each skill's map outputs are merged into the message's enrichment
+    /// context, bound as the `enrichment` input of the router and skills.
+    /// A failing preprocessor is logged and skipped.
+    #[serde(default)]
+    pub preprocessor_skills: Vec<String>,
+
+    /// Message sent while a slow skill runs, then edited into the answer
+    ///
+    /// A skill counts as slow when it calls external services or runs past
+    /// `thinking_threshold_ms`. Unset disables the placeholder.
+    #[serde(default)]
+    pub thinking_placeholder: Option<String>,
+
+    /// Execution time after which the thinking placeholder is sent
+    #[serde(default = "default_thinking_threshold_ms")]
+    pub thinking_threshold_ms: u64,
+
+    /// Event sharing with peer gateways
+    #[serde(default)]
+    pub federation: FederationConfig,
+
+    /// Channel permission confidence below which actions are denied
+    #[serde(default = "default_min_permission_confidence")]
+    pub min_permission_confidence: f32,
+
+    /// Message sent to senders whose action was denied
+    #[serde(default = "default_denied_message")]
+    pub denied_message: String,
+
+    /// Most turns a skill may claim a conversation for
+    #[serde(default = "default_max_claim_turns")]
+    pub max_claim_turns: u32,
+
+    /// Reply to conversation no skill handles (unset echoes the message back)
+    #[serde(default)]
+    pub default_response: Option<String>,
+
+    /// Whether the default response lists the available commands
+    #[serde(default)]
+    pub default_response_suggestions: bool,
+
+    /// Per-user cooldown in seconds for individual commands, keyed by the
+    /// name of the skill the command routes to (`status` for `/status`)
+    #[serde(default)]
+    pub command_cooldowns: HashMap<String, u64>,
+
+    /// Custom skill storage allowed per installer
+    #[serde(default)]
+    pub skill_quota: SkillQuota,
+
+    /// Whether skills in `skills_path` that fail verification are skipped or abort startup
+    #[serde(default)]
+    pub skill_load_mode: SkillLoadMode,
+
+    /// Directory recording skills that already passed verification (unset disables)
+    #[serde(default)]
+    pub skill_cache_path: Option<PathBuf>,
+
+    /// JSONL file the PCA of every executed action is appended to (unset disables)
+    #[serde(default)]
+    pub audit_log_path: Option<PathBuf>,
+
+    /// Screening applied to inbound attachments before skills see them
+    #[serde(default)]
+    pub attachments: AttachmentPolicy,
+
+    /// Normalization of sender ids for session keys and allowlists
+    #[serde(default)]
+    pub sender_ids: SenderIdConfig,
+
+    /// Trusted user ids per channel name. Their actions pass regardless of
+    /// `min_permission_confidence`, unless the channel refuses them outright
+    #[serde(default)]
+    pub allowed_users: HashMap<String, Vec<String>>,
+
+    /// Interval between heartbeat events while running (0 disables)
+    #[serde(default = "default_heartbeat_interval_ms")]
+    pub heartbeat_interval_ms: u64,
+
+    /// JSON file pending `/remind` reminders are kept in (unset keeps them in memory)
+    #[serde(default)]
+    pub reminders_path: Option<PathBuf>,
+
+    /// Interval between checks for due reminders while running
+    #[serde(default = "default_reminder_poll_interval_ms")]
+    pub reminder_poll_interval_ms: u64,
+
+    /// Pending reminders one user may have at a time (0 for no limit)
+    #[serde(default = "default_max_reminders_per_user")]
+    pub max_reminders_per_user: usize,
+
+    /// Outbound HTTP calls made by skills
+    #[serde(default)]
+    pub http: HttpConfig,
+
+    /// JSON file of named signing keys (name to hex seed) for the `Sign` op
+    #[serde(default)]
+    pub keystore_path: Option<PathBuf>,
+
+    /// Development mode: lets `Sign` take a raw `seed` param from the graph
+    #[serde(default)]
+    pub dev_mode: bool,
 }
 
 /// Server configuration.
@@ -57,6 +214,10 @@ pub struct ServerConfig {
     /// Maximum WebSocket connections
     #[serde(default = "default_max_connections")]
     pub max_connections: usize,
+
+    /// Bearer token for the `/channels` admin routes; they are off when unset
+    #[serde(default)]
+    pub admin_token: Option<String>,
 }
 
 /// Session configuration.
@@ -77,6 +238,36 @@ pub struct SessionConfig {
     /// Trust score decay rate (per interaction without positive feedback)
     #[serde(default = "default_trust_decay")]
     pub trust_decay: f32,
+
+    /// Minimum interval between a user's commands in milliseconds (0 disables)
+    #[serde(default)]
+    pub command_cooldown_ms: u64,
+
+    /// Trust gained per successfully executed action, scaled by its confidence
+    #[serde(default = "default_success_reward")]
+    pub success_reward: f32,
+
+    /// Trust lost per action that fails verification or delivery
+    #[serde(default = "default_failure_penalty")]
+    pub failure_penalty: f32,
+
+    /// Evict a user's least recently active session at `max_per_user`,
+    /// instead of refusing new sessions
+    #[serde(default = "default_true")]
+    pub evict_oldest: bool,
+
+    /// Keep sessions across restarts
+    #[serde(default)]
+    pub persist: bool,
+
+    /// Session store file (default: `sessions.json` beside `skills_path`)
+    #[serde(default)]
+    pub store_path: Option<PathBuf>,
+
+    /// Characters of each user's last message kept in session context, so
+    /// graphs can compare it with the next one (0 keeps nothing)
+    #[serde(default)]
+    pub last_message_chars: usize,
 }
 
 /// Logging configuration.
@@ -94,6 +285,139 @@ pub struct LoggingConfig {
     pub file_path: Option<PathBuf>,
 }
 
+/// Skill execution configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionConfig {
+    /// Global execution timeout in milliseconds
+    #[serde(default = "default_execution_timeout")]
+    pub timeout_ms: u64,
+
+    /// Upper bound for per-skill timeout overrides in milliseconds
+    #[serde(default = "default_max_skill_timeout")]
+    pub max_skill_timeout_ms: u64,
+}
+
+/// Outbound HTTP configuration for skills.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HttpConfig {
+    /// Hosts skills may call; `*.example.com` matches subdomains. Empty
+    /// disables HTTP calls, and `http://` URIs read graph inputs instead.
+    /// Loopback, private and link-local addresses are never called.
+    #[serde(default)]
+    pub allowed_hosts: Vec<String>,
+}
+
+/// Dead-letter queue configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetterConfig {
+    /// Maximum number of dead letters kept (oldest are evicted first)
+    #[serde(default = "default_dead_letter_capacity")]
+    pub capacity: usize,
+
+    /// File to persist dead letters to (None for in-memory only)
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+
+    /// Retries before an action that fails to send is dead-lettered
+    #[serde(default = "default_delivery_retries")]
+    pub delivery_retries: u32,
+
+    /// JSONL file to persist undeliverable actions to (None for in-memory only)
+    #[serde(default)]
+    pub delivery_path: Option<PathBuf>,
+
+    /// Oldest interrupted delivery resent on startup, in milliseconds
+    #[serde(default = "default_redelivery_max_age")]
+    pub redelivery_max_age_ms: u64,
+}
+
+/// Federation configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederationConfig {
+    /// ID this gateway reports to peers (defaults to `host:port`)
+    #[serde(default)]
+    pub node_id: Option<String>,
+
+    /// WebSocket URLs of peer gateways (empty disables forwarding)
+    #[serde(default)]
+    pub peers: Vec<String>,
+
+    /// Shared secret peers sign events with; events from peers are only
+    /// accepted when set
+    #[serde(default)]
+    pub secret: Option<String>,
+
+    /// Event types forwarded to peers
+    #[serde(default = "default_forwarded_events")]
+    pub forward_events: Vec<String>,
+
+    /// How far a peer event's send time may be from now, in seconds.
+    /// Older events are rejected as replays
+    #[serde(default = "default_peer_max_skew_secs")]
+    pub max_skew_secs: u64,
+}
+
+/// Strategy for combining the confidences of several execution traces.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfidenceAggregation {
+    /// The lowest trace confidence
+    #[default]
+    Min,
+    /// The product of all trace confidences
+    Product,
+    /// Weighted mean, using `ConfidenceConfig::weights`
+    Weighted,
+}
+
+/// Confidence aggregation configuration.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfidenceConfig {
+    /// How trace confidences are combined
+    #[serde(default)]
+    pub aggregation: ConfidenceAggregation,
+
+    /// Weights by trace position (routing first, then the skill) for
+    /// `Weighted`; missing weights count as 1.0
+    #[serde(default)]
+    pub weights: Vec<f32>,
+}
+
+impl ConfidenceConfig {
+    /// Combine per-trace confidences, in trace order.
+    ///
+    /// Returns `None` when no trace reported a confidence.
+    pub fn aggregate(&self, confidences: &[(usize, f32)]) -> Option<f32> {
+        if confidences.is_empty() {
+            return None;
+        }
+        let value = match self.aggregation {
+            ConfidenceAggregation::Min => confidences.iter().map(|&(_, c)| c).fold(1.0, f32::min),
+            ConfidenceAggregation::Product => confidences.iter().map(|&(_, c)| c).product(),
+            ConfidenceAggregation::Weighted => {
+                let weight = |index: usize| self.weights.get(index).copied().unwrap_or(1.0);
+                let total: f32 = confidences.iter().map(|&(i, _)| weight(i)).sum();
+                if total <= 0.0 {
+                    return None;
+                }
+                confidences.iter().map(|&(i, c)| weight(i) * c).sum::<f32>() / total
+            }
+        };
+        Some(value.clamp(0.0, 1.0))
+    }
+}
+
+/// Handling of replies whose channel is not connected.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UndeliverablePolicy {
+    /// Drop the reply, emit an error event and mark the channel's sessions stale
+    #[default]
+    Drop,
+    /// Keep the reply and deliver it when the channel reconnects
+    Queue,
+}
+
 // Default value functions
 fn default_host() -> String {
     "127.0.0.1".to_string()
@@ -143,10 +467,78 @@ fn default_trust_decay() -> f32 {
     0.01
 }
 
+fn default_success_reward() -> f32 {
+    0.05
+}
+
+fn default_failure_penalty() -> f32 {
+    0.1
+}
+
 fn default_log_level() -> String {
     "info".to_string()
 }
 
+fn default_max_trace_len() -> usize {
+    1024
+}
+
+fn default_thinking_threshold_ms() -> u64 {
+    2_000
+}
+
+fn default_heartbeat_interval_ms() -> u64 {
+    30_000
+}
+
+fn default_max_reminders_per_user() -> usize {
+    20
+}
+
+fn default_reminder_poll_interval_ms() -> u64 {
+    1_000
+}
+
+fn default_min_permission_confidence() -> f32 {
+    0.2
+}
+
+fn default_peer_max_skew_secs() -> u64 {
+    300
+}
+
+fn default_max_claim_turns() -> u32 {
+    20
+}
+
+fn default_denied_message() -> String {
+    "You're not permitted to do that.".to_string()
+}
+
+fn default_forwarded_events() -> Vec<String> {
+    super::federation::DEFAULT_FORWARDED_EVENTS.iter().map(|t| t.to_string()).collect()
+}
+
+fn default_dead_letter_capacity() -> usize {
+    1000
+}
+
+fn default_delivery_retries() -> u32 {
+    2
+}
+
+fn default_redelivery_max_age() -> u64 {
+    3_600_000 // 1 hour
+}
+
+fn default_execution_timeout() -> u64 {
+    30_000 // 30 seconds
+}
+
+fn default_max_skill_timeout() -> u64 {
+    120_000 // 2 minutes
+}
+
 impl Default for GatewayConfig {
     fn default() -> Self {
         Self {
@@ -157,6 +549,39 @@ impl Default for GatewayConfig {
             graphs_path: default_graphs_path(),
             session: SessionConfig::default(),
             logging: LoggingConfig::default(),
+            execution: ExecutionConfig::default(),
+            proofs_enabled: true,
+            confidence: ConfidenceConfig::default(),
+            max_trace_len: default_max_trace_len(),
+            dead_letter: DeadLetterConfig::default(),
+            skill_features: HashMap::new(),
+            input_mappings: HashMap::new(),
+            preprocessor_skills: Vec::new(),
+            thinking_placeholder: None,
+            thinking_threshold_ms: default_thinking_threshold_ms(),
+            federation: FederationConfig::default(),
+            min_permission_confidence: default_min_permission_confidence(),
+            denied_message: default_denied_message(),
+            max_claim_turns: default_max_claim_turns(),
+            default_response: None,
+            default_response_suggestions: false,
+            command_cooldowns: HashMap::new(),
+            skill_quota: SkillQuota::default(),
+            skill_load_mode: SkillLoadMode::default(),
+            skill_cache_path: None,
+            audit_log_path: None,
+            attachments: AttachmentPolicy::default(),
+            sender_ids: SenderIdConfig::default(),
+            allowed_users: HashMap::new(),
+            heartbeat_interval_ms: default_heartbeat_interval_ms(),
+            reminders_path: None,
+            reminder_poll_interval_ms: default_reminder_poll_interval_ms(),
+            max_reminders_per_user: default_max_reminders_per_user(),
+            http: HttpConfig::default(),
+            keystore_path: None,
+            dev_mode: false,
+            quiet_hours: None,
+            undeliverable_policy: UndeliverablePolicy::default(),
         }
     }
 }
@@ -168,6 +593,7 @@ impl Default for ServerConfig {
             port: default_port(),
             cors_enabled: default_true(),
             max_connections: default_max_connections(),
+            admin_token: None,
         }
     }
 }
@@ -179,6 +605,13 @@ impl Default for SessionConfig {
             max_per_user: default_max_sessions_per_user(),
             initial_trust: default_initial_trust(),
             trust_decay: default_trust_decay(),
+            command_cooldown_ms: 0,
+            success_reward: default_success_reward(),
+            failure_penalty: default_failure_penalty(),
+            evict_oldest: default_true(),
+            persist: false,
+            store_path: None,
+            last_message_chars: 0,
         }
     }
 }
@@ -193,6 +626,52 @@ impl Default for LoggingConfig {
     }
 }
 
+impl Default for ExecutionConfig {
+    fn default() -> Self {
+        Self {
+            timeout_ms: default_execution_timeout(),
+            max_skill_timeout_ms: default_max_skill_timeout(),
+        }
+    }
+}
+
+impl Default for DeadLetterConfig {
+    fn default() -> Self {
+        Self {
+            capacity: default_dead_letter_capacity(),
+            path: None,
+            delivery_retries: default_delivery_retries(),
+            delivery_path: None,
+            redelivery_max_age_ms: default_redelivery_max_age(),
+        }
+    }
+}
+
+impl Default for FederationConfig {
+    fn default() -> Self {
+        Self {
+            node_id: None,
+            peers: Vec::new(),
+            secret: None,
+            forward_events: default_forwarded_events(),
+            max_skew_secs: default_peer_max_skew_secs(),
+        }
+    }
+}
+
+impl ExecutionConfig {
+    /// Resolve the execution budget for a skill.
+    ///
+    /// A per-skill timeout overrides the global one but is clamped to
+    /// `max_skill_timeout_ms`.
+    pub fn timeout_for(&self, skill_timeout_ms: Option<u64>) -> u64 {
+        match skill_timeout_ms {
+            Some(ms) => ms.min(self.max_skill_timeout_ms),
+            None => self.timeout_ms,
+        }
+    }
+}
+
 impl GatewayConfig {
     /// Create a new default configuration.
     pub fn new() -> Self {
@@ -242,6 +721,31 @@ impl GatewayConfig {
             });
         }
 
+        if self.max_trace_len == 0 {
+            return Err(ConfigError::InvalidValue {
+                key: "max_trace_len".to_string(),
+                reason: "Maximum trace length must be greater than zero".to_string(),
+            });
+        }
+
+        if let Some(quiet_hours) = &self.quiet_hours {
+            quiet_hours.validate()?;
+        }
+
+        if !self.federation.peers.is_empty() && self.federation.secret.is_none() {
+            return Err(ConfigError::InvalidValue {
+                key: "federation.secret".to_string(),
+                reason: "Forwarding to peers requires a shared secret".to_string(),
+            });
+        }
+
+        if self.execution.timeout_ms == 0 {
+            return Err(ConfigError::InvalidValue {
+                key: "execution.timeout_ms".to_string(),
+                reason: "Execution timeout must be greater than zero".to_string(),
+            });
+        }
+
         Ok(())
     }
 
@@ -250,6 +754,16 @@ impl GatewayConfig {
         format!("{}:{}", self.server.host, self.server.port)
     }
 
+    /// File sessions are persisted to when `session.persist` is set.
+    pub fn session_store_path(&self) -> PathBuf {
+        self.session.store_path.clone().unwrap_or_else(|| {
+            self.skills_path
+                .parent()
+                .unwrap_or_else(|| Path::new("."))
+                .join("sessions.json")
+        })
+    }
+
     /// Create a configuration for testing.
     pub fn for_testing() -> Self {
         Self {
@@ -258,6 +772,7 @@ impl GatewayConfig {
                 port: 0, // OS will assign a port
                 cors_enabled: true,
                 max_connections: 10,
+                admin_token: None,
             },
             ..Default::default()
         }
@@ -297,4 +812,15 @@ mod tests {
         let restored: GatewayConfig = serde_json::from_str(&json).unwrap();
         assert_eq!(config.server.port, restored.server.port);
     }
+
+    #[test]
+    fn test_skill_timeout_clamped() {
+        let execution = ExecutionConfig {
+            timeout_ms: 1_000,
+            max_skill_timeout_ms: 5_000,
+        };
+        assert_eq!(execution.timeout_for(None), 1_000);
+        assert_eq!(execution.timeout_for(Some(3_000)), 3_000);
+        assert_eq!(execution.timeout_for(Some(60_000)), 5_000);
+    }
 }
@@ -0,0 +1,349 @@
+//! Dead-letter queues for unprocessable messages and undeliverable actions.
+//!
+//! Messages that fail in `Gateway::process_message` are recorded here with
+//! the error and a timestamp so operators can inspect or replay them.
+//! Actions that `Gateway::execute_action` could not deliver after retrying
+//! go to a [`DeliveryStore`] for later redelivery.
+
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{ContentHash, IncomingMessage, ProofCarryingAction};
+
+/// A message that could not be processed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    /// Identifier of the dead letter (the message hash)
+    pub id: ContentHash,
+
+    /// The original message
+    pub message: IncomingMessage,
+
+    /// Error that caused the failure
+    pub error: String,
+
+    /// Unix timestamp in milliseconds when the failure was recorded
+    pub timestamp: u64,
+}
+
+/// Bounded queue of dead letters, optionally persisted as JSON.
+#[derive(Debug)]
+pub struct DeadLetterQueue {
+    entries: VecDeque<DeadLetter>,
+    capacity: usize,
+    path: Option<PathBuf>,
+}
+
+impl DeadLetterQueue {
+    /// Create an in-memory queue holding at most `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity,
+            path: None,
+        }
+    }
+
+    /// Persist the queue to `path`, loading any entries already stored there.
+    pub fn with_path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        if let Ok(contents) = std::fs::read_to_string(&path) {
+            match serde_json::from_str::<Vec<DeadLetter>>(&contents) {
+                Ok(entries) => {
+                    self.entries = entries.into();
+                    self.trim();
+                }
+                Err(e) => tracing::warn!("Ignoring unreadable dead-letter file {}: {}", path.display(), e),
+            }
+        }
+        self.path = Some(path);
+        self
+    }
+
+    /// Record a failed message, evicting the oldest entry when full.
+    ///
+    /// A message that fails again replaces its previous record.
+    pub fn push(&mut self, message: IncomingMessage, error: impl Into<String>) -> ContentHash {
+        let id = message.id;
+        self.entries.retain(|entry| entry.id != id);
+        self.entries.push_back(DeadLetter {
+            id,
+            message,
+            error: error.into(),
+            timestamp: chrono::Utc::now().timestamp_millis() as u64,
+        });
+        self.trim();
+        self.persist();
+        id
+    }
+
+    /// Get a dead letter by id.
+    pub fn get(&self, id: &ContentHash) -> Option<&DeadLetter> {
+        self.entries.iter().find(|entry| &entry.id == id)
+    }
+
+    /// Remove and return a dead letter by id.
+    pub fn remove(&mut self, id: &ContentHash) -> Option<DeadLetter> {
+        let index = self.entries.iter().position(|entry| &entry.id == id)?;
+        let entry = self.entries.remove(index);
+        self.persist();
+        entry
+    }
+
+    /// All dead letters, oldest first.
+    pub fn list(&self) -> Vec<DeadLetter> {
+        self.entries.iter().cloned().collect()
+    }
+
+    /// Number of dead letters.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn trim(&mut self) {
+        while self.entries.len() > self.capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    fn persist(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let result = serde_json::to_string_pretty(&self.entries)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(path, json).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            tracing::warn!("Failed to persist dead letters to {}: {}", path.display(), e);
+        }
+    }
+}
+
+impl Default for DeadLetterQueue {
+    fn default() -> Self {
+        Self::new(1000)
+    }
+}
+
+/// An action that could not be delivered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedDelivery {
+    /// Identifier of the failed delivery (hash of the PCA)
+    pub id: ContentHash,
+
+    /// The action that failed to deliver
+    pub pca: ProofCarryingAction,
+
+    /// Channel the action was addressed to
+    pub channel_id: String,
+
+    /// Recipient on that channel
+    pub recipient_id: String,
+
+    /// Error from the last delivery attempt
+    pub error: String,
+
+    /// Unix timestamp in milliseconds when the failure was recorded
+    pub timestamp: u64,
+
+    /// Whether all retries were used up; exhausted deliveries are only
+    /// redelivered on request, never automatically
+    #[serde(default)]
+    pub exhausted: bool,
+}
+
+impl FailedDelivery {
+    /// Record a failed delivery of `pca`.
+    pub fn new(pca: ProofCarryingAction, channel_id: &str, recipient_id: &str, error: impl Into<String>) -> Self {
+        Self {
+            id: Self::id_for(&pca),
+            pca,
+            channel_id: channel_id.to_string(),
+            recipient_id: recipient_id.to_string(),
+            error: error.into(),
+            timestamp: chrono::Utc::now().timestamp_millis() as u64,
+            exhausted: false,
+        }
+    }
+
+    /// Set when the failure was recorded.
+    pub fn with_timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Mark the delivery as having used up all its retries.
+    pub fn with_exhausted(mut self, exhausted: bool) -> Self {
+        self.exhausted = exhausted;
+        self
+    }
+}
+
+impl FailedDelivery {
+    /// The id a failed delivery of `pca` is stored under.
+    pub fn id_for(pca: &ProofCarryingAction) -> ContentHash {
+        ContentHash::from_bytes(&serde_json::to_vec(pca).unwrap_or_default())
+    }
+}
+
+/// Storage for actions that could not be delivered.
+pub trait DeliveryStore: Send + Sync {
+    /// Record a failed delivery, replacing any earlier record with the same id.
+    fn push(&mut self, delivery: FailedDelivery);
+
+    /// Remove and return a failed delivery by id.
+    fn remove(&mut self, id: &ContentHash) -> Option<FailedDelivery>;
+
+    /// All failed deliveries, oldest first.
+    fn list(&self) -> Vec<FailedDelivery>;
+}
+
+/// Failed deliveries kept in memory and optionally appended to a JSONL file.
+#[derive(Debug, Default)]
+pub struct JsonlDeliveryStore {
+    entries: Vec<FailedDelivery>,
+    path: Option<PathBuf>,
+}
+
+impl JsonlDeliveryStore {
+    /// Create an in-memory store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Persist to the JSONL file at `path`, loading any entries already stored there.
+    pub fn with_path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        if let Ok(contents) = std::fs::read_to_string(&path) {
+            for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+                match serde_json::from_str::<FailedDelivery>(line) {
+                    Ok(entry) => {
+                        self.entries.retain(|existing| existing.id != entry.id);
+                        self.entries.push(entry);
+                    }
+                    Err(e) => tracing::warn!("Skipping unreadable failed delivery in {}: {}", path.display(), e),
+                }
+            }
+        }
+        self.path = Some(path);
+        self
+    }
+
+    fn append(&self, entry: &FailedDelivery) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let result = serde_json::to_string(entry)
+            .map_err(|e| e.to_string())
+            .and_then(|line| {
+                std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .and_then(|mut file| writeln!(file, "{}", line))
+                    .map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            tracing::warn!("Failed to persist failed delivery to {}: {}", path.display(), e);
+        }
+    }
+
+    fn rewrite(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let mut contents = String::new();
+        for entry in &self.entries {
+            if let Ok(line) = serde_json::to_string(entry) {
+                contents.push_str(&line);
+                contents.push('\n');
+            }
+        }
+        if let Err(e) = std::fs::write(path, contents) {
+            tracing::warn!("Failed to persist failed deliveries to {}: {}", path.display(), e);
+        }
+    }
+}
+
+impl DeliveryStore for JsonlDeliveryStore {
+    fn push(&mut self, delivery: FailedDelivery) {
+        self.entries.retain(|entry| entry.id != delivery.id);
+        self.append(&delivery);
+        self.entries.push(delivery);
+    }
+
+    fn remove(&mut self, id: &ContentHash) -> Option<FailedDelivery> {
+        let index = self.entries.iter().position(|entry| &entry.id == id)?;
+        let entry = self.entries.remove(index);
+        self.rewrite();
+        Some(entry)
+    }
+
+    fn list(&self) -> Vec<FailedDelivery> {
+        self.entries.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_is_bounded() {
+        let mut queue = DeadLetterQueue::new(2);
+        let first = queue.push(IncomingMessage::new("test", "user", "one"), "boom");
+        queue.push(IncomingMessage::new("test", "user", "two"), "boom");
+        queue.push(IncomingMessage::new("test", "user", "three"), "boom");
+
+        assert_eq!(queue.len(), 2);
+        assert!(queue.get(&first).is_none());
+        assert_eq!(queue.list()[1].message.content, "three");
+    }
+
+    #[test]
+    fn test_queue_persists() {
+        let path = std::env::temp_dir().join(format!(
+            "dead-letters-{}.json",
+            ContentHash::from_string(&format!("{:?}", std::time::SystemTime::now())).to_hex()
+        ));
+
+        let id = {
+            let mut queue = DeadLetterQueue::new(10).with_path(&path);
+            queue.push(IncomingMessage::new("test", "user", "hello"), "Skill not found")
+        };
+
+        let mut reloaded = DeadLetterQueue::new(10).with_path(&path);
+        assert_eq!(reloaded.get(&id).unwrap().error, "Skill not found");
+        assert!(reloaded.remove(&id).is_some());
+        assert!(DeadLetterQueue::new(10).with_path(&path).is_empty());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_delivery_store_persists_jsonl() {
+        let path = std::env::temp_dir().join(format!(
+            "failed-deliveries-{}.jsonl",
+            ContentHash::from_string(&format!("{:?}", std::time::SystemTime::now())).to_hex()
+        ));
+
+        let delivery = FailedDelivery::new(ProofCarryingAction::pending(), "test", "user", "send failed");
+        let id = delivery.id;
+        JsonlDeliveryStore::new().with_path(&path).push(delivery);
+
+        let mut reloaded = JsonlDeliveryStore::new().with_path(&path);
+        assert_eq!(reloaded.list()[0].error, "send failed");
+        assert!(reloaded.remove(&id).is_some());
+        assert!(JsonlDeliveryStore::new().with_path(&path).list().is_empty());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -4,6 +4,7 @@
 //! loosely coupled communication between gateway components.
 
 use std::collections::HashMap;
+use std::marker::PhantomData;
 use std::sync::Arc;
 use tokio::sync::{broadcast, RwLock};
 use serde::{Serialize, Deserialize};
@@ -49,6 +50,13 @@ pub enum GatewayEvent {
         trust_score: f32,
     },
 
+    /// A user's trust score changed after an action
+    TrustChanged {
+        channel_id: String,
+        user_id: String,
+        trust_score: f32,
+    },
+
     /// A session expired
     SessionExpired {
         session_id: ContentHash,
@@ -104,6 +112,7 @@ impl GatewayEvent {
             GatewayEvent::ActionExecuted { .. } => "action_executed",
             GatewayEvent::SessionCreated { .. } => "session_created",
             GatewayEvent::SessionUpdated { .. } => "session_updated",
+            GatewayEvent::TrustChanged { .. } => "trust_changed",
             GatewayEvent::SessionExpired { .. } => "session_expired",
             GatewayEvent::SkillInvoked { .. } => "skill_invoked",
             GatewayEvent::Error { .. } => "error",
@@ -217,6 +226,14 @@ impl EventBus {
         EventSubscriber { receiver }
     }
 
+    /// Subscribe to the payloads of one event type, skipping all other events.
+    pub fn subscribe_typed<T: FromEvent>(&self) -> TypedSubscriber<T> {
+        TypedSubscriber {
+            subscriber: self.subscribe(),
+            _payload: PhantomData,
+        }
+    }
+
     /// Get the number of active subscribers.
     pub fn subscriber_count(&self) -> usize {
         self.sender.receiver_count()
@@ -234,6 +251,11 @@ impl EventBus {
         self.history.read().await.clone()
     }
 
+    /// Keep only the history events for which `keep` returns true.
+    pub async fn retain_history(&self, keep: impl Fn(&GatewayEvent) -> bool) {
+        self.history.write().await.retain(|event| keep(event));
+    }
+
     /// Clear event history.
     pub async fn clear_history(&self) {
         self.history.write().await.clear();
@@ -289,6 +311,62 @@ impl EventSubscriber {
     }
 }
 
+/// Extracts a typed payload from the events it applies to.
+pub trait FromEvent: Sized {
+    /// Get the payload, or `None` for events of other types.
+    fn from_event(event: &GatewayEvent) -> Option<Self>;
+}
+
+/// Payload of a `GatewayEvent::MessageProcessed` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageProcessedPayload {
+    /// Hash of the processed message
+    pub message_hash: ContentHash,
+    /// Skill that handled the message
+    pub skill_hash: ContentHash,
+    /// Confidence of the resulting action
+    pub confidence: Confidence,
+}
+
+impl FromEvent for MessageProcessedPayload {
+    fn from_event(event: &GatewayEvent) -> Option<Self> {
+        match event {
+            GatewayEvent::MessageProcessed { message_hash, skill_hash, confidence } => Some(Self {
+                message_hash: *message_hash,
+                skill_hash: *skill_hash,
+                confidence: *confidence,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Subscriber receiving only the payloads of one event type.
+pub struct TypedSubscriber<T> {
+    subscriber: EventSubscriber,
+    _payload: PhantomData<T>,
+}
+
+impl<T: FromEvent> TypedSubscriber<T> {
+    /// Receive the next matching payload.
+    pub async fn recv(&mut self) -> Result<T, broadcast::error::RecvError> {
+        loop {
+            if let Some(payload) = T::from_event(&self.subscriber.recv().await?) {
+                return Ok(payload);
+            }
+        }
+    }
+
+    /// Try to receive a matching payload without blocking.
+    pub fn try_recv(&mut self) -> Result<T, broadcast::error::TryRecvError> {
+        loop {
+            if let Some(payload) = T::from_event(&self.subscriber.try_recv()?) {
+                return Ok(payload);
+            }
+        }
+    }
+}
+
 /// Event filter for selective subscription.
 pub struct EventFilter {
     /// Event types to include (empty = all)
@@ -401,6 +479,26 @@ mod tests {
         assert_eq!(history.len(), 2);
     }
 
+    #[tokio::test]
+    async fn test_typed_subscriber_skips_other_events() {
+        let bus = EventBus::new();
+        let mut processed = bus.subscribe_typed::<MessageProcessedPayload>();
+
+        bus.publish(GatewayEvent::error("test", "ignored")).await;
+        bus.publish(GatewayEvent::MessageProcessed {
+            message_hash: ContentHash::from_string("message"),
+            skill_hash: ContentHash::from_string("skill:echo"),
+            confidence: Confidence::new(0.9),
+        }).await;
+        bus.publish(GatewayEvent::GatewayStarted { timestamp: 0 }).await;
+
+        let payload = processed.recv().await.unwrap();
+        assert_eq!(payload.message_hash, ContentHash::from_string("message"));
+        assert_eq!(payload.skill_hash, ContentHash::from_string("skill:echo"));
+        assert_eq!(payload.confidence.value(), 0.9);
+        assert!(processed.try_recv().is_err());
+    }
+
     #[test]
     fn test_event_filter() {
         let filter = EventFilter::all()
@@ -0,0 +1,284 @@
+//! Event sharing between gateway instances.
+//!
+//! Deployments behind a load balancer see messages from the same user on
+//! different instances. A `FederationBridge` forwards selected events to
+//! peer gateways, which apply them so session trust stays eventually
+//! consistent across instances.
+//!
+//! Events are signed with a shared secret together with the time they were
+//! sent; receivers drop events outside a time window or seen before.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::SinkExt;
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpStream;
+use tokio::sync::{broadcast, Mutex, RwLock};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+use crate::channels::common::{hmac_sha256, verify_hmac_sha256};
+use crate::error::GatewayError;
+use super::events::{EventBus, GatewayEvent};
+use super::server::ClientMessage;
+use super::session::SessionManager;
+
+/// Event types forwarded to peers by default.
+pub const DEFAULT_FORWARDED_EVENTS: &[&str] = &["trust_changed"];
+
+/// An event shared by a peer gateway.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerEvent {
+    /// Node ID of the gateway the event originated on
+    pub origin: String,
+    /// The forwarded event
+    pub event: GatewayEvent,
+    /// Unix seconds the event was sent at, covered by the signature
+    #[serde(default)]
+    pub sent_at: i64,
+    /// Hex HMAC-SHA256 of the origin, send time and event under the
+    /// federation secret
+    #[serde(default)]
+    pub signature: String,
+}
+
+impl PeerEvent {
+    /// Create an event sent now, signed with the federation `secret`.
+    pub fn signed(origin: &str, event: GatewayEvent, secret: &str) -> Self {
+        Self::signed_at(origin, event, secret, chrono::Utc::now().timestamp())
+    }
+
+    /// Create an event sent at `sent_at` (Unix seconds), signed with the
+    /// federation `secret`.
+    pub fn signed_at(origin: &str, event: GatewayEvent, secret: &str, sent_at: i64) -> Self {
+        let mut peer_event = Self {
+            origin: origin.to_string(),
+            event,
+            sent_at,
+            signature: String::new(),
+        };
+        peer_event.signature = hex::encode(hmac_sha256(secret.as_bytes(), &peer_event.sign_message()));
+        peer_event
+    }
+
+    /// Check the signature against the federation `secret`.
+    pub fn verify(&self, secret: &str) -> bool {
+        match hex::decode(&self.signature) {
+            Ok(mac) => verify_hmac_sha256(secret.as_bytes(), &self.sign_message(), &mac),
+            Err(_) => false,
+        }
+    }
+
+    fn sign_message(&self) -> Vec<u8> {
+        let event = serde_json::to_vec(&self.event).unwrap_or_default();
+        let mut message = Vec::with_capacity(16 + self.origin.len() + event.len());
+        message.extend_from_slice(&(self.origin.len() as u64).to_le_bytes());
+        message.extend_from_slice(self.origin.as_bytes());
+        message.extend_from_slice(&self.sent_at.to_le_bytes());
+        message.extend_from_slice(&event);
+        message
+    }
+}
+
+/// Accepts peer events that are signed, recent and not seen before.
+pub struct PeerEventGuard {
+    secret: String,
+    max_skew_secs: u64,
+    /// Signatures accepted within the window, with their send time
+    seen: std::sync::Mutex<HashMap<String, i64>>,
+}
+
+impl PeerEventGuard {
+    /// Accept events signed with `secret` and sent at most `max_skew_secs`
+    /// from now.
+    pub fn new(secret: &str, max_skew_secs: u64) -> Self {
+        Self {
+            secret: secret.to_string(),
+            max_skew_secs,
+            seen: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Check an event, recording it so a replay is rejected.
+    pub fn check(&self, event: &PeerEvent) -> Result<(), String> {
+        if !event.verify(&self.secret) {
+            return Err("Invalid peer event signature".to_string());
+        }
+        let now = chrono::Utc::now().timestamp();
+        let skew = now.abs_diff(event.sent_at);
+        if skew > self.max_skew_secs {
+            return Err(format!(
+                "Peer event is {}s from now, outside the {}s window",
+                skew, self.max_skew_secs
+            ));
+        }
+        // Entries older than the window are forgotten; their timestamps fail anyway
+        let cutoff = now - self.max_skew_secs as i64;
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        seen.retain(|_, at| *at >= cutoff);
+        if seen.insert(event.signature.clone(), event.sent_at).is_some() {
+            return Err("Replayed peer event".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Delivers events to one peer gateway.
+#[async_trait]
+pub trait PeerTransport: Send + Sync {
+    /// Send an event to the peer.
+    async fn send(&self, event: &PeerEvent) -> Result<(), GatewayError>;
+}
+
+/// Peer reached over the gateway WebSocket protocol.
+///
+/// The connection is opened on first use and reopened after a failed send.
+pub struct WebSocketPeer {
+    url: String,
+    stream: Mutex<Option<WebSocketStream<MaybeTlsStream<TcpStream>>>>,
+}
+
+impl WebSocketPeer {
+    /// Create a peer for the `/ws` endpoint at `url`.
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            stream: Mutex::new(None),
+        }
+    }
+}
+
+#[async_trait]
+impl PeerTransport for WebSocketPeer {
+    async fn send(&self, event: &PeerEvent) -> Result<(), GatewayError> {
+        let message = ClientMessage::PeerEvent {
+            origin: event.origin.clone(),
+            event: Box::new(event.event.clone()),
+            sent_at: event.sent_at,
+            signature: event.signature.clone(),
+        };
+        let json = serde_json::to_string(&message)
+            .map_err(|e| GatewayError::PeerError(e.to_string()))?;
+
+        let mut stream = self.stream.lock().await;
+        if stream.is_none() {
+            let (socket, _) = connect_async(self.url.as_str())
+                .await
+                .map_err(|e| GatewayError::PeerError(format!("{}: {}", self.url, e)))?;
+            *stream = Some(socket);
+        }
+        let result = match stream.as_mut() {
+            Some(socket) => socket.send(Message::Text(json)).await,
+            None => return Ok(()),
+        };
+        result.map_err(|e| {
+            *stream = None;
+            GatewayError::PeerError(format!("{}: {}", self.url, e))
+        })
+    }
+}
+
+/// Peer running in the same process, applying events to its sessions directly.
+#[derive(Clone)]
+pub struct LocalPeer {
+    sessions: Arc<RwLock<SessionManager>>,
+}
+
+impl LocalPeer {
+    /// Create a peer that applies events to `sessions`.
+    pub fn new(sessions: Arc<RwLock<SessionManager>>) -> Self {
+        Self { sessions }
+    }
+}
+
+#[async_trait]
+impl PeerTransport for LocalPeer {
+    async fn send(&self, event: &PeerEvent) -> Result<(), GatewayError> {
+        self.sessions.write().await.apply_peer_event(&event.event);
+        Ok(())
+    }
+}
+
+/// Forwards selected events from an event bus to peer gateways.
+///
+/// Events applied from peers are not republished, so bridges in both
+/// directions do not echo events back and forth.
+pub struct FederationBridge {
+    origin: String,
+    peers: Vec<Arc<dyn PeerTransport>>,
+    event_types: HashSet<String>,
+    secret: Option<String>,
+}
+
+impl FederationBridge {
+    /// Create a bridge for the gateway with node ID `origin`.
+    pub fn new(origin: &str) -> Self {
+        Self {
+            origin: origin.to_string(),
+            peers: Vec::new(),
+            event_types: DEFAULT_FORWARDED_EVENTS.iter().map(|t| t.to_string()).collect(),
+            secret: None,
+        }
+    }
+
+    /// Sign forwarded events with the shared federation secret.
+    pub fn with_secret(mut self, secret: &str) -> Self {
+        self.secret = Some(secret.to_string());
+        self
+    }
+
+    /// Add a peer to forward events to.
+    pub fn with_peer(mut self, peer: Arc<dyn PeerTransport>) -> Self {
+        self.peers.push(peer);
+        self
+    }
+
+    /// Replace the event types forwarded (see `GatewayEvent::event_type`).
+    pub fn with_event_types(mut self, event_types: &[String]) -> Self {
+        self.event_types = event_types.iter().cloned().collect();
+        self
+    }
+
+    /// Check whether an event is forwarded to peers.
+    pub fn forwards(&self, event: &GatewayEvent) -> bool {
+        self.event_types.contains(event.event_type())
+    }
+
+    /// Send one event to every peer, logging failures.
+    pub async fn forward(&self, event: GatewayEvent) {
+        let event = match &self.secret {
+            Some(secret) => PeerEvent::signed(&self.origin, event, secret),
+            None => PeerEvent {
+                origin: self.origin.clone(),
+                event,
+                sent_at: chrono::Utc::now().timestamp(),
+                signature: String::new(),
+            },
+        };
+        for peer in &self.peers {
+            if let Err(e) = peer.send(&event).await {
+                tracing::warn!("Failed to forward {} to peer: {}", event.event.event_type(), e);
+            }
+        }
+    }
+
+    /// Forward matching events from `bus` until it closes.
+    pub fn spawn(self, bus: &EventBus) -> JoinHandle<()> {
+        let mut subscriber = bus.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match subscriber.recv().await {
+                    Ok(event) if self.forwards(&event) => self.forward(event).await,
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!("Federation bridge skipped {} events", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+}
+
@@ -0,0 +1,78 @@
+//! Canonical user identities across channels.
+//!
+//! Channels report sender ids in their own formats (Telegram numeric ids,
+//! Discord snowflakes, Slack `U…` ids). A [`SenderNormalizer`] maps each raw
+//! id to the canonical form used for session keys and allowlist checks.
+//! Replies are still addressed with the raw id the channel reported.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Maps a channel's raw sender id to its canonical form.
+pub trait SenderNormalizer: Send + Sync {
+    /// Canonical id of `raw_id` on `channel`.
+    fn normalize_sender(&self, channel: &str, raw_id: &str) -> String;
+}
+
+/// Normalizer leaving ids unchanged.
+#[derive(Debug, Default)]
+pub struct IdentityNormalizer;
+
+impl SenderNormalizer for IdentityNormalizer {
+    fn normalize_sender(&self, _channel: &str, raw_id: &str) -> String {
+        raw_id.to_string()
+    }
+}
+
+/// Configurable sender id normalization. The default leaves ids unchanged.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SenderIdConfig {
+    /// Lowercase ids
+    #[serde(default)]
+    pub lowercase: bool,
+
+    /// Prefix added to ids, keyed by channel (e.g. `"telegram" = "tg:"`)
+    #[serde(default)]
+    pub prefixes: HashMap<String, String>,
+
+    /// Canonical ids keyed by `channel:raw_id`, merging several ids of one
+    /// channel into one user; applied instead of the other rules. Users are
+    /// still scoped by channel, so aliases on different channels never
+    /// share sessions
+    #[serde(default)]
+    pub aliases: HashMap<String, String>,
+}
+
+impl SenderNormalizer for SenderIdConfig {
+    fn normalize_sender(&self, channel: &str, raw_id: &str) -> String {
+        let raw_id = raw_id.trim();
+        if let Some(alias) = self.aliases.get(&format!("{}:{}", channel, raw_id)) {
+            return alias.clone();
+        }
+        let id = if self.lowercase { raw_id.to_lowercase() } else { raw_id.to_string() };
+        match self.prefixes.get(channel) {
+            Some(prefix) if !id.starts_with(prefix.as_str()) => format!("{}{}", prefix, id),
+            _ => id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_normalization_is_idempotent() {
+        let config = SenderIdConfig {
+            lowercase: true,
+            prefixes: HashMap::from([("slack".to_string(), "slack:".to_string())]),
+            aliases: HashMap::from([("telegram:42".to_string(), "alice".to_string())]),
+        };
+        assert_eq!(config.normalize_sender("slack", " U123 "), "slack:u123");
+        assert_eq!(config.normalize_sender("slack", "slack:u123"), "slack:u123");
+        assert_eq!(config.normalize_sender("telegram", "42"), "alice");
+        assert_eq!(config.normalize_sender("discord", "Bob"), "bob");
+        assert_eq!(IdentityNormalizer.normalize_sender("slack", "U123"), "U123");
+    }
+}
@@ -31,26 +31,56 @@
 //!
 //! See: `AGENT-7-0OPENCLAW-GATEWAY.md`
 
+pub mod attachments;
+pub mod audit;
+pub use crate::clock;
 pub mod config;
+pub mod dead_letter;
+pub mod privacy;
+pub mod quiet_hours;
+pub mod scheduler;
 pub mod session;
+pub mod session_store;
 pub mod router;
 pub mod proof;
+pub mod signing;
 pub mod events;
+pub mod federation;
+pub mod identity;
 pub mod server;
+pub mod warmup;
 
 // Re-exports
-pub use config::GatewayConfig;
+pub use config::{
+    ConfidenceAggregation, ConfidenceConfig, DeadLetterConfig, ExecutionConfig, FederationConfig,
+    GatewayConfig, HttpConfig, UndeliverablePolicy,
+};
+pub use dead_letter::{DeadLetter, DeadLetterQueue, DeliveryStore, FailedDelivery, JsonlDeliveryStore};
+pub use attachments::{AttachmentPolicy, AttachmentScanner, AttachmentViolation};
+pub use audit::{AuditSink, JsonlAuditSink, MessageQueueAuditSink, NoopAuditSink};
+pub use clock::{Clock, SystemClock, MockClock};
+pub use privacy::{UserDataExport, UserScope};
+pub use quiet_hours::{QuietHours, HeldMessage};
+pub use scheduler::{JsonReminderStore, Reminder, ReminderStore};
 pub use session::{Session, SessionManager, SessionInfo};
+pub use session_store::{SessionStore, MemorySessionStore, JsonFileSessionStore};
 pub use router::{Router, RouteResult};
-pub use proof::{ProofGenerator, ProofBuilder, ExecutionTrace};
-pub use events::{EventBus, GatewayEvent, EventSubscriber, EventFilter};
-pub use server::{GatewayServer, ServerState, ServerMessage, ClientMessage};
+pub use proof::{ProofGenerator, ProofBuilder, ExecutionTrace, VerifyPolicy};
+pub use signing::{Signer, Verifier, Ed25519Signer, Ed25519Verifier};
+pub use events::{
+    EventBus, GatewayEvent, EventSubscriber, EventFilter, FromEvent, MessageProcessedPayload,
+    TypedSubscriber,
+};
+pub use identity::{IdentityNormalizer, SenderIdConfig, SenderNormalizer};
+pub use federation::{FederationBridge, LocalPeer, PeerEvent, PeerEventGuard, PeerTransport, WebSocketPeer};
+pub use server::{ChannelControl, GatewayServer, ServerState, ServerMessage, ClientMessage};
+pub use warmup::{CoreGraphStatus, GraphSource, WarmUpReport};
 
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
-use crate::runtime::{GraphInterpreter, Value};
+use crate::runtime::{GraphInterpreter, HttpResolver, Keystore, RuntimeConfig, Value};
 use crate::types::{
     Action,
     ActionLane,
@@ -59,10 +89,77 @@ use crate::types::{
     IncomingMessage,
     OutgoingMessage,
     ProofCarryingAction,
+    UserRef,
 };
 use crate::error::GatewayError;
-use crate::channels::Channel;
-use crate::skills::SkillRegistry;
+use crate::channels::{Channel, ChannelFeature, ChannelRegistry, ChannelStatus};
+use crate::channels::common::{with_retry, RetryPolicy, RetryResult};
+use crate::skills::{SkillRegistry, VerificationCache};
+
+/// Session context key holding the sender's last message, truncated to
+/// `session.last_message_chars` (not stored when that is 0).
+pub const LAST_MESSAGE_KEY: &str = "last_message";
+
+/// Skill input carrying the sender's previous message (empty unless
+/// `session.last_message_chars` is set).
+pub const PREVIOUS_MESSAGE_INPUT: &str = "previous_message";
+
+/// Message metadata key and graph input carrying preprocessor results.
+pub const ENRICHMENT_INPUT: &str = "enrichment";
+
+/// Turns a conversation claim lasts when the skill does not output `max_turns`.
+pub const DEFAULT_CLAIM_TURNS: u32 = 5;
+
+/// Skill reference recorded when a skill is unavailable on a channel.
+pub const UNAVAILABLE_SKILL_REF: &str = "skill:unavailable";
+
+/// Reference recorded in the trace when gating denies an action.
+pub const DENIED_ACTION_REF: &str = "gate:denied";
+
+/// Skill reference the router emits for commands it does not know.
+pub const UNKNOWN_COMMAND_SKILL_REF: &str = "skill:unknown_command";
+
+/// Skill reference the router emits for `/skills`.
+pub const LIST_SKILLS_SKILL_REF: &str = "skill:list";
+
+/// Skill reference the router emits for `/remind`.
+pub const REMINDER_SKILL_REF: &str = "skill:reminder";
+
+/// Reply to a `/remind` that could not be parsed.
+const REMINDER_USAGE: &str = "Usage: /remind <duration> <text>, e.g. /remind 1h30m stretch";
+
+/// Skill references answered by the gateway when no installed skill matches.
+const BUILTIN_SKILL_REFS: &[&str] = &[
+    "skill:help",
+    "skill:status",
+    LIST_SKILLS_SKILL_REF,
+    "skill:session",
+    "skill:search",
+    REMINDER_SKILL_REF,
+    "skill:trade",
+    UNKNOWN_COMMAND_SKILL_REF,
+    "skill:greeting",
+    "skill:qa",
+    "skill:assistant",
+    "skill:acknowledge",
+    "skill:conversation",
+    "skill:default",
+];
+
+/// Shared outcome of an in-flight message (errors are rendered to strings).
+type InFlightReceiver = tokio::sync::watch::Receiver<Option<Result<ProofCarryingAction, String>>>;
+
+/// Removes an in-flight entry when its leader finishes or is cancelled.
+struct InFlightGuard {
+    in_flight: Arc<std::sync::Mutex<HashMap<ContentHash, InFlightReceiver>>>,
+    id: ContentHash,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.in_flight.lock().unwrap().remove(&self.id);
+    }
+}
 
 /// Main Gateway structure.
 ///
@@ -78,9 +175,12 @@ pub struct Gateway {
     
     /// Message router
     router: Arc<RwLock<Router>>,
+
+    /// Whether the router was set explicitly, so warm-up keeps it
+    router_configured: Arc<std::sync::atomic::AtomicBool>,
     
-    /// Registered channels
-    channels: HashMap<String, Arc<dyn Channel>>,
+    /// Registered channels and their connection state
+    channels: Arc<std::sync::RwLock<ChannelRegistry>>,
     
     /// Skill registry
     skills: Arc<RwLock<SkillRegistry>>,
@@ -93,12 +193,54 @@ pub struct Gateway {
     
     /// Event bus for internal communication
     event_bus: EventBus,
+
+    /// Messages that failed processing
+    dead_letters: Arc<RwLock<DeadLetterQueue>>,
+
+    /// Actions that could not be delivered
+    failed_deliveries: Arc<RwLock<Box<dyn DeliveryStore>>>,
+
+    /// Source of the current time
+    clock: Arc<dyn Clock>,
+
+    /// Non-urgent messages held during quiet hours
+    held_messages: Arc<RwLock<Vec<HeldMessage>>>,
+
+    /// Reminders waiting for their due time
+    reminders: Arc<RwLock<Box<dyn ReminderStore>>>,
+
+    /// Replies queued for channels that are disconnected
+    undelivered: Arc<RwLock<Vec<OutgoingMessage>>>,
+
+    /// Messages currently being processed, for coalescing duplicates
+    in_flight: Arc<std::sync::Mutex<HashMap<ContentHash, InFlightReceiver>>>,
+
+    /// Stop signals of running channel listeners; dropping one stops its listener
+    listener_stops: Arc<std::sync::Mutex<HashMap<String, tokio::sync::watch::Sender<()>>>>,
     
     /// Gateway configuration
     config: GatewayConfig,
     
     /// Whether the gateway is running
     running: Arc<RwLock<bool>>,
+
+    /// When the gateway was created, for reporting uptime
+    started_at: std::time::Instant,
+
+    /// Messages processed since the last heartbeat
+    processed_since_heartbeat: Arc<std::sync::atomic::AtomicU64>,
+
+    /// Sinks every executed action is exported to
+    audit_sinks: Vec<Arc<dyn AuditSink>>,
+
+    /// Audit records still being written by their sinks
+    audit_tasks: Arc<std::sync::Mutex<tokio::task::JoinSet<()>>>,
+
+    /// Hook inspecting inbound attachments
+    attachment_scanner: Option<Arc<dyn AttachmentScanner>>,
+
+    /// Maps raw sender ids to canonical ones
+    sender_normalizer: Arc<dyn SenderNormalizer>,
 }
 
 impl Gateway {
@@ -130,32 +272,108 @@ impl Gateway {
             generator
         };
 
+        if !config.proofs_enabled {
+            tracing::warn!(
+                "PROOF GENERATION IS DISABLED: actions are unsigned and unverified. \
+                 Only use this in trusted deployments."
+            );
+        }
+
         // Initialize session manager with config
         let session_config = session::SessionManagerConfig {
             timeout_seconds: config.session.timeout_seconds,
             max_per_user: config.session.max_per_user,
             initial_trust: config.session.initial_trust,
             trust_decay: config.session.trust_decay,
+            success_reward: config.session.success_reward,
+            failure_penalty: config.session.failure_penalty,
+            evict_oldest: config.session.evict_oldest,
         };
 
         // Initialize router
         let router = Self::create_default_router();
 
-        let mut skill_registry = SkillRegistry::new(&config.skills_path);
+        let mut skill_registry = SkillRegistry::new(&config.skills_path).with_quota(config.skill_quota);
+        if let Some(path) = &config.skill_cache_path {
+            skill_registry = skill_registry.with_verification_cache(VerificationCache::new(path));
+        }
         if let Err(e) = skill_registry.load_builtin() {
             tracing::warn!("Failed to load built-in skills at startup: {}", e);
         }
+        skill_registry
+            .load_installed(config.skill_load_mode)
+            .map_err(|e| GatewayError::InvalidConfig(format!("Failed to load skills: {}", e)))?;
+
+        let mut dead_letters = DeadLetterQueue::new(config.dead_letter.capacity);
+        if let Some(path) = &config.dead_letter.path {
+            dead_letters = dead_letters.with_path(path);
+        }
+        let mut failed_deliveries = JsonlDeliveryStore::new();
+        if let Some(path) = &config.dead_letter.delivery_path {
+            failed_deliveries = failed_deliveries.with_path(path);
+        }
+        let mut reminders = JsonReminderStore::new();
+        if let Some(path) = &config.reminders_path {
+            reminders = reminders.with_path(path);
+        }
+        let sessions = if config.session.persist {
+            let store = JsonFileSessionStore::new(config.session_store_path());
+            SessionManager::with_store(session_config, Box::new(store))
+        } else {
+            SessionManager::with_config(session_config)
+        };
+        // Per-skill timeouts are applied around each execution; the
+        // runtime budget only caps the longest one
+        let runtime_timeout_ms = config.execution.max_skill_timeout_ms.max(config.execution.timeout_ms);
+        let mut runtime = GraphInterpreter::new(RuntimeConfig {
+            timeout_ms: runtime_timeout_ms,
+            dev_mode: config.dev_mode,
+            ..Default::default()
+        });
+        if let Some(path) = &config.keystore_path {
+            runtime = runtime.with_keystore(Keystore::load(path)?);
+        }
+        if !config.http.allowed_hosts.is_empty() {
+            runtime = runtime.with_external_resolver(Arc::new(
+                HttpResolver::new(std::time::Duration::from_millis(runtime_timeout_ms))
+                    .with_allowed_hosts(config.http.allowed_hosts.clone()),
+            ));
+        }
+        let audit_sinks: Vec<Arc<dyn AuditSink>> = config
+            .audit_log_path
+            .iter()
+            .map(|path| Arc::new(JsonlAuditSink::new(path)) as Arc<dyn AuditSink>)
+            .collect();
 
         Ok(Self {
-            sessions: Arc::new(RwLock::new(SessionManager::with_config(session_config))),
+            sessions: Arc::new(RwLock::new(sessions)),
             router: Arc::new(RwLock::new(router)),
-            channels: HashMap::new(),
+            router_configured: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            channels: Arc::new(std::sync::RwLock::new(ChannelRegistry::new())),
             skills: Arc::new(RwLock::new(skill_registry)),
-            runtime: Arc::new(GraphInterpreter::default()),
-            proof_generator: Arc::new(proof_generator),
+            runtime: Arc::new(runtime),
+            proof_generator: Arc::new(
+                proof_generator
+                    .with_max_trace_len(config.max_trace_len)
+                    .with_confidence_config(config.confidence.clone()),
+            ),
             event_bus: EventBus::new().with_history(1000),
-            config,
+            dead_letters: Arc::new(RwLock::new(dead_letters)),
+            failed_deliveries: Arc::new(RwLock::new(Box::new(failed_deliveries))),
+            clock: Arc::new(SystemClock),
+            held_messages: Arc::new(RwLock::new(Vec::new())),
+            reminders: Arc::new(RwLock::new(Box::new(reminders))),
+            undelivered: Arc::new(RwLock::new(Vec::new())),
+            in_flight: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            listener_stops: Arc::new(std::sync::Mutex::new(HashMap::new())),
             running: Arc::new(RwLock::new(false)),
+            started_at: std::time::Instant::now(),
+            processed_since_heartbeat: Arc::new(std::sync::atomic::AtomicU64::new(0)),
+            audit_sinks,
+            audit_tasks: Arc::new(std::sync::Mutex::new(tokio::task::JoinSet::new())),
+            attachment_scanner: None,
+            sender_normalizer: Arc::new(config.sender_ids.clone()),
+            config,
         })
     }
 
@@ -165,16 +383,168 @@ impl Gateway {
         Router::with_defaults()
     }
 
+    /// Sign PCAs with a custom signer instead of the configured keypair.
+    pub fn with_signer(mut self, signer: Box<dyn Signer>) -> Self {
+        self.proof_generator = Arc::new(
+            ProofGenerator::with_signer(signer)
+                .with_max_trace_len(self.config.max_trace_len)
+                .with_confidence_config(self.config.confidence.clone()),
+        );
+        self
+    }
+
+    /// Use a custom store for actions that could not be delivered.
+    pub fn with_delivery_store(mut self, store: Box<dyn DeliveryStore>) -> Self {
+        self.failed_deliveries = Arc::new(RwLock::new(store));
+        self
+    }
+
+    /// Use a custom store for pending reminders.
+    pub fn with_reminder_store(mut self, store: Box<dyn ReminderStore>) -> Self {
+        self.reminders = Arc::new(RwLock::new(store));
+        self
+    }
+
+    /// Export every executed action to `sink`.
+    pub fn with_audit_sink(mut self, sink: Arc<dyn AuditSink>) -> Self {
+        self.audit_sinks.push(sink);
+        self
+    }
+
+    /// Inspect inbound attachments with `scanner` after the attachment policy.
+    pub fn with_attachment_scanner(mut self, scanner: Arc<dyn AttachmentScanner>) -> Self {
+        self.attachment_scanner = Some(scanner);
+        self
+    }
+
+    /// Normalize sender ids with `normalizer` instead of the configured rules.
+    pub fn with_sender_normalizer(mut self, normalizer: Arc<dyn SenderNormalizer>) -> Self {
+        self.sender_normalizer = normalizer;
+        self
+    }
+
+    /// Canonical identity of `raw_id` on `channel`.
+    pub fn user_ref(&self, channel: &str, raw_id: &str) -> UserRef {
+        UserRef::new(channel, &self.sender_normalizer.normalize_sender(channel, raw_id))
+    }
+
+    /// Use a custom time source.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Register a channel.
     pub fn register_channel(&mut self, channel: Arc<dyn Channel>) {
+        tracing::info!("Registering channel: {}", channel.name());
+        self.channels.write().unwrap().register_shared(channel);
+    }
+
+    /// Disconnect a channel, stopping its listener.
+    ///
+    /// Replies addressed to it afterwards follow `undeliverable_policy`.
+    /// Returns whether the channel was connected.
+    pub async fn disconnect_channel(&self, name: &str, reason: &str) -> bool {
+        if !self.channels.write().unwrap().disconnect(name, reason) {
+            return false;
+        }
+        self.listener_stops.lock().unwrap().remove(name);
+        tracing::info!("Disconnected channel {}: {}", name, reason);
+        self.event_bus.publish(GatewayEvent::ChannelDisconnected {
+            channel_id: name.to_string(),
+            reason: reason.to_string(),
+        }).await;
+        true
+    }
+
+    /// Connect a disconnected channel again, resuming its listener while the
+    /// gateway runs, and deliver replies queued while it was away.
+    ///
+    /// Returns one result per queued reply.
+    pub async fn connect_channel(
+        &self,
+        name: &str,
+    ) -> Result<Vec<Result<ProofCarryingAction, crate::error::ChannelError>>, GatewayError> {
+        if !self.channels.write().unwrap().connect(name) {
+            return match self.channels.read().unwrap().has(name) {
+                true => Err(GatewayError::ExecutionError(format!("Channel {} is already connected", name))),
+                false => Err(GatewayError::ChannelNotFound(name.to_string())),
+            };
+        }
+        Ok(self.resume_channel(name).await)
+    }
+
+    /// Register a channel again and deliver replies queued while it was away.
+    ///
+    /// Returns one result per queued reply.
+    pub async fn reconnect_channel(
+        &self,
+        channel: Arc<dyn Channel>,
+    ) -> Vec<Result<ProofCarryingAction, crate::error::ChannelError>> {
         let name = channel.name().to_string();
-        tracing::info!("Registering channel: {}", name);
-        self.channels.insert(name, channel);
+        self.channels.write().unwrap().register_shared(channel);
+        self.resume_channel(&name).await
+    }
+
+    /// Lifecycle status of a registered channel.
+    pub fn channel_status(&self, name: &str) -> Option<ChannelStatus> {
+        self.channels.read().unwrap().status(name)
+    }
+
+    /// Lifecycle status of every registered channel.
+    pub fn channel_statuses(&self) -> Vec<ChannelStatus> {
+        self.channels.read().unwrap().statuses()
+    }
+
+    /// Listen on a newly connected channel and flush its queued replies.
+    async fn resume_channel(&self, name: &str) -> Vec<Result<ProofCarryingAction, crate::error::ChannelError>> {
+        let Some(channel) = self.get_channel(name) else {
+            return Vec::new();
+        };
+        if *self.running.read().await {
+            self.spawn_listener(name.to_string(), channel.clone());
+        }
+        self.sessions.write().await.set_channel_stale(name, false);
+        self.event_bus.publish(GatewayEvent::ChannelConnected { channel_id: name.to_string() }).await;
+
+        let queued: Vec<OutgoingMessage> = {
+            let mut undelivered = self.undelivered.write().await;
+            let (queued, others) = undelivered.drain(..).partition(|m| m.channel_id == name);
+            *undelivered = others;
+            queued
+        };
+        channel.send_batch(queued).await
+    }
+
+    /// Get replies queued for disconnected channels.
+    pub async fn undelivered_messages(&self) -> Vec<OutgoingMessage> {
+        self.undelivered.read().await.clone()
+    }
+
+    /// Apply `undeliverable_policy` to a reply whose channel is not connected.
+    async fn handle_undeliverable(&self, message: &OutgoingMessage) {
+        match self.config.undeliverable_policy {
+            UndeliverablePolicy::Queue => {
+                tracing::info!("Channel {} disconnected, queueing reply", message.channel_id);
+                self.undelivered.write().await.push(message.clone());
+            }
+            UndeliverablePolicy::Drop => {
+                tracing::warn!("Channel {} disconnected, dropping reply", message.channel_id);
+                self.sessions.write().await.set_channel_stale(&message.channel_id, true);
+                self.event_bus.publish(GatewayEvent::Error {
+                    source: "gateway".to_string(),
+                    message: format!(
+                        "Reply to {} dropped: channel {} is not connected",
+                        message.recipient_id, message.channel_id
+                    ),
+                }).await;
+            }
+        }
     }
 
-    /// Get a registered channel by name.
+    /// Get a connected channel by name.
     pub fn get_channel(&self, name: &str) -> Option<Arc<dyn Channel>> {
-        self.channels.get(name).cloned()
+        self.channels.read().unwrap().connected(name)
     }
 
     /// Get the event bus.
@@ -190,10 +560,137 @@ impl Gateway {
     /// Process an incoming message.
     ///
     /// This is the main entry point for message processing.
-    /// Returns a Proof-Carrying Action.
+    /// Returns a Proof-Carrying Action. Messages that fail are recorded
+    /// in the dead-letter queue.
+    ///
+    /// Concurrent calls with the same message id are coalesced: only the
+    /// first is executed and the others receive its result.
     pub async fn process_message(
         &self,
         message: IncomingMessage,
+    ) -> Result<ProofCarryingAction, GatewayError> {
+        let id = message.id;
+        let leader = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get(&id) {
+                Some(receiver) => Err(receiver.clone()),
+                None => {
+                    let (sender, receiver) = tokio::sync::watch::channel(None);
+                    in_flight.insert(id, receiver);
+                    Ok(sender)
+                }
+            }
+        };
+
+        let sender = match leader {
+            Ok(sender) => sender,
+            Err(mut receiver) => {
+                tracing::debug!("Coalescing duplicate in-flight message {}", id);
+                let outcome = receiver
+                    .wait_for(Option::is_some)
+                    .await
+                    .map_err(|_| GatewayError::ExecutionError("In-flight request was abandoned".to_string()))?
+                    .clone();
+                return match outcome {
+                    Some(Ok(pca)) => Ok(pca),
+                    Some(Err(e)) => Err(GatewayError::ExecutionError(e)),
+                    None => Err(GatewayError::ExecutionError("In-flight request was abandoned".to_string())),
+                };
+            }
+        };
+
+        let _guard = InFlightGuard { in_flight: self.in_flight.clone(), id };
+        let result = self.process_uncoalesced(message).await;
+        sender.send_replace(Some(match &result {
+            Ok(pca) => Ok(pca.clone()),
+            Err(e) => Err(e.to_string()),
+        }));
+        result
+    }
+
+    /// Process a message, recording failures in the dead-letter queue.
+    async fn process_uncoalesced(
+        &self,
+        message: IncomingMessage,
+    ) -> Result<ProofCarryingAction, GatewayError> {
+        match self.handle_message(message.clone()).await {
+            Ok(pca) => Ok(pca),
+            Err(err) => {
+                tracing::warn!("Dead-lettering message {}: {}", message.id, err);
+                self.dead_letters.write().await.push(message, err.to_string());
+                Err(err)
+            }
+        }
+    }
+
+    /// Get all dead letters, oldest first.
+    pub async fn dead_letters(&self) -> Vec<DeadLetter> {
+        self.dead_letters.read().await.list()
+    }
+
+    /// Remove a dead letter and process its message again.
+    ///
+    /// If processing fails again, the message returns to the queue.
+    pub async fn retry_dead_letter(
+        &self,
+        id: &ContentHash,
+    ) -> Result<ProofCarryingAction, GatewayError> {
+        let dead_letter = self
+            .dead_letters
+            .write()
+            .await
+            .remove(id)
+            .ok_or(GatewayError::DeadLetterNotFound(*id))?;
+        self.process_message(dead_letter.message).await
+    }
+
+    /// Get all actions that could not be delivered, oldest first.
+    pub async fn failed_deliveries(&self) -> Vec<FailedDelivery> {
+        self.failed_deliveries.read().await.list()
+    }
+
+    /// Remove a failed delivery and execute its action again.
+    ///
+    /// If delivery fails again, the action returns to the store.
+    pub async fn redeliver(&self, id: &ContentHash) -> Result<ProofCarryingAction, GatewayError> {
+        let delivery = self
+            .failed_deliveries
+            .write()
+            .await
+            .remove(id)
+            .ok_or(GatewayError::DeadLetterNotFound(*id))?;
+        let mut pca = delivery.pca;
+        self.execute_action(&mut pca).await?;
+        Ok(pca)
+    }
+
+    /// Try once more to deliver stored deliveries that were interrupted mid-retry.
+    ///
+    /// Run in the background on startup, so a restart during backoff does not
+    /// lose the action. Exhausted deliveries and ones older than
+    /// `redelivery_max_age_ms` are left for [`Gateway::redeliver`]. Deliveries
+    /// that fail again stay stored.
+    pub async fn redeliver_pending(&self) -> Vec<Result<ProofCarryingAction, GatewayError>> {
+        let oldest = (self.clock.now().timestamp_millis().max(0) as u64)
+            .saturating_sub(self.config.dead_letter.redelivery_max_age_ms);
+        let pending: Vec<_> = self
+            .failed_deliveries
+            .read()
+            .await
+            .list()
+            .into_iter()
+            .filter(|delivery| !delivery.exhausted && delivery.timestamp >= oldest)
+            .collect();
+        let mut results = Vec::with_capacity(pending.len());
+        for delivery in pending {
+            results.push(self.redeliver(&delivery.id).await);
+        }
+        results
+    }
+
+    async fn handle_message(
+        &self,
+        mut message: IncomingMessage,
     ) -> Result<ProofCarryingAction, GatewayError> {
         tracing::debug!("Processing message from {}/{}", message.channel_id, message.sender_id);
 
@@ -204,12 +701,39 @@ impl Gateway {
             message_hash: message.id,
         }).await;
 
-        // 1. Get or create session
-        let (session_id, session_hash, trust_score) = {
+        // 1. Get or create session, remembering the previous message so
+        // graphs can detect near-duplicate floods
+        let user = self.user_ref(&message.channel_id, &message.sender_id);
+        let (session_id, session_hash, trust_score, previous_message, cooldown_reply, claimed_skill) = {
             let mut sessions = self.sessions.write().await;
-            let session = sessions.get_or_create(&message.channel_id, &message.sender_id)
+            let session = sessions.get_or_create(&user.channel, &user.id)
                 .map_err(|e| GatewayError::RouterError(e.to_string()))?;
-            (session.id, session.hash(), session.trust_score.value())
+            let (session_id, session_hash, trust_score) =
+                (session.id, session.hash(), session.trust_score.value());
+
+            let session = sessions.get_mut(&session_id).ok_or_else(|| {
+                GatewayError::SessionError("Session disappeared".to_string())
+            })?;
+            let last_message_chars = self.config.session.last_message_chars;
+            let previous_message = if last_message_chars > 0 {
+                let previous = session
+                    .get_context(LAST_MESSAGE_KEY)
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string());
+                let kept: String = message.content.chars().take(last_message_chars).collect();
+                session.set_context(LAST_MESSAGE_KEY, serde_json::json!(kept));
+                previous
+            } else {
+                None
+            };
+            let cooldown_reply = self.check_command_cooldown(session, &message);
+            let claimed_skill = match cooldown_reply {
+                Some(_) => None,
+                None => session.take_claim_turn(),
+            };
+            sessions.persist(&session_id);
+
+            (session_id, session_hash, trust_score, previous_message, cooldown_reply, claimed_skill)
         };
 
         // Publish session event if new
@@ -218,105 +742,181 @@ impl Gateway {
             trust_score,
         }).await;
 
-        // 2. Route the message
-        let (route_result, route_trace) = {
-            let mut router = self.router.write().await;
-            router.route(&message).await?
+        if let Some(reply) = cooldown_reply {
+            tracing::debug!("Command from {} rejected by cooldown", message.sender_id);
+            let action = Action::SendMessage(
+                OutgoingMessage::new(&message.channel_id, &message.sender_id, &reply)
+                    .reply_to(message.id),
+            );
+            return self.generate_pca(action, session_hash, message.id, vec![]).await;
+        }
+
+        if let Err(reason) = self.screen_attachments(&mut message).await {
+            let (action, trace) = self.deny(&message, reason, ExecutionTrace::new());
+            return self.generate_pca(action, session_hash, message.id, vec![trace]).await;
+        }
+
+        // 2. Enrich the message with preprocessor skills, then route it
+        // A skill that claimed the conversation bypasses the router
+        self.run_preprocessors(&mut message).await;
+        let (route_result, route_trace) = match claimed_skill {
+            Some(skill_hash) => self.claimed_route(skill_hash, &message).await?,
+            None => {
+                let router = self.router.read().await;
+                router.route(&message).await?
+            }
         };
 
         tracing::debug!("Routed to skill: {} ({})", route_result.route_name, route_result.skill_hash);
 
+        let runs_skill = matches!(route_result.lane, ActionLane::Execute | ActionLane::AskApproval);
+        if runs_skill {
+            if let Some(secs) = self.skill_cooldown_remaining(&session_id, &route_result.skill_ref).await {
+                tracing::debug!("{} from {} rejected by cooldown", route_result.skill_ref, message.sender_id);
+                let command = message.content.split_whitespace().next().unwrap_or_default();
+                let reply = format!("{} is on cooldown, try again in {} seconds.", command, secs);
+                let action = Action::SendMessage(
+                    OutgoingMessage::new(&message.channel_id, &message.sender_id, &reply).reply_to(message.id),
+                );
+                return self.generate_pca(action, session_hash, message.id, vec![route_trace]).await;
+            }
+        }
+
+        let mut route_result = route_result;
+        route_result
+            .params
+            .insert(PREVIOUS_MESSAGE_INPUT.to_string(), previous_message.unwrap_or_default());
+
         // Publish skill invoked event
         self.event_bus.publish(GatewayEvent::SkillInvoked {
             skill_hash: route_result.skill_hash,
             skill_name: route_result.route_name.clone(),
         }).await;
 
-        // 3. Apply lane policy then execute skill when permitted.
-        let (action, skill_trace) = match route_result.lane {
-            ActionLane::Execute => {
-                self.execute_skill(
-                    &route_result.skill_ref,
-                    &route_result.skill_hash,
-                    &message,
-                    &route_result.params,
-                )
-                .await?
-            }
-            ActionLane::Clarify => (
-                Action::SendMessage(
-                    OutgoingMessage::new(
-                        &message.channel_id,
-                        &message.sender_id,
-                        "I need more detail before taking action. Please clarify intent and parameters.",
-                    )
-                    .reply_to(message.id),
-                ),
-                ExecutionTrace::new(),
-            ),
-            ActionLane::Deny => (
-                Action::SendMessage(
-                    OutgoingMessage::new(
-                        &message.channel_id,
-                        &message.sender_id,
-                        "Request denied due to low confidence and permission risk.",
-                    )
-                    .reply_to(message.id),
-                ),
-                ExecutionTrace::new(),
-            ),
-            ActionLane::AskApproval => {
-                let (candidate, trace) = self
-                    .execute_skill(
+        // 3. Apply lane policy then execute skill when permitted, showing
+        // the thinking placeholder if execution is slow.
+        let expect_slow = runs_skill
+            && self.config.thinking_placeholder.is_some()
+            && self.calls_external(&route_result.skill_ref, &route_result.skill_hash).await;
+        let (outcome, placeholder_id) = self.with_thinking_placeholder(&message, expect_slow, async {
+            Ok::<_, GatewayError>(match route_result.lane {
+                ActionLane::Execute => {
+                    self.execute_skill(
                         &route_result.skill_ref,
                         &route_result.skill_hash,
                         &message,
                         &route_result.params,
                     )
-                    .await?;
-                let action = match candidate {
-                    Action::SendMessage(mut msg) => {
-                        msg.content = format!(
-                            "{}\n\nApproval required before execution. Reply `approve` to continue.",
-                            msg.content
-                        );
-                        Action::SendMessage(msg)
-                    }
-                    _ => Action::SendMessage(
+                    .await?
+                }
+                ActionLane::Clarify => (
+                    Action::SendMessage(
                         OutgoingMessage::new(
                             &message.channel_id,
                             &message.sender_id,
-                            "Approval required before execution. Reply `approve` to continue.",
+                            "I need more detail before taking action. Please clarify intent and parameters.",
                         )
                         .reply_to(message.id),
                     ),
-                };
-                (action, trace)
+                    ExecutionTrace::new(),
+                ),
+                ActionLane::Deny => self.deny(
+                    &message,
+                    "Low confidence and permission risk".to_string(),
+                    ExecutionTrace::new(),
+                ),
+                ActionLane::AskApproval => {
+                    let (candidate, trace) = self
+                        .execute_skill(
+                            &route_result.skill_ref,
+                            &route_result.skill_hash,
+                            &message,
+                            &route_result.params,
+                        )
+                        .await?;
+                    let action = match candidate {
+                        Action::SendMessage(mut msg) => {
+                            msg.content = format!(
+                                "{}\n\nApproval required before execution. Reply `approve` to continue.",
+                                msg.content
+                            );
+                            Action::SendMessage(msg)
+                        }
+                        _ => Action::SendMessage(
+                            OutgoingMessage::new(
+                                &message.channel_id,
+                                &message.sender_id,
+                                "Approval required before execution. Reply `approve` to continue.",
+                            )
+                            .reply_to(message.id),
+                        ),
+                    };
+                    (action, trace)
+                }
+                ActionLane::Defer => (
+                    Action::NoOp {
+                        reason: "Execution deferred by confidence lane".to_string(),
+                    },
+                    ExecutionTrace::new(),
+                ),
+            })
+        })
+        .await;
+        let (action, skill_trace) = match outcome {
+            Ok(outcome) => outcome,
+            Err(err) => {
+                if let Some(id) = &placeholder_id {
+                    self.clear_placeholder(&message, id, "Sorry, that failed.").await;
+                }
+                return Err(err);
             }
-            ActionLane::Defer => (
-                Action::NoOp {
-                    reason: "Execution deferred by confidence lane".to_string(),
-                },
-                ExecutionTrace::new(),
-            ),
         };
+        let (mut action, skill_trace) = self.gate_action(action, skill_trace, &message);
+        // Only a skill that ran and passed the gate starts its cooldown
+        if route_result.lane == ActionLane::Execute && !matches!(action, Action::Denied { .. }) {
+            self.record_skill_use(&session_id, &route_result.skill_ref).await;
+        }
+        self.schedule_approved_reminder(&route_result, &action, &message).await;
+        // The reply or denial notice replaces the placeholder; other actions remove it
+        match (placeholder_id, &mut action) {
+            (Some(id), Action::SendMessage(msg) | Action::Denied { notice: msg, .. }) => msg.edit_of = Some(id),
+            (Some(id), _) => self.clear_placeholder(&message, &id, "Done.").await,
+            (None, _) => {}
+        }
 
         // 4. Generate proof-carrying action
-        let pca = self.proof_generator.generate(
-            action,
-            session_hash,
-            message.id,
-            vec![route_trace, skill_trace],
-        ).map_err(|e| GatewayError::VmError(e.to_string()))?;
+        let pca = self
+            .generate_pca(action, session_hash, message.id, vec![route_trace, skill_trace])
+            .await?;
 
         // 5. Update session
-        {
+        let new_trust = {
             let mut sessions = self.sessions.write().await;
             sessions.update(&session_id, &pca).await
                 .map_err(|e| GatewayError::RouterError(e.to_string()))?;
+            let trust_score = sessions.get_mut(&session_id).map(|session| {
+                match &pca.action {
+                    Action::ClaimConversation { skill_hash, max_turns } => {
+                        session.claim(*skill_hash, (*max_turns).min(self.config.max_claim_turns))
+                    }
+                    Action::ReleaseConversation => session.release(),
+                    _ => {}
+                }
+                session.trust_score.value()
+            });
+            sessions.persist(&session_id);
+            trust_score
+        };
+        if let Some(trust_score) = new_trust {
+            self.event_bus.publish(GatewayEvent::TrustChanged {
+                channel_id: user.channel.clone(),
+                user_id: user.id.clone(),
+                trust_score,
+            }).await;
         }
 
         // Publish completion event
+        self.processed_since_heartbeat.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
         self.event_bus.publish(GatewayEvent::MessageProcessed {
             message_hash: message.id,
             skill_hash: route_result.skill_hash,
@@ -327,6 +927,270 @@ impl Gateway {
         Ok(pca)
     }
 
+    /// Deny `action` when the channel's permission confidence for the
+    /// sender is below `min_permission_confidence`.
+    fn gate_action(
+        &self,
+        action: Action,
+        trace: ExecutionTrace,
+        message: &IncomingMessage,
+    ) -> (Action, ExecutionTrace) {
+        if action.is_noop() || matches!(action, Action::Denied { .. }) {
+            return (action, trace);
+        }
+        let Some(channel) = self.get_channel(&message.channel_id) else {
+            return (action, trace);
+        };
+        // The channel's verdict (workspace, guild, ...) always applies; a
+        // trusted user only skips the confidence threshold. Entries are
+        // compared in canonical form, so they match whatever format the
+        // operator wrote them in
+        let confidence = channel.evaluate_message_permission(&action, message).value();
+        let user = self.user_ref(&message.channel_id, &message.sender_id);
+        let trusted = self
+            .config
+            .allowed_users
+            .get(&message.channel_id)
+            .is_some_and(|users| users.iter().any(|entry| self.user_ref(&message.channel_id, entry) == user));
+        let threshold = self.config.min_permission_confidence;
+        if confidence >= threshold || (trusted && confidence > 0.0) {
+            return (action, trace);
+        }
+        let reason = format!(
+            "{} permission confidence {:.2} is below {:.2}",
+            action.action_type(), confidence, threshold
+        );
+        self.deny(message, reason, trace)
+    }
+
+    /// Build a `Denied` action, recording the denial in the trace so the
+    /// proof covers it.
+    fn deny(
+        &self,
+        message: &IncomingMessage,
+        reason: String,
+        mut trace: ExecutionTrace,
+    ) -> (Action, ExecutionTrace) {
+        tracing::info!("Denied action for {} on {}: {}", message.sender_id, message.channel_id, reason);
+        trace.add_node(ContentHash::from_string(&format!("{}:{}", DENIED_ACTION_REF, reason)));
+        let notice = OutgoingMessage::new(&message.channel_id, &message.sender_id, &self.config.denied_message)
+            .reply_to(message.id);
+        (Action::Denied { reason, notice }, trace)
+    }
+
+    /// Apply the attachment policy and scanner to a message's attachments.
+    ///
+    /// Failing attachments are removed and listed under `attachment_notes`
+    /// in the message metadata, or reject the message when the policy says so.
+    async fn screen_attachments(&self, message: &mut IncomingMessage) -> Result<(), String> {
+        let policy = &self.config.attachments;
+        let mut kept = Vec::with_capacity(message.attachments.len());
+        let mut notes = Vec::new();
+        for attachment in std::mem::take(&mut message.attachments) {
+            let mut verdict = policy.check(&attachment);
+            if let (Ok(()), Some(scanner)) = (&verdict, &self.attachment_scanner) {
+                verdict = scanner.scan(&attachment).await;
+            }
+            let Err(reason) = verdict else {
+                kept.push(attachment);
+                continue;
+            };
+            let reason = format!("attachment '{}' {}", attachment.file_name, reason);
+            if policy.on_violation == AttachmentViolation::Reject {
+                return Err(reason);
+            }
+            tracing::info!("Stripped {} from {} on {}", reason, message.sender_id, message.channel_id);
+            self.event_bus.publish(GatewayEvent::custom("attachment_stripped", serde_json::json!({
+                "channel_id": message.channel_id,
+                "sender_id": message.sender_id,
+                "message_hash": message.id.to_hex(),
+                "reason": reason,
+            }))).await;
+            notes.push(reason);
+        }
+        message.attachments = kept;
+        if !notes.is_empty() {
+            if !message.metadata.is_object() {
+                message.metadata = serde_json::json!({});
+            }
+            message.metadata["attachment_notes"] = serde_json::json!(notes);
+        }
+        Ok(())
+    }
+
+    /// State namespace of a skill within the sender's session.
+    ///
+    /// Skills cannot read or overwrite state saved by other skills or in
+    /// other sessions.
+    async fn state_namespace(&self, skill_hash: &ContentHash, message: &IncomingMessage) -> String {
+        let user = self.user_ref(&message.channel_id, &message.sender_id);
+        let session = self
+            .sessions
+            .read()
+            .await
+            .session_id_for(&user.channel, &user.id)
+            .map(|id| id.to_hex())
+            .unwrap_or_else(|| user.to_string());
+        format!("{}/{}", skill_hash.to_hex(), session)
+    }
+
+    /// Route to the skill holding a conversation claim.
+    ///
+    /// The router still runs, so a route it denies stays denied, and the
+    /// claimed skill keeps its own lane (e.g. approval for trades).
+    async fn claimed_route(
+        &self,
+        skill_hash: ContentHash,
+        message: &IncomingMessage,
+    ) -> Result<(RouteResult, ExecutionTrace), GatewayError> {
+        let skill_ref = self
+            .skills
+            .read()
+            .await
+            .get(&skill_hash)
+            .map(|entry| format!("skill:{}", entry.metadata.name))
+            .unwrap_or_else(|| format!("skill:{}", skill_hash));
+        let (routed, mut trace) = self.router.read().await.route(message).await?;
+        let lane = match routed.lane {
+            ActionLane::Deny => ActionLane::Deny,
+            _ => Router::skill_lane(&skill_ref).unwrap_or(ActionLane::Execute),
+        };
+        let route = RouteResult {
+            skill_ref,
+            skill_hash,
+            confidence: routed.confidence,
+            route_name: "active_skill".to_string(),
+            lane,
+            params: HashMap::new(),
+        };
+        trace.add_node(skill_hash);
+        Ok((route, trace))
+    }
+
+    /// Generate a PCA, signed unless proofs are disabled.
+    async fn generate_pca(
+        &self,
+        action: Action,
+        session_hash: ContentHash,
+        input_hash: ContentHash,
+        traces: Vec<ExecutionTrace>,
+    ) -> Result<ProofCarryingAction, GatewayError> {
+        if !self.config.proofs_enabled {
+            return Ok(self.proof_generator.generate_unsigned(action, session_hash, input_hash, traces).await);
+        }
+        self.proof_generator
+            .generate(action, session_hash, input_hash, traces)
+            .await
+            .map_err(|e| GatewayError::VmError(e.to_string()))
+    }
+
+    /// Check whether a skill calls external services.
+    async fn calls_external(&self, skill_ref: &str, skill_hash: &ContentHash) -> bool {
+        let skills = self.skills.read().await;
+        let skill_from_name = skill_ref.strip_prefix("skill:").and_then(|name| skills.get_by_name(name));
+        skills
+            .get(skill_hash)
+            .or(skill_from_name)
+            .is_some_and(|skill| skill.graph.has_external_calls())
+    }
+
+    /// Run `work`, sending the configured thinking placeholder if it is slow.
+    ///
+    /// The placeholder goes out immediately when `expect_slow`, otherwise once
+    /// `thinking_threshold_ms` has passed. Returns the placeholder's platform
+    /// id when one was sent and the channel can edit it.
+    async fn with_thinking_placeholder<T>(
+        &self,
+        message: &IncomingMessage,
+        expect_slow: bool,
+        work: impl std::future::Future<Output = T>,
+    ) -> (T, Option<String>) {
+        let (Some(placeholder), Some(channel)) = (
+            self.config.thinking_placeholder.as_deref(),
+            self.get_channel(&message.channel_id),
+        ) else {
+            return (work.await, None);
+        };
+
+        tokio::pin!(work);
+        if !expect_slow {
+            let threshold = std::time::Duration::from_millis(self.config.thinking_threshold_ms);
+            if let Ok(output) = tokio::time::timeout(threshold, &mut work).await {
+                return (output, None);
+            }
+        }
+
+        let outgoing = OutgoingMessage::new(&message.channel_id, &message.sender_id, placeholder)
+            .reply_to(message.id);
+        let (sent, output) = tokio::join!(channel.send_editable(outgoing), work);
+        let placeholder_id = sent.unwrap_or_else(|e| {
+            tracing::warn!("Failed to send thinking placeholder on {}: {}", message.channel_id, e);
+            None
+        });
+        (output, placeholder_id)
+    }
+
+    /// Delete the thinking placeholder `id` sent in reply to `message`, or
+    /// replace it with `fallback` where the channel cannot delete.
+    async fn clear_placeholder(&self, message: &IncomingMessage, id: &str, fallback: &str) {
+        let Some(channel) = self.get_channel(&message.channel_id) else {
+            return;
+        };
+        if channel.delete(id, &message.sender_id).await.is_ok() {
+            return;
+        }
+        let outgoing = OutgoingMessage::new(&message.channel_id, &message.sender_id, fallback);
+        if let Err(e) = channel.edit(id, outgoing).await {
+            tracing::warn!("Failed to clear thinking placeholder on {}: {}", message.channel_id, e);
+        }
+    }
+
+    /// Enforce the per-user cooldown between commands.
+    ///
+    /// Returns the reply when `message` is a command sent too soon after the
+    /// previous command; otherwise records it.
+    fn check_command_cooldown(&self, session: &mut Session, message: &IncomingMessage) -> Option<String> {
+        let cooldown_ms = self.config.session.command_cooldown_ms;
+        if cooldown_ms == 0 || !message.content.trim_start().starts_with('/') {
+            return None;
+        }
+        let now = self.clock.now().timestamp_millis().max(0) as u64;
+        if let Some(secs) = cooldown_remaining(session.last_command_at, now, cooldown_ms) {
+            return Some(format!("Please wait {}s before sending another command.", secs));
+        }
+        session.last_command_at = Some(now);
+        None
+    }
+
+    /// Seconds left of the per-user cooldown of the skill a message was
+    /// routed to, if it was used too recently.
+    ///
+    /// Keyed on the routed skill rather than the typed command, so aliases
+    /// and spellings such as `/Status` or `/status@bot` share it.
+    async fn skill_cooldown_remaining(&self, session_id: &ContentHash, skill_ref: &str) -> Option<u64> {
+        let skill = skill_ref.strip_prefix("skill:").unwrap_or(skill_ref);
+        let cooldown_ms = self.config.command_cooldowns.get(skill)? * 1000;
+        let now = self.clock.now().timestamp_millis().max(0) as u64;
+
+        let sessions = self.sessions.read().await;
+        let last = sessions.get(session_id)?.command_used_at.get(skill).copied();
+        cooldown_remaining(last, now, cooldown_ms)
+    }
+
+    /// Start the cooldown of a skill that just ran, if it has one.
+    async fn record_skill_use(&self, session_id: &ContentHash, skill_ref: &str) {
+        let skill = skill_ref.strip_prefix("skill:").unwrap_or(skill_ref);
+        if !self.config.command_cooldowns.contains_key(skill) {
+            return;
+        }
+        let now = self.clock.now().timestamp_millis().max(0) as u64;
+        let mut sessions = self.sessions.write().await;
+        if let Some(session) = sessions.get_mut(session_id) {
+            session.command_used_at.insert(skill.to_string(), now);
+            sessions.persist(session_id);
+        }
+    }
+
     /// Execute a skill graph.
     ///
     async fn execute_skill(
@@ -341,41 +1205,248 @@ impl Gateway {
             .and_then(|name| skills.get_by_name(name));
 
         let action = if let Some(skill) = skills.get(skill_hash).or(skill_from_name) {
-            let runtime_graph = skill
-                .graph
+            let missing = self.missing_features(&skill.metadata, &message.channel_id);
+            if !missing.is_empty() {
+                tracing::warn!(
+                    "Skill {} needs {:?}, unsupported on channel {}",
+                    skill.metadata.name, missing, message.channel_id
+                );
+                let action = Action::SendMessage(
+                    OutgoingMessage::new(
+                        &message.channel_id,
+                        &message.sender_id,
+                        &format!("`{}` is not available on this channel.", skill.metadata.name),
+                    )
+                    .reply_to(message.id),
+                );
+                let mut trace = ExecutionTrace::new();
+                trace.add_node(ContentHash::from_string(UNAVAILABLE_SKILL_REF));
+                return Ok((action, trace));
+            }
+
+            let runtime_graph = skill
+                .graph
                 .to_runtime_graph()
                 .map_err(|e| GatewayError::VmError(e.to_string()))?;
 
             let mut inputs = HashMap::new();
-            inputs.insert("message".to_string(), Value::String(message.content.clone()));
-            inputs.insert("sender".to_string(), Value::String(message.sender_id.clone()));
-            inputs.insert("channel".to_string(), Value::String(message.channel_id.clone()));
+            inputs.insert("message".to_string(), Value::String(message.content.as_str().into()));
+            inputs.insert("sender".to_string(), Value::String(message.sender_id.as_str().into()));
+            inputs.insert("channel".to_string(), Value::String(message.channel_id.as_str().into()));
+            if let Some(enrichment) = message.metadata.get(ENRICHMENT_INPUT) {
+                inputs.insert(ENRICHMENT_INPUT.to_string(), Value::from_json(enrichment));
+            }
             for (k, v) in params {
-                inputs.insert(k.clone(), Value::String(v.clone()));
+                inputs.insert(k.clone(), Value::String(v.as_str().into()));
             }
+            self.bind_mapped_inputs(skill_ref, skill, params, &mut inputs)?;
+            let namespace = self.state_namespace(&skill.hash, message).await;
 
-            let exec_result = self
-                .runtime
-                .execute(&runtime_graph, inputs)
-                .await
-                .map_err(|e| GatewayError::VmError(e.to_string()))?;
+            let timeout_ms = self.config.execution.timeout_for(skill.metadata.timeout_ms);
+            let exec_result = tokio::time::timeout(
+                std::time::Duration::from_millis(timeout_ms),
+                self.runtime.execute_in_namespace(&runtime_graph, inputs, &namespace),
+            )
+            .await
+            .map_err(|_| GatewayError::Timeout { timeout_ms })?
+            .map_err(|e| GatewayError::VmError(e.to_string()))?;
 
             let trace = ExecutionTrace::from_graph_execution(&exec_result);
-            let action = self.action_from_skill_output(message, &exec_result.outputs);
+            // A claim may hand the conversation to another installed skill
+            let claim_target = exec_result
+                .outputs
+                .get("skill")
+                .and_then(Value::as_string)
+                .and_then(|name| skills.get_by_name(name))
+                .map_or(skill.hash, |entry| entry.hash);
+            let action = self.action_from_skill_output(message, &exec_result.outputs, claim_target);
             return Ok((action, trace));
-        } else {
+        } else if skill_ref == LIST_SKILLS_SKILL_REF {
+            Self::skill_list_reply(message, &skills)
+        } else if skill_ref == REMINDER_SKILL_REF {
+            self.reminder_reply(message).await
+        } else if skill_ref == UNKNOWN_COMMAND_SKILL_REF {
+            let commands = self.router.read().await.commands();
+            Self::unknown_command_reply(message, &commands)
+        } else if BUILTIN_SKILL_REFS.contains(&skill_ref) {
             // Built-in command handling
-            self.handle_builtin_command(message, params)?
+            let commands = if self.config.default_response_suggestions {
+                self.router.read().await.commands()
+            } else {
+                Vec::new()
+            };
+            self.handle_builtin_command(message, params, &commands)?
+        } else {
+            return Err(GatewayError::SkillNotFound { hash: *skill_hash });
         };
         let mut trace = ExecutionTrace::new();
         trace.add_node(*skill_hash);
         Ok((action, trace))
     }
 
+    /// Run the configured preprocessor skills, merging their map outputs
+    /// into the message's enrichment metadata.
+    async fn run_preprocessors(&self, message: &mut IncomingMessage) {
+        if self.config.preprocessor_skills.is_empty() {
+            return;
+        }
+
+        let mut enrichment = serde_json::Map::new();
+        for name in &self.config.preprocessor_skills {
+            match self.run_preprocessor(name, message, &enrichment).await {
+                Ok(fields) => enrichment.extend(fields),
+                Err(e) => tracing::warn!("Skipping preprocessor '{}': {}", name, e),
+            }
+        }
+
+        if !message.metadata.is_object() {
+            message.metadata = serde_json::json!({});
+        }
+        message.metadata[ENRICHMENT_INPUT] = serde_json::Value::Object(enrichment);
+    }
+
+    /// Execute one preprocessor skill, returning the fields of its map outputs.
+    async fn run_preprocessor(
+        &self,
+        name: &str,
+        message: &IncomingMessage,
+        enrichment: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<serde_json::Map<String, serde_json::Value>, GatewayError> {
+        let (runtime_graph, timeout_ms, skill_hash) = {
+            let skills = self.skills.read().await;
+            let skill = skills.get_by_name(name).ok_or_else(|| {
+                GatewayError::ExecutionError(format!("Preprocessor skill '{}' is not installed", name))
+            })?;
+            let graph = skill
+                .graph
+                .to_runtime_graph()
+                .map_err(|e| GatewayError::VmError(e.to_string()))?;
+            (graph, self.config.execution.timeout_for(skill.metadata.timeout_ms), skill.hash)
+        };
+        let namespace = self.state_namespace(&skill_hash, message).await;
+
+        let mut inputs = HashMap::new();
+        inputs.insert("message".to_string(), Value::String(message.content.as_str().into()));
+        inputs.insert("sender".to_string(), Value::String(message.sender_id.as_str().into()));
+        inputs.insert("channel".to_string(), Value::String(message.channel_id.as_str().into()));
+        inputs.insert(
+            ENRICHMENT_INPUT.to_string(),
+            Value::from_json(&serde_json::Value::Object(enrichment.clone())),
+        );
+
+        let exec_result = tokio::time::timeout(
+            std::time::Duration::from_millis(timeout_ms),
+            self.runtime.execute_in_namespace(&runtime_graph, inputs, &namespace),
+        )
+        .await
+        .map_err(|_| GatewayError::Timeout { timeout_ms })?
+        .map_err(|e| GatewayError::VmError(e.to_string()))?;
+
+        let mut fields = serde_json::Map::new();
+        for output in exec_result.outputs.values() {
+            if let serde_json::Value::Object(map) = output.to_canonical_json() {
+                fields.extend(map);
+            }
+        }
+        if fields.is_empty() {
+            return Err(GatewayError::ExecutionError(format!(
+                "Preprocessor skill '{}' produced no map output",
+                name
+            )));
+        }
+        Ok(fields)
+    }
+
+    /// Bind route params to a skill's declared inputs via the route's input mapping.
+    ///
+    /// Values are coerced to the input's declared type. Without a mapping for
+    /// `skill_ref` the inputs are left as they are.
+    fn bind_mapped_inputs(
+        &self,
+        skill_ref: &str,
+        skill: &crate::skills::SkillEntry,
+        params: &HashMap<String, String>,
+        inputs: &mut HashMap<String, Value>,
+    ) -> Result<(), GatewayError> {
+        let Some(mapping) = self.config.input_mappings.get(skill_ref) else {
+            return Ok(());
+        };
+
+        for node in &skill.graph.nodes {
+            let crate::skills::SkillNode::Input { name, tensor_type, .. } = node else {
+                continue;
+            };
+            let bound = mapping
+                .iter()
+                .filter(|(_, input)| *input == name)
+                .find_map(|(param, _)| params.get(param));
+
+            if let Some(raw) = bound {
+                inputs.insert(name.clone(), Self::coerce_input(name, raw, tensor_type)?);
+            } else {
+                let required = skill
+                    .metadata
+                    .inputs
+                    .iter()
+                    .find(|input| &input.name == name)
+                    .is_none_or(|input| input.required);
+                if required && !inputs.contains_key(name) {
+                    return Err(GatewayError::ExecutionError(format!(
+                        "Required input '{}' of skill '{}' is not mapped from the route",
+                        name, skill.metadata.name
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse a route param into a skill input's declared type.
+    fn coerce_input(name: &str, raw: &str, tensor_type: &str) -> Result<Value, GatewayError> {
+        let invalid = || {
+            GatewayError::ExecutionError(format!("Input '{}' expects {}, got '{}'", name, tensor_type, raw))
+        };
+        let raw_trimmed = raw.trim();
+        match tensor_type {
+            "int" | "integer" | "i64" => raw_trimmed.parse().map(Value::Int).map_err(|_| invalid()),
+            "float" | "number" | "f64" => raw_trimmed
+                .parse::<f64>()
+                .ok()
+                .filter(|f| f.is_finite())
+                .map(Value::Float)
+                .ok_or_else(invalid),
+            "bool" | "boolean" => raw_trimmed.parse().map(Value::Bool).map_err(|_| invalid()),
+            _ => Ok(Value::from(raw)),
+        }
+    }
+
+    /// Features a skill requires that the message's channel lacks.
+    ///
+    /// Channels that are not registered are assumed to support everything.
+    fn missing_features(
+        &self,
+        metadata: &crate::skills::SkillMetadata,
+        channel_id: &str,
+    ) -> Vec<ChannelFeature> {
+        let Some(channel) = self.get_channel(channel_id) else {
+            return Vec::new();
+        };
+
+        let configured = self.config.skill_features.get(&metadata.name);
+        let mut missing = Vec::new();
+        for feature in metadata.required_features.iter().chain(configured.into_iter().flatten()) {
+            if !channel.supports(*feature) && !missing.contains(feature) {
+                missing.push(*feature);
+            }
+        }
+        missing
+    }
+
     fn action_from_skill_output(
         &self,
         message: &IncomingMessage,
         outputs: &HashMap<String, Value>,
+        claim_target: ContentHash,
     ) -> Action {
         let action_type = outputs
             .get("action_type")
@@ -390,6 +1461,16 @@ impl Gateway {
                     .unwrap_or("skill requested noop")
                     .to_string(),
             },
+            "claim_conversation" => Action::ClaimConversation {
+                skill_hash: claim_target,
+                max_turns: outputs
+                    .get("max_turns")
+                    .and_then(Value::as_int)
+                    .and_then(|turns| u32::try_from(turns).ok())
+                    .unwrap_or(DEFAULT_CLAIM_TURNS)
+                    .min(self.config.max_claim_turns),
+            },
+            "release_conversation" => Action::ReleaseConversation,
             "update_session" => Action::UpdateSession {
                 session_id: ContentHash::from_string(
                     outputs
@@ -408,7 +1489,7 @@ impl Gateway {
                     .or_else(|| {
                         outputs
                             .values()
-                            .find_map(|v| if let Value::String(s) = v { Some(s.as_str()) } else { None })
+                            .find_map(|v| if let Value::String(s) = v { Some(&**s) } else { None })
                     })
                     .unwrap_or("Skill executed.");
 
@@ -420,11 +1501,57 @@ impl Gateway {
         }
     }
 
+    /// List installed skills by name with the first line of their description.
+    fn skill_list_reply(message: &IncomingMessage, skills: &SkillRegistry) -> Action {
+        let mut entries = skills.list();
+        entries.sort_by(|a, b| a.metadata.name.cmp(&b.metadata.name));
+
+        let mut content = String::from("Installed skills:");
+        for entry in entries {
+            let description = entry.metadata.description.lines().next().unwrap_or("").trim();
+            if description.is_empty() {
+                content.push_str(&format!("\n{}", entry.metadata.name));
+            } else {
+                content.push_str(&format!("\n{} - {}", entry.metadata.name, description));
+            }
+        }
+        Action::SendMessage(
+            OutgoingMessage::new(&message.channel_id, &message.sender_id, &content)
+                .reply_to(message.id),
+        )
+    }
+
+    /// Reply to an unrecognised command, suggesting the closest known one.
+    fn unknown_command_reply(message: &IncomingMessage, commands: &[String]) -> Action {
+        let command = message.content.split_whitespace().next().unwrap_or("").to_lowercase();
+        // Only suggest commands within a third of the typed length
+        let max_distance = (command.chars().count() / 3).max(1);
+        let suggestion = commands
+            .iter()
+            .map(|known| (crate::runtime::edit_distance(&command, known), known))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, known)| known);
+
+        let content = match suggestion {
+            Some(known) => format!(
+                "Unknown command {}. Did you mean {}? Try /help for a list of commands.",
+                command, known
+            ),
+            None => format!("Unknown command {}. Try /help for a list of commands.", command),
+        };
+        Action::SendMessage(
+            OutgoingMessage::new(&message.channel_id, &message.sender_id, &content)
+                .reply_to(message.id),
+        )
+    }
+
     /// Handle built-in commands.
     fn handle_builtin_command(
         &self,
         message: &IncomingMessage,
         _params: &HashMap<String, String>,
+        commands: &[String],
     ) -> Result<Action, GatewayError> {
         let content = message.content.trim();
         
@@ -437,6 +1564,7 @@ impl Gateway {
                  /status - Show gateway status\n\
                  /skills - List installed skills\n\
                  /session - Show session info\n\
+                 /remind - Set a reminder, e.g. /remind 10m stretch\n\
                  /trade - Run simulation-first trade flow",
             ).reply_to(message.id)))
         } else if content.starts_with("/status") {
@@ -447,49 +1575,177 @@ impl Gateway {
                     "Gateway Status:\n\
                      Channels: {}\n\
                      Running: {}",
-                    self.channels.len(),
+                    self.channels.read().unwrap().connected_count(),
                     // Can't await here, so use placeholder
                     "checking..."
                 ),
             ).reply_to(message.id)))
         } else {
             // Default conversation response
+            let reply = match &self.config.default_response {
+                Some(response) if commands.is_empty() => response.clone(),
+                Some(response) => format!("{}\nAvailable commands: {}", response, commands.join(", ")),
+                None => format!("Received: {}", content),
+            };
             Ok(Action::SendMessage(OutgoingMessage::new(
                 &message.channel_id,
                 &message.sender_id,
-                &format!("Received: {}", content),
+                &reply,
             ).reply_to(message.id)))
         }
     }
 
     /// Execute a proof-carrying action.
+    ///
+    /// Executed actions raise the session's trust; denied actions and
+    /// actions failing verification lower it. Delivery failures and no-ops
+    /// leave it unchanged, since they say nothing about the sender.
     pub async fn execute_action(
         &self,
         pca: &mut ProofCarryingAction,
     ) -> Result<(), GatewayError> {
-        // Verify the proof first
-        self.proof_generator.verify(pca)
-            .map_err(|e| GatewayError::VmError(format!("Proof verification failed: {}", e)))?;
+        let (result, success) = match self.verify_action(pca) {
+            Ok(()) => {
+                let result = self.perform_action(pca).await;
+                self.audit(pca);
+                let success = match (&pca.action, &result) {
+                    (Action::Denied { .. }, _) => Some(false),
+                    (Action::NoOp { .. }, _) | (_, Err(_)) => None,
+                    (_, Ok(())) => Some(true),
+                };
+                (result, success)
+            }
+            Err(e) => (Err(e), Some(false)),
+        };
+        if let Some(success) = success {
+            self.record_action_outcome(pca, success).await;
+        }
+        result
+    }
 
+    /// Check an action's proof, when proofs are enabled.
+    fn verify_action(&self, pca: &ProofCarryingAction) -> Result<(), GatewayError> {
+        if self.config.proofs_enabled {
+            self.proof_generator.verify(pca)
+                .map_err(|e| GatewayError::VmError(format!("Proof verification failed: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    /// Hand an executed action to the audit sinks without waiting on them.
+    fn audit(&self, pca: &ProofCarryingAction) {
+        let mut tasks = self.audit_tasks.lock().unwrap();
+        while tasks.try_join_next().is_some() {}
+        for sink in &self.audit_sinks {
+            let sink = sink.clone();
+            let pca = pca.clone();
+            tasks.spawn(async move {
+                if let Err(e) = sink.record(&pca).await {
+                    tracing::warn!("Audit sink failed to record {}: {}", pca, e);
+                }
+            });
+        }
+    }
+
+    /// Feed the outcome of an action back into its session's trust.
+    async fn record_action_outcome(&self, pca: &ProofCarryingAction, success: bool) {
+        let updated = {
+            let mut sessions = self.sessions.write().await;
+            sessions
+                .record_outcome(&pca.session_hash, success, pca.confidence)
+                .and_then(|trust| {
+                    let session = sessions.get(&pca.session_hash)?;
+                    Some((session.channel_id.clone(), session.user_id.clone(), trust))
+                })
+        };
+        if let Some((channel_id, user_id, trust)) = updated {
+            self.event_bus.publish(GatewayEvent::TrustChanged {
+                channel_id,
+                user_id,
+                trust_score: trust.value(),
+            }).await;
+        }
+    }
+
+    /// Carry out a verified action.
+    async fn perform_action(
+        &self,
+        pca: &mut ProofCarryingAction,
+    ) -> Result<(), GatewayError> {
         match &pca.action {
-            Action::SendMessage(msg) => {
-                if let Some(channel) = self.channels.get(&msg.channel_id) {
-                    let receipt = channel.send(msg.clone()).await
-                        .map_err(|e| GatewayError::ChannelNotFound(e.to_string()))?;
+            Action::SendMessage(msg) | Action::Denied { notice: msg, .. } => {
+                if let Some(channel) = self.get_channel(&msg.channel_id) {
+                    let policy = RetryPolicy::new(self.config.dead_letter.delivery_retries);
+                    let persisted = std::sync::atomic::AtomicBool::new(false);
+                    // Once the platform rejects the edit, send a new message instead
+                    let edit_rejected = std::sync::atomic::AtomicBool::new(false);
+                    let outcome = with_retry(&policy, || async {
+                        let fresh = || OutgoingMessage { edit_of: None, ..msg.clone() };
+                        let edit_of = msg
+                            .edit_of
+                            .as_ref()
+                            .filter(|_| !edit_rejected.load(std::sync::atomic::Ordering::SeqCst));
+                        let result = match edit_of {
+                            Some(message_id) => match channel.edit(message_id, msg.clone()).await {
+                                Err(e) => {
+                                    tracing::debug!("Edit of {} rejected, sending a new message: {}", message_id, e);
+                                    edit_rejected.store(true, std::sync::atomic::Ordering::SeqCst);
+                                    channel.send(fresh()).await
+                                }
+                                edited => edited,
+                            },
+                            None => channel.send(fresh()).await,
+                        };
+                        // Store the action before backing off, so a restart
+                        // during the retries does not lose it
+                        if let Err(e) = &result {
+                            if policy.max_retries > 0
+                                && !persisted.swap(true, std::sync::atomic::Ordering::SeqCst)
+                            {
+                                let delivery = self.failed_delivery(pca, msg, e.to_string());
+                                self.failed_deliveries.write().await.push(delivery);
+                            }
+                        }
+                        result
+                    })
+                    .await;
+                    let receipt = match outcome {
+                        RetryResult::Success(receipt) => {
+                            if persisted.into_inner() {
+                                self.failed_deliveries.write().await.remove(&FailedDelivery::id_for(pca));
+                            }
+                            receipt
+                        }
+                        RetryResult::Failed { last_error, .. } => {
+                            return Err(self.dead_letter_delivery(pca, msg, last_error.to_string()).await);
+                        }
+                        RetryResult::RateLimited { retry_after } => {
+                            let error = format!("Rate limited, retry after {:?}", retry_after);
+                            return Err(self.dead_letter_delivery(pca, msg, error).await);
+                        }
+                    };
+                    let edited = msg.edit_of.as_ref().filter(|_| !edit_rejected.into_inner());
+                    if let (None, Some(stale)) = (edited, &msg.edit_of) {
+                        // The message that should have been edited is now out of date
+                        if let Err(e) = channel.delete(stale, &msg.recipient_id).await {
+                            tracing::debug!("Could not remove message {}: {}", stale, e);
+                        }
+                    }
+                    let kind = if edited.is_some() { "message_edited" } else { "message_sent" };
                     pca.effect_trace.push(EffectReceipt {
-                        kind: "message_sent".to_string(),
+                        kind: kind.to_string(),
                         target: msg.channel_id.clone(),
-                        receipt_id: ContentHash::from_string(&format!(
-                            "{}:{}:{}",
-                            msg.channel_id, msg.recipient_id, msg.content
-                        )),
+                        receipt_id: msg.receipt_id(),
                         details: serde_json::json!({
                             "recipient_id": msg.recipient_id,
                             "reply_to": msg.reply_to.map(|v| v.to_hex()),
+                            "edit_of": edited,
                             "channel_proof_signed": receipt.is_signed(),
                         }),
                         timestamp: chrono::Utc::now().timestamp_millis() as u64,
                     });
+                } else {
+                    self.handle_undeliverable(msg).await;
                 }
             }
             Action::ExecuteSkill { skill_hash, inputs: _ } => {
@@ -501,9 +1757,12 @@ impl Gateway {
             Action::NoOp { reason } => {
                 tracing::debug!("NoOp: {}", reason);
             }
+            Action::ClaimConversation { .. } | Action::ReleaseConversation => {
+                // Applied to the session when the message was processed
+            }
         }
 
-        if !pca.effect_trace.is_empty() {
+        if self.config.proofs_enabled && !pca.effect_trace.is_empty() {
             self.proof_generator
                 .resign(pca)
                 .map_err(|e| GatewayError::VmError(format!("Failed to sign effect trace: {}", e)))?;
@@ -519,6 +1778,264 @@ impl Gateway {
         Ok(())
     }
 
+    /// Record an action that could not be delivered, returning the error to report.
+    async fn dead_letter_delivery(
+        &self,
+        pca: &ProofCarryingAction,
+        msg: &OutgoingMessage,
+        error: String,
+    ) -> GatewayError {
+        tracing::warn!("Dead-lettering undeliverable action to {}/{}: {}", msg.channel_id, msg.recipient_id, error);
+        let delivery = self.failed_delivery(pca, msg, error.clone()).with_exhausted(true);
+        self.failed_deliveries.write().await.push(delivery);
+        GatewayError::ChannelNotFound(error)
+    }
+
+    fn failed_delivery(&self, pca: &ProofCarryingAction, msg: &OutgoingMessage, error: String) -> FailedDelivery {
+        FailedDelivery::new(pca.clone(), &msg.channel_id, &msg.recipient_id, error)
+            .with_timestamp(self.clock.now().timestamp_millis().max(0) as u64)
+    }
+
+    /// Broadcast a message to several recipients on one channel.
+    ///
+    /// Delivery goes through `Channel::send_batch`, so platforms with a bulk
+    /// API avoid per-message overhead. Returns one result per recipient;
+    /// recipients in quiet hours get a pending action and are delivered later.
+    pub async fn broadcast(
+        &self,
+        channel_id: &str,
+        recipients: &[String],
+        content: &str,
+    ) -> Result<Vec<Result<ProofCarryingAction, crate::error::ChannelError>>, GatewayError> {
+        let channel = self
+            .get_channel(channel_id)
+            .ok_or_else(|| GatewayError::ChannelNotFound(channel_id.to_string()))?;
+
+        let mut results = Vec::with_capacity(recipients.len());
+        let mut to_send = Vec::new();
+        for recipient in recipients {
+            let message = OutgoingMessage::new(channel_id, recipient, content);
+            if self.hold_if_quiet(&message).await {
+                results.push(Some(Ok(ProofCarryingAction::pending())));
+            } else {
+                results.push(None);
+                to_send.push(message);
+            }
+        }
+
+        let mut sent = channel.send_batch(to_send).await.into_iter();
+        Ok(results
+            .into_iter()
+            .map(|held| held.or_else(|| sent.next()).unwrap_or_else(|| {
+                Err(crate::error::ChannelError::SendFailed("Missing batch result".to_string()))
+            }))
+            .collect())
+    }
+
+    /// Send a scheduled (non-interactive) message.
+    ///
+    /// Non-urgent messages are held during quiet hours and `None` is
+    /// returned; they are delivered by [`Gateway::release_held`].
+    pub async fn schedule_message(
+        &self,
+        message: OutgoingMessage,
+        urgent: bool,
+    ) -> Result<Option<ProofCarryingAction>, GatewayError> {
+        if !urgent && self.hold_if_quiet(&message).await {
+            return Ok(None);
+        }
+
+        let channel = self
+            .get_channel(&message.channel_id)
+            .ok_or_else(|| GatewayError::ChannelNotFound(message.channel_id.clone()))?;
+        channel
+            .send(message)
+            .await
+            .map(Some)
+            .map_err(|e| GatewayError::ExecutionError(format!("Scheduled send failed: {}", e)))
+    }
+
+    /// Get messages currently held for quiet hours.
+    pub async fn held_messages(&self) -> Vec<HeldMessage> {
+        self.held_messages.read().await.clone()
+    }
+
+    /// Deliver held messages whose quiet hours have ended.
+    pub async fn release_held(&self) -> Vec<Result<ProofCarryingAction, crate::error::ChannelError>> {
+        let now = self.clock.now();
+        let due: Vec<HeldMessage> = {
+            let mut held = self.held_messages.write().await;
+            let (due, waiting) = held.drain(..).partition(|h| h.release_at <= now);
+            *held = waiting;
+            due
+        };
+
+        let mut results = Vec::with_capacity(due.len());
+        for held in due {
+            let result = match self.get_channel(&held.message.channel_id) {
+                Some(channel) => channel.send(held.message).await,
+                None => Err(crate::error::ChannelError::SendFailed(format!(
+                    "Channel not registered: {}",
+                    held.message.channel_id
+                ))),
+            };
+            results.push(result);
+        }
+        results
+    }
+
+    /// Hold `message` if its recipient is in quiet hours.
+    async fn hold_if_quiet(&self, message: &OutgoingMessage) -> bool {
+        let Some(quiet_hours) = &self.config.quiet_hours else {
+            return false;
+        };
+        let Some(release_at) = quiet_hours.held_until(message, self.clock.now()) else {
+            return false;
+        };
+
+        tracing::debug!("Holding message to {} until {}", message.recipient_id, release_at);
+        self.held_messages.write().await.push(HeldMessage {
+            message: message.clone(),
+            release_at,
+        });
+        true
+    }
+
+    /// Reply to `/remind <duration> <text>` with the due time, or with usage
+    /// help. Nothing is stored here: the reminder is scheduled by
+    /// [`Self::schedule_approved_reminder`] once the reply passed the gate.
+    async fn reminder_reply(&self, message: &IncomingMessage) -> Action {
+        let reply = match self.plan_reminder(message).await {
+            Ok(reminder) => format!("I'll remind you at {}.", reminder.due_at.format("%Y-%m-%d %H:%M UTC")),
+            Err(reply) => reply,
+        };
+        Action::SendMessage(
+            OutgoingMessage::new(&message.channel_id, &message.sender_id, &reply).reply_to(message.id),
+        )
+    }
+
+    /// The reminder `/remind <duration> <text>` asks for, or the reply
+    /// explaining why there is none.
+    async fn plan_reminder(&self, message: &IncomingMessage) -> Result<Reminder, String> {
+        let args = message.content.trim().strip_prefix("/remind").unwrap_or_default().trim();
+        let (duration, text) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+        let (Some(delay_ms), text) = (crate::runtime::parse_duration_ms(duration), text.trim()) else {
+            return Err(REMINDER_USAGE.to_string());
+        };
+        if text.is_empty() {
+            return Err(REMINDER_USAGE.to_string());
+        }
+        let limit = self.config.max_reminders_per_user;
+        if limit > 0 && self.pending_reminders_for(&message.channel_id, &message.sender_id).await >= limit {
+            return Err(format!("You already have {} pending reminders.", limit));
+        }
+        let due_at = self.clock.now() + chrono::Duration::milliseconds(delay_ms as i64);
+        Ok(Reminder::new(due_at, &message.channel_id, &message.sender_id, text))
+    }
+
+    /// Number of reminders pending for a user.
+    async fn pending_reminders_for(&self, channel_id: &str, recipient_id: &str) -> usize {
+        self.reminders
+            .read()
+            .await
+            .list()
+            .iter()
+            .filter(|reminder| reminder.channel_id == channel_id && reminder.recipient_id == recipient_id)
+            .count()
+    }
+
+    /// Schedule the reminder a `/remind` asked for, once its lane executed
+    /// and the gate let the confirmation through.
+    async fn schedule_approved_reminder(&self, route: &RouteResult, action: &Action, message: &IncomingMessage) {
+        if route.lane != ActionLane::Execute
+            || route.skill_ref != REMINDER_SKILL_REF
+            || !matches!(action, Action::SendMessage(_))
+        {
+            return;
+        }
+        if let Ok(reminder) = self.plan_reminder(message).await {
+            if let Err(e) = self.schedule_reminder(reminder).await {
+                tracing::warn!("Not scheduling reminder for {}: {}", message.sender_id, e);
+            }
+        }
+    }
+
+    /// Store `reminder` until it is due, unless its recipient already has
+    /// `max_reminders_per_user` pending.
+    pub async fn schedule_reminder(&self, reminder: Reminder) -> Result<(), GatewayError> {
+        let mut reminders = self.reminders.write().await;
+        let limit = self.config.max_reminders_per_user;
+        let pending = reminders
+            .list()
+            .iter()
+            .filter(|r| r.channel_id == reminder.channel_id && r.recipient_id == reminder.recipient_id)
+            .count();
+        if limit > 0 && pending >= limit {
+            return Err(GatewayError::ExecutionError(format!(
+                "{} already has {} pending reminders",
+                reminder.recipient_id, pending
+            )));
+        }
+        reminders.push(reminder);
+        Ok(())
+    }
+
+    /// Get reminders waiting for their due time.
+    pub async fn pending_reminders(&self) -> Vec<Reminder> {
+        self.reminders.read().await.list()
+    }
+
+    /// Deliver reminders that are due as proof-carrying `SendMessage` actions.
+    ///
+    /// Each due reminder is removed before delivery, so it fires at most once.
+    pub async fn fire_due_reminders(&self) -> Vec<Result<ProofCarryingAction, GatewayError>> {
+        let now = self.clock.now();
+        let due: Vec<Reminder> = {
+            let mut reminders = self.reminders.write().await;
+            let due: Vec<Reminder> = reminders.list().into_iter().filter(|r| r.is_due(now)).collect();
+            for reminder in &due {
+                reminders.remove(&reminder.id);
+            }
+            due
+        };
+
+        let mut results = Vec::with_capacity(due.len());
+        for reminder in due {
+            let action = Action::SendMessage(OutgoingMessage::new(
+                &reminder.channel_id,
+                &reminder.recipient_id,
+                &format!("Reminder: {}", reminder.text),
+            ));
+            let mut trace = ExecutionTrace::new().with_confidence(1.0);
+            trace.add_node(ContentHash::from_string(REMINDER_SKILL_REF));
+            let result = match self.generate_pca(action, reminder.id, reminder.id, vec![trace]).await {
+                Ok(mut pca) => self.execute_action(&mut pca).await.map(|()| pca),
+                Err(e) => Err(e),
+            };
+            results.push(result);
+        }
+        results
+    }
+
+    /// Fire due reminders every `interval` until the gateway stops.
+    pub fn spawn_scheduler(&self, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        let gateway = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if !*gateway.running.read().await {
+                    break;
+                }
+                for result in gateway.fire_due_reminders().await {
+                    if let Err(err) = result {
+                        tracing::error!("Failed to deliver reminder: {}", err);
+                    }
+                }
+            }
+        })
+    }
+
     /// Start the gateway.
     pub async fn run(&self) -> Result<(), GatewayError> {
         // Check if already running
@@ -532,47 +2049,70 @@ impl Gateway {
 
         tracing::info!("Starting 0-openclaw Gateway");
 
+        if let Err(e) = self.warm_up().await {
+            *self.running.write().await = false;
+            return Err(e);
+        }
+
         // Publish start event
         self.event_bus.publish(GatewayEvent::GatewayStarted {
             timestamp: chrono::Utc::now().timestamp_millis() as u64,
         }).await;
 
         // Start channel listeners
-        for (name, channel) in &self.channels {
-            let channel = channel.clone();
-            let channel_name = name.clone();
+        let connected = self.channels.read().unwrap().connected_channels();
+        for channel in connected {
+            let name = channel.name().to_string();
+            self.spawn_listener(name.clone(), channel);
+
+            // Publish channel connected event
+            self.event_bus.publish(GatewayEvent::ChannelConnected {
+                channel_id: name.clone(),
+            }).await;
+        }
+
+        // Resume deliveries interrupted by the last shutdown
+        {
             let gateway = self.clone();
+            tokio::spawn(async move {
+                for result in gateway.redeliver_pending().await {
+                    if let Err(err) = result {
+                        tracing::warn!("Redelivery of stored action failed: {}", err);
+                    }
+                }
+            });
+        }
 
+        // Deliver messages held for quiet hours once their window ends
+        if self.config.quiet_hours.is_some() {
+            let gateway = self.clone();
             tokio::spawn(async move {
-                tracing::info!("Starting channel listener: {}", channel_name);
-                
+                let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
                 loop {
-                    match channel.receive().await {
-                        Ok(message) => {
-                            tracing::debug!("Received message on {}: {}", channel_name, message.id);
-                            match gateway.process_message(message).await {
-                                Ok(mut pca) => {
-                                    if let Err(err) = gateway.execute_action(&mut pca).await {
-                                        tracing::error!("Action execution failed on {}: {}", channel_name, err);
-                                    }
-                                }
-                                Err(err) => {
-                                    tracing::error!("Gateway processing failed on {}: {}", channel_name, err);
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            tracing::error!("Channel {} receive error: {}", channel_name, e);
-                            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                    interval.tick().await;
+                    for result in gateway.release_held().await {
+                        if let Err(err) = result {
+                            tracing::error!("Failed to deliver held message: {}", err);
                         }
                     }
                 }
             });
+        }
 
-            // Publish channel connected event
-            self.event_bus.publish(GatewayEvent::ChannelConnected {
-                channel_id: name.clone(),
-            }).await;
+        // Deliver reminders when they come due
+        self.spawn_scheduler(std::time::Duration::from_millis(self.config.reminder_poll_interval_ms.max(1)));
+
+        // Publish vitals for external monitors
+        if self.config.heartbeat_interval_ms > 0 {
+            self.spawn_heartbeat(std::time::Duration::from_millis(self.config.heartbeat_interval_ms));
+        }
+
+        // Share events with peer gateways
+        if !self.config.federation.peers.is_empty() {
+            let peers = self.config.federation.peers.iter()
+                .map(|url| Arc::new(WebSocketPeer::new(url)) as Arc<dyn PeerTransport>)
+                .collect();
+            self.federate(peers);
         }
 
         // Start WebSocket server
@@ -580,15 +2120,119 @@ impl Gateway {
             self.event_bus.clone(),
             &self.config.server.host,
             self.config.server.port,
-        );
+        )
+        .with_skills(self.skills.clone());
+        let server = match &self.config.federation.secret {
+            Some(secret) => server.with_federation(self.sessions.clone(), secret, self.config.federation.max_skew_secs),
+            None => server,
+        };
+        let server = match &self.config.server.admin_token {
+            Some(token) => server.with_channel_control(Arc::new(self.clone()), token),
+            None => server,
+        };
         
         server.start().await
     }
 
+    /// Process and act on every message `channel` receives.
+    ///
+    /// Processing and delivery failures are published as error events. The
+    /// listener stops once the channel is disconnected or connected anew,
+    /// cancelling a pending `receive`; a message already being handled is
+    /// finished first.
+    fn spawn_listener(&self, channel_name: String, channel: Arc<dyn Channel>) -> tokio::task::JoinHandle<()> {
+        let gateway = self.clone();
+        let connected = self.channels.read().unwrap().generation(&channel_name).is_some();
+        let (stop, mut stopped) = tokio::sync::watch::channel(());
+        if connected {
+            // Replacing the previous listener's sender stops that listener
+            self.listener_stops.lock().unwrap().insert(channel_name.clone(), stop);
+        }
+        tokio::spawn(async move {
+            if !connected {
+                return;
+            }
+            tracing::info!("Starting channel listener: {}", channel_name);
+
+            loop {
+                let received = tokio::select! {
+                    biased;
+                    _ = stopped.changed() => {
+                        tracing::debug!("Channel {} disconnected, stopping its listener", channel_name);
+                        break;
+                    }
+                    received = channel.receive() => received,
+                };
+                match received {
+                    Ok(message) => {
+                        tracing::debug!("Received message on {}: {}", channel_name, message.id);
+                        let result = match gateway.process_message(message).await {
+                            Ok(mut pca) => gateway.execute_action(&mut pca).await,
+                            Err(err) => Err(err),
+                        };
+                        if let Err(err) = result {
+                            tracing::error!("Handling message on {} failed: {}", channel_name, err);
+                            gateway.event_bus.publish(GatewayEvent::error(&channel_name, &err.to_string())).await;
+                        }
+                    }
+                    Err(e) => {
+                        tracing::error!("Channel {} receive error: {}", channel_name, e);
+                        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                    }
+                }
+            }
+        })
+    }
+
+    /// Publish a `heartbeat` custom event every `interval` until the gateway stops.
+    ///
+    /// The event data carries `uptime_ms`, `sessions` and `messages_processed`
+    /// since the previous heartbeat.
+    pub fn spawn_heartbeat(&self, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        let gateway = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+            loop {
+                ticker.tick().await;
+                if !*gateway.running.read().await {
+                    break;
+                }
+                let processed = gateway
+                    .processed_since_heartbeat
+                    .swap(0, std::sync::atomic::Ordering::Relaxed);
+                gateway.event_bus.publish(GatewayEvent::custom("heartbeat", serde_json::json!({
+                    "uptime_ms": gateway.started_at.elapsed().as_millis() as u64,
+                    "sessions": gateway.session_count().await,
+                    "messages_processed": processed,
+                }))).await;
+            }
+        })
+    }
+
+    /// Forward the configured federation events to `peers` in the background.
+    pub fn federate(&self, peers: Vec<Arc<dyn PeerTransport>>) -> tokio::task::JoinHandle<()> {
+        let federation = &self.config.federation;
+        let node_id = federation.node_id.clone().unwrap_or_else(|| {
+            format!("{}:{}", self.config.server.host, self.config.server.port)
+        });
+        let mut bridge = FederationBridge::new(&node_id).with_event_types(&federation.forward_events);
+        if let Some(secret) = &federation.secret {
+            bridge = bridge.with_secret(secret);
+        }
+        let bridge = peers.into_iter().fold(bridge, |bridge, peer| bridge.with_peer(peer));
+        bridge.spawn(&self.event_bus)
+    }
+
+    /// A peer that applies federated events to this gateway's sessions.
+    pub fn local_peer(&self) -> LocalPeer {
+        LocalPeer::new(self.sessions.clone())
+    }
+
     /// Stop the gateway gracefully.
     pub async fn stop(&self) -> Result<(), GatewayError> {
         let mut running = self.running.write().await;
         *running = false;
+        self.sessions.write().await.flush();
 
         // Publish stop event
         self.event_bus.publish(GatewayEvent::GatewayStopped {
@@ -600,21 +2244,179 @@ impl Gateway {
         Ok(())
     }
 
-    /// Get session information.
-    pub async fn get_session_info(&self, session_id: &ContentHash) -> Option<SessionInfo> {
-        let sessions = self.sessions.read().await;
-        sessions.get(session_id).map(SessionInfo::from)
+    /// Scope of the data held about `user_id` on `channel_id`.
+    async fn user_scope(&self, channel_id: &str, user_id: &str) -> UserScope {
+        let user = self.user_ref(channel_id, user_id);
+        let session_ids: Vec<ContentHash> = self
+            .sessions
+            .read()
+            .await
+            .list()
+            .into_iter()
+            .filter(|session| session.channel_id == user.channel && session.user_id == user.id)
+            .map(|session| session.id)
+            .collect();
+        UserScope::new(user, session_ids).with_normalizer(self.sender_normalizer.clone())
     }
 
-    /// List all active sessions.
-    pub async fn list_sessions(&self) -> Vec<SessionInfo> {
-        let sessions = self.sessions.read().await;
-        sessions.list().iter().map(|s| SessionInfo::from(*s)).collect()
-    }
+    /// Gather everything held about a user, for data-subject access requests.
+    ///
+    /// Audit sinks that fail to read back are logged and skipped.
+    pub async fn export_user_data(&self, channel_id: &str, user_id: &str) -> UserDataExport {
+        let scope = self.user_scope(channel_id, user_id).await;
 
-    /// Get the number of active sessions.
-    pub async fn session_count(&self) -> usize {
-        self.sessions.read().await.count()
+        let sessions = {
+            let sessions = self.sessions.read().await;
+            scope.session_ids.iter().filter_map(|id| sessions.get(id).cloned()).collect()
+        };
+        let trust_history = self
+            .event_bus
+            .history()
+            .await
+            .iter()
+            .filter(|event| scope.matches_event(event))
+            .filter_map(|event| match event {
+                GatewayEvent::TrustChanged { trust_score, .. } => Some(*trust_score),
+                _ => None,
+            })
+            .collect();
+        let mut actions = Vec::new();
+        self.settle_audit().await;
+        for sink in &self.audit_sinks {
+            match sink.export_user(&scope).await {
+                Ok(found) => actions.extend(found),
+                Err(e) => tracing::warn!("Failed to export audit records for {}: {}", scope.user, e),
+            }
+        }
+        let mut pending_messages: Vec<OutgoingMessage> = self
+            .held_messages
+            .read()
+            .await
+            .iter()
+            .map(|held| held.message.clone())
+            .filter(|message| scope.matches_outgoing(message))
+            .collect();
+        pending_messages.extend(
+            self.undelivered.read().await.iter().filter(|message| scope.matches_outgoing(message)).cloned(),
+        );
+
+        UserDataExport {
+            sessions,
+            trust_history,
+            actions,
+            dead_letters: self
+                .dead_letters
+                .read()
+                .await
+                .list()
+                .into_iter()
+                .filter(|dead| scope.matches_user(&dead.message.channel_id, &dead.message.sender_id))
+                .collect(),
+            failed_deliveries: self
+                .failed_deliveries
+                .read()
+                .await
+                .list()
+                .into_iter()
+                .filter(|failed| scope.matches_user(&failed.channel_id, &failed.recipient_id))
+                .collect(),
+            pending_messages,
+            reminders: self
+                .reminders
+                .read()
+                .await
+                .list()
+                .into_iter()
+                .filter(|reminder| scope.matches_user(&reminder.channel_id, &reminder.recipient_id))
+                .collect(),
+            user: scope.user,
+        }
+    }
+
+    /// Erase everything held about a user, including their entries in
+    /// persisted stores and audit sinks.
+    ///
+    /// Skill state is keyed by opaque namespaces the gateway cannot
+    /// enumerate, so state backends must be cleaned up separately. Audit
+    /// records are matched by the user's current sessions and by message
+    /// recipient; recorded actions without a recipient (`NoOp`,
+    /// `ExecuteSkill`, ...) from sessions that have already expired carry no
+    /// user identity and are kept. Audit writes still in flight finish
+    /// before erasure. Every sink is asked to erase; the first sink that
+    /// fails or cannot erase is reported as the error.
+    pub async fn delete_user_data(&self, channel_id: &str, user_id: &str) -> Result<(), GatewayError> {
+        let scope = self.user_scope(channel_id, user_id).await;
+
+        {
+            let mut sessions = self.sessions.write().await;
+            for id in &scope.session_ids {
+                sessions.remove(id);
+            }
+        }
+        {
+            let mut dead_letters = self.dead_letters.write().await;
+            for dead in dead_letters.list() {
+                if scope.matches_user(&dead.message.channel_id, &dead.message.sender_id) {
+                    dead_letters.remove(&dead.id);
+                }
+            }
+        }
+        {
+            let mut failed_deliveries = self.failed_deliveries.write().await;
+            for failed in failed_deliveries.list() {
+                if scope.matches_user(&failed.channel_id, &failed.recipient_id) {
+                    failed_deliveries.remove(&failed.id);
+                }
+            }
+        }
+        {
+            let mut reminders = self.reminders.write().await;
+            for reminder in reminders.list() {
+                if scope.matches_user(&reminder.channel_id, &reminder.recipient_id) {
+                    reminders.remove(&reminder.id);
+                }
+            }
+        }
+        self.held_messages.write().await.retain(|held| !scope.matches_outgoing(&held.message));
+        self.undelivered.write().await.retain(|message| !scope.matches_outgoing(message));
+        self.event_bus.retain_history(|event| !scope.matches_event(event)).await;
+
+        self.settle_audit().await;
+        let mut failure = None;
+        for sink in &self.audit_sinks {
+            if let Err(e) = sink.erase_user(&scope).await {
+                tracing::warn!("Failed to erase audit records for {}: {}", scope.user, e);
+                failure.get_or_insert(e);
+            }
+        }
+        if let Some(e) = failure {
+            return Err(e);
+        }
+        tracing::info!("Deleted data for {}", scope.user);
+        Ok(())
+    }
+
+    /// Wait for audit records handed to sinks so far to be written.
+    async fn settle_audit(&self) {
+        let mut tasks = std::mem::take(&mut *self.audit_tasks.lock().unwrap());
+        while tasks.join_next().await.is_some() {}
+    }
+
+    /// Get session information.
+    pub async fn get_session_info(&self, session_id: &ContentHash) -> Option<SessionInfo> {
+        let sessions = self.sessions.read().await;
+        sessions.get(session_id).map(SessionInfo::from)
+    }
+
+    /// List all active sessions.
+    pub async fn list_sessions(&self) -> Vec<SessionInfo> {
+        let sessions = self.sessions.read().await;
+        sessions.list().iter().map(|s| SessionInfo::from(*s)).collect()
+    }
+
+    /// Get the number of active sessions.
+    pub async fn session_count(&self) -> usize {
+        self.sessions.read().await.count()
     }
 
     /// Clean up expired sessions.
@@ -625,12 +2427,64 @@ impl Gateway {
     /// Set the default skill for routing.
     pub async fn set_default_skill(&self, skill_hash: ContentHash) {
         self.router.write().await.set_default_skill(skill_hash);
+        self.router_configured.store(true, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    /// Eagerly load the core graphs and re-verify installed skills.
+    ///
+    /// Each core graph is checked at the path its component reads. A router
+    /// graph at `router_graph_path` that parses replaces the built-in router,
+    /// unless a router was already set through [`Self::load_router_graph`] or
+    /// [`Self::set_default_skill`]. Fails only when `skill_load_mode` is
+    /// strict and an installed skill is rejected.
+    pub async fn warm_up(&self) -> Result<WarmUpReport, GatewayError> {
+        let router_path = &self.config.router_graph_path;
+        let mut report = WarmUpReport::default();
+
+        for name in warmup::CORE_GRAPHS {
+            let path = match name {
+                "router" => router_path.clone(),
+                "session" => session::SESSION_GRAPH_PATH.into(),
+                "proof" => proof::PROOF_GRAPH_PATH.into(),
+                _ => crate::skills::VERIFIER_GRAPH_PATH.into(),
+            };
+            let status = CoreGraphStatus::load(name, &path);
+            if let GraphSource::BuiltIn { reason } = &status.source {
+                tracing::warn!("Core graph {} not loaded from {}: {}", name, path.display(), reason);
+            }
+            report.graphs.push(status);
+        }
+
+        let configured = self.router_configured.load(std::sync::atomic::Ordering::SeqCst);
+        if !configured && report.graph("router").is_some_and(|status| !status.is_fallback()) {
+            *self.router.write().await = Router::from_file(router_path)?;
+        }
+
+        let skills = self.skills.read().await;
+        for entry in skills.list_custom() {
+            report.skills_verified += 1;
+            let reason = match crate::skills::SkillVerifier::verify(&entry.graph) {
+                Ok(result) if result.safe => continue,
+                Ok(result) => result.errors.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("; "),
+                Err(e) => e.to_string(),
+            };
+            tracing::warn!("Installed skill {} failed verification: {}", entry.metadata.name, reason);
+            if self.config.skill_load_mode == crate::skills::SkillLoadMode::Strict {
+                return Err(GatewayError::InvalidConfig(format!(
+                    "Skill {} failed verification: {}", entry.metadata.name, reason
+                )));
+            }
+            report.skill_failures.push((entry.metadata.name.clone(), reason));
+        }
+
+        Ok(report)
     }
 
     /// Load a custom router from a graph file.
     pub async fn load_router_graph(&self, path: &str) -> Result<(), GatewayError> {
         let new_router = Router::from_file(path)?;
         *self.router.write().await = new_router;
+        self.router_configured.store(true, std::sync::atomic::Ordering::SeqCst);
         Ok(())
     }
 }
@@ -647,25 +2501,67 @@ impl Clone for Gateway {
         Self {
             sessions: self.sessions.clone(),
             router: self.router.clone(),
+            router_configured: self.router_configured.clone(),
             channels: self.channels.clone(),
             skills: self.skills.clone(),
             runtime: self.runtime.clone(),
             proof_generator: self.proof_generator.clone(),
             event_bus: self.event_bus.clone(),
+            dead_letters: self.dead_letters.clone(),
+            failed_deliveries: self.failed_deliveries.clone(),
+            clock: self.clock.clone(),
+            held_messages: self.held_messages.clone(),
+            reminders: self.reminders.clone(),
+            undelivered: self.undelivered.clone(),
+            in_flight: self.in_flight.clone(),
+            listener_stops: self.listener_stops.clone(),
             config: self.config.clone(),
             running: self.running.clone(),
+            started_at: self.started_at,
+            processed_since_heartbeat: self.processed_since_heartbeat.clone(),
+            audit_sinks: self.audit_sinks.clone(),
+            audit_tasks: self.audit_tasks.clone(),
+            attachment_scanner: self.attachment_scanner.clone(),
+            sender_normalizer: self.sender_normalizer.clone(),
+        }
+    }
+}
+
+/// Whole seconds left of a `cooldown_ms` cooldown started at `last`, if any.
+fn cooldown_remaining(last: Option<u64>, now: u64, cooldown_ms: u64) -> Option<u64> {
+    let elapsed = now.saturating_sub(last?);
+    (elapsed < cooldown_ms).then(|| (cooldown_ms - elapsed).div_ceil(1000))
+}
+
+#[async_trait::async_trait]
+impl ChannelControl for Gateway {
+    fn statuses(&self) -> Vec<ChannelStatus> {
+        self.channel_statuses()
+    }
+
+    async fn connect(&self, name: &str) -> Result<(), GatewayError> {
+        for result in self.connect_channel(name).await? {
+            if let Err(err) = result {
+                tracing::error!("Failed to deliver queued reply on {}: {}", name, err);
+            }
         }
+        Ok(())
+    }
+
+    async fn disconnect(&self, name: &str, reason: &str) -> bool {
+        self.disconnect_channel(name, reason).await
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::Confidence;
 
     #[test]
     fn test_gateway_creation() {
         let gateway = Gateway::new().unwrap();
-        assert!(gateway.channels.is_empty());
+        assert!(gateway.channel_statuses().is_empty());
     }
 
     #[test]
@@ -731,15 +2627,1746 @@ mod tests {
         }
     }
 
+    async fn install_slow_skill(gateway: &Gateway, name: &str, timeout_ms: Option<u64>) {
+        let graph = crate::skills::SkillGraph::builder(name)
+            .add_input("message", "string")
+            .add_operation("content", crate::skills::Op::Wait { ms: 100 }, vec!["message"])
+            .output("content")
+            .build();
+        let mut metadata = crate::skills::SkillMetadata::new(name, "Slow skill");
+        metadata.timeout_ms = timeout_ms;
+        gateway.skills.write().await.install_with_metadata(graph, metadata, false).unwrap();
+    }
+
     #[tokio::test]
-    async fn test_set_default_skill() {
+    async fn test_per_skill_timeout_overrides_global() {
+        let mut config = GatewayConfig::for_testing();
+        config.execution.timeout_ms = 20;
+        let gateway = Gateway::with_config(config).unwrap();
+        install_slow_skill(&gateway, "slow_default", None).await;
+        install_slow_skill(&gateway, "slow_generous", Some(5_000)).await;
+
+        let message = IncomingMessage::new("test", "user", "ping");
+        let params = HashMap::new();
+        let zero = ContentHash::zero();
+
+        let result = gateway.execute_skill("skill:slow_default", &zero, &message, &params).await;
+        assert!(matches!(result, Err(GatewayError::Timeout { timeout_ms: 20 })));
+
+        let (action, _) = gateway
+            .execute_skill("skill:slow_generous", &zero, &message, &params)
+            .await
+            .unwrap();
+        match action {
+            Action::SendMessage(msg) => assert_eq!(msg.content, "ping"),
+            other => panic!("Expected SendMessage, got {:?}", other),
+        }
+    }
+
+    /// Channel that logs sends and edits, optionally supporting edits.
+    struct EditLogChannel {
+        editable: bool,
+        rejects_edits: bool,
+        log: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl crate::channels::Channel for EditLogChannel {
+        fn name(&self) -> &str {
+            "test"
+        }
+
+        async fn receive(&self) -> Result<IncomingMessage, crate::error::ChannelError> {
+            Err(crate::error::ChannelError::ChannelClosed)
+        }
+
+        async fn send(&self, message: OutgoingMessage) -> Result<ProofCarryingAction, crate::error::ChannelError> {
+            self.log.lock().unwrap().push(format!("send: {}", message.content));
+            Ok(ProofCarryingAction::pending())
+        }
+
+        async fn send_editable(
+            &self,
+            message: OutgoingMessage,
+        ) -> Result<Option<String>, crate::error::ChannelError> {
+            self.send(message).await?;
+            Ok(self.editable.then(|| "m1".to_string()))
+        }
+
+        async fn edit(
+            &self,
+            message_id: &str,
+            message: OutgoingMessage,
+        ) -> Result<ProofCarryingAction, crate::error::ChannelError> {
+            if self.rejects_edits {
+                self.log.lock().unwrap().push(format!("edit {} rejected", message_id));
+                return Err(crate::error::ChannelError::SendFailed("message too old to edit".to_string()));
+            }
+            self.log.lock().unwrap().push(format!("edit {}: {}", message_id, message.content));
+            Ok(ProofCarryingAction::pending())
+        }
+
+        fn evaluate_permission(&self, _action: &Action, _sender: &str) -> crate::types::Confidence {
+            crate::types::Confidence::neutral()
+        }
+
+        fn allowlist(&self) -> &[String] {
+            &[]
+        }
+
+        fn supports(&self, _feature: crate::channels::ChannelFeature) -> bool {
+            false
+        }
+    }
+
+    async fn run_with_placeholder(editable: bool, threshold_ms: u64) -> Vec<String> {
+        let mut config = GatewayConfig::for_testing();
+        config.thinking_placeholder = Some("working on it...".to_string());
+        config.thinking_threshold_ms = threshold_ms;
+        let mut gateway = Gateway::with_config(config).unwrap();
+        let channel = Arc::new(EditLogChannel { editable, rejects_edits: false, log: std::sync::Mutex::new(Vec::new()) });
+        gateway.register_channel(channel.clone());
+        install_slow_skill(&gateway, "slow", None).await;
+        *gateway.router.write().await = router_to("skill:slow");
+
+        let mut pca = gateway.process_message(IncomingMessage::new("test", "user", "ping")).await.unwrap();
+        gateway.execute_action(&mut pca).await.unwrap();
+        let log = channel.log.lock().unwrap().clone();
+        log
+    }
+
+    #[tokio::test]
+    async fn test_slow_skill_sends_placeholder_then_edits() {
+        assert_eq!(run_with_placeholder(true, 20).await, vec!["send: working on it...", "edit m1: ping"]);
+
+        // Without edit support the answer arrives as a second message
+        assert_eq!(run_with_placeholder(false, 20).await, vec!["send: working on it...", "send: ping"]);
+
+        // Fast enough: no placeholder
+        assert_eq!(run_with_placeholder(true, 5_000).await, vec!["send: ping"]);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_edit_falls_back_to_send() {
+        let mut config = GatewayConfig::for_testing();
+        config.thinking_placeholder = Some("working on it...".to_string());
+        config.thinking_threshold_ms = 20;
+        let mut gateway = Gateway::with_config(config).unwrap();
+        let channel = Arc::new(EditLogChannel {
+            editable: true,
+            rejects_edits: true,
+            log: std::sync::Mutex::new(Vec::new()),
+        });
+        gateway.register_channel(channel.clone());
+        install_slow_skill(&gateway, "slow", None).await;
+        *gateway.router.write().await = router_to("skill:slow");
+
+        let mut pca = gateway.process_message(IncomingMessage::new("test", "user", "ping")).await.unwrap();
+        gateway.execute_action(&mut pca).await.unwrap();
+
+        assert_eq!(
+            channel.log.lock().unwrap().clone(),
+            vec!["send: working on it...", "edit m1 rejected", "send: ping"]
+        );
+        let receipt = pca.effect_trace.last().unwrap();
+        assert_eq!(receipt.kind, "message_sent");
+        assert!(receipt.details["edit_of"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_placeholder_cleared_without_reply() {
+        let mut config = GatewayConfig::for_testing();
+        config.thinking_placeholder = Some("working on it...".to_string());
+        config.thinking_threshold_ms = 20;
+        let mut gateway = Gateway::with_config(config).unwrap();
+        let channel = Arc::new(EditLogChannel { editable: true, rejects_edits: false, log: std::sync::Mutex::new(Vec::new()) });
+        gateway.register_channel(channel.clone());
+        let graph = crate::skills::SkillGraph::builder("slow_noop")
+            .add_input("message", "string")
+            .add_operation("waited", crate::skills::Op::Wait { ms: 100 }, vec!["message"])
+            .add_constant("noop", serde_json::json!("noop"))
+            .add_operation("action_type", crate::skills::Op::Identity, vec!["noop"])
+            .outputs(vec!["action_type", "waited"])
+            .build();
+        gateway.skills.write().await.install_graph("slow_noop", graph, false).unwrap();
+        install_slow_skill(&gateway, "slow", None).await;
+        let run = |gateway: Gateway, skill: &'static str| {
+            let channel = channel.clone();
+            async move {
+                channel.log.lock().unwrap().clear();
+                *gateway.router.write().await = router_to(skill);
+                let mut pca = gateway.process_message(IncomingMessage::new("test", "user", "ping")).await.unwrap();
+                gateway.execute_action(&mut pca).await.unwrap();
+                (pca.action, channel.log.lock().unwrap().clone())
+            }
+        };
+
+        // No reply: the placeholder is edited away (this channel cannot delete)
+        let (action, log) = run(gateway.clone(), "skill:slow_noop").await;
+        assert!(matches!(action, Action::NoOp { .. }), "{:?}", action);
+        assert_eq!(log, vec!["send: working on it...", "edit m1: Done."]);
+
+        // A denial notice replaces the placeholder
+        gateway.config.min_permission_confidence = 1.1;
+        let denied = gateway.config.denied_message.clone();
+        let (action, log) = run(gateway.clone(), "skill:slow").await;
+        assert!(matches!(action, Action::Denied { .. }), "{:?}", action);
+        assert_eq!(log, vec!["send: working on it...".to_string(), format!("edit m1: {}", denied)]);
+    }
+
+    /// Signer that counts signing calls.
+    struct CountingSigner {
+        inner: Ed25519Signer,
+        count: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl Signer for CountingSigner {
+        fn scheme(&self) -> crate::types::SigScheme {
+            self.inner.scheme()
+        }
+
+        fn sign(&self, message: &[u8]) -> Result<Vec<u8>, crate::error::ProofError> {
+            self.count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.inner.sign(message)
+        }
+
+        fn verifier(&self) -> Box<dyn Verifier> {
+            self.inner.verifier()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_disabled_proofs_skip_signing() {
+        let signs = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let signer = || Box::new(CountingSigner { inner: Ed25519Signer::generate(), count: signs.clone() });
+        let message = || IncomingMessage::new("test", "user", "/help");
+
+        let mut gateway = Gateway::with_config(GatewayConfig::for_testing()).unwrap().with_signer(signer());
+        gateway.register_channel(Arc::new(crate::channels::TestChannel::new("test")));
+        let mut pca = gateway.process_message(message()).await.unwrap();
+        assert!(pca.is_signed() && !pca.unsigned);
+        gateway.execute_action(&mut pca).await.unwrap();
+        let signed_count = signs.swap(0, std::sync::atomic::Ordering::SeqCst);
+        assert!(signed_count > 0);
+
+        let mut config = GatewayConfig::for_testing();
+        config.proofs_enabled = false;
+        let mut gateway = Gateway::with_config(config).unwrap().with_signer(signer());
+        gateway.register_channel(Arc::new(crate::channels::TestChannel::new("test")));
+        let mut pca = gateway.process_message(message()).await.unwrap();
+        assert!(pca.unsigned);
+        assert!(!pca.is_signed());
+        // Unsigned actions still execute, without verification or re-signing
+        gateway.execute_action(&mut pca).await.unwrap();
+        assert_eq!(pca.effect_count(), 1);
+        assert_eq!(signs.load(std::sync::atomic::Ordering::SeqCst), 0);
+    }
+
+    async fn reply_to_disconnected(policy: UndeliverablePolicy) -> (Gateway, EventSubscriber) {
+        let mut config = GatewayConfig::for_testing();
+        config.undeliverable_policy = policy;
+        let mut gateway = Gateway::with_config(config).unwrap();
+        gateway.register_channel(Arc::new(crate::channels::TestChannel::new("test")));
+
+        let mut pca = gateway.process_message(IncomingMessage::new("test", "user", "/help")).await.unwrap();
+        assert!(gateway.disconnect_channel("test", "flapping").await);
+        let events = gateway.event_bus().subscribe();
+        gateway.execute_action(&mut pca).await.unwrap();
+        assert_eq!(pca.effect_count(), 0);
+        (gateway, events)
+    }
+
+    #[tokio::test]
+    async fn test_undeliverable_reply_dropped() {
+        let (gateway, mut events) = reply_to_disconnected(UndeliverablePolicy::Drop).await;
+
+        assert!(gateway.undelivered_messages().await.is_empty());
+        assert!(matches!(events.try_recv(), Ok(GatewayEvent::Error { .. })));
+        let sessions = gateway.sessions.read().await;
+        assert!(sessions.sessions_for_channel("test").iter().all(|s| s.channel_stale));
+    }
+
+    #[tokio::test]
+    async fn test_undeliverable_reply_queued_until_reconnect() {
+        let (gateway, _events) = reply_to_disconnected(UndeliverablePolicy::Queue).await;
+
+        let queued = gateway.undelivered_messages().await;
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].recipient_id, "user");
+
+        let delivered = gateway.reconnect_channel(Arc::new(crate::channels::TestChannel::new("test"))).await;
+        assert_eq!(delivered.len(), 1);
+        assert!(delivered[0].is_ok());
+        assert!(gateway.undelivered_messages().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_broadcast() {
+        let mut gateway = Gateway::new().unwrap();
+        gateway.register_channel(Arc::new(crate::channels::TestChannel::new("test")));
+
+        let recipients = vec!["alice".to_string(), "bob".to_string()];
+        let results = gateway.broadcast("test", &recipients, "hello").await.unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.is_ok()));
+
+        assert!(matches!(
+            gateway.broadcast("missing", &recipients, "hello").await,
+            Err(GatewayError::ChannelNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_last_message_stored_in_session() {
+        // Nothing is kept unless configured
         let gateway = Gateway::new().unwrap();
-        let custom_default = ContentHash::from_string("skill:custom_default");
-        
-        gateway.set_default_skill(custom_default).await;
-        
-        // Verify the router has the updated default
-        let router = gateway.router.read().await;
-        assert!(router.graph().name.len() > 0);
+        gateway.process_message(IncomingMessage::new("test", "user", "first")).await.unwrap();
+        assert!(gateway.sessions.read().await.list()[0].get_context(LAST_MESSAGE_KEY).is_none());
+
+        let mut config = GatewayConfig::for_testing();
+        config.session.last_message_chars = 4;
+        let gateway = Gateway::with_config(config).unwrap();
+        gateway.process_message(IncomingMessage::new("test", "user", "first")).await.unwrap();
+        gateway.process_message(IncomingMessage::new("test", "user", "second")).await.unwrap();
+
+        let sessions = gateway.sessions.read().await;
+        let session = sessions.list()[0];
+        assert_eq!(session.get_context(LAST_MESSAGE_KEY), Some(&serde_json::json!("seco")));
+    }
+
+    fn router_to(skill_ref: &str) -> Router {
+        router_to_lane(skill_ref, None)
+    }
+
+    /// A router sending every message to `skill_ref`, on `lane` if given.
+    fn router_to_lane(skill_ref: &str, lane: Option<&str>) -> Router {
+        use crate::runtime::types::{GraphNode, NodeType};
+
+        let constant = |id: &str, value: &str| GraphNode {
+            id: id.to_string(),
+            node_type: NodeType::Constant { value: Value::String(value.into()) },
+            inputs: vec![],
+            params: serde_json::json!({}),
+        };
+        let mut nodes = vec![constant("skill_target", skill_ref)];
+        nodes.extend(lane.map(|lane| constant("route_lane", lane)));
+        Router::new(crate::runtime::Graph {
+            name: "fixed_router".to_string(),
+            version: 1,
+            description: "Routes every message to one skill".to_string(),
+            outputs: nodes.iter().map(|node| node.id.clone()).collect(),
+            nodes,
+            entry_point: "skill_target".to_string(),
+            metadata: serde_json::json!({}),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_unknown_skill_dead_lettered() {
+        let gateway = Gateway::new().unwrap();
+        *gateway.router.write().await = router_to("skill:missing");
+
+        let message = IncomingMessage::new("test", "user", "do something");
+        let err = gateway.process_message(message.clone()).await.unwrap_err();
+        assert!(matches!(err, GatewayError::SkillNotFound { .. }));
+
+        let dead_letters = gateway.dead_letters().await;
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(dead_letters[0].id, message.id);
+        assert_eq!(dead_letters[0].message.content, "do something");
+        assert!(dead_letters[0].error.contains("Skill not found"));
+
+        // Retrying once the route is fixed drains the queue
+        *gateway.router.write().await = router_to("skill:echo");
+        let pca = gateway.retry_dead_letter(&message.id).await.unwrap();
+        assert!(pca.is_signed());
+        assert!(gateway.dead_letters().await.is_empty());
+        assert!(gateway.retry_dead_letter(&message.id).await.is_err());
+    }
+
+    async fn install_file_skill(gateway: &Gateway) {
+        let graph = crate::skills::SkillGraph::builder("send_file")
+            .add_input("message", "string")
+            .add_operation("content", crate::skills::Op::Identity, vec!["message"])
+            .output("content")
+            .build();
+        let metadata = crate::skills::SkillMetadata::new("send_file", "Sends a file")
+            .with_required_feature(ChannelFeature::Files);
+        gateway.skills.write().await.install_with_metadata(graph, metadata, false).unwrap();
+        *gateway.router.write().await = router_to("skill:send_file");
+    }
+
+    #[tokio::test]
+    async fn test_route_params_bind_to_skill_inputs() {
+        let config = GatewayConfig {
+            input_mappings: HashMap::from([(
+                "skill:search".to_string(),
+                HashMap::from([
+                    ("arg0".to_string(), "query".to_string()),
+                    ("arg1".to_string(), "max_results".to_string()),
+                ]),
+            )]),
+            ..Default::default()
+        };
+        let gateway = Gateway::with_config(config).unwrap();
+        let graph = crate::skills::SkillGraph::builder("search")
+            .add_input("query", "string")
+            .add_input("max_results", "int")
+            .add_operation("content", crate::skills::Op::Identity, vec!["query"])
+            .output("content")
+            .build();
+        let metadata = crate::skills::SkillMetadata::new("search", "Echoes the query")
+            .with_input("query", "Search terms", "string", true)
+            .with_input("max_results", "Result limit", "int", false);
+        // Replace the built-ins so `/search` resolves to the skill above
+        let mut skills = SkillRegistry::new("/tmp/skills");
+        skills.install_with_metadata(graph, metadata, false).unwrap();
+        *gateway.skills.write().await = skills;
+
+        let pca = gateway
+            .process_message(IncomingMessage::new("test", "user", "/search rust"))
+            .await
+            .unwrap();
+        match &pca.action {
+            Action::SendMessage(msg) => assert_eq!(msg.content, "rust"),
+            other => panic!("Expected SendMessage, got {:?}", other),
+        }
+
+        // A required input with nothing to bind is an error
+        let err = gateway
+            .process_message(IncomingMessage::new("test", "user", "/search"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("query"), "{}", err);
+
+        // Mapped values are coerced to the declared type
+        let err = gateway
+            .process_message(IncomingMessage::new("test", "user", "/search rust many"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("max_results"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_configured_keystore_reaches_sign() {
+        let dir = tempfile::tempdir().unwrap();
+        let keystore_path = dir.path().join("keys.json");
+        std::fs::write(&keystore_path, serde_json::json!({ "receipts": hex::encode([3u8; 32]) }).to_string())
+            .unwrap();
+        let graph = crate::runtime::parse_graph(
+            r#"Graph {
+                name: "sign_receipt",
+                version: 1,
+                nodes: [
+                    { id: "message", type: "External", uri: "input://message" },
+                    { id: "signature", type: "Operation", op: "Sign", inputs: ["message"], params: { key: "receipts" } },
+                    { id: "seeded", type: "Operation", op: "Sign", inputs: ["message"], params: { seed: "0303030303030303030303030303030303030303030303030303030303030303", dev_mode: true } }
+                ],
+                entry_point: "message",
+                outputs: ["signature"]
+            }"#,
+        )
+        .unwrap();
+        let inputs = || HashMap::from([("message".to_string(), Value::String("paid".into()))]);
+
+        let mut config = GatewayConfig::for_testing();
+        config.keystore_path = Some(keystore_path.clone());
+        let gateway = Gateway::with_config(config).unwrap();
+        let err = gateway.runtime.execute(&graph, inputs()).await.unwrap_err();
+        assert!(err.to_string().contains("dev_mode"), "{}", err);
+
+        let mut config = GatewayConfig::for_testing();
+        config.keystore_path = Some(keystore_path);
+        config.dev_mode = true;
+        let result = Gateway::with_config(config).unwrap().runtime.execute(&graph, inputs()).await.unwrap();
+        assert!(matches!(result.outputs.get("signature"), Some(Value::Bytes(bytes)) if bytes.len() == 64));
+
+        let mut config = GatewayConfig::for_testing();
+        config.keystore_path = Some(dir.path().join("missing.json"));
+        assert!(Gateway::with_config(config).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_search_answers_without_http_config() {
+        // No allowed hosts: the search skill's http URI reads graph inputs
+        // instead of calling out, as before HTTP support
+        let gateway = Gateway::new().unwrap();
+        let pca = gateway
+            .process_message(IncomingMessage::new("test", "user", "/search rust"))
+            .await
+            .unwrap();
+        assert!(matches!(pca.action, Action::SendMessage(_)), "{:?}", pca.action);
+        assert!(gateway.dead_letters().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_skill_unavailable_without_required_feature() {
+        let mut gateway = Gateway::new().unwrap();
+        gateway.register_channel(Arc::new(crate::channels::TestChannel::new("plain")));
+        install_file_skill(&gateway).await;
+
+        let pca = gateway
+            .process_message(IncomingMessage::new("plain", "user", "report.pdf"))
+            .await
+            .unwrap();
+
+        match &pca.action {
+            Action::SendMessage(msg) => assert!(msg.content.contains("not available")),
+            other => panic!("Expected SendMessage, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_skill_runs_with_required_feature() {
+        let mut gateway = Gateway::new().unwrap();
+        gateway.register_channel(Arc::new(
+            crate::channels::TestChannel::new("files").with_features(vec![ChannelFeature::Files]),
+        ));
+        install_file_skill(&gateway).await;
+
+        let pca = gateway
+            .process_message(IncomingMessage::new("files", "user", "report.pdf"))
+            .await
+            .unwrap();
+
+        match &pca.action {
+            Action::SendMessage(msg) => assert_eq!(msg.content, "report.pdf"),
+            other => panic!("Expected SendMessage, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_config_declares_required_features() {
+        let mut config = GatewayConfig::default();
+        config
+            .skill_features
+            .insert("echo".to_string(), vec![ChannelFeature::Voice]);
+        let mut gateway = Gateway::with_config(config).unwrap();
+        gateway.register_channel(Arc::new(crate::channels::TestChannel::new("plain")));
+        *gateway.router.write().await = router_to("skill:echo");
+
+        let pca = gateway
+            .process_message(IncomingMessage::new("plain", "user", "hi"))
+            .await
+            .unwrap();
+
+        match &pca.action {
+            Action::SendMessage(msg) => assert!(msg.content.contains("not available")),
+            other => panic!("Expected SendMessage, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_quiet_hours_hold_scheduled_messages() {
+        use chrono::{NaiveTime, TimeZone, Utc};
+
+        let config = GatewayConfig {
+            quiet_hours: Some(QuietHours::new(
+                NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+                NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+                "UTC",
+            )),
+            ..Default::default()
+        };
+        let clock = MockClock::new(Utc.with_ymd_and_hms(2026, 3, 10, 23, 0, 0).unwrap());
+        let mut gateway = Gateway::with_config(config).unwrap().with_clock(Arc::new(clock.clone()));
+        gateway.register_channel(Arc::new(crate::channels::TestChannel::new("test")));
+
+        let reminder = OutgoingMessage::new("test", "user", "Stand-up at 9");
+        assert!(gateway.schedule_message(reminder.clone(), false).await.unwrap().is_none());
+        assert_eq!(gateway.held_messages().await.len(), 1);
+
+        // Urgent messages bypass quiet hours
+        assert!(gateway.schedule_message(reminder, true).await.unwrap().is_some());
+
+        // Still quiet: nothing is released
+        clock.advance(chrono::Duration::hours(7) + chrono::Duration::minutes(59));
+        assert!(gateway.release_held().await.is_empty());
+
+        clock.advance(chrono::Duration::minutes(1));
+        let released = gateway.release_held().await;
+        assert_eq!(released.len(), 1);
+        assert!(released[0].is_ok());
+        assert!(gateway.held_messages().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_command_cooldown() {
+        use chrono::{TimeZone, Utc};
+
+        let config = GatewayConfig {
+            session: config::SessionConfig { command_cooldown_ms: 5_000, ..Default::default() },
+            ..Default::default()
+        };
+        let clock = MockClock::new(Utc.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap());
+        let gateway = Gateway::with_config(config).unwrap().with_clock(Arc::new(clock.clone()));
+        let reply = |pca: ProofCarryingAction| match pca.action {
+            Action::SendMessage(msg) => msg.content,
+            other => panic!("Expected SendMessage, got {:?}", other),
+        };
+
+        let first = gateway.process_message(IncomingMessage::new("test", "user", "/help")).await.unwrap();
+        assert!(!reply(first).contains("Please wait"));
+
+        // A rapid second command trips the cooldown
+        clock.advance(chrono::Duration::seconds(1));
+        let second = gateway.process_message(IncomingMessage::new("test", "user", "/status")).await.unwrap();
+        assert_eq!(reply(second), "Please wait 4s before sending another command.");
+
+        // Plain messages are not commands
+        let chat = gateway.process_message(IncomingMessage::new("test", "user", "hello")).await.unwrap();
+        assert!(!format!("{:?}", chat.action).contains("Please wait"));
+
+        // A spaced command is processed
+        clock.advance(chrono::Duration::seconds(5));
+        let third = gateway.process_message(IncomingMessage::new("test", "user", "/status")).await.unwrap();
+        assert!(!reply(third).contains("Please wait"));
+    }
+
+    #[tokio::test]
+    async fn test_per_command_cooldown() {
+        use chrono::{TimeZone, Utc};
+
+        let mut config = GatewayConfig::for_testing();
+        config.command_cooldowns.insert("status".to_string(), 30);
+        let clock = MockClock::new(Utc.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap());
+        let mut gateway = Gateway::with_config(config).unwrap().with_clock(Arc::new(clock.clone()));
+        let channel = Arc::new(GatedChannel { blocked: String::new(), sent: std::sync::Mutex::new(Vec::new()) });
+        gateway.register_channel(channel.clone());
+        let reply = |pca: ProofCarryingAction| match pca.action {
+            Action::SendMessage(msg) => msg.content,
+            other => panic!("Expected SendMessage, got {:?}", other),
+        };
+
+        // A use the gate denies does not start the cooldown
+        gateway.config.min_permission_confidence = 1.1;
+        let denied = gateway.process_message(IncomingMessage::new("test", "user", "/status")).await.unwrap();
+        assert!(matches!(denied.action, Action::Denied { .. }));
+        gateway.config.min_permission_confidence = GatewayConfig::for_testing().min_permission_confidence;
+
+        let first = gateway.process_message(IncomingMessage::new("test", "user", "/status")).await.unwrap();
+        assert!(!reply(first).contains("cooldown"));
+
+        // The second use is answered with the remaining time, in the signed action
+        clock.advance(chrono::Duration::seconds(10));
+        let second = gateway.process_message(IncomingMessage::new("test", "user", "/status")).await.unwrap();
+        assert_eq!(reply(second), "/status is on cooldown, try again in 20 seconds.");
+        assert!(channel.sent.lock().unwrap().is_empty());
+
+        // Other commands and other users are unaffected
+        let help = gateway.process_message(IncomingMessage::new("test", "user", "/help")).await.unwrap();
+        assert!(!reply(help).contains("cooldown"));
+        let other = gateway.process_message(IncomingMessage::new("test", "other", "/status")).await.unwrap();
+        assert!(!reply(other).contains("cooldown"));
+
+        clock.advance(chrono::Duration::seconds(20));
+        let third = gateway.process_message(IncomingMessage::new("test", "user", "/status")).await.unwrap();
+        assert!(!reply(third).contains("cooldown"));
+
+        // Other spellings routed to the same skill share its cooldown
+        *gateway.router.write().await = router_to("skill:status");
+        for spelling in ["/Status", "/status@bot"] {
+            let pca = gateway.process_message(IncomingMessage::new("test", "user", spelling)).await.unwrap();
+            assert_eq!(reply(pca), format!("{} is on cooldown, try again in 30 seconds.", spelling));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unknown_command_suggests_closest() {
+        let gateway = Gateway::new().unwrap();
+
+        let pca = gateway
+            .process_message(IncomingMessage::new("test", "user", "/halp"))
+            .await
+            .unwrap();
+        match &pca.action {
+            Action::SendMessage(msg) => {
+                assert!(msg.content.contains("Did you mean /help?"), "{}", msg.content);
+            }
+            other => panic!("Expected SendMessage, got {:?}", other),
+        }
+
+        let pca = gateway
+            .process_message(IncomingMessage::new("test", "user", "/xyzzy"))
+            .await
+            .unwrap();
+        match &pca.action {
+            Action::SendMessage(msg) => {
+                assert!(!msg.content.contains("Did you mean"));
+                assert!(msg.content.contains("/help"));
+            }
+            other => panic!("Expected SendMessage, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_duplicates_are_coalesced() {
+        let gateway = Gateway::new().unwrap();
+        install_slow_skill(&gateway, "slow", None).await;
+        *gateway.router.write().await = router_to("skill:slow");
+
+        let message = IncomingMessage::new("test", "user", "once");
+        let (first, second) = tokio::join!(
+            gateway.process_message(message.clone()),
+            gateway.process_message(message.clone()),
+        );
+
+        let (first, second) = (first.unwrap(), second.unwrap());
+        assert_eq!(first.signature, second.signature);
+
+        let executions = gateway
+            .event_bus()
+            .history()
+            .await
+            .iter()
+            .filter(|event| event.event_type() == "skill_invoked")
+            .count();
+        assert_eq!(executions, 1);
+        assert!(gateway.in_flight.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_set_default_skill() {
+        let gateway = Gateway::new().unwrap();
+        let custom_default = ContentHash::from_string("skill:custom_default");
+        
+        gateway.set_default_skill(custom_default).await;
+        
+        // Verify the router has the updated default
+        let router = gateway.router.read().await;
+        assert!(router.graph().name.len() > 0);
+    }
+
+    fn router_on_sentiment() -> Router {
+        use crate::runtime::types::{GraphNode, NodeType};
+
+        let node = |id: &str, node_type: NodeType, inputs: &[&str], params: serde_json::Value| GraphNode {
+            id: id.to_string(),
+            node_type,
+            inputs: inputs.iter().map(|s| s.to_string()).collect(),
+            params,
+        };
+        let op = |name: &str| NodeType::Operation { op: name.to_string() };
+        let constant = |value: &str| NodeType::Constant { value: Value::String(value.into()) };
+
+        Router::new(crate::runtime::Graph {
+            name: "sentiment_router".to_string(),
+            version: 1,
+            description: "Routes negative messages to support".to_string(),
+            nodes: vec![
+                node(
+                    "enrichment",
+                    NodeType::External { uri: "input://enrichment".to_string() },
+                    &[],
+                    serde_json::json!({}),
+                ),
+                node("sentiment", op("GetField"), &["enrichment"], serde_json::json!({"field": "sentiment"})),
+                node("negative", constant("negative"), &[], serde_json::json!({})),
+                node("is_negative", op("Equals"), &["sentiment", "negative"], serde_json::json!({})),
+                node("support", constant("skill:support"), &[], serde_json::json!({})),
+                node("echo", constant("skill:echo"), &[], serde_json::json!({})),
+                node("skill_target", op("If"), &["is_negative", "support", "echo"], serde_json::json!({})),
+            ],
+            outputs: vec!["skill_target".to_string()],
+            entry_point: "enrichment".to_string(),
+            metadata: serde_json::json!({}),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_preprocessor_enrichment_drives_routing() {
+        let config = GatewayConfig {
+            preprocessor_skills: vec!["missing".to_string(), "sentiment".to_string()],
+            ..Default::default()
+        };
+        let gateway = Gateway::with_config(config).unwrap();
+        {
+            let mut skills = gateway.skills.write().await;
+            let sentiment = crate::skills::SkillGraph::builder("sentiment")
+                .add_input("message", "string")
+                .add_constant("fields", serde_json::json!({"sentiment": "negative"}))
+                .output("fields")
+                .build();
+            skills.install_graph("sentiment", sentiment, false).unwrap();
+            let support = crate::skills::SkillGraph::builder("support")
+                .add_input("message", "string")
+                .add_constant("reply", serde_json::json!("Sorry to hear that."))
+                .add_operation("content", crate::skills::Op::Identity, vec!["reply"])
+                .output("content")
+                .build();
+            skills.install_graph("support", support, false).unwrap();
+        }
+        *gateway.router.write().await = router_on_sentiment();
+
+        // The missing preprocessor is skipped; the sentiment one still applies
+        let pca = gateway
+            .process_message(IncomingMessage::new("test", "user", "this is broken"))
+            .await
+            .unwrap();
+        let Action::SendMessage(reply) = &pca.action else { panic!("expected a reply") };
+        assert_eq!(reply.content, "Sorry to hear that.");
+
+        // Without preprocessors the router falls through to echo
+        let plain = Gateway::new().unwrap();
+        *plain.router.write().await = router_on_sentiment();
+        let pca = plain
+            .process_message(IncomingMessage::new("test", "user", "this is broken"))
+            .await
+            .unwrap();
+        let Action::SendMessage(reply) = &pca.action else { panic!("expected a reply") };
+        assert_ne!(reply.content, "Sorry to hear that.");
+    }
+
+    /// Install `start_booking`, claiming the conversation for `max_turns`
+    /// turns of `booking`, and route every message to it.
+    async fn install_booking(gateway: &Gateway, max_turns: i64) -> ContentHash {
+        let mut skills = gateway.skills.write().await;
+        let start = crate::skills::SkillGraph::builder("start_booking")
+            .add_input("message", "string")
+            .add_constant("action_type", serde_json::json!("claim_conversation"))
+            .add_constant("skill", serde_json::json!("booking"))
+            .add_constant("max_turns", serde_json::json!(max_turns))
+            .outputs(vec!["action_type", "skill", "max_turns"])
+            .build();
+        skills.install_graph("start_booking", start, false).unwrap();
+        let booking = crate::skills::SkillGraph::builder("booking")
+            .add_input("message", "string")
+            .add_constant("reply", serde_json::json!("Which date?"))
+            .add_operation("content", crate::skills::Op::Identity, vec!["reply"])
+            .output("content")
+            .build();
+        skills.install_graph("booking", booking, false).unwrap();
+        *gateway.router.write().await = router_to("skill:start_booking");
+        skills.get_by_name("booking").unwrap().hash
+    }
+
+    #[tokio::test]
+    async fn test_claimed_conversation_bypasses_router() {
+        let gateway = Gateway::new().unwrap();
+        let booking_hash = install_booking(&gateway, 2).await;
+
+        let send = |content: &str| gateway.process_message(IncomingMessage::new("test", "user", content));
+        let pca = send("book a table").await.unwrap();
+        assert!(matches!(
+            pca.action,
+            Action::ClaimConversation { skill_hash, max_turns: 2 } if skill_hash == booking_hash
+        ));
+
+        // The router would start a new booking; the claim routes to booking instead
+        for content in ["tomorrow", "at noon"] {
+            let pca = send(content).await.unwrap();
+            let Action::SendMessage(reply) = &pca.action else { panic!("expected a reply") };
+            assert_eq!(reply.content, "Which date?");
+        }
+
+        // The turn limit released the claim, so routing applies again
+        let pca = send("book another").await.unwrap();
+        assert!(matches!(pca.action, Action::ClaimConversation { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_claimed_turns_keep_limits_and_policy() {
+        let mut config = GatewayConfig::for_testing();
+        config.max_claim_turns = 3;
+        let mut gateway = Gateway::with_config(config).unwrap();
+        gateway.register_channel(Arc::new(crate::channels::TestChannel::new("test")));
+        install_booking(&gateway, 1_000_000).await;
+
+        let message = || IncomingMessage::new("test", "user", "book a table");
+        let pca = gateway.process_message(message()).await.unwrap();
+        assert!(matches!(pca.action, Action::ClaimConversation { max_turns: 3, .. }), "{:?}", pca.action);
+
+        // A route the router denies stays denied during a claim
+        *gateway.router.write().await = router_to_lane("skill:start_booking", Some("deny"));
+        let pca = gateway.process_message(message()).await.unwrap();
+        assert!(matches!(pca.action, Action::Denied { .. }), "{:?}", pca.action);
+
+        // So does an action the channel's permission gate refuses
+        *gateway.router.write().await = router_to("skill:start_booking");
+        gateway.config.min_permission_confidence = 1.1;
+        let pca = gateway.process_message(message()).await.unwrap();
+        assert!(matches!(pca.action, Action::Denied { .. }), "{:?}", pca.action);
+    }
+
+    #[test]
+    fn test_release_conversation_clears_claim() {
+        let mut session = Session::new("test", "user", 0.5);
+        let skill = ContentHash::from_string("skill:booking");
+        session.claim(skill, 3);
+        assert_eq!(session.take_claim_turn(), Some(skill));
+        session.release();
+        assert_eq!(session.take_claim_turn(), None);
+    }
+
+    struct FlakyChannel {
+        working: std::sync::atomic::AtomicBool,
+        attempts: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl crate::channels::Channel for FlakyChannel {
+        fn name(&self) -> &str {
+            "test"
+        }
+
+        async fn receive(&self) -> Result<IncomingMessage, crate::error::ChannelError> {
+            Err(crate::error::ChannelError::ChannelClosed)
+        }
+
+        async fn send(&self, _message: OutgoingMessage) -> Result<ProofCarryingAction, crate::error::ChannelError> {
+            use std::sync::atomic::Ordering;
+            self.attempts.fetch_add(1, Ordering::SeqCst);
+            if self.working.load(Ordering::SeqCst) {
+                Ok(ProofCarryingAction::pending())
+            } else {
+                Err(crate::error::ChannelError::SendFailed("upstream unavailable".to_string()))
+            }
+        }
+
+        fn evaluate_permission(&self, _action: &Action, _sender: &str) -> crate::types::Confidence {
+            crate::types::Confidence::neutral()
+        }
+
+        fn allowlist(&self) -> &[String] {
+            &[]
+        }
+
+        fn supports(&self, _feature: crate::channels::ChannelFeature) -> bool {
+            false
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_delivery_dead_lettered_and_redelivered() {
+        use std::sync::atomic::Ordering;
+
+        let mut config = GatewayConfig::for_testing();
+        config.dead_letter.delivery_retries = 1;
+        let mut gateway = Gateway::with_config(config).unwrap();
+        let channel = Arc::new(FlakyChannel {
+            working: std::sync::atomic::AtomicBool::new(false),
+            attempts: std::sync::atomic::AtomicUsize::new(0),
+        });
+        gateway.register_channel(channel.clone());
+
+        let mut pca = gateway.process_message(IncomingMessage::new("test", "user", "/help")).await.unwrap();
+        assert!(gateway.execute_action(&mut pca).await.is_err());
+        assert_eq!(channel.attempts.load(Ordering::SeqCst), 2);
+
+        let failed = gateway.failed_deliveries().await;
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].recipient_id, "user");
+        assert!(failed[0].error.contains("upstream unavailable"));
+
+        channel.working.store(true, Ordering::SeqCst);
+        let delivered = gateway.redeliver(&failed[0].id).await.unwrap();
+        assert_eq!(delivered.effect_count(), 1);
+        assert!(gateway.failed_deliveries().await.is_empty());
+        assert!(gateway.redeliver(&failed[0].id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_federated_trust_propagates_to_peer() {
+        let gateway_a = Gateway::with_config(GatewayConfig::for_testing()).unwrap();
+        let gateway_b = Gateway::with_config(GatewayConfig::for_testing()).unwrap();
+        let bridge = gateway_a.federate(vec![Arc::new(gateway_b.local_peer())]);
+
+        // Peers only update sessions they already have
+        gateway_a.process_message(IncomingMessage::new("test", "bob", "/help")).await.unwrap();
+        gateway_b.process_message(IncomingMessage::new("test", "alice", "hello")).await.unwrap();
+        let initial_b = gateway_b.list_sessions().await[0].trust_score;
+
+        gateway_a.process_message(IncomingMessage::new("test", "alice", "/help")).await.unwrap();
+        gateway_a.process_message(IncomingMessage::new("test", "alice", "/help")).await.unwrap();
+        let trust_a = gateway_a.list_sessions().await.into_iter()
+            .find(|s| s.user_id == "alice")
+            .unwrap()
+            .trust_score;
+        assert_ne!(trust_a, initial_b);
+
+        let mut trust_b = None;
+        for _ in 0..100 {
+            trust_b = gateway_b.list_sessions().await.first().map(|s| s.trust_score);
+            if trust_b == Some(trust_a) {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(trust_b, Some(trust_a));
+        let sessions_b = gateway_b.list_sessions().await;
+        assert_eq!(sessions_b.len(), 1);
+        assert_eq!(sessions_b[0].user_id, "alice");
+        bridge.abort();
+    }
+
+    #[tokio::test]
+    async fn test_action_outcomes_adjust_trust() {
+        let gateway = Gateway::with_config(GatewayConfig::for_testing()).unwrap();
+        let trust = |user: &'static str| {
+            let gateway = &gateway;
+            async move {
+                gateway.list_sessions().await.into_iter()
+                    .find(|s| s.user_id == user)
+                    .map(|s| s.trust_score)
+                    .unwrap()
+            }
+        };
+
+        for _ in 0..3 {
+            let mut pca = gateway.process_message(IncomingMessage::new("test", "steady", "/help")).await.unwrap();
+            assert!(gateway.execute_action(&mut pca).await.is_ok());
+
+            let mut pca = gateway.process_message(IncomingMessage::new("test", "flaky", "/help")).await.unwrap();
+            pca.confidence = crate::types::Confidence::new(0.01);
+            assert!(gateway.execute_action(&mut pca).await.is_err());
+        }
+
+        let (steady, flaky) = (trust("steady").await, trust("flaky").await);
+        assert!(steady > flaky, "steady {} should exceed flaky {}", steady, flaky);
+        assert!(flaky < GatewayConfig::for_testing().session.initial_trust);
+    }
+
+    #[tokio::test]
+    async fn test_denials_and_delivery_failures_do_not_reward_trust() {
+        let mut gateway = Gateway::with_config(GatewayConfig::for_testing()).unwrap();
+        gateway.register_channel(Arc::new(crate::channels::TestChannel::new("test")));
+        let initial = GatewayConfig::for_testing().session.initial_trust;
+        let trust = |gateway: &Gateway, user: &'static str| {
+            let gateway = gateway.clone();
+            async move {
+                gateway.list_sessions().await.into_iter().find(|s| s.user_id == user).unwrap().trust_score
+            }
+        };
+
+        // A delivered denial notice lowers trust
+        gateway.config.min_permission_confidence = 1.1;
+        let mut pca = gateway.process_message(IncomingMessage::new("test", "denied", "/help")).await.unwrap();
+        assert!(matches!(pca.action, Action::Denied { .. }));
+        gateway.execute_action(&mut pca).await.unwrap();
+        assert!(trust(&gateway, "denied").await < initial);
+
+        // A reply the platform fails to deliver is not the sender's fault
+        gateway.config.min_permission_confidence = 0.0;
+        gateway.config.dead_letter.delivery_retries = 0;
+        gateway.register_channel(Arc::new(FlakyChannel {
+            working: std::sync::atomic::AtomicBool::new(false),
+            attempts: std::sync::atomic::AtomicUsize::new(0),
+        }));
+        let mut pca = gateway.process_message(IncomingMessage::new("test", "stranded", "/help")).await.unwrap();
+        let before = trust(&gateway, "stranded").await;
+        assert!(gateway.execute_action(&mut pca).await.is_err());
+        assert_eq!(trust(&gateway, "stranded").await, before);
+    }
+
+    #[tokio::test]
+    async fn test_skills_command_lists_installed_skills() {
+        let gateway = Gateway::new().unwrap();
+        let pca = gateway.process_message(IncomingMessage::new("test", "user", "/skills")).await.unwrap();
+
+        let Action::SendMessage(msg) = &pca.action else {
+            panic!("Expected SendMessage action");
+        };
+        assert!(msg.content.starts_with("Installed skills:"));
+        for name in ["echo", "search", "browser", "calendar"] {
+            assert!(
+                msg.content.lines().any(|line| line.starts_with(&format!("{} - ", name))),
+                "{} missing from {}", name, msg.content
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_persisted_delivery_redelivered_after_restart() {
+        use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = GatewayConfig::for_testing();
+        config.keypair_path = dir.path().join("keypair");
+        config.dead_letter.delivery_retries = 1;
+        config.dead_letter.delivery_path = Some(dir.path().join("deliveries.jsonl"));
+
+        // The gateway stopped while backing off after a failed send
+        let before = Gateway::with_config(config.clone()).unwrap();
+        let pca = before.process_message(IncomingMessage::new("test", "user", "/help")).await.unwrap();
+        drop(before);
+        JsonlDeliveryStore::new()
+            .with_path(dir.path().join("deliveries.jsonl"))
+            .push(FailedDelivery::new(pca, "test", "user", "send failed"));
+
+        // A new gateway over the same queue path picks the action up
+        let mut after = Gateway::with_config(config.clone()).unwrap();
+        let channel = Arc::new(FlakyChannel {
+            working: AtomicBool::new(true),
+            attempts: AtomicUsize::new(0),
+        });
+        after.register_channel(channel.clone());
+        assert_eq!(after.failed_deliveries().await.len(), 1);
+
+        let results = after.redeliver_pending().await;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].as_ref().unwrap().effect_count(), 1);
+        assert_eq!(channel.attempts.load(Ordering::SeqCst), 1);
+        assert!(Gateway::with_config(config).unwrap().failed_deliveries().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dead_lettered_delivery_not_resent_on_restart() {
+        use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = GatewayConfig::for_testing();
+        config.keypair_path = dir.path().join("keypair");
+        config.dead_letter.delivery_retries = 1;
+        config.dead_letter.delivery_path = Some(dir.path().join("deliveries.jsonl"));
+
+        let mut before = Gateway::with_config(config.clone()).unwrap();
+        before.register_channel(Arc::new(FlakyChannel {
+            working: AtomicBool::new(false),
+            attempts: AtomicUsize::new(0),
+        }));
+        let mut pca = before.process_message(IncomingMessage::new("test", "user", "/help")).await.unwrap();
+        assert!(before.execute_action(&mut pca).await.is_err());
+
+        // An interrupted delivery past the maximum age is not resent either
+        let stale = before.process_message(IncomingMessage::new("test", "user", "/status")).await.unwrap();
+        drop(before);
+        JsonlDeliveryStore::new()
+            .with_path(dir.path().join("deliveries.jsonl"))
+            .push(FailedDelivery::new(stale, "test", "user", "send failed").with_timestamp(0));
+
+        let mut after = Gateway::with_config(config).unwrap();
+        let channel = Arc::new(FlakyChannel {
+            working: AtomicBool::new(true),
+            attempts: AtomicUsize::new(0),
+        });
+        after.register_channel(channel.clone());
+
+        assert!(after.redeliver_pending().await.is_empty());
+        assert_eq!(channel.attempts.load(Ordering::SeqCst), 0);
+
+        // Both stay stored for an operator to redeliver explicitly
+        let stored = after.failed_deliveries().await;
+        assert_eq!(stored.len(), 2);
+        assert!(stored[0].exhausted);
+        assert!(after.redeliver(&stored[0].id).await.is_ok());
+        assert_eq!(channel.attempts.load(Ordering::SeqCst), 1);
+    }
+
+    struct GatedChannel {
+        blocked: String,
+        sent: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl crate::channels::Channel for GatedChannel {
+        fn name(&self) -> &str {
+            "test"
+        }
+
+        async fn receive(&self) -> Result<IncomingMessage, crate::error::ChannelError> {
+            Err(crate::error::ChannelError::ChannelClosed)
+        }
+
+        async fn send(&self, message: OutgoingMessage) -> Result<ProofCarryingAction, crate::error::ChannelError> {
+            self.sent.lock().unwrap().push(message.content);
+            Ok(ProofCarryingAction::pending())
+        }
+
+        fn evaluate_permission(&self, _action: &Action, sender: &str) -> crate::types::Confidence {
+            if sender == self.blocked {
+                crate::types::Confidence::new(0.1)
+            } else {
+                crate::types::Confidence::neutral()
+            }
+        }
+
+        fn allowlist(&self) -> &[String] {
+            &[]
+        }
+
+        fn supports(&self, _feature: crate::channels::ChannelFeature) -> bool {
+            false
+        }
+    }
+
+    #[tokio::test]
+    async fn test_low_permission_sender_is_denied() {
+        let mut config = GatewayConfig::for_testing();
+        config.denied_message = "Sorry, you can't do that here.".to_string();
+        let mut gateway = Gateway::with_config(config).unwrap();
+        let channel = Arc::new(GatedChannel {
+            blocked: "mallory".to_string(),
+            sent: std::sync::Mutex::new(Vec::new()),
+        });
+        gateway.register_channel(channel.clone());
+
+        let mut pca = gateway.process_message(IncomingMessage::new("test", "mallory", "ping")).await.unwrap();
+        match &pca.action {
+            Action::Denied { reason, notice } => {
+                assert!(reason.contains("0.10 is below 0.20"), "{}", reason);
+                assert_eq!(notice.recipient_id, "mallory");
+            }
+            other => panic!("expected Denied, got {:?}", other),
+        }
+        gateway.execute_action(&mut pca).await.unwrap();
+        assert_eq!(channel.sent.lock().unwrap().as_slice(), ["Sorry, you can't do that here."]);
+
+        let pca = gateway.process_message(IncomingMessage::new("test", "alice", "ping")).await.unwrap();
+        assert!(!matches!(pca.action, Action::Denied { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_publishes_vitals_until_stopped() {
+        let gateway = Gateway::with_config(GatewayConfig::for_testing()).unwrap();
+        *gateway.running.write().await = true;
+        gateway.process_message(IncomingMessage::new("test", "user", "hello")).await.unwrap();
+        let mut events = gateway.event_bus().subscribe();
+        let handle = gateway.spawn_heartbeat(std::time::Duration::from_millis(20));
+
+        let mut beats = Vec::new();
+        while beats.len() < 2 {
+            if let GatewayEvent::Custom { name, data } = events.recv().await.unwrap() {
+                assert_eq!(name, "heartbeat");
+                beats.push(data);
+            }
+        }
+        assert!(beats[1]["uptime_ms"].as_u64() > beats[0]["uptime_ms"].as_u64());
+        assert_eq!(beats[0]["sessions"], 1);
+        assert_eq!(beats[0]["messages_processed"], 1);
+        assert_eq!(beats[1]["messages_processed"], 0);
+
+        gateway.stop().await.unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(1), handle).await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_listener_delivers_reply_to_received_message() {
+        let mut gateway = Gateway::with_config(GatewayConfig::for_testing()).unwrap();
+        let channel = Arc::new(
+            crate::channels::TestChannel::new("test").with_incoming(IncomingMessage::new("test", "user", "ping")),
+        );
+        gateway.register_channel(channel.clone());
+
+        let listener = gateway.spawn_listener("test".to_string(), channel.clone());
+        let sent = tokio::time::timeout(std::time::Duration::from_secs(2), async {
+            loop {
+                let sent = channel.sent();
+                if !sent.is_empty() {
+                    return sent;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        listener.abort();
+
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].recipient_id, "user");
+        assert!(sent[0].content.contains("ping"), "{}", sent[0].content);
+    }
+
+    #[tokio::test]
+    async fn test_listener_publishes_processing_errors() {
+        let mut config = GatewayConfig::for_testing();
+        config.session.max_per_user = 0;
+        config.session.evict_oldest = false;
+        let mut gateway = Gateway::with_config(config).unwrap();
+        let channel = Arc::new(
+            crate::channels::TestChannel::new("test").with_incoming(IncomingMessage::new("test", "user", "ping")),
+        );
+        gateway.register_channel(channel.clone());
+        let mut events = gateway.event_bus().subscribe();
+
+        let listener = gateway.spawn_listener("test".to_string(), channel.clone());
+        let error = tokio::time::timeout(std::time::Duration::from_secs(2), async {
+            loop {
+                if let GatewayEvent::Error { source, message } = events.recv().await.unwrap() {
+                    return (source, message);
+                }
+            }
+        })
+        .await
+        .unwrap();
+        listener.abort();
+
+        assert_eq!(error.0, "test");
+        assert!(error.1.contains("Too many sessions"), "{}", error.1);
+        assert!(channel.sent().is_empty());
+    }
+
+    struct RecordingAuditSink {
+        recorded: std::sync::Mutex<Vec<ContentHash>>,
+        delay: std::time::Duration,
+    }
+
+    #[async_trait::async_trait]
+    impl AuditSink for RecordingAuditSink {
+        async fn record(&self, pca: &ProofCarryingAction) -> Result<(), GatewayError> {
+            tokio::time::sleep(self.delay).await;
+            self.recorded.lock().unwrap().push(pca.input_hash);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_executed_actions_reach_audit_sinks() {
+        let sink = |delay| Arc::new(RecordingAuditSink {
+            recorded: std::sync::Mutex::new(Vec::new()),
+            delay,
+        });
+        let fast = sink(std::time::Duration::ZERO);
+        let slow = sink(std::time::Duration::from_secs(60));
+        let mut gateway = Gateway::with_config(GatewayConfig::for_testing())
+            .unwrap()
+            .with_audit_sink(slow.clone())
+            .with_audit_sink(fast.clone());
+        gateway.register_channel(Arc::new(crate::channels::TestChannel::new("test")));
+
+        let mut pca = gateway.process_message(IncomingMessage::new("test", "user", "/help")).await.unwrap();
+        // The slow sink must not hold up execution
+        tokio::time::timeout(std::time::Duration::from_secs(5), gateway.execute_action(&mut pca))
+            .await
+            .expect("execution stalled on a slow audit sink")
+            .unwrap();
+
+        for _ in 0..100 {
+            if !fast.recorded.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(*fast.recorded.lock().unwrap(), vec![pca.input_hash]);
+        assert!(slow.recorded.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_attachment_policy_passes_strips_or_rejects() {
+        use crate::types::Attachment;
+
+        let mut config = GatewayConfig::for_testing();
+        config.attachments.max_bytes = Some(1024);
+        config.attachments.allowed_mime_types = vec!["image/*".to_string(), "application/pdf".to_string()];
+        let gateway = Gateway::with_config(config.clone()).unwrap();
+        let mut events = gateway.event_bus().subscribe();
+
+        let photo = Attachment::new("cat.png", "image/png", 512);
+        let mut message = IncomingMessage::new("test", "user", "look")
+            .with_attachment(photo.clone())
+            .with_attachment(Attachment::new("huge.pdf", "application/pdf", 4096));
+        gateway.screen_attachments(&mut message).await.unwrap();
+        assert_eq!(message.attachments, vec![photo]);
+        let notes = message.metadata["attachment_notes"].as_array().unwrap();
+        assert_eq!(notes.len(), 1);
+        assert!(notes[0].as_str().unwrap().contains("huge.pdf"));
+        let GatewayEvent::Custom { name, .. } = events.recv().await.unwrap() else {
+            panic!("expected a custom event");
+        };
+        assert_eq!(name, "attachment_stripped");
+
+        config.attachments.on_violation = AttachmentViolation::Reject;
+        let gateway = Gateway::with_config(config).unwrap();
+        let pca = gateway
+            .process_message(
+                IncomingMessage::new("test", "user", "ping")
+                    .with_attachment(Attachment::new("setup.exe", "application/x-msdownload", 100)),
+            )
+            .await
+            .unwrap();
+        let Action::Denied { reason, .. } = &pca.action else {
+            panic!("expected a denial, got {:?}", pca.action);
+        };
+        assert!(reason.contains("setup.exe") && reason.contains("not allowed"), "{}", reason);
+    }
+
+    struct StrictAllowlistChannel {
+        allowlist: Vec<String>,
+        refused: Vec<String>,
+    }
+
+    #[async_trait::async_trait]
+    impl crate::channels::Channel for StrictAllowlistChannel {
+        fn name(&self) -> &str {
+            "test"
+        }
+
+        async fn receive(&self) -> Result<IncomingMessage, crate::error::ChannelError> {
+            Err(crate::error::ChannelError::ChannelClosed)
+        }
+
+        async fn send(&self, _message: OutgoingMessage) -> Result<ProofCarryingAction, crate::error::ChannelError> {
+            Ok(ProofCarryingAction::pending())
+        }
+
+        fn evaluate_permission(&self, _action: &Action, sender: &str) -> Confidence {
+            // Raw comparison, as a platform adapter would do
+            if self.allowlist.iter().any(|user| user == sender) {
+                Confidence::full()
+            } else if self.refused.iter().any(|user| user == sender) {
+                Confidence::none()
+            } else {
+                Confidence::new(0.1)
+            }
+        }
+
+        fn allowlist(&self) -> &[String] {
+            &self.allowlist
+        }
+
+        fn supports(&self, _feature: crate::channels::ChannelFeature) -> bool {
+            false
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sender_ids_normalized_for_sessions_and_allowlists() {
+        let mut config = GatewayConfig::for_testing();
+        config.sender_ids.lowercase = true;
+        config.sender_ids.prefixes.insert("test".to_string(), "t:".to_string());
+        config.allowed_users.insert("test".to_string(), vec!["Alice".to_string(), "intruder".to_string()]);
+        let mut gateway = Gateway::with_config(config).unwrap();
+        gateway.register_channel(Arc::new(StrictAllowlistChannel {
+            allowlist: Vec::new(),
+            refused: vec!["intruder".to_string()],
+        }));
+        assert_eq!(gateway.user_ref("test", "ALICE"), UserRef::new("test", "t:alice"));
+
+        for raw in ["ALICE", "alice "] {
+            let pca = gateway.process_message(IncomingMessage::new("test", raw, "ping")).await.unwrap();
+            let Action::SendMessage(reply) = &pca.action else {
+                panic!("{} was not allowed: {:?}", raw, pca.action);
+            };
+            // Replies still go to the id the channel reported
+            assert_eq!(reply.recipient_id, raw);
+        }
+        let sessions = gateway.list_sessions().await;
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].user_id, "t:alice");
+
+        let pca = gateway.process_message(IncomingMessage::new("test", "Mallory", "ping")).await.unwrap();
+        assert!(matches!(pca.action, Action::Denied { .. }), "{:?}", pca.action);
+
+        // Trusted users are still refused when the channel refuses them
+        let pca = gateway.process_message(IncomingMessage::new("test", "intruder", "ping")).await.unwrap();
+        assert!(matches!(pca.action, Action::Denied { .. }), "{:?}", pca.action);
+    }
+
+    #[tokio::test]
+    async fn test_persisted_sessions_survive_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = GatewayConfig::for_testing();
+        config.skills_path = dir.path().join("skills");
+        config.session.persist = true;
+        assert_eq!(config.session_store_path(), dir.path().join("sessions.json"));
+
+        let before = {
+            let mut gateway = Gateway::with_config(config.clone()).unwrap();
+            gateway.register_channel(Arc::new(crate::channels::TestChannel::new("test")));
+            let mut pca = gateway.process_message(IncomingMessage::new("test", "alice", "/help")).await.unwrap();
+            gateway.execute_action(&mut pca).await.unwrap();
+            gateway.list_sessions().await.remove(0)
+        };
+        assert!(before.history_length > 0);
+
+        let gateway = Gateway::with_config(config).unwrap();
+        let sessions = gateway.list_sessions().await;
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].id, before.id);
+        assert_eq!(sessions[0].history_length, before.history_length);
+        assert_eq!(sessions[0].trust_score, before.trust_score);
+    }
+
+    #[tokio::test]
+    async fn test_reminder_scheduled_only_when_executed() {
+        let mut config = GatewayConfig::for_testing();
+        config.max_reminders_per_user = 2;
+        let mut gateway = Gateway::with_config(config).unwrap();
+        gateway.register_channel(Arc::new(crate::channels::TestChannel::new("test")));
+        let remind = |text: &str| IncomingMessage::new("test", "user", text);
+
+        // Awaiting approval: the reminder is not stored
+        *gateway.router.write().await = router_to_lane(REMINDER_SKILL_REF, Some("ask_approval"));
+        gateway.process_message(remind("/remind 10m approve me")).await.unwrap();
+        assert!(gateway.pending_reminders().await.is_empty());
+
+        // Denied by the permission gate: not stored either
+        gateway.config.min_permission_confidence = 1.1;
+        *gateway.router.write().await = router_to_lane(REMINDER_SKILL_REF, Some("execute"));
+        let pca = gateway.process_message(remind("/remind 10m denied")).await.unwrap();
+        assert!(matches!(pca.action, Action::Denied { .. }));
+        assert!(gateway.pending_reminders().await.is_empty());
+
+        // Executed: stored, up to the per-user cap
+        gateway.config.min_permission_confidence = 0.0;
+        gateway.process_message(remind("/remind 10m one")).await.unwrap();
+        gateway.process_message(remind("/remind 10m two")).await.unwrap();
+        let pca = gateway.process_message(remind("/remind 10m three")).await.unwrap();
+        assert!(format!("{:?}", pca.action).contains("already have 2 pending reminders"));
+        assert_eq!(gateway.pending_reminders().await.len(), 2);
+        let other = IncomingMessage::new("test", "someone_else", "/remind 10m theirs");
+        gateway.process_message(other).await.unwrap();
+        assert_eq!(gateway.pending_reminders().await.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_reminder_fires_once_when_due() {
+        use chrono::{TimeZone, Utc};
+
+        let clock = MockClock::new(Utc.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap());
+        let channel = Arc::new(crate::channels::TestChannel::new("test"));
+        let mut gateway = Gateway::with_config(GatewayConfig::for_testing())
+            .unwrap()
+            .with_clock(Arc::new(clock.clone()));
+        gateway.register_channel(channel.clone());
+
+        let usage = gateway.process_message(IncomingMessage::new("test", "user", "/remind soon")).await.unwrap();
+        assert!(format!("{:?}", usage.action).contains("Usage: /remind"));
+        assert!(gateway.pending_reminders().await.is_empty());
+
+        let mut pca = gateway
+            .process_message(IncomingMessage::new("test", "user", "/remind 10m stretch"))
+            .await
+            .unwrap();
+        assert!(format!("{:?}", pca.action).contains("2026-03-10 12:10 UTC"));
+        gateway.execute_action(&mut pca).await.unwrap();
+        assert_eq!(gateway.pending_reminders().await.len(), 1);
+
+        clock.advance(chrono::Duration::minutes(9));
+        assert!(gateway.fire_due_reminders().await.is_empty());
+
+        clock.advance(chrono::Duration::minutes(1));
+        let fired = gateway.fire_due_reminders().await;
+        assert_eq!(fired.len(), 1);
+        let fired = fired[0].as_ref().unwrap();
+        assert!(fired.signature.iter().any(|b| *b != 0));
+        assert!(gateway.pending_reminders().await.is_empty());
+
+        let sent = channel.sent();
+        assert_eq!(sent.last().unwrap().content, "Reminder: stretch");
+        assert_eq!(sent.last().unwrap().recipient_id, "user");
+
+        clock.advance(chrono::Duration::minutes(10));
+        assert!(gateway.fire_due_reminders().await.is_empty());
+        assert_eq!(channel.sent().len(), sent.len());
+    }
+
+    #[tokio::test]
+    async fn test_export_and_delete_user_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let audit_path = dir.path().join("audit.jsonl");
+        let mut config = GatewayConfig::for_testing();
+        config.audit_log_path = Some(audit_path.clone());
+        let mut gateway = Gateway::with_config(config).unwrap();
+        gateway.register_channel(Arc::new(crate::channels::TestChannel::new("test")));
+
+        for user in ["alice", "bob"] {
+            let mut pca = gateway.process_message(IncomingMessage::new("test", user, "ping")).await.unwrap();
+            gateway.execute_action(&mut pca).await.unwrap();
+        }
+        gateway
+            .process_message(IncomingMessage::new("test", "alice", "/remind 1h stretch"))
+            .await
+            .unwrap();
+
+        // Export and erasure wait for records still being written
+        let export = gateway.export_user_data("test", "alice").await;
+        assert_eq!(export.user, UserRef::new("test", "alice"));
+        assert_eq!(export.sessions.len(), 1);
+        assert!(!export.sessions[0].history.is_empty());
+        assert!(!export.trust_history.is_empty());
+        assert_eq!(export.actions.len(), 1);
+        assert_eq!(export.reminders.len(), 1);
+        assert!(serde_json::to_string(&export).is_ok());
+
+        gateway.delete_user_data("test", "alice").await.unwrap();
+        assert!(gateway.export_user_data("test", "alice").await.is_empty());
+        assert!(gateway.pending_reminders().await.is_empty());
+        assert_eq!(std::fs::read_to_string(&audit_path).unwrap().lines().count(), 1);
+
+        // Other users are untouched
+        let bob = gateway.export_user_data("test", "bob").await;
+        assert_eq!(bob.sessions.len(), 1);
+        assert_eq!(bob.actions.len(), 1);
+
+        // A sink that cannot erase fails the request instead of keeping data silently
+        let (queue, _receiver) = MessageQueueAuditSink::new("proofs", 8);
+        let gateway = gateway.with_audit_sink(Arc::new(queue));
+        assert!(gateway.delete_user_data("test", "bob").await.is_err());
+        assert_eq!(std::fs::read_to_string(&audit_path).unwrap().lines().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_disconnected_channel_stops_being_polled() {
+        use crate::channels::ChannelState;
+
+        let channel = Arc::new(
+            crate::channels::TestChannel::new("test").with_incoming(IncomingMessage::new("test", "user", "ping")),
+        );
+        let mut gateway = Gateway::with_config(GatewayConfig::for_testing()).unwrap();
+        gateway.register_channel(channel.clone());
+        *gateway.running.write().await = true;
+        assert_eq!(gateway.channel_status("test").unwrap().state, ChannelState::Connected);
+
+        assert!(gateway.disconnect_channel("test", "maintenance").await);
+        assert!(gateway.get_channel("test").is_none());
+        let status = gateway.channel_status("test").unwrap();
+        assert_eq!(status.state, ChannelState::Disconnected);
+        assert_eq!(status.last_disconnect_reason.as_deref(), Some("maintenance"));
+
+        // A listener for a disconnected channel exits without polling
+        gateway.spawn_listener("test".to_string(), channel.clone()).await.unwrap();
+        assert!(channel.sent().is_empty());
+
+        // Connecting again resumes polling
+        assert!(gateway.connect_channel("test").await.unwrap().is_empty());
+        assert!(gateway.connect_channel("test").await.is_err());
+        assert!(matches!(gateway.connect_channel("missing").await, Err(GatewayError::ChannelNotFound(_))));
+        for _ in 0..50 {
+            if !channel.sent().is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(channel.sent()[0].content.contains("ping"));
+        let status = gateway.channel_status("test").unwrap();
+        assert_eq!(status.state, ChannelState::Connected);
+        assert_eq!(status.reconnects, 1);
+        *gateway.running.write().await = false;
+    }
+
+    /// Channel whose `receive` waits for messages pushed by the test.
+    struct QueueChannel {
+        incoming: tokio::sync::Mutex<tokio::sync::mpsc::UnboundedReceiver<IncomingMessage>>,
+        sent: std::sync::Mutex<Vec<OutgoingMessage>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Channel for QueueChannel {
+        fn name(&self) -> &str {
+            "test"
+        }
+
+        async fn receive(&self) -> Result<IncomingMessage, crate::error::ChannelError> {
+            self.incoming.lock().await.recv().await.ok_or(crate::error::ChannelError::ChannelClosed)
+        }
+
+        async fn send(&self, message: OutgoingMessage) -> Result<ProofCarryingAction, crate::error::ChannelError> {
+            self.sent.lock().unwrap().push(message);
+            Ok(ProofCarryingAction::pending())
+        }
+
+        fn evaluate_permission(&self, _action: &Action, _sender: &str) -> Confidence {
+            Confidence::neutral()
+        }
+
+        fn allowlist(&self) -> &[String] {
+            &[]
+        }
+
+        fn supports(&self, _feature: ChannelFeature) -> bool {
+            false
+        }
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_stops_listener_waiting_in_receive() {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let channel = Arc::new(QueueChannel {
+            incoming: tokio::sync::Mutex::new(rx),
+            sent: std::sync::Mutex::new(Vec::new()),
+        });
+        let mut gateway = Gateway::with_config(GatewayConfig::for_testing()).unwrap();
+        gateway.register_channel(channel.clone());
+
+        let listener = gateway.spawn_listener("test".to_string(), channel.clone());
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        assert!(gateway.disconnect_channel("test", "maintenance").await);
+        tokio::time::timeout(std::time::Duration::from_secs(1), listener).await.unwrap().unwrap();
+
+        // Only the listener of the new connection receives, and nothing is dropped
+        *gateway.running.write().await = true;
+        gateway.connect_channel("test").await.unwrap();
+        tx.send(IncomingMessage::new("test", "user", "ping")).unwrap();
+        for _ in 0..100 {
+            if !channel.sent.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        let sent = channel.sent.lock().unwrap().clone();
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].content.contains("ping"), "{}", sent[0].content);
+        assert!(gateway.disconnect_channel("test", "done").await);
+        *gateway.running.write().await = false;
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_reports_malformed_core_graph() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("router.0"), "Graph { nodes: [ not a graph").unwrap();
+
+        let mut config = GatewayConfig::for_testing();
+        config.router_graph_path = dir.path().join("router.0");
+        let gateway = Gateway::with_config(config).unwrap();
+
+        let report = gateway.warm_up().await.unwrap();
+        assert!(!report.is_clean());
+        assert_eq!(report.graphs.len(), warmup::CORE_GRAPHS.len());
+        let router = report.graph("router").unwrap();
+        assert_eq!(router.path, dir.path().join("router.0"));
+        assert!(matches!(&router.source, GraphSource::BuiltIn { reason } if reason.contains("parse")));
+        assert!(report.fallbacks().iter().any(|status| status.name == "router"));
+
+        // The built-in router still handles messages
+        let pca = gateway.process_message(IncomingMessage::new("test", "user", "/help")).await.unwrap();
+        assert!(matches!(pca.action, Action::SendMessage(_)));
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_keeps_configured_router() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut graph = Router::with_defaults().graph().clone();
+        std::fs::write(dir.path().join("router.0"), format!("Graph {}", serde_json::to_string(&graph).unwrap())).unwrap();
+        graph.name = "custom_router".to_string();
+        let custom = dir.path().join("custom.0");
+        std::fs::write(&custom, format!("Graph {}", serde_json::to_string(&graph).unwrap())).unwrap();
+
+        let mut config = GatewayConfig::for_testing();
+        config.router_graph_path = dir.path().join("router.0");
+        let gateway = Gateway::with_config(config.clone()).unwrap();
+        gateway.load_router_graph(custom.to_str().unwrap()).await.unwrap();
+        let report = gateway.warm_up().await.unwrap();
+        assert_eq!(gateway.router.read().await.graph().name, "custom_router");
+        assert_eq!(report.graph("session").unwrap().path, std::path::Path::new(session::SESSION_GRAPH_PATH));
+        assert_eq!(report.graph("proof").unwrap().path, std::path::Path::new(proof::PROOF_GRAPH_PATH));
+
+        let fresh = Gateway::with_config(config).unwrap();
+        fresh.warm_up().await.unwrap();
+        assert_eq!(fresh.router.read().await.graph().name, Router::with_defaults().graph().name);
+    }
+
+    #[tokio::test]
+    async fn test_default_response_replaces_echo() {
+        async fn reply(gateway: Gateway) -> String {
+            let message = IncomingMessage::new("test", "user", "what is the meaning of life");
+            match gateway.process_message(message).await.unwrap().action {
+                Action::SendMessage(msg) => msg.content,
+                other => panic!("Expected SendMessage, got {:?}", other),
+            }
+        }
+
+        let echo = reply(Gateway::with_config(GatewayConfig::for_testing()).unwrap()).await;
+        assert!(echo.starts_with("Received: what is the meaning of life"));
+
+        let mut config = GatewayConfig::for_testing();
+        config.default_response = Some("I'm not sure how to help with that.".to_string());
+        let plain = reply(Gateway::with_config(config.clone()).unwrap()).await;
+        assert!(plain.starts_with("I'm not sure how to help with that."));
+        assert!(!plain.contains("Received:"));
+
+        config.default_response_suggestions = true;
+        let suggested = reply(Gateway::with_config(config).unwrap()).await;
+        assert!(suggested.starts_with("I'm not sure how to help with that.\nAvailable commands: "));
+        assert!(suggested.contains("/help"));
     }
 }
@@ -0,0 +1,160 @@
+//! Per-user data export and deletion.
+//!
+//! Regulated deployments must be able to hand a user everything stored
+//! about them, or erase it. A [`UserScope`] identifies that data across the
+//! gateway's sessions, queues, event history and audit sinks.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::types::{Action, ContentHash, OutgoingMessage, ProofCarryingAction, UserRef};
+use super::dead_letter::{DeadLetter, FailedDelivery};
+use super::events::GatewayEvent;
+use super::identity::{IdentityNormalizer, SenderNormalizer};
+use super::scheduler::Reminder;
+use super::session::Session;
+
+/// The data belonging to one user.
+#[derive(Clone)]
+pub struct UserScope {
+    /// Canonical identity of the user
+    pub user: UserRef,
+
+    /// Sessions the user holds
+    pub session_ids: HashSet<ContentHash>,
+
+    normalizer: Arc<dyn SenderNormalizer>,
+}
+
+impl UserScope {
+    /// Scope `user` and the given sessions, matching raw ids exactly.
+    pub fn new(user: UserRef, session_ids: impl IntoIterator<Item = ContentHash>) -> Self {
+        Self {
+            user,
+            session_ids: session_ids.into_iter().collect(),
+            normalizer: Arc::new(IdentityNormalizer),
+        }
+    }
+
+    /// Normalize raw sender and recipient ids with `normalizer` before matching.
+    pub fn with_normalizer(mut self, normalizer: Arc<dyn SenderNormalizer>) -> Self {
+        self.normalizer = normalizer;
+        self
+    }
+
+    /// Whether `raw_id` on `channel` is this user.
+    pub fn matches_user(&self, channel: &str, raw_id: &str) -> bool {
+        channel == self.user.channel && self.normalizer.normalize_sender(channel, raw_id) == self.user.id
+    }
+
+    /// Whether `message` is addressed to this user.
+    pub fn matches_outgoing(&self, message: &OutgoingMessage) -> bool {
+        self.matches_user(&message.channel_id, &message.recipient_id)
+    }
+
+    /// Whether `pca` belongs to one of the user's sessions or messages them.
+    ///
+    /// PCAs carry only a session hash, so actions without a recipient from
+    /// sessions that no longer exist cannot be attributed to the user.
+    pub fn matches_pca(&self, pca: &ProofCarryingAction) -> bool {
+        self.session_ids.contains(&pca.session_hash)
+            || match &pca.action {
+                Action::SendMessage(message) | Action::Denied { notice: message, .. } => {
+                    self.matches_outgoing(message)
+                }
+                _ => false,
+            }
+    }
+
+    /// Whether `event` names this user or one of their sessions.
+    pub fn matches_event(&self, event: &GatewayEvent) -> bool {
+        match event {
+            GatewayEvent::MessageReceived { channel_id, sender_id: user_id, .. }
+            | GatewayEvent::SessionCreated { channel_id, user_id, .. }
+            | GatewayEvent::TrustChanged { channel_id, user_id, .. } => self.matches_user(channel_id, user_id),
+            GatewayEvent::SessionUpdated { session_id, .. }
+            | GatewayEvent::SessionExpired { session_id } => self.session_ids.contains(session_id),
+            GatewayEvent::ActionExecuted { action: Some(pca), .. } => self.matches_pca(pca),
+            _ => false,
+        }
+    }
+}
+
+/// Everything the gateway holds about one user.
+#[derive(Debug, Clone, Serialize)]
+pub struct UserDataExport {
+    /// The user the export is for
+    pub user: UserRef,
+
+    /// The user's sessions, including their action history
+    pub sessions: Vec<Session>,
+
+    /// Trust scores from the event history, oldest first
+    pub trust_history: Vec<f32>,
+
+    /// Actions recorded by audit sinks
+    pub actions: Vec<ProofCarryingAction>,
+
+    /// The user's messages that failed processing
+    pub dead_letters: Vec<DeadLetter>,
+
+    /// Actions to the user that could not be delivered
+    pub failed_deliveries: Vec<FailedDelivery>,
+
+    /// Messages to the user held for quiet hours or a disconnected channel
+    pub pending_messages: Vec<OutgoingMessage>,
+
+    /// Reminders waiting to be sent to the user
+    pub reminders: Vec<Reminder>,
+}
+
+impl UserDataExport {
+    /// Whether nothing is stored about the user.
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+            && self.trust_history.is_empty()
+            && self.actions.is_empty()
+            && self.dead_letters.is_empty()
+            && self.failed_deliveries.is_empty()
+            && self.pending_messages.is_empty()
+            && self.reminders.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gateway::SenderIdConfig;
+
+    #[test]
+    fn test_scope_matches_normalized_ids() {
+        let session = ContentHash::from_string("session");
+        let normalizer = SenderIdConfig { lowercase: true, ..Default::default() };
+        let scope = UserScope::new(UserRef::new("test", "alice"), [session])
+            .with_normalizer(Arc::new(normalizer));
+
+        assert!(scope.matches_user("test", "Alice"));
+        assert!(!scope.matches_user("other", "alice"));
+        assert!(scope.matches_outgoing(&OutgoingMessage::new("test", "ALICE", "hi")));
+
+        let mut pca = ProofCarryingAction::pending();
+        assert!(!scope.matches_pca(&pca));
+        pca.session_hash = session;
+        assert!(scope.matches_pca(&pca));
+        let mut denied = ProofCarryingAction::pending();
+        denied.action = Action::Denied {
+            reason: "test".to_string(),
+            notice: OutgoingMessage::new("test", "Alice", "no"),
+        };
+        assert!(scope.matches_pca(&denied));
+
+        assert!(scope.matches_event(&GatewayEvent::SessionExpired { session_id: session }));
+        assert!(!scope.matches_event(&GatewayEvent::TrustChanged {
+            channel_id: "test".to_string(),
+            user_id: "bob".to_string(),
+            trust_score: 0.5,
+        }));
+    }
+}
@@ -3,19 +3,30 @@
 //! This module handles cryptographic signing and verification of actions,
 //! creating the core "proof-carrying" property that makes 0-openclaw trustworthy.
 
-use ed25519_dalek::{SigningKey, VerifyingKey, Signature, Signer, Verifier};
-use rand::rngs::OsRng;
+use ed25519_dalek::VerifyingKey;
+use std::collections::HashMap;
 use std::path::Path;
 
-use crate::types::{Action, Confidence, ContentHash, EffectReceipt, ProofCarryingAction};
+use crate::types::{Action, Confidence, ContentHash, ProofCarryingAction, RedactedPca, SigScheme, TraceNode};
 use crate::error::ProofError;
+use super::config::ConfidenceConfig;
+use super::signing::{verifier_for, Ed25519Signer, Ed25519Verifier, Signer, Verifier};
+
+/// File the proof generation graph is read from.
+pub const PROOF_GRAPH_PATH: &str = "graphs/core/proof.0";
 
 /// Execution trace from graph evaluation.
 #[derive(Debug, Clone, Default)]
 pub struct ExecutionTrace {
-    /// Hashes of evaluated nodes
+    /// Hashes of evaluated nodes; the projection that gets signed
     pub nodes: Vec<ContentHash>,
     
+    /// Evaluated nodes with op and input/output hashes, when known
+    pub entries: Vec<TraceNode>,
+    
+    /// Confidence reported by the graph that produced this trace
+    pub confidence: Option<f32>,
+    
     /// Whether this trace came from cache
     pub cached: bool,
     
@@ -32,9 +43,8 @@ impl ExecutionTrace {
     /// Create a cached trace (for repeated/cached operations).
     pub fn cached() -> Self {
         Self {
-            nodes: Vec::new(),
             cached: true,
-            execution_time_us: 0,
+            ..Self::default()
         }
     }
 
@@ -53,53 +63,117 @@ impl ExecutionTrace {
         self.nodes.is_empty()
     }
 
+    /// Set the confidence reported for this trace.
+    pub fn with_confidence(mut self, confidence: f32) -> Self {
+        self.confidence = Some(confidence);
+        self
+    }
+
+    /// Add an executed node, recording its hash projection.
+    pub fn add_entry(&mut self, entry: TraceNode) {
+        self.nodes.push(entry.hash());
+        self.entries.push(entry);
+    }
+
     /// Create from a graph execution result.
+    ///
+    /// Uses the executed nodes' inputs and outputs when the runtime recorded
+    /// them, otherwise falls back to hashing node ids.
     pub fn from_graph_execution(exec_result: &crate::runtime::ExecutionResult) -> Self {
+        if exec_result.nodes.is_empty() {
+            return Self {
+                nodes: exec_result.trace.iter()
+                    .map(|node_id| ContentHash::from_string(node_id))
+                    .collect(),
+                confidence: Some(exec_result.confidence as f32),
+                ..Self::default()
+            };
+        }
+
+        let mut trace = Self::new().with_confidence(exec_result.confidence as f32);
+        for entry in &exec_result.nodes {
+            trace.add_entry(entry.clone());
+        }
+        trace
+    }
+}
+
+/// Timestamp bounds checked by [`ProofGenerator::verify_with_policy`].
+#[derive(Debug, Clone, Copy)]
+pub struct VerifyPolicy {
+    /// Oldest PCA accepted, to limit replay of old signed actions
+    pub max_age: std::time::Duration,
+
+    /// How far ahead of the verifier's clock a PCA may be dated
+    pub max_future_skew: std::time::Duration,
+}
+
+impl Default for VerifyPolicy {
+    fn default() -> Self {
         Self {
-            nodes: exec_result.trace.iter()
-                .map(|node_id| ContentHash::from_string(node_id))
-                .collect(),
-            cached: false,
-            execution_time_us: 0,
+            max_age: std::time::Duration::from_secs(300),
+            max_future_skew: std::time::Duration::from_secs(30),
         }
     }
 }
 
 /// Generator for Proof-Carrying Actions.
 pub struct ProofGenerator {
-    /// Ed25519 signing key
-    signing_key: SigningKey,
+    /// Signer used for new PCAs
+    signer: Box<dyn Signer>,
     
-    /// Cached verifying key
-    verifying_key: VerifyingKey,
+    /// Verifiers keyed by the scheme they accept
+    verifiers: HashMap<SigScheme, Box<dyn Verifier>>,
     
     /// Graph interpreter for proof calculations
     interpreter: std::sync::Arc<crate::runtime::GraphInterpreter>,
     
     /// Proof generation graph
     proof_graph: Option<crate::runtime::Graph>,
+    
+    /// Maximum decision trace length stored in a PCA
+    max_trace_len: Option<usize>,
+    
+    /// How per-trace confidences are combined
+    confidence: ConfidenceConfig,
 }
 
 impl ProofGenerator {
-    /// Create a new ProofGenerator with a random keypair.
+    /// Create a new ProofGenerator with a random Ed25519 keypair.
     pub fn new_random() -> Self {
-        let signing_key = SigningKey::generate(&mut OsRng);
-        let verifying_key = signing_key.verifying_key();
+        Self::with_signer(Box::new(Ed25519Signer::generate()))
+    }
+
+    /// Create a ProofGenerator that signs with the given signer.
+    ///
+    /// The signer's own verifier is registered for its scheme.
+    pub fn with_signer(signer: Box<dyn Signer>) -> Self {
+        let mut verifiers = HashMap::new();
+        verifiers.insert(signer.scheme(), signer.verifier());
         let interpreter = std::sync::Arc::new(crate::runtime::GraphInterpreter::default());
         let proof_graph = Self::load_proof_graph();
         
         Self {
-            signing_key,
-            verifying_key,
+            signer,
+            verifiers,
             interpreter,
             proof_graph,
+            max_trace_len: None,
+            confidence: ConfidenceConfig::default(),
         }
     }
 
+    /// Register a verifier, replacing any existing one for its scheme.
+    ///
+    /// This keeps PCAs signed under other schemes verifiable.
+    pub fn with_verifier(mut self, verifier: Box<dyn Verifier>) -> Self {
+        self.verifiers.insert(verifier.scheme(), verifier);
+        self
+    }
+
     /// Load the proof generation graph.
     fn load_proof_graph() -> Option<crate::runtime::Graph> {
-        let graph_path = "graphs/core/proof.0";
-        if let Ok(content) = std::fs::read_to_string(graph_path) {
+        if let Ok(content) = std::fs::read_to_string(PROOF_GRAPH_PATH) {
             if let Ok(graph) = crate::runtime::parse_graph(&content) {
                 return Some(graph);
             }
@@ -120,44 +194,76 @@ impl ProofGenerator {
 
         let mut arr = [0u8; 32];
         arr.copy_from_slice(&key_bytes);
-        
-        let signing_key = SigningKey::from_bytes(&arr);
-        let verifying_key = signing_key.verifying_key();
-        let interpreter = std::sync::Arc::new(crate::runtime::GraphInterpreter::default());
-        let proof_graph = Self::load_proof_graph();
 
-        Ok(Self {
-            signing_key,
-            verifying_key,
-            interpreter,
-            proof_graph,
-        })
+        Ok(Self::with_signer(Box::new(Ed25519Signer::from_bytes(&arr))))
     }
 
     /// Save the keypair to a file.
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), ProofError> {
-        std::fs::write(path, self.signing_key.to_bytes())
+        let key_bytes = self.signer.export_key().ok_or_else(|| {
+            ProofError::KeyGenerationFailed("Signer does not support key export".to_string())
+        })?;
+        std::fs::write(path, key_bytes)
             .map_err(|e| ProofError::KeyGenerationFailed(e.to_string()))
     }
 
-    /// Get the public verifying key.
-    pub fn verifying_key(&self) -> &VerifyingKey {
-        &self.verifying_key
+    /// Get the signature scheme used for new PCAs.
+    pub fn scheme(&self) -> SigScheme {
+        self.signer.scheme()
     }
 
     /// Get the public key as bytes.
-    pub fn public_key_bytes(&self) -> [u8; 32] {
-        self.verifying_key.to_bytes()
+    pub fn public_key_bytes(&self) -> Vec<u8> {
+        self.signer.verifier().public_key_bytes()
+    }
+
+    /// Truncate decision traces longer than `max_len`, committing to the
+    /// full trace via its Merkle root.
+    pub fn with_max_trace_len(mut self, max_len: usize) -> Self {
+        self.max_trace_len = Some(max_len);
+        self
+    }
+
+    /// Set how per-trace confidences are combined.
+    pub fn with_confidence_config(mut self, config: ConfidenceConfig) -> Self {
+        self.confidence = config;
+        self
+    }
+
+    /// Compute structural confidence with `graph` instead of `graphs/core/proof.0`.
+    ///
+    /// The graph receives `trace_len`, `cached_count` and `timings_us`
+    /// inputs and must produce a `confidence` output.
+    pub fn with_proof_graph(mut self, graph: crate::runtime::Graph) -> Self {
+        self.proof_graph = Some(graph);
+        self
     }
 
     /// Generate a Proof-Carrying Action.
-    pub fn generate(
+    pub async fn generate(
         &self,
         action: Action,
         session_hash: ContentHash,
         input_hash: ContentHash,
         traces: Vec<ExecutionTrace>,
     ) -> Result<ProofCarryingAction, ProofError> {
+        let mut pca = self.generate_unsigned(action, session_hash, input_hash, traces).await;
+        pca.unsigned = false;
+        pca.signature = self.signer.sign(&Self::build_sign_message(&pca))?;
+        Ok(pca)
+    }
+
+    /// Build a Proof-Carrying Action without signing it.
+    ///
+    /// The result is flagged `unsigned` and fails verification. Only for
+    /// deployments that have disabled proofs.
+    pub async fn generate_unsigned(
+        &self,
+        action: Action,
+        session_hash: ContentHash,
+        input_hash: ContentHash,
+        traces: Vec<ExecutionTrace>,
+    ) -> ProofCarryingAction {
         let timestamp = chrono::Utc::now().timestamp_millis() as u64;
 
         // Combine all traces
@@ -167,172 +273,270 @@ impl ProofGenerator {
             .collect();
 
         // Calculate combined confidence
-        let confidence = self.calculate_confidence(&decision_trace, &traces);
-
-        // Build message to sign
-        let message = self.build_sign_message(
-            &action,
-            &session_hash,
-            &input_hash,
-            &decision_trace,
-            &[],
-            confidence,
-            timestamp,
-        );
+        let confidence = self.calculate_confidence(&decision_trace, &traces).await;
 
-        // Sign the message
-        let signature: Signature = self.signing_key.sign(&message);
+        let (decision_trace, truncated, trace_root) = match self.max_trace_len {
+            Some(max_len) if decision_trace.len() > max_len => {
+                let root = ContentHash::merkle_root(&decision_trace);
+                (Self::sample_trace(&decision_trace, max_len), true, Some(root))
+            }
+            _ => (decision_trace, false, None),
+        };
 
-        Ok(ProofCarryingAction {
+        ProofCarryingAction {
+            schema_version: crate::types::PCA_SCHEMA_VERSION,
             action,
+            action_nonce: ProofCarryingAction::new_action_nonce(),
             session_hash,
             input_hash,
             decision_trace,
+            truncated,
+            trace_root,
             effect_trace: Vec::new(),
             confidence,
-            signature: signature.to_bytes(),
+            sig_scheme: self.signer.scheme(),
+            signature: Vec::new(),
+            unsigned: true,
             timestamp,
-        })
+        }
+    }
+
+    /// Keep the head and tail of a trace, `max_len` entries in total.
+    fn sample_trace(trace: &[ContentHash], max_len: usize) -> Vec<ContentHash> {
+        let head = max_len.div_ceil(2);
+        let tail = max_len - head;
+        trace[..head]
+            .iter()
+            .chain(&trace[trace.len() - tail..])
+            .copied()
+            .collect()
     }
 
     /// Verify a Proof-Carrying Action.
+    ///
+    /// Dispatches on the scheme recorded in the PCA.
     pub fn verify(&self, pca: &ProofCarryingAction) -> Result<bool, ProofError> {
-        let message = self.build_sign_message(
-            &pca.action,
-            &pca.session_hash,
-            &pca.input_hash,
-            &pca.decision_trace,
-            &pca.effect_trace,
-            pca.confidence,
-            pca.timestamp,
-        );
-
-        let signature = Signature::from_bytes(&pca.signature);
-        
-        self.verifying_key
-            .verify(&message, &signature)
+        let verifier = self.verifiers.get(&pca.sig_scheme).ok_or_else(|| {
+            ProofError::VerificationFailed(format!(
+                "Unsupported signature scheme: {}",
+                pca.sig_scheme
+            ))
+        })?;
+
+        verifier.verify(&Self::build_sign_message(pca), &pca.signature).map(|_| true)
+    }
+
+    /// Verify a redacted PCA against the original PCA's signature.
+    pub fn verify_redacted(&self, redacted: &RedactedPca) -> Result<bool, ProofError> {
+        let verifier = self.verifiers.get(&redacted.sig_scheme).ok_or_else(|| {
+            ProofError::VerificationFailed(format!(
+                "Unsupported signature scheme: {}",
+                redacted.sig_scheme
+            ))
+        })?;
+
+        verifier
+            .verify(&Self::build_redacted_sign_message(redacted), &redacted.signature)
             .map(|_| true)
-            .map_err(|e| ProofError::VerificationFailed(e.to_string()))
     }
 
-    /// Verify a PCA with a specific public key.
+    /// Verify a PCA's signature and that its timestamp is within `policy`.
+    pub fn verify_with_policy(
+        &self,
+        pca: &ProofCarryingAction,
+        policy: VerifyPolicy,
+    ) -> Result<bool, ProofError> {
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        self.verify_with_policy_at(pca, policy, now)
+    }
+
+    /// [`Self::verify_with_policy`] against an explicit current time (Unix ms).
+    pub fn verify_with_policy_at(
+        &self,
+        pca: &ProofCarryingAction,
+        policy: VerifyPolicy,
+        now: u64,
+    ) -> Result<bool, ProofError> {
+        self.verify(pca)?;
+
+        let oldest = now.saturating_sub(policy.max_age.as_millis() as u64);
+        let newest = now.saturating_add(policy.max_future_skew.as_millis() as u64);
+        if pca.timestamp < oldest || pca.timestamp > newest {
+            return Err(ProofError::TimestampOutOfRange { timestamp: pca.timestamp, now });
+        }
+        Ok(true)
+    }
+
+    /// Verify an Ed25519-signed PCA with a specific public key.
     pub fn verify_with_key(
         pca: &ProofCarryingAction,
         public_key: &VerifyingKey,
     ) -> Result<bool, ProofError> {
-        let message = Self::build_sign_message_static(
-            &pca.action,
-            &pca.session_hash,
-            &pca.input_hash,
-            &pca.decision_trace,
-            &pca.effect_trace,
-            pca.confidence,
-            pca.timestamp,
-        );
-
-        let signature = Signature::from_bytes(&pca.signature);
-        
-        public_key
-            .verify(&message, &signature)
+        if pca.sig_scheme != SigScheme::Ed25519 {
+            return Err(ProofError::VerificationFailed(format!(
+                "Expected ed25519 signature, found {}",
+                pca.sig_scheme
+            )));
+        }
+
+        Ed25519Verifier::new(*public_key)
+            .verify(&Self::build_sign_message(pca), &pca.signature)
+            .map(|_| true)
+    }
+
+    /// Verify a PCA with raw public key bytes for the scheme it records.
+    ///
+    /// Used to check a PCA independently of the gateway that signed it.
+    pub fn verify_with_public_key(
+        pca: &ProofCarryingAction,
+        public_key: &[u8],
+    ) -> Result<bool, ProofError> {
+        if pca.unsigned {
+            return Err(ProofError::VerificationFailed("PCA was never signed".to_string()));
+        }
+        let expected_len = match pca.sig_scheme {
+            SigScheme::Ed25519 => ed25519_dalek::SIGNATURE_LENGTH,
+        };
+        if pca.signature.len() != expected_len {
+            return Err(ProofError::VerificationFailed(format!(
+                "Invalid {} signature length: expected {} bytes, found {}",
+                pca.sig_scheme,
+                expected_len,
+                pca.signature.len()
+            )));
+        }
+
+        verifier_for(pca.sig_scheme, public_key)?
+            .verify(&Self::build_sign_message(pca), &pca.signature)
             .map(|_| true)
-            .map_err(|e| ProofError::VerificationFailed(e.to_string()))
     }
 
     /// Re-sign a PCA after effect receipts are attached.
     pub fn resign(&self, pca: &mut ProofCarryingAction) -> Result<(), ProofError> {
-        let message = self.build_sign_message(
-            &pca.action,
-            &pca.session_hash,
-            &pca.input_hash,
-            &pca.decision_trace,
-            &pca.effect_trace,
-            pca.confidence,
-            pca.timestamp,
-        );
-        let signature: Signature = self.signing_key.sign(&message);
-        pca.signature = signature.to_bytes();
+        pca.sig_scheme = self.signer.scheme();
+        pca.signature = self.signer.sign(&Self::build_sign_message(pca))?;
         Ok(())
     }
 
-    /// Build the message to be signed.
-    fn build_sign_message(
-        &self,
-        action: &Action,
-        session_hash: &ContentHash,
-        input_hash: &ContentHash,
-        decision_trace: &[ContentHash],
-        effect_trace: &[EffectReceipt],
-        confidence: Confidence,
-        timestamp: u64,
-    ) -> Vec<u8> {
-        Self::build_sign_message_static(
-            action,
-            session_hash,
-            input_hash,
-            decision_trace,
-            effect_trace,
-            confidence,
-            timestamp,
-        )
-    }
-
-    /// Static version of build_sign_message for use without self.
-    fn build_sign_message_static(
-        action: &Action,
-        session_hash: &ContentHash,
-        input_hash: &ContentHash,
-        decision_trace: &[ContentHash],
-        effect_trace: &[EffectReceipt],
-        confidence: Confidence,
-        timestamp: u64,
-    ) -> Vec<u8> {
+    /// Build the message to be signed from all signed PCA fields.
+    ///
+    /// The action is committed to by hash, so the redacted form of a PCA
+    /// produces the same message.
+    fn build_sign_message(pca: &ProofCarryingAction) -> Vec<u8> {
+        Self::build_redacted_sign_message(&pca.redact())
+    }
+
+    /// Build the sign message from a PCA's redacted form.
+    fn build_redacted_sign_message(pca: &RedactedPca) -> Vec<u8> {
         let mut message = Vec::new();
 
-        // Serialize action
-        let action_bytes = serde_json::to_vec(action).unwrap_or_default();
-        message.extend_from_slice(&action_bytes);
+        // Add the signature scheme, so a signature cannot be replayed under another
+        message.extend_from_slice(pca.sig_scheme.as_str().as_bytes());
+        message.push(0);
+
+        // Add action type and action hash
+        message.extend_from_slice(pca.action_type.as_bytes());
+        message.push(0);
+        message.extend_from_slice(pca.action_hash.as_bytes());
         
         // Add session hash
-        message.extend_from_slice(session_hash.as_bytes());
+        message.extend_from_slice(pca.session_hash.as_bytes());
         
         // Add input hash
-        message.extend_from_slice(input_hash.as_bytes());
+        message.extend_from_slice(pca.input_hash.as_bytes());
 
-        // Add decision trace
-        for trace_hash in decision_trace {
+        // Add decision trace, length-prefixed so it cannot run into the root
+        message.extend_from_slice(&(pca.decision_trace.len() as u64).to_le_bytes());
+        for trace_hash in &pca.decision_trace {
             message.extend_from_slice(trace_hash.as_bytes());
         }
 
+        // Add the truncation flag and the full-trace root, if any
+        message.push(pca.truncated as u8);
+        match &pca.trace_root {
+            Some(root) => {
+                message.push(1);
+                message.extend_from_slice(root.as_bytes());
+            }
+            None => message.push(0),
+        }
+
         // Add effect trace receipts
-        let receipt_bytes = serde_json::to_vec(effect_trace).unwrap_or_default();
+        let receipt_bytes = serde_json::to_vec(&pca.effect_trace).unwrap_or_default();
+        message.extend_from_slice(&(receipt_bytes.len() as u64).to_le_bytes());
         message.extend_from_slice(&receipt_bytes);
 
         // Add confidence
-        message.extend_from_slice(&confidence.value().to_le_bytes());
+        message.extend_from_slice(&pca.confidence.value().to_le_bytes());
         
         // Add timestamp
-        message.extend_from_slice(&timestamp.to_le_bytes());
+        message.extend_from_slice(&pca.timestamp.to_le_bytes());
 
         message
     }
 
     /// Calculate confidence score from execution traces using the 0-lang graph.
-    fn calculate_confidence(
+    async fn calculate_confidence(
         &self,
         trace: &[ContentHash],
         traces: &[ExecutionTrace],
     ) -> Confidence {
-        // Try graph-based calculation if available
-        if let Some(_graph) = &self.proof_graph {
-            // For now, use fallback - graph-based confidence would require
-            // async execution which is complex for this sync context
-            // The graph is prepared for future async refactoring
+        // Prefer the proof graph, falling back to direct calculation,
+        // then cap by the traces' own confidences
+        let structural = match self.graph_confidence(trace, traces).await {
+            Some(confidence) => confidence,
+            None => Self::calculate_confidence_fallback(trace, traces),
+        };
+        let reported: Vec<(usize, f32)> = traces
+            .iter()
+            .enumerate()
+            .filter_map(|(i, t)| t.confidence.map(|c| (i, c)))
+            .collect();
+        match self.confidence.aggregate(&reported) {
+            Some(aggregated) => Confidence::new(structural.value().min(aggregated)),
+            None => structural,
         }
-        
-        // Fallback to direct calculation
-        Self::calculate_confidence_fallback(trace, traces)
     }
     
+    /// Run the proof graph, if any, and read its `confidence` output.
+    async fn graph_confidence(
+        &self,
+        trace: &[ContentHash],
+        traces: &[ExecutionTrace],
+    ) -> Option<Confidence> {
+        use crate::runtime::Value;
+
+        let graph = self.proof_graph.as_ref()?;
+        let cached = traces.iter().filter(|t| t.cached).count();
+        let inputs = HashMap::from([
+            ("trace_len".to_string(), Value::Int(trace.len() as i64)),
+            ("cached_count".to_string(), Value::Int(cached as i64)),
+            (
+                "timings_us".to_string(),
+                Value::Array(
+                    traces
+                        .iter()
+                        .map(|t| Value::Int(t.execution_time_us as i64))
+                        .collect(),
+                ),
+            ),
+        ]);
+
+        match self.interpreter.execute(graph, inputs).await {
+            Ok(result) => {
+                let value = result.outputs.get("confidence").and_then(Value::as_float);
+                if value.is_none() {
+                    tracing::warn!("Proof graph produced no numeric 'confidence' output");
+                }
+                value.map(|v| Confidence::new(v as f32))
+            }
+            Err(e) => {
+                tracing::warn!("Proof graph failed, using fallback confidence: {}", e);
+                None
+            }
+        }
+    }
+
     /// Fallback confidence calculation without graph.
     fn calculate_confidence_fallback(
         trace: &[ContentHash],
@@ -400,7 +604,7 @@ impl<'a> ProofBuilder<'a> {
     }
 
     /// Build the Proof-Carrying Action.
-    pub fn build(self) -> Result<ProofCarryingAction, ProofError> {
+    pub async fn build(self) -> Result<ProofCarryingAction, ProofError> {
         let action = self.action
             .ok_or_else(|| ProofError::SigningFailed("Missing action".to_string()))?;
         let session_hash = self.session_hash
@@ -408,13 +612,14 @@ impl<'a> ProofBuilder<'a> {
         let input_hash = self.input_hash
             .ok_or_else(|| ProofError::SigningFailed("Missing input hash".to_string()))?;
 
-        self.generator.generate(action, session_hash, input_hash, self.traces)
+        self.generator.generate(action, session_hash, input_hash, self.traces).await
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::gateway::config::ConfidenceAggregation;
     use crate::types::OutgoingMessage;
 
     #[test]
@@ -423,8 +628,8 @@ mod tests {
         assert_eq!(generator.public_key_bytes().len(), 32);
     }
 
-    #[test]
-    fn test_generate_and_verify() {
+    #[tokio::test]
+    async fn test_generate_and_verify() {
         let generator = ProofGenerator::new_random();
         
         let action = Action::SendMessage(OutgoingMessage::new("test", "user", "Hello"));
@@ -436,14 +641,150 @@ mod tests {
             session_hash,
             input_hash,
             vec![ExecutionTrace::new()],
-        ).unwrap();
+        ).await.unwrap();
 
         assert!(pca.is_signed());
         assert!(generator.verify(&pca).unwrap());
     }
 
-    #[test]
-    fn test_tampered_pca_fails_verification() {
+    #[tokio::test]
+    async fn test_scheme_dispatched_verification() {
+        let generator = ProofGenerator::new_random();
+        assert_eq!(generator.scheme(), SigScheme::Ed25519);
+
+        let pca = generator.generate(
+            Action::NoOp { reason: "test".to_string() },
+            ContentHash::from_string("session"),
+            ContentHash::from_string("input"),
+            vec![],
+        ).await.unwrap();
+        assert_eq!(pca.sig_scheme, SigScheme::Ed25519);
+
+        // A verifier-only generator dispatches on the recorded scheme.
+        let public_key = generator.public_key_bytes();
+        let verifier = crate::gateway::signing::verifier_for(pca.sig_scheme, &public_key).unwrap();
+        let checker = ProofGenerator::new_random().with_verifier(verifier);
+        assert!(checker.verify(&pca).unwrap());
+
+        // PCAs serialized before the scheme was recorded default to Ed25519.
+        let mut json = serde_json::to_value(&pca).unwrap();
+        json.as_object_mut().unwrap().remove("sig_scheme");
+        let legacy: ProofCarryingAction = serde_json::from_value(json).unwrap();
+        assert_eq!(legacy.sig_scheme, SigScheme::Ed25519);
+        assert!(checker.verify(&legacy).unwrap());
+
+        // The recorded scheme is part of the signed message
+        let message = ProofGenerator::build_sign_message(&pca);
+        assert!(message.starts_with(b"ed25519\0"));
+    }
+
+    #[tokio::test]
+    async fn test_verify_with_policy_checks_timestamp() {
+        let generator = ProofGenerator::new_random();
+        let policy = VerifyPolicy::default();
+        let mut pca = generator.generate(
+            Action::NoOp { reason: "test".to_string() },
+            ContentHash::from_string("session"),
+            ContentHash::from_string("input"),
+            vec![],
+        ).await.unwrap();
+        let now = pca.timestamp;
+
+        assert!(generator.verify_with_policy_at(&pca, policy, now).unwrap());
+
+        // Older than max_age: a replayed action
+        let expired_at = now + policy.max_age.as_millis() as u64 + 1;
+        assert!(matches!(
+            generator.verify_with_policy_at(&pca, policy, expired_at),
+            Err(ProofError::TimestampOutOfRange { .. })
+        ));
+
+        // Dated beyond the allowed skew
+        pca.timestamp = now + policy.max_future_skew.as_millis() as u64 + 1;
+        generator.resign(&mut pca).unwrap();
+        assert!(matches!(
+            generator.verify_with_policy_at(&pca, policy, now),
+            Err(ProofError::TimestampOutOfRange { .. })
+        ));
+
+        // The timestamp is signed, so it cannot be refreshed without the key
+        pca.timestamp = now;
+        assert!(generator.verify_with_policy_at(&pca, policy, now).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_long_trace_is_truncated_with_root() {
+        let generator = ProofGenerator::new_random().with_max_trace_len(10);
+        let full_trace: Vec<ContentHash> = (0..100)
+            .map(|i| ContentHash::from_string(&format!("node{}", i)))
+            .collect();
+        let trace = ExecutionTrace { nodes: full_trace.clone(), entries: Vec::new(), confidence: None, cached: false, execution_time_us: 0 };
+
+        let mut pca = generator.generate(
+            Action::NoOp { reason: "test".to_string() },
+            ContentHash::from_string("session"),
+            ContentHash::from_string("input"),
+            vec![trace],
+        ).await.unwrap();
+
+        assert!(pca.truncated);
+        assert_eq!(pca.decision_trace.len(), 10);
+        assert_eq!(pca.decision_trace[0], full_trace[0]);
+        assert_eq!(pca.decision_trace[9], full_trace[99]);
+        assert_eq!(pca.trace_root, Some(ContentHash::merkle_root(&full_trace)));
+        assert!(pca.matches_full_trace(&full_trace));
+        assert!(generator.verify(&pca).unwrap());
+
+        // The root is covered by the signature
+        pca.trace_root = Some(ContentHash::from_string("forged"));
+        assert!(generator.verify(&pca).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_untruncated_pca_cannot_be_relabelled_truncated() {
+        let generator = ProofGenerator::new_random();
+        let trace_nodes: Vec<ContentHash> = ["a", "b", "c"].iter().map(|s| ContentHash::from_string(s)).collect();
+        let trace = ExecutionTrace { nodes: trace_nodes.clone(), entries: Vec::new(), confidence: None, cached: false, execution_time_us: 0 };
+        let pca = generator.generate(
+            Action::NoOp { reason: "test".to_string() },
+            ContentHash::from_string("session"),
+            ContentHash::from_string("input"),
+            vec![trace],
+        ).await.unwrap();
+        assert!(!pca.truncated);
+
+        // Moving the last trace entry into the root slot changes the message
+        let mut forged = pca.clone();
+        forged.decision_trace.pop();
+        forged.truncated = true;
+        forged.trace_root = Some(trace_nodes[2]);
+        assert!(generator.verify(&forged).is_err());
+
+        let mut flagged = pca.clone();
+        flagged.truncated = true;
+        assert!(generator.verify(&flagged).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_short_trace_is_not_truncated() {
+        let generator = ProofGenerator::new_random().with_max_trace_len(10);
+        let mut trace = ExecutionTrace::new();
+        trace.add_node(ContentHash::from_string("node"));
+
+        let pca = generator.generate(
+            Action::NoOp { reason: "test".to_string() },
+            ContentHash::from_string("session"),
+            ContentHash::from_string("input"),
+            vec![trace],
+        ).await.unwrap();
+
+        assert!(!pca.truncated);
+        assert!(pca.trace_root.is_none());
+        assert_eq!(pca.decision_trace.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_tampered_pca_fails_verification() {
         let generator = ProofGenerator::new_random();
         
         let action = Action::SendMessage(OutgoingMessage::new("test", "user", "Hello"));
@@ -455,7 +796,7 @@ mod tests {
             session_hash,
             input_hash,
             vec![],
-        ).unwrap();
+        ).await.unwrap();
 
         // Tamper with the PCA
         pca.confidence = Confidence::new(0.1);
@@ -476,6 +817,48 @@ mod tests {
         assert!(!trace.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_trace_from_execution_records_nodes() {
+        use crate::runtime::{Graph, GraphInterpreter, GraphNode, NodeType, Value};
+
+        let node = |id: &str, node_type: NodeType, inputs: &[&str]| GraphNode {
+            id: id.to_string(),
+            node_type,
+            inputs: inputs.iter().map(|s| s.to_string()).collect(),
+            params: serde_json::json!({}),
+        };
+        let graph = Graph {
+            name: "trace".to_string(),
+            version: 1,
+            description: String::new(),
+            nodes: vec![
+                node("message", NodeType::External { uri: "input://message".to_string() }, &[]),
+                node("upper", NodeType::Operation { op: "ToUpper".to_string() }, &["message"]),
+            ],
+            outputs: vec!["upper".to_string()],
+            entry_point: "message".to_string(),
+            metadata: serde_json::json!({}),
+        };
+        let hash_of = |value: &Value| ContentHash::from_bytes(&value.canonical_bytes());
+
+        let inputs = HashMap::from([("message".to_string(), Value::String("hi".into()))]);
+        let result = GraphInterpreter::default().execute(&graph, inputs).await.unwrap();
+        let trace = ExecutionTrace::from_graph_execution(&result);
+
+        let ids: Vec<&str> = trace.entries.iter().map(|e| e.node_id.as_str()).collect();
+        assert_eq!(ids, result.trace);
+        let upper = &trace.entries[1];
+        assert_eq!(upper.op, "ToUpper");
+        assert_eq!(upper.output_hash, hash_of(&Value::String("HI".into())));
+        assert_eq!(trace.entries[0].output_hash, hash_of(&Value::String("hi".into())));
+        assert_eq!(trace.nodes, trace.entries.iter().map(TraceNode::hash).collect::<Vec<_>>());
+
+        // Different input, same path: the signed projection still differs
+        let inputs = HashMap::from([("message".to_string(), Value::String("ho".into()))]);
+        let other = GraphInterpreter::default().execute(&graph, inputs).await.unwrap();
+        assert_ne!(ExecutionTrace::from_graph_execution(&other).nodes, trace.nodes);
+    }
+
     #[test]
     fn test_cached_trace() {
         let trace = ExecutionTrace::cached();
@@ -483,8 +866,8 @@ mod tests {
         assert!(trace.is_empty());
     }
 
-    #[test]
-    fn test_proof_builder() {
+    #[tokio::test]
+    async fn test_proof_builder() {
         let generator = ProofGenerator::new_random();
         
         let pca = ProofBuilder::new(&generator)
@@ -493,27 +876,241 @@ mod tests {
             .input_hash(ContentHash::from_string("input"))
             .add_trace(ExecutionTrace::new())
             .build()
+            .await
             .unwrap();
 
         assert!(pca.is_signed());
     }
 
-    #[test]
-    fn test_confidence_calculation() {
+    #[tokio::test]
+    async fn test_confidence_calculation() {
         let generator = ProofGenerator::new_random();
         
         // Short trace should have high confidence
         let short_trace = vec![ContentHash::from_string("node1")];
-        let short_traces = vec![ExecutionTrace { nodes: short_trace.clone(), cached: false, execution_time_us: 0 }];
-        let short_conf = generator.calculate_confidence(&short_trace, &short_traces);
+        let short_traces = vec![ExecutionTrace { nodes: short_trace.clone(), entries: Vec::new(), confidence: None, cached: false, execution_time_us: 0 }];
+        let short_conf = generator.calculate_confidence(&short_trace, &short_traces).await;
         
         // Long trace should have lower confidence
         let long_trace: Vec<ContentHash> = (0..100)
             .map(|i| ContentHash::from_string(&format!("node{}", i)))
             .collect();
-        let long_traces = vec![ExecutionTrace { nodes: long_trace.clone(), cached: false, execution_time_us: 0 }];
-        let long_conf = generator.calculate_confidence(&long_trace, &long_traces);
+        let long_traces = vec![ExecutionTrace { nodes: long_trace.clone(), entries: Vec::new(), confidence: None, cached: false, execution_time_us: 0 }];
+        let long_conf = generator.calculate_confidence(&long_trace, &long_traces).await;
         
         assert!(short_conf.value() > long_conf.value());
     }
+
+    async fn aggregated_confidence(aggregation: ConfidenceAggregation, weights: Vec<f32>) -> f32 {
+        let generator = ProofGenerator::new_random()
+            .with_confidence_config(ConfidenceConfig { aggregation, weights });
+        let traces = vec![
+            ExecutionTrace::new().with_confidence(0.9),
+            ExecutionTrace::new().with_confidence(0.5),
+        ];
+        let pca = generator.generate(
+            Action::NoOp { reason: "test".to_string() },
+            ContentHash::from_string("session"),
+            ContentHash::from_string("input"),
+            traces,
+        ).await.unwrap();
+        pca.confidence.value()
+    }
+
+    #[tokio::test]
+    async fn test_confidence_aggregation_min_vs_product() {
+        let min = aggregated_confidence(ConfidenceAggregation::Min, Vec::new()).await;
+        let product = aggregated_confidence(ConfidenceAggregation::Product, Vec::new()).await;
+        let weighted = aggregated_confidence(ConfidenceAggregation::Weighted, vec![1.0, 3.0]).await;
+
+        assert!((min - 0.5).abs() < 1e-6);
+        assert!((product - 0.45).abs() < 1e-6);
+        assert!((weighted - 0.6).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_low_confidence_route_lowers_pca_under_every_policy() {
+        let mut route = ExecutionTrace::new().with_confidence(0.3);
+        route.add_node(ContentHash::from_string("route"));
+        let mut skill = ExecutionTrace::new().with_confidence(0.9);
+        skill.add_node(ContentHash::from_string("skill"));
+
+        let mut results = Vec::new();
+        for aggregation in [ConfidenceAggregation::Product, ConfidenceAggregation::Min, ConfidenceAggregation::Weighted] {
+            let generator = ProofGenerator::new_random()
+                .with_confidence_config(ConfidenceConfig { aggregation, weights: Vec::new() });
+            let pca = generator.generate(
+                Action::NoOp { reason: "test".to_string() },
+                ContentHash::from_string("session"),
+                ContentHash::from_string("input"),
+                vec![route.clone(), skill.clone()],
+            ).await.unwrap();
+            results.push(pca.confidence.value());
+        }
+
+        assert!((results[0] - 0.27).abs() < 1e-6);
+        assert!((results[1] - 0.3).abs() < 1e-6);
+        assert!((results[2] - 0.6).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_redacted_pca_verifies_without_content() {
+        let generator = ProofGenerator::new_random();
+        let action = Action::SendMessage(OutgoingMessage::new("test", "user", "my secret plans"));
+        let pca = generator.generate(
+            action,
+            ContentHash::from_string("session"),
+            ContentHash::from_string("input"),
+            vec![ExecutionTrace::new()],
+        ).await.unwrap();
+
+        let redacted = pca.redact();
+        assert_eq!(redacted.action_type, "SendMessage");
+        assert_eq!(redacted.action_hash, pca.action.salted_hash(&pca.action_nonce));
+        assert!(generator.verify_redacted(&redacted).unwrap());
+        let json = serde_json::to_string(&redacted).unwrap();
+        assert!(!json.contains("my secret plans"));
+        assert!(!json.contains(&hex::encode(&pca.action_nonce)));
+
+        // Guessing the content is not enough to reproduce the hash
+        assert_ne!(redacted.action_hash, pca.action.salted_hash(&[]));
+        let again = generator.generate(
+            pca.action.clone(),
+            ContentHash::from_string("session"),
+            ContentHash::from_string("input"),
+            vec![ExecutionTrace::new()],
+        ).await.unwrap();
+        assert_ne!(again.redact().action_hash, redacted.action_hash);
+
+        let mut forged = redacted.clone();
+        forged.action_hash = ContentHash::from_string("other");
+        assert!(generator.verify_redacted(&forged).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pca_file_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("action.json");
+        let generator = ProofGenerator::new_random();
+        let mut trace = ExecutionTrace::new().with_confidence(0.8);
+        trace.add_node(ContentHash::from_string("skill:test"));
+        let pca = generator.generate(
+            Action::NoOp { reason: "test".to_string() },
+            ContentHash::from_string("session"),
+            ContentHash::from_string("input"),
+            vec![trace],
+        ).await.unwrap();
+
+        pca.save(&path).unwrap();
+        let json = std::fs::read_to_string(&path).unwrap();
+        assert!(json.contains(&ContentHash::from_string("session").to_hex()));
+        assert!(json.contains(&format!("\"schema_version\": {}", crate::types::PCA_SCHEMA_VERSION)));
+
+        let loaded = ProofCarryingAction::load(&path).unwrap();
+        assert_eq!(loaded.session_hash, pca.session_hash);
+        assert_eq!(loaded.decision_trace, pca.decision_trace);
+        assert!(generator.verify(&loaded).unwrap());
+
+        // Byte-array hashes from the earlier encoding still load
+        let mut legacy = serde_json::to_value(&pca).unwrap();
+        legacy["session_hash"] = serde_json::json!(pca.session_hash.0);
+        let legacy = ProofCarryingAction::from_json(&legacy.to_string()).unwrap();
+        assert!(generator.verify(&legacy).unwrap());
+
+        // Files from before the signing format change are rejected
+        let mut unversioned = serde_json::to_value(&pca).unwrap();
+        unversioned.as_object_mut().unwrap().remove("schema_version");
+        let err = ProofCarryingAction::from_json(&unversioned.to_string()).unwrap_err();
+        assert!(err.to_string().contains("re-issued"), "{}", err);
+        let mut unsalted = serde_json::to_value(&pca).unwrap();
+        unsalted.as_object_mut().unwrap().remove("action_nonce");
+        unsalted["schema_version"] = 2.into();
+        let err = ProofCarryingAction::from_json(&unsalted.to_string()).unwrap_err();
+        assert!(err.to_string().contains("re-issued"), "{}", err);
+
+        // Newer schema versions are rejected
+        let mut future = serde_json::to_value(&pca).unwrap();
+        future["schema_version"] = (crate::types::PCA_SCHEMA_VERSION + 1).into();
+        assert!(matches!(
+            ProofCarryingAction::from_json(&future.to_string()),
+            Err(ProofError::InvalidFormat(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_edited_confidence_fails_verification() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("action.json");
+        let generator = ProofGenerator::new_random();
+        let mut trace = ExecutionTrace::new().with_confidence(0.4);
+        trace.add_node(ContentHash::from_string("skill:test"));
+        let pca = generator.generate(
+            Action::NoOp { reason: "test".to_string() },
+            ContentHash::from_string("session"),
+            ContentHash::from_string("input"),
+            vec![trace],
+        ).await.unwrap();
+        pca.save(&path).unwrap();
+
+        let mut json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        json["confidence"] = serde_json::json!(0.99);
+        std::fs::write(&path, json.to_string()).unwrap();
+
+        let edited = ProofCarryingAction::load(&path).unwrap();
+        assert!((edited.confidence.value() - 0.99).abs() < 1e-6);
+        assert!(!generator.verify(&edited).unwrap_or(false));
+    }
+
+    #[tokio::test]
+    async fn test_proof_graph_sets_confidence() {
+        use crate::runtime::types::{Graph, GraphNode, NodeType};
+        use crate::runtime::Value;
+
+        let node = |id: &str, node_type: NodeType| GraphNode {
+            id: id.to_string(),
+            node_type,
+            inputs: Vec::new(),
+            params: serde_json::json!({}),
+        };
+        let graph = Graph {
+            name: "fixed_confidence".to_string(),
+            version: 1,
+            description: String::new(),
+            nodes: vec![
+                node("trace_len", NodeType::External { uri: "input://trace_len".to_string() }),
+                node("confidence", NodeType::Constant { value: Value::Float(0.42) }),
+            ],
+            outputs: vec!["confidence".to_string()],
+            entry_point: "trace_len".to_string(),
+            metadata: serde_json::json!({}),
+        };
+        let generator = ProofGenerator::new_random().with_proof_graph(graph);
+
+        let pca = generator.generate(
+            Action::NoOp { reason: "test".to_string() },
+            ContentHash::from_string("session"),
+            ContentHash::from_string("input"),
+            vec![ExecutionTrace::new()],
+        ).await.unwrap();
+        assert!((pca.confidence.value() - 0.42).abs() < 1e-6);
+        assert!(generator.verify(&pca).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_shipped_proof_graph_matches_fallback() {
+        let graph = ProofGenerator::load_proof_graph().expect("graphs/core/proof.0 should parse");
+        let generator = ProofGenerator::new_random().with_proof_graph(graph);
+
+        for (len, cached) in [(1, 0), (10, 2), (100, 0), (1000, 0)] {
+            let trace: Vec<ContentHash> = (0..len)
+                .map(|i| ContentHash::from_string(&format!("node{}", i)))
+                .collect();
+            let traces: Vec<ExecutionTrace> = (0..=cached)
+                .map(|i| ExecutionTrace { cached: i < cached, ..ExecutionTrace::new() })
+                .collect();
+            let from_graph = generator.graph_confidence(&trace, &traces).await.expect("graph path");
+            let fallback = ProofGenerator::calculate_confidence_fallback(&trace, &traces);
+            assert!((from_graph.value() - fallback.value()).abs() < 1e-6, "trace of {}", len);
+        }
+    }
 }
@@ -0,0 +1,189 @@
+//! Quiet hours for outbound messages.
+//!
+//! During quiet hours, non-urgent messages such as reminders and broadcasts
+//! are held until the window closes. Interactive replies are never held.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, FixedOffset, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::ConfigError;
+use crate::types::OutgoingMessage;
+
+/// A daily window during which non-urgent messages are held.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuietHours {
+    /// Local start of the window (e.g. `22:00:00`)
+    pub start: NaiveTime,
+
+    /// Local end of the window (e.g. `07:00:00`); may be before `start`
+    pub end: NaiveTime,
+
+    /// Default timezone as `UTC` or a fixed offset such as `+02:00`
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+
+    /// Timezone overrides keyed by channel id or `channel_id:user_id`
+    ///
+    /// A user entry takes precedence over its channel entry.
+    #[serde(default)]
+    pub timezones: HashMap<String, String>,
+}
+
+/// An outbound message held until quiet hours end.
+#[derive(Debug, Clone)]
+pub struct HeldMessage {
+    /// The message to deliver
+    pub message: OutgoingMessage,
+
+    /// When the message becomes deliverable
+    pub release_at: DateTime<Utc>,
+}
+
+fn default_timezone() -> String {
+    "UTC".to_string()
+}
+
+/// Parse a timezone given as `UTC` or a `±HH:MM` offset.
+pub fn parse_timezone(timezone: &str) -> Result<FixedOffset, ConfigError> {
+    let invalid = || ConfigError::InvalidValue {
+        key: "quiet_hours.timezone".to_string(),
+        reason: format!("Unsupported timezone '{}', expected UTC or ±HH:MM", timezone),
+    };
+
+    let tz = timezone.trim();
+    if tz.eq_ignore_ascii_case("utc") || tz == "Z" {
+        return FixedOffset::east_opt(0).ok_or_else(invalid);
+    }
+
+    let (sign, rest) = match tz.split_at(tz.len().min(1)) {
+        ("+", rest) => (1, rest),
+        ("-", rest) => (-1, rest),
+        _ => return Err(invalid()),
+    };
+    let (hours, minutes) = rest.split_once(':').ok_or_else(invalid)?;
+    let hours: i32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+    if hours > 23 || minutes > 59 {
+        return Err(invalid());
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)).ok_or_else(invalid)
+}
+
+impl QuietHours {
+    /// Create a window in the given default timezone.
+    pub fn new(start: NaiveTime, end: NaiveTime, timezone: &str) -> Self {
+        Self {
+            start,
+            end,
+            timezone: timezone.to_string(),
+            timezones: HashMap::new(),
+        }
+    }
+
+    /// Set the timezone for a channel, or for one user when `user_id` is given.
+    pub fn with_timezone(mut self, channel_id: &str, user_id: Option<&str>, timezone: &str) -> Self {
+        let key = match user_id {
+            Some(user) => format!("{}:{}", channel_id, user),
+            None => channel_id.to_string(),
+        };
+        self.timezones.insert(key, timezone.to_string());
+        self
+    }
+
+    /// Check that every configured timezone parses.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        parse_timezone(&self.timezone)?;
+        for timezone in self.timezones.values() {
+            parse_timezone(timezone)?;
+        }
+        Ok(())
+    }
+
+    /// Timezone offset that applies to a recipient.
+    fn offset_for(&self, channel_id: &str, user_id: &str) -> FixedOffset {
+        let timezone = self
+            .timezones
+            .get(&format!("{}:{}", channel_id, user_id))
+            .or_else(|| self.timezones.get(channel_id))
+            .unwrap_or(&self.timezone);
+        parse_timezone(timezone).unwrap_or_else(|_| FixedOffset::east_opt(0).expect("zero offset"))
+    }
+
+    fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+
+    /// If `message` falls inside quiet hours at `now`, the time the window ends.
+    pub fn held_until(&self, message: &OutgoingMessage, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let offset = self.offset_for(&message.channel_id, &message.recipient_id);
+        let local = now.with_timezone(&offset);
+        if !self.contains(local.time()) {
+            return None;
+        }
+
+        let mut end_date = local.date_naive();
+        if local.time() >= self.end {
+            end_date += Duration::days(1);
+        }
+        let end = offset
+            .from_local_datetime(&end_date.and_time(self.end))
+            .single()?;
+        Some(end.with_timezone(&Utc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 10, hour, minute, 0).unwrap()
+    }
+
+    fn window() -> QuietHours {
+        QuietHours::new(
+            NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+            NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+            "UTC",
+        )
+    }
+
+    #[test]
+    fn test_window_wrapping_midnight() {
+        let message = OutgoingMessage::new("test", "user", "reminder");
+        let quiet = window();
+
+        assert_eq!(quiet.held_until(&message, at(12, 0)), None);
+        assert_eq!(
+            quiet.held_until(&message, at(23, 30)),
+            Some(Utc.with_ymd_and_hms(2026, 3, 11, 7, 0, 0).unwrap())
+        );
+        assert_eq!(quiet.held_until(&message, at(3, 0)), Some(at(7, 0)));
+    }
+
+    #[test]
+    fn test_user_timezone_overrides_channel() {
+        let quiet = window()
+            .with_timezone("test", None, "+05:00")
+            .with_timezone("test", Some("traveller"), "-05:00");
+
+        // 20:00 UTC is 01:00 at +05:00 but 15:00 at -05:00
+        let local = OutgoingMessage::new("test", "user", "hi");
+        let traveller = OutgoingMessage::new("test", "traveller", "hi");
+        assert!(quiet.held_until(&local, at(20, 0)).is_some());
+        assert!(quiet.held_until(&traveller, at(20, 0)).is_none());
+    }
+
+    #[test]
+    fn test_parse_timezone() {
+        assert_eq!(parse_timezone("UTC").unwrap().local_minus_utc(), 0);
+        assert_eq!(parse_timezone("-03:30").unwrap().local_minus_utc(), -(3 * 3600 + 1800));
+        assert!(parse_timezone("Europe/Paris").is_err());
+    }
+}
@@ -4,14 +4,15 @@
 //! should handle each incoming message. All routing logic is defined in the
 //! graph file (graphs/core/router.0), not in Rust code.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
 use crate::types::{ActionLane, ContentHash, IncomingMessage};
 use crate::error::GatewayError;
 use crate::runtime::{GraphInterpreter, Graph, Value, ExecutionResult};
 use super::proof::ExecutionTrace;
+use super::ENRICHMENT_INPUT;
 
 /// Route information describing how to handle a message.
 #[derive(Debug, Clone)]
@@ -35,10 +36,90 @@ pub struct RouteResult {
     pub params: HashMap<String, String>,
 }
 
+/// Default capacity of the route cache.
+pub const DEFAULT_MAX_CACHE_ENTRIES: usize = 4096;
+
+/// Route cache with least-recently-used eviction.
+struct RouteCache {
+    /// Cached routes with the tick of their last access
+    entries: HashMap<ContentHash, (RouteResult, u64)>,
+
+    /// Access order, oldest tick first
+    order: BTreeMap<u64, ContentHash>,
+
+    /// Monotonic access counter
+    tick: u64,
+
+    /// Maximum number of entries kept
+    capacity: usize,
+}
+
+impl RouteCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+            capacity,
+        }
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    /// Look up a route and mark it as most recently used.
+    fn get(&mut self, key: &ContentHash) -> Option<RouteResult> {
+        let tick = self.next_tick();
+        let (result, last) = self.entries.get_mut(key)?;
+        self.order.remove(last);
+        *last = tick;
+        self.order.insert(tick, *key);
+        Some(result.clone())
+    }
+
+    fn insert(&mut self, key: ContentHash, result: RouteResult) {
+        if self.capacity == 0 {
+            return;
+        }
+        let tick = self.next_tick();
+        if let Some((_, last)) = self.entries.insert(key, (result, tick)) {
+            self.order.remove(&last);
+        }
+        self.order.insert(tick, key);
+        self.evict();
+    }
+
+    /// Drop least-recently-used entries until the cache fits its capacity.
+    fn evict(&mut self) {
+        while self.entries.len() > self.capacity {
+            let Some((_, oldest)) = self.order.pop_first() else { break };
+            self.entries.remove(&oldest);
+        }
+    }
+
+    fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict();
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
 /// Graph-based message router.
 /// 
 /// This router executes a 0-lang graph to make routing decisions.
 /// All routing logic is in the graph file, not in Rust code.
+///
+/// Routing takes `&self`; the route cache is shared between concurrent calls.
 pub struct Router {
     /// The routing graph
     graph: Graph,
@@ -49,8 +130,8 @@ pub struct Router {
     /// Default skill hash (fallback)
     default_skill: ContentHash,
     
-    /// Cached routes for fast lookup
-    route_cache: HashMap<ContentHash, RouteResult>,
+    /// Cached routes for fast lookup, bounded with LRU eviction
+    route_cache: RwLock<RouteCache>,
     
     /// Whether to use caching
     caching_enabled: bool,
@@ -71,7 +152,7 @@ impl Router {
             graph,
             interpreter: Arc::new(GraphInterpreter::default()),
             default_skill: ContentHash::from_string("skill:default"),
-            route_cache: HashMap::new(),
+            route_cache: RwLock::new(RouteCache::new(DEFAULT_MAX_CACHE_ENTRIES)),
             caching_enabled: true,
         })
     }
@@ -82,7 +163,7 @@ impl Router {
             graph,
             interpreter: Arc::new(GraphInterpreter::default()),
             default_skill: ContentHash::from_string("skill:default"),
-            route_cache: HashMap::new(),
+            route_cache: RwLock::new(RouteCache::new(DEFAULT_MAX_CACHE_ENTRIES)),
             caching_enabled: true,
         }
     }
@@ -242,28 +323,44 @@ impl Router {
     pub fn set_caching(&mut self, enabled: bool) {
         self.caching_enabled = enabled;
         if !enabled {
-            self.route_cache.clear();
+            self.clear_cache();
         }
     }
 
+    /// Set the maximum number of cached routes, evicting the least recently
+    /// used entries if the cache is already larger.
+    pub fn set_max_cache_entries(&mut self, max_entries: usize) {
+        self.route_cache.write().unwrap().set_capacity(max_entries);
+    }
+
+    /// Maximum number of cached routes.
+    pub fn max_cache_entries(&self) -> usize {
+        self.route_cache.read().unwrap().capacity
+    }
+
     /// Route a message to a skill by executing the routing graph.
     pub async fn route(
-        &mut self,
+        &self,
         message: &IncomingMessage,
     ) -> Result<(RouteResult, ExecutionTrace), GatewayError> {
         // Check cache first
-        if self.caching_enabled {
-            let cache_key = Self::cache_key(message);
-            if let Some(cached) = self.route_cache.get(&cache_key) {
-                return Ok((cached.clone(), ExecutionTrace::cached()));
+        let cache_key = if self.caching_enabled { Self::cache_key(message) } else { None };
+        if let Some(cache_key) = &cache_key {
+            let cached = self.route_cache.write().unwrap().get(cache_key);
+            if let Some(cached) = cached {
+                let trace = ExecutionTrace::cached().with_confidence(cached.confidence);
+                return Ok((cached.clone(), trace));
             }
         }
 
         // Build graph inputs
         let mut inputs = HashMap::new();
-        inputs.insert("message".to_string(), Value::String(message.content.clone()));
-        inputs.insert("sender".to_string(), Value::String(message.sender_id.clone()));
-        inputs.insert("channel".to_string(), Value::String(message.channel_id.clone()));
+        inputs.insert("message".to_string(), Value::String(message.content.as_str().into()));
+        inputs.insert("sender".to_string(), Value::String(message.sender_id.as_str().into()));
+        inputs.insert("channel".to_string(), Value::String(message.channel_id.as_str().into()));
+        if let Some(enrichment) = message.metadata.get(ENRICHMENT_INPUT) {
+            inputs.insert(ENRICHMENT_INPUT.to_string(), Value::from_json(enrichment));
+        }
 
         // Execute the routing graph
         let exec_result = self.interpreter.execute(&self.graph, inputs).await?;
@@ -271,13 +368,12 @@ impl Router {
         // Extract routing result from graph outputs
         let result = self.extract_route_result(&exec_result, message)?;
 
-        // Build execution trace
-        let trace = ExecutionTrace::from_graph_execution(&exec_result);
+        // Build execution trace, carrying the routing confidence
+        let trace = ExecutionTrace::from_graph_execution(&exec_result).with_confidence(result.confidence);
 
         // Cache the result
-        if self.caching_enabled {
-            let cache_key = Self::cache_key(message);
-            self.route_cache.insert(cache_key, result.clone());
+        if let Some(cache_key) = cache_key {
+            self.route_cache.write().unwrap().insert(cache_key, result.clone());
         }
 
         Ok((result, trace))
@@ -306,10 +402,14 @@ impl Router {
             let conf = info.get("confidence")
                 .and_then(|v| v.as_float())
                 .unwrap_or(0.5) as f32;
-            let name = info.get("matched_input")
+            let matched = info.get("matched_input")
                 .and_then(|v| v.as_string())
-                .unwrap_or("default")
-                .to_string();
+                .unwrap_or("default");
+            // Routes decided by a regex node are named after its pattern
+            let name = match self.graph.get_node(matched).map(|node| &node.node_type) {
+                Some(crate::runtime::types::NodeType::Regex { pattern, .. }) => format!("regex:{}", pattern),
+                _ => matched.to_string(),
+            };
             (conf, name)
         } else {
             (exec_result.confidence as f32, "graph_route".to_string())
@@ -349,11 +449,14 @@ impl Router {
         })
     }
 
+    /// Lane `skill_ref` always takes, whatever the route's confidence.
+    pub fn skill_lane(skill_ref: &str) -> Option<ActionLane> {
+        (skill_ref == "skill:trade").then_some(ActionLane::AskApproval)
+    }
+
     fn lane_from_result(exec_result: &ExecutionResult, confidence: f32) -> ActionLane {
-        if let Some(skill) = exec_result.outputs.get("skill_target").and_then(|v| v.as_string()) {
-            if skill == "skill:trade" {
-                return ActionLane::AskApproval;
-            }
+        if let Some(lane) = exec_result.outputs.get("skill_target").and_then(|v| v.as_string()).and_then(Self::skill_lane) {
+            return lane;
         }
 
         if let Some(lane) = exec_result.outputs.get("route_lane").and_then(|v| v.as_string()) {
@@ -381,18 +484,26 @@ impl Router {
     }
 
     /// Generate a cache key for a message.
-    fn cache_key(message: &IncomingMessage) -> ContentHash {
+    ///
+    /// Commands are keyed by the whole command line, since the cached result
+    /// carries the extracted params. Free text is routed by intent and never
+    /// repeats exactly, so it is not cached.
+    fn cache_key(message: &IncomingMessage) -> Option<ContentHash> {
         if message.content.starts_with('/') {
-            let command = message.content.split_whitespace().next().unwrap_or("");
-            ContentHash::from_string(command)
+            let command = message.content.split_whitespace().collect::<Vec<_>>().join(" ");
+            // Enrichment can change the route, so it is part of the key
+            Some(match message.metadata.get(ENRICHMENT_INPUT) {
+                Some(enrichment) => ContentHash::from_string(&format!("{}\n{}", command, enrichment)),
+                None => ContentHash::from_string(&command),
+            })
         } else {
-            ContentHash::from_bytes(format!("nocache:{}", message.id.to_hex()).as_bytes())
+            None
         }
     }
 
     /// Clear the route cache.
-    pub fn clear_cache(&mut self) {
-        self.route_cache.clear();
+    pub fn clear_cache(&self) {
+        self.route_cache.write().unwrap().clear();
     }
 
     /// Get the routing graph.
@@ -400,9 +511,30 @@ impl Router {
         &self.graph
     }
 
+    /// Commands (keys starting with `/`) known to the routing graph's lookup tables.
+    pub fn commands(&self) -> Vec<String> {
+        use crate::runtime::types::NodeType;
+
+        let mut commands: Vec<String> = self
+            .graph
+            .nodes
+            .iter()
+            .filter_map(|node| match &node.node_type {
+                NodeType::Lookup { table, .. } => Some(table.keys()),
+                _ => None,
+            })
+            .flatten()
+            .filter(|key| key.starts_with('/'))
+            .cloned()
+            .collect();
+        commands.sort();
+        commands.dedup();
+        commands
+    }
+
     /// Get the cache size.
     pub fn cache_size(&self) -> usize {
-        self.route_cache.len()
+        self.route_cache.read().unwrap().len()
     }
 }
 
@@ -422,7 +554,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_router_command_routing() {
-        let mut router = Router::with_defaults();
+        let router = Router::with_defaults();
         
         let (result, trace) = router.route(&test_message("/help")).await.unwrap();
         
@@ -432,7 +564,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_router_conversation_routing() {
-        let mut router = Router::with_defaults();
+        let router = Router::with_defaults();
         
         let (result, _trace) = router.route(&test_message("hello there")).await.unwrap();
         
@@ -444,7 +576,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_router_caching() {
-        let mut router = Router::with_defaults();
+        let router = Router::with_defaults();
         
         // First call
         let (_, trace1) = router.route(&test_message("/help")).await.unwrap();
@@ -462,7 +594,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_router_param_extraction() {
-        let mut router = Router::with_defaults();
+        let router = Router::with_defaults();
         
         let (result, _) = router.route(&test_message("/search rust async")).await.unwrap();
         
@@ -470,6 +602,16 @@ mod tests {
         assert!(result.params.contains_key("args") || result.params.contains_key("arg0"));
     }
 
+    #[test]
+    fn test_default_router_graph_schema() {
+        let router = Router::with_defaults();
+        let inputs = router.graph().input_names();
+        for name in ["message", "sender", "channel"] {
+            assert!(inputs.iter().any(|input| input == name), "missing {} in {:?}", name, inputs);
+        }
+        assert!(!router.graph().output_names().is_empty());
+    }
+
     #[tokio::test]
     async fn test_router_default_skill() {
         let mut router = Router::with_defaults();
@@ -482,4 +624,141 @@ mod tests {
         assert!(!result.skill_hash.is_zero());
         assert!(result.confidence > 0.0);
     }
+
+    #[tokio::test]
+    async fn test_concurrent_routing_shares_cache() {
+        let router = Arc::new(Router::with_defaults());
+        let commands = ["/help", "/status", "/search rust"];
+
+        let handles: Vec<_> = (0..32)
+            .map(|i| {
+                let router = router.clone();
+                let command = commands[i % commands.len()];
+                tokio::spawn(async move {
+                    let (result, _) = router.route(&test_message(command)).await.unwrap();
+                    (command, result)
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            let (command, result) = handle.await.unwrap();
+            let expected = match command {
+                "/help" => "skill:help",
+                "/status" => "skill:status",
+                _ => "skill:search",
+            };
+            assert_eq!(result.skill_ref, expected);
+        }
+        assert_eq!(router.cache_size(), commands.len());
+
+        let (_, trace) = router.route(&test_message("/status")).await.unwrap();
+        assert!(trace.cached);
+    }
+
+    #[tokio::test]
+    async fn test_route_cache_evicts_least_recently_used() {
+        let mut router = Router::with_defaults();
+        router.set_max_cache_entries(3);
+
+        for i in 0..10 {
+            router.route(&test_message(&format!("/search item{}", i))).await.unwrap();
+        }
+        assert_eq!(router.cache_size(), 3);
+
+        // Touch the oldest survivor so the next insert evicts item8 instead
+        let (_, trace) = router.route(&test_message("/search item7")).await.unwrap();
+        assert!(trace.cached);
+        router.route(&test_message("/help")).await.unwrap();
+        assert_eq!(router.cache_size(), 3);
+
+        let (_, trace) = router.route(&test_message("/search item7")).await.unwrap();
+        assert!(trace.cached);
+        let (_, trace) = router.route(&test_message("/search item8")).await.unwrap();
+        assert!(!trace.cached);
+
+        // Free text is never cached
+        router.route(&test_message("hello there")).await.unwrap();
+        assert!(router.cache_size() <= 3);
+
+        router.set_max_cache_entries(1);
+        assert_eq!(router.cache_size(), 1);
+    }
+
+    fn regex_router() -> Router {
+        use crate::runtime::types::{GraphNode, NodeType, RouteCondition};
+
+        let node = |id: &str, node_type: NodeType, inputs: &[&str]| GraphNode {
+            id: id.to_string(),
+            node_type,
+            inputs: inputs.iter().map(|s| s.to_string()).collect(),
+            params: serde_json::json!({}),
+        };
+        let regex = |pattern: &str, target: &str| NodeType::Regex {
+            pattern: pattern.to_string(),
+            target: target.to_string(),
+        };
+        let condition = |input: &str, threshold: f64, confidence: f64| RouteCondition {
+            input: input.to_string(),
+            match_value: None,
+            threshold,
+            target: input.to_string(),
+            confidence,
+        };
+        Router::new(Graph {
+            name: "regex_router".to_string(),
+            version: 1,
+            description: String::new(),
+            nodes: vec![
+                node("message", NodeType::External { uri: "input://message".to_string() }, &[]),
+                node("weather", regex(r"(?i)\b(weather|forecast)\b", "skill:weather"), &["message"]),
+                node("greeting", regex(r"(?i)^(hi|hello)\b", "skill:greeting"), &["message"]),
+                node("fallback", NodeType::Constant { value: Value::String("skill:default".into()) }, &[]),
+                node(
+                    "route_decision",
+                    NodeType::Route {
+                        conditions: vec![
+                            condition("weather", 0.9, 0.9),
+                            condition("greeting", 0.9, 0.8),
+                            condition("fallback", 0.0, 0.5),
+                        ],
+                    },
+                    &["weather", "greeting", "fallback"],
+                ),
+                node(
+                    "skill_target",
+                    NodeType::Switch {
+                        cases: vec![
+                            (Value::String("weather".into()), "weather".to_string()),
+                            (Value::String("greeting".into()), "greeting".to_string()),
+                        ],
+                        default: Some("fallback".to_string()),
+                    },
+                    &["route_decision.target", "weather", "greeting", "fallback"],
+                ),
+            ],
+            outputs: vec!["skill_target".to_string(), "route_decision".to_string()],
+            entry_point: "message".to_string(),
+            metadata: serde_json::json!({}),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_regex_routing() {
+        let router = regex_router();
+
+        let (result, _) = router.route(&test_message("What's the Forecast for tomorrow?")).await.unwrap();
+        assert_eq!(result.skill_ref, "skill:weather");
+        assert_eq!(result.route_name, r"regex:(?i)\b(weather|forecast)\b");
+        assert!((result.confidence - 0.9).abs() < 1e-6);
+
+        let (result, _) = router.route(&test_message("hello there")).await.unwrap();
+        assert_eq!(result.skill_ref, "skill:greeting");
+        assert_eq!(result.route_name, r"regex:(?i)^(hi|hello)\b");
+
+        // "weathervane" is not the word "weather"; falls through to the default
+        let (result, _) = router.route(&test_message("the weathervane spins")).await.unwrap();
+        assert_eq!(result.skill_ref, "skill:default");
+        assert_eq!(result.route_name, "fallback");
+    }
 }
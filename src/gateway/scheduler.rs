@@ -0,0 +1,161 @@
+//! Reminders delivered at a later time.
+//!
+//! The built-in reminder skill stores a [`Reminder`] per `/remind` command in
+//! a [`ReminderStore`]. The gateway's scheduler task fires due reminders as
+//! proof-carrying `SendMessage` actions and removes them, so each fires once.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::types::ContentHash;
+
+/// A message to deliver at `due_at`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reminder {
+    /// Identifier of the reminder
+    pub id: ContentHash,
+
+    /// When the reminder fires
+    pub due_at: DateTime<Utc>,
+
+    /// Channel to deliver on
+    pub channel_id: String,
+
+    /// Recipient within the channel
+    pub recipient_id: String,
+
+    /// Reminder text
+    pub text: String,
+}
+
+impl Reminder {
+    /// Create a reminder, deriving its id from its contents.
+    pub fn new(due_at: DateTime<Utc>, channel_id: &str, recipient_id: &str, text: &str) -> Self {
+        let id = ContentHash::from_string(&format!(
+            "reminder:{}:{}:{}:{}",
+            due_at.timestamp_millis(), channel_id, recipient_id, text
+        ));
+        Self {
+            id,
+            due_at,
+            channel_id: channel_id.to_string(),
+            recipient_id: recipient_id.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    /// Whether the reminder is due at `now`.
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.due_at <= now
+    }
+}
+
+/// Storage for pending reminders.
+pub trait ReminderStore: Send + Sync {
+    /// Store a reminder, replacing any earlier one with the same id.
+    fn push(&mut self, reminder: Reminder);
+
+    /// Remove and return a reminder by id.
+    fn remove(&mut self, id: &ContentHash) -> Option<Reminder>;
+
+    /// All pending reminders, earliest first.
+    fn list(&self) -> Vec<Reminder>;
+}
+
+/// Reminders kept in memory and optionally mirrored to a JSON file.
+#[derive(Debug, Default)]
+pub struct JsonReminderStore {
+    entries: Vec<Reminder>,
+    path: Option<PathBuf>,
+}
+
+impl JsonReminderStore {
+    /// Create an in-memory store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Persist to the JSON file at `path`, loading any reminders already stored there.
+    pub fn with_path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        if let Ok(contents) = std::fs::read_to_string(&path) {
+            match serde_json::from_str::<Vec<Reminder>>(&contents) {
+                Ok(entries) => self.entries = entries,
+                Err(e) => tracing::warn!("Ignoring unreadable reminders in {}: {}", path.display(), e),
+            }
+        }
+        self.path = Some(path);
+        self
+    }
+
+    fn rewrite(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let result = serde_json::to_vec(&self.entries)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| write_atomic(path, &contents).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            tracing::warn!("Failed to persist reminders to {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Write `contents` to a temporary file, flush it to disk and rename it
+/// over `path`, so a crash mid-write leaves the previous contents intact.
+fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let tmp = PathBuf::from(format!("{}.tmp", path.display()));
+    let mut file = std::fs::File::create(&tmp)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+    std::fs::rename(&tmp, path)
+}
+
+impl ReminderStore for JsonReminderStore {
+    fn push(&mut self, reminder: Reminder) {
+        self.entries.retain(|entry| entry.id != reminder.id);
+        let index = self.entries.partition_point(|entry| entry.due_at <= reminder.due_at);
+        self.entries.insert(index, reminder);
+        self.rewrite();
+    }
+
+    fn remove(&mut self, id: &ContentHash) -> Option<Reminder> {
+        let index = self.entries.iter().position(|entry| &entry.id == id)?;
+        let entry = self.entries.remove(index);
+        self.rewrite();
+        Some(entry)
+    }
+
+    fn list(&self) -> Vec<Reminder> {
+        self.entries.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_orders_and_persists_reminders() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("reminders.json");
+        let now = Utc::now();
+        let later = Reminder::new(now + chrono::Duration::hours(2), "test", "user", "later");
+        let sooner = Reminder::new(now + chrono::Duration::minutes(5), "test", "user", "sooner");
+
+        let mut store = JsonReminderStore::new().with_path(&path);
+        store.push(later.clone());
+        store.push(sooner.clone());
+        assert_eq!(store.list(), vec![sooner.clone(), later.clone()]);
+        assert!(!dir.path().join("reminders.json.tmp").exists());
+
+        let mut reloaded = JsonReminderStore::new().with_path(&path);
+        assert_eq!(reloaded.remove(&sooner.id), Some(sooner));
+        assert_eq!(JsonReminderStore::new().with_path(&path).list(), vec![later]);
+    }
+}
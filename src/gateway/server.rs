@@ -8,20 +8,26 @@ use std::net::SocketAddr;
 use axum::{
     extract::{
         ws::{WebSocket, WebSocketUpgrade, Message},
-        State,
+        Path, State,
     },
+    http::{header, HeaderMap, StatusCode},
     response::IntoResponse,
-    routing::get,
+    routing::{get, post},
     Router,
     Json,
 };
 use tokio::sync::{broadcast, RwLock};
 use tower_http::cors::{CorsLayer, Any};
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
 
+use crate::channels::ChannelStatus;
 use crate::error::GatewayError;
-use super::session::SessionInfo;
+use crate::skills::SkillRegistry;
+use super::session::{SessionInfo, SessionManager};
 use super::events::{EventBus, GatewayEvent};
+use super::federation::{PeerEvent, PeerEventGuard};
 
 /// Server message sent to WebSocket clients.
 #[derive(Clone, Serialize, Deserialize)]
@@ -93,6 +99,31 @@ pub enum ClientMessage {
     GetSession {
         session_id: String,
     },
+
+    /// Event forwarded by a federated peer gateway
+    PeerEvent {
+        origin: String,
+        event: Box<GatewayEvent>,
+        /// See `PeerEvent::sent_at`
+        #[serde(default)]
+        sent_at: i64,
+        /// See `PeerEvent::signature`
+        #[serde(default)]
+        signature: String,
+    },
+}
+
+/// Runtime channel control exposed on the admin routes.
+#[async_trait]
+pub trait ChannelControl: Send + Sync {
+    /// Lifecycle status of every registered channel.
+    fn statuses(&self) -> Vec<ChannelStatus>;
+
+    /// Connect a registered, disconnected channel.
+    async fn connect(&self, name: &str) -> Result<(), GatewayError>;
+
+    /// Disconnect a channel. Returns whether it was connected.
+    async fn disconnect(&self, name: &str, reason: &str) -> bool;
 }
 
 /// Shared server state.
@@ -108,6 +139,16 @@ pub struct ServerState {
 
     /// Server version
     version: String,
+
+    /// Installed skills, for the skill schema route
+    skills: Option<Arc<RwLock<SkillRegistry>>>,
+
+    /// Sessions that events from peer gateways are applied to, with the
+    /// guard admitting them
+    federation: Option<(Arc<RwLock<SessionManager>>, PeerEventGuard)>,
+
+    /// Channel control for the admin routes, with the bearer token they require
+    channel_control: Option<(Arc<dyn ChannelControl>, String)>,
 }
 
 impl ServerState {
@@ -120,9 +161,31 @@ impl ServerState {
             broadcast_tx,
             session_count: Arc::new(RwLock::new(0)),
             version: env!("CARGO_PKG_VERSION").to_string(),
+            skills: None,
+            federation: None,
+            channel_control: None,
         }
     }
 
+    /// Serve schemas for the skills in this registry.
+    pub fn with_skills(mut self, skills: Arc<RwLock<SkillRegistry>>) -> Self {
+        self.skills = Some(skills);
+        self
+    }
+
+    /// Apply events from peer gateways signed with `secret` and sent at
+    /// most `max_skew_secs` ago to these sessions, each at most once.
+    pub fn with_federation(mut self, sessions: Arc<RwLock<SessionManager>>, secret: &str, max_skew_secs: u64) -> Self {
+        self.federation = Some((sessions, PeerEventGuard::new(secret, max_skew_secs)));
+        self
+    }
+
+    /// Serve the `/channels` admin routes to callers presenting `admin_token`.
+    pub fn with_channel_control(mut self, control: Arc<dyn ChannelControl>, admin_token: &str) -> Self {
+        self.channel_control = Some((control, admin_token.to_string()));
+        self
+    }
+
     /// Update the session count.
     pub async fn update_session_count(&self, count: usize) {
         *self.session_count.write().await = count;
@@ -159,13 +222,55 @@ impl GatewayServer {
         }
     }
 
+    /// Serve schemas for the skills in this registry.
+    ///
+    /// Call before `state()` is shared; later calls leave shared state unchanged.
+    pub fn with_skills(mut self, skills: Arc<RwLock<SkillRegistry>>) -> Self {
+        if let Some(state) = Arc::get_mut(&mut self.state) {
+            state.skills = Some(skills);
+        }
+        self
+    }
+
+    /// Apply events from peer gateways signed with `secret` and sent at
+    /// most `max_skew_secs` ago to these sessions, each at most once.
+    ///
+    /// Call before `state()` is shared; later calls leave shared state unchanged.
+    pub fn with_federation(mut self, sessions: Arc<RwLock<SessionManager>>, secret: &str, max_skew_secs: u64) -> Self {
+        if let Some(state) = Arc::get_mut(&mut self.state) {
+            state.federation = Some((sessions, PeerEventGuard::new(secret, max_skew_secs)));
+        }
+        self
+    }
+
+    /// Serve the `/channels` admin routes to callers presenting `admin_token`.
+    ///
+    /// Call before `state()` is shared; later calls leave shared state unchanged.
+    pub fn with_channel_control(mut self, control: Arc<dyn ChannelControl>, admin_token: &str) -> Self {
+        if let Some(state) = Arc::get_mut(&mut self.state) {
+            state.channel_control = Some((control, admin_token.to_string()));
+        }
+        self
+    }
+
     /// Get a reference to the server state.
     pub fn state(&self) -> Arc<ServerState> {
         self.state.clone()
     }
 
-    /// Start the server.
+    /// Start the server on the configured address.
     pub async fn start(&self) -> Result<(), GatewayError> {
+        let addr: SocketAddr = format!("{}:{}", self.host, self.port)
+            .parse()
+            .map_err(|e| GatewayError::ServerError(format!("Invalid address: {}", e)))?;
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .map_err(|e| GatewayError::ServerError(e.to_string()))?;
+        self.serve(listener).await
+    }
+
+    /// Serve on an already bound listener.
+    pub async fn serve(&self, listener: tokio::net::TcpListener) -> Result<(), GatewayError> {
         let state = self.state.clone();
 
         // Build router
@@ -174,6 +279,10 @@ impl GatewayServer {
             .route("/health", get(Self::health_handler))
             .route("/sessions", get(Self::sessions_handler))
             .route("/stats", get(Self::stats_handler))
+            .route("/skills/:name/schema", get(Self::skill_schema_handler))
+            .route("/channels", get(Self::channels_handler))
+            .route("/channels/:name/connect", post(Self::channel_connect_handler))
+            .route("/channels/:name/disconnect", post(Self::channel_disconnect_handler))
             .layer(
                 CorsLayer::new()
                     .allow_origin(Any)
@@ -182,21 +291,15 @@ impl GatewayServer {
             )
             .with_state(state);
 
-        let addr: SocketAddr = format!("{}:{}", self.host, self.port)
-            .parse()
-            .map_err(|e| GatewayError::ServerError(format!("Invalid address: {}", e)))?;
-
-        tracing::info!("Gateway server listening on {}", addr);
+        if let Ok(addr) = listener.local_addr() {
+            tracing::info!("Gateway server listening on {}", addr);
+        }
 
         // Publish gateway started event
         self.state.event_bus.publish(GatewayEvent::GatewayStarted {
             timestamp: chrono::Utc::now().timestamp_millis() as u64,
         }).await;
 
-        let listener = tokio::net::TcpListener::bind(addr)
-            .await
-            .map_err(|e| GatewayError::ServerError(e.to_string()))?;
-
         axum::serve(listener, app)
             .await
             .map_err(|e| GatewayError::ServerError(e.to_string()))
@@ -266,7 +369,7 @@ impl GatewayServer {
     /// Handle a client message.
     async fn handle_client_message(
         msg: ClientMessage,
-        _state: &ServerState,
+        state: &ServerState,
     ) -> ServerMessage {
         match msg {
             ClientMessage::Ping { timestamp } => {
@@ -299,6 +402,27 @@ impl GatewayServer {
                     message: format!("Session not found: {}", session_id),
                 }
             }
+            ClientMessage::PeerEvent { origin, event, sent_at, signature } => {
+                let Some((sessions, guard)) = &state.federation else {
+                    return ServerMessage::Error {
+                        code: "FORBIDDEN".to_string(),
+                        message: "Federation is not enabled".to_string(),
+                    };
+                };
+                let event = PeerEvent { origin, event: *event, sent_at, signature };
+                if let Err(reason) = guard.check(&event) {
+                    tracing::warn!("Rejected peer event from {}: {}", event.origin, reason);
+                    return ServerMessage::Error {
+                        code: "UNAUTHORIZED".to_string(),
+                        message: reason,
+                    };
+                }
+                let applied = sessions.write().await.apply_peer_event(&event.event);
+                ServerMessage::Event {
+                    event_type: "peer_event".to_string(),
+                    data: serde_json::json!({ "origin": event.origin, "applied": applied }),
+                }
+            }
         }
     }
 
@@ -315,6 +439,89 @@ impl GatewayServer {
         Json(Vec::new())
     }
 
+    /// Skill input schema handler.
+    async fn skill_schema_handler(
+        State(state): State<Arc<ServerState>>,
+        Path(name): Path<String>,
+    ) -> Result<Json<serde_json::Value>, StatusCode> {
+        let skills = state.skills.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+        skills
+            .read()
+            .await
+            .input_schema(&name)
+            .map(Json)
+            .ok_or(StatusCode::NOT_FOUND)
+    }
+
+    /// Channel control of `state` if `headers` carry its admin token.
+    ///
+    /// The routes are absent (404) when no admin token is configured.
+    fn channel_control<'a>(
+        state: &'a ServerState,
+        headers: &HeaderMap,
+    ) -> Result<&'a dyn ChannelControl, (StatusCode, String)> {
+        let (control, token) = state
+            .channel_control
+            .as_ref()
+            .ok_or((StatusCode::NOT_FOUND, "Channel control is not enabled".to_string()))?;
+        let presented = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .unwrap_or_default();
+        if !bool::from(presented.as_bytes().ct_eq(token.as_bytes())) {
+            return Err((StatusCode::UNAUTHORIZED, "Invalid admin token".to_string()));
+        }
+        Ok(control.as_ref())
+    }
+
+    /// Channel status list handler.
+    async fn channels_handler(
+        State(state): State<Arc<ServerState>>,
+        headers: HeaderMap,
+    ) -> Result<Json<Vec<ChannelStatus>>, (StatusCode, String)> {
+        Ok(Json(Self::channel_control(&state, &headers)?.statuses()))
+    }
+
+    /// Status of `name` after a connect or disconnect.
+    fn channel_status(control: &dyn ChannelControl, name: &str) -> Result<Json<ChannelStatus>, (StatusCode, String)> {
+        control
+            .statuses()
+            .into_iter()
+            .find(|status| status.name == name)
+            .map(Json)
+            .ok_or((StatusCode::NOT_FOUND, format!("Channel not found: {}", name)))
+    }
+
+    /// Channel connect handler.
+    async fn channel_connect_handler(
+        State(state): State<Arc<ServerState>>,
+        Path(name): Path<String>,
+        headers: HeaderMap,
+    ) -> Result<Json<ChannelStatus>, (StatusCode, String)> {
+        let control = Self::channel_control(&state, &headers)?;
+        match control.connect(&name).await {
+            Ok(()) => Self::channel_status(control, &name),
+            Err(e @ GatewayError::ChannelNotFound(_)) => Err((StatusCode::NOT_FOUND, e.to_string())),
+            Err(e) => Err((StatusCode::CONFLICT, e.to_string())),
+        }
+    }
+
+    /// Channel disconnect handler.
+    async fn channel_disconnect_handler(
+        State(state): State<Arc<ServerState>>,
+        Path(name): Path<String>,
+        headers: HeaderMap,
+    ) -> Result<Json<ChannelStatus>, (StatusCode, String)> {
+        let control = Self::channel_control(&state, &headers)?;
+        let disconnected = control.disconnect(&name, "disconnected from the CLI").await;
+        let status = Self::channel_status(control, &name)?;
+        match disconnected {
+            true => Ok(status),
+            false => Err((StatusCode::CONFLICT, format!("Channel {} is already disconnected", name))),
+        }
+    }
+
     /// Stats handler.
     async fn stats_handler(
         State(state): State<Arc<ServerState>>,
@@ -400,4 +607,88 @@ mod tests {
         state.update_session_count(10).await;
         assert_eq!(*state.session_count.read().await, 10);
     }
+
+    #[tokio::test]
+    async fn test_skill_schema_handler() {
+        let mut registry = SkillRegistry::new("/tmp/skills");
+        let graph = crate::skills::SkillGraph::builder("greet")
+            .add_input("name", "string")
+            .add_input("times", "i64")
+            .add_operation("result", crate::skills::Op::Identity, vec!["name"])
+            .output("result")
+            .build();
+        let metadata = crate::skills::SkillMetadata::new("greet", "Greets someone")
+            .with_input("name", "Who to greet", "string", true)
+            .with_input("times", "Repetitions", "i64", false);
+        registry.install_with_metadata(graph, metadata, false).unwrap();
+        let state = Arc::new(ServerState::new(EventBus::new()).with_skills(Arc::new(RwLock::new(registry))));
+
+        let Json(schema) = GatewayServer::skill_schema_handler(State(state.clone()), Path("greet".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(schema["properties"]["name"]["type"], "string");
+        assert_eq!(schema["properties"]["times"]["type"], "integer");
+        assert_eq!(schema["required"], serde_json::json!(["name"]));
+
+        let missing = GatewayServer::skill_schema_handler(State(state), Path("missing".to_string())).await;
+        assert_eq!(missing.unwrap_err(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_unauthenticated_peer_event_rejected() {
+        let mut sessions = SessionManager::new();
+        sessions.get_or_create("test", "alice").unwrap();
+        let sessions = Arc::new(RwLock::new(sessions));
+        let trust_changed = |user_id: &str| GatewayEvent::TrustChanged {
+            channel_id: "test".to_string(),
+            user_id: user_id.to_string(),
+            trust_score: 1.0,
+        };
+        let client_message = |event: PeerEvent| ClientMessage::PeerEvent {
+            origin: event.origin,
+            event: Box::new(event.event),
+            sent_at: event.sent_at,
+            signature: event.signature,
+        };
+        let is_error = |msg: &ServerMessage| matches!(msg, ServerMessage::Error { .. });
+        let trust = |sessions: &Arc<RwLock<SessionManager>>| {
+            let sessions = sessions.clone();
+            async move {
+                let sessions = sessions.read().await;
+                let id = sessions.session_id_for("test", "alice").unwrap();
+                sessions.get(&id).unwrap().trust_score.value()
+            }
+        };
+        let initial = trust(&sessions).await;
+
+        // Federation disabled: nothing is accepted, even when signed
+        let closed = ServerState::new(EventBus::new());
+        let signed = PeerEvent::signed("peer", trust_changed("alice"), "secret");
+        assert!(is_error(&GatewayServer::handle_client_message(client_message(signed.clone()), &closed).await));
+
+        let state = ServerState::new(EventBus::new()).with_federation(sessions.clone(), "secret", 300);
+        let unsigned = PeerEvent {
+            origin: "peer".to_string(),
+            event: trust_changed("alice"),
+            sent_at: chrono::Utc::now().timestamp(),
+            signature: String::new(),
+        };
+        assert!(is_error(&GatewayServer::handle_client_message(client_message(unsigned), &state).await));
+        let forged = PeerEvent::signed("peer", trust_changed("alice"), "guess");
+        assert!(is_error(&GatewayServer::handle_client_message(client_message(forged), &state).await));
+        let stale = PeerEvent::signed_at("peer", trust_changed("alice"), "secret", chrono::Utc::now().timestamp() - 3600);
+        assert!(is_error(&GatewayServer::handle_client_message(client_message(stale), &state).await));
+        assert_eq!(trust(&sessions).await, initial);
+
+        assert!(!is_error(&GatewayServer::handle_client_message(client_message(signed.clone()), &state).await));
+        assert_eq!(trust(&sessions).await, 1.0);
+
+        // A captured event cannot be applied again
+        assert!(is_error(&GatewayServer::handle_client_message(client_message(signed), &state).await));
+
+        // Signed events for unknown users do not create sessions
+        let stranger = PeerEvent::signed("peer", trust_changed("mallory"), "secret");
+        GatewayServer::handle_client_message(client_message(stranger), &state).await;
+        assert_eq!(sessions.read().await.count(), 1);
+    }
 }
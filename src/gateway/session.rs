@@ -8,17 +8,22 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
-use crate::types::{ContentHash, Confidence, ProofCarryingAction};
+use crate::types::{ContentHash, Confidence, ProofCarryingAction, UserRef};
 use crate::error::SessionError;
 use crate::runtime::{GraphInterpreter, Graph, Value};
+use super::events::GatewayEvent;
+use super::session_store::{MemorySessionStore, SessionStore};
+
+/// File the session management graph is read from.
+pub const SESSION_GRAPH_PATH: &str = "graphs/core/session.0";
 
 /// Session manager responsible for creating and maintaining sessions.
 pub struct SessionManager {
     /// Active sessions by session ID
     sessions: HashMap<ContentHash, Session>,
     
-    /// Index: (channel_id, user_id) -> session_id
-    user_sessions: HashMap<(String, String), ContentHash>,
+    /// Index: user -> session_id
+    user_sessions: HashMap<UserRef, ContentHash>,
     
     /// Session configuration
     config: SessionManagerConfig,
@@ -28,6 +33,9 @@ pub struct SessionManager {
     
     /// Session management graph
     session_graph: Option<Graph>,
+
+    /// Storage every session change is written through to
+    store: Box<dyn SessionStore>,
 }
 
 /// Configuration for the session manager.
@@ -44,6 +52,16 @@ pub struct SessionManagerConfig {
     
     /// Trust score decay rate
     pub trust_decay: f32,
+
+    /// Trust gained per successful action, scaled by its confidence
+    pub success_reward: f32,
+
+    /// Trust lost per failed action
+    pub failure_penalty: f32,
+
+    /// Evict the user's least recently active session when `max_per_user`
+    /// is reached, instead of refusing the new one
+    pub evict_oldest: bool,
 }
 
 impl Default for SessionManagerConfig {
@@ -53,12 +71,15 @@ impl Default for SessionManagerConfig {
             max_per_user: 10,
             initial_trust: 0.5,
             trust_decay: 0.01,
+            success_reward: 0.05,
+            failure_penalty: 0.1,
+            evict_oldest: true,
         }
     }
 }
 
 /// A user session.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Session {
     /// Unique session identifier
     pub id: ContentHash,
@@ -83,10 +104,26 @@ pub struct Session {
     
     /// Last activity timestamp (Unix milliseconds)
     pub last_activity: u64,
+
+    /// Time of the last accepted command (Unix milliseconds)
+    pub last_command_at: Option<u64>,
+
+    /// Time each skill with its own cooldown was last run, by skill name
+    #[serde(default)]
+    pub command_used_at: HashMap<String, u64>,
+
+    /// Set when a reply was dropped because the channel was disconnected
+    pub channel_stale: bool,
+
+    /// Skill that has claimed the conversation, bypassing the router
+    pub active_skill: Option<ContentHash>,
+
+    /// Messages left before the claim on `active_skill` lapses
+    pub claim_turns_remaining: u32,
 }
 
 /// Session state data.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 pub struct SessionState {
     /// Serialized state data
     pub data: Vec<u8>,
@@ -116,9 +153,38 @@ impl Session {
             trust_score: Confidence::new(initial_trust),
             created_at: now,
             last_activity: now,
+            last_command_at: None,
+            command_used_at: HashMap::new(),
+            channel_stale: false,
+            active_skill: None,
+            claim_turns_remaining: 0,
         }
     }
 
+    /// Let a skill handle the next `max_turns` messages directly.
+    pub fn claim(&mut self, skill_hash: ContentHash, max_turns: u32) {
+        self.active_skill = (max_turns > 0).then_some(skill_hash);
+        self.claim_turns_remaining = max_turns;
+    }
+
+    /// Release any conversation claim.
+    pub fn release(&mut self) {
+        self.active_skill = None;
+        self.claim_turns_remaining = 0;
+    }
+
+    /// Consume one turn of the current claim, returning the claiming skill.
+    ///
+    /// The claim is released once its last turn is taken.
+    pub fn take_claim_turn(&mut self) -> Option<ContentHash> {
+        let skill = self.active_skill?;
+        self.claim_turns_remaining = self.claim_turns_remaining.saturating_sub(1);
+        if self.claim_turns_remaining == 0 {
+            self.active_skill = None;
+        }
+        Some(skill)
+    }
+
     /// Generate a unique session ID.
     fn generate_id(channel_id: &str, user_id: &str, timestamp: u64) -> ContentHash {
         ContentHash::from_bytes(
@@ -186,14 +252,47 @@ impl SessionManager {
             config,
             interpreter,
             session_graph,
+            store: Box::new(MemorySessionStore::new()),
+        }
+    }
+
+    /// Create a session manager backed by `store`, restoring its sessions.
+    pub fn with_store(config: SessionManagerConfig, store: Box<dyn SessionStore>) -> Self {
+        let mut manager = Self::with_config(config);
+        for session in store.list() {
+            let key = UserRef::new(&session.channel_id, &session.user_id);
+            let replaces_newer = manager
+                .user_sessions
+                .get(&key)
+                .and_then(|id| manager.sessions.get(id))
+                .is_some_and(|existing| existing.last_activity > session.last_activity);
+            if !replaces_newer {
+                manager.user_sessions.insert(key, session.id);
+            }
+            manager.sessions.insert(session.id, session);
+        }
+        manager.store = store;
+        manager
+    }
+
+    /// Write a session's current state to the store.
+    ///
+    /// Call after changing a session through [`Self::get_mut`].
+    pub fn persist(&mut self, session_id: &ContentHash) {
+        if let Some(session) = self.sessions.get(session_id) {
+            self.store.save(session);
         }
     }
     
+    /// Write any changes the store buffers through to storage.
+    pub fn flush(&mut self) {
+        self.store.flush();
+    }
+
     /// Load the session management graph.
     fn load_session_graph() -> Option<Graph> {
         // Try to load from file first
-        let graph_path = "graphs/core/session.0";
-        if let Ok(content) = std::fs::read_to_string(graph_path) {
+        if let Ok(content) = std::fs::read_to_string(SESSION_GRAPH_PATH) {
             if let Ok(graph) = crate::runtime::parse_graph(&content) {
                 return Some(graph);
             }
@@ -267,7 +366,7 @@ impl SessionManager {
         channel_id: &str,
         user_id: &str,
     ) -> Result<&Session, SessionError> {
-        let key = (channel_id.to_string(), user_id.to_string());
+        let key = UserRef::new(channel_id, user_id);
         
         // Check if session exists and is not expired
         if let Some(&session_id) = self.user_sessions.get(&key) {
@@ -277,20 +376,60 @@ impl SessionManager {
                 }
                 // Session expired, remove it
                 self.sessions.remove(&session_id);
+                self.store.remove(&session_id);
             }
             self.user_sessions.remove(&key);
         }
 
+        self.make_room_for(user_id)?;
+
         // Create new session
         let session = Session::new(channel_id, user_id, self.config.initial_trust);
         let session_id = session.id;
         
+        self.store.save(&session);
         self.sessions.insert(session_id, session);
         self.user_sessions.insert(key, session_id);
 
         Ok(self.sessions.get(&session_id).unwrap())
     }
 
+    /// Ensure `user_id` is below `max_per_user` before a session is added.
+    fn make_room_for(&mut self, user_id: &str) -> Result<(), SessionError> {
+        let limit = self.config.max_per_user;
+        loop {
+            let user_sessions: Vec<&Session> = self.sessions
+                .values()
+                .filter(|session| session.user_id == user_id)
+                .collect();
+            if user_sessions.len() < limit {
+                return Ok(());
+            }
+            if !self.config.evict_oldest {
+                return Err(SessionError::TooManySessions { limit });
+            }
+            let Some(oldest) = user_sessions
+                .iter()
+                .min_by_key(|session| (session.last_activity, session.created_at))
+                .map(|session| (session.id, session.channel_id.clone()))
+            else {
+                // A limit of zero leaves nothing to evict
+                return Err(SessionError::TooManySessions { limit });
+            };
+            tracing::debug!("Evicting session {:?} of {} on {}", oldest.0, user_id, oldest.1);
+            self.sessions.remove(&oldest.0);
+            self.store.remove(&oldest.0);
+            self.user_sessions.remove(&UserRef::new(&oldest.1, user_id));
+        }
+    }
+
+    /// ID of the current session of `user_id` on `channel_id`, if any.
+    pub fn session_id_for(&self, channel_id: &str, user_id: &str) -> Option<ContentHash> {
+        self.user_sessions
+            .get(&UserRef::new(channel_id, user_id))
+            .copied()
+    }
+
     /// Get a session by ID.
     pub fn get(&self, session_id: &ContentHash) -> Option<&Session> {
         self.sessions.get(session_id)
@@ -332,10 +471,65 @@ impl SessionManager {
         session.state.hash = ContentHash::from_bytes(
             &session.state.version.to_le_bytes()
         );
+        self.persist(session_id);
 
         Ok(())
     }
 
+    /// Adjust a session's trust by the outcome of executing an action.
+    ///
+    /// Success adds `success_reward` scaled by the action's confidence;
+    /// failure subtracts `failure_penalty`. Returns the new trust score.
+    pub fn record_outcome(
+        &mut self,
+        session_id: &ContentHash,
+        success: bool,
+        confidence: Confidence,
+    ) -> Option<Confidence> {
+        let delta = if success {
+            self.config.success_reward * confidence.value()
+        } else {
+            -self.config.failure_penalty
+        };
+        let session = self.sessions.get_mut(session_id)?;
+        session.trust_score = Confidence::new(session.trust_score.value() + delta);
+        let trust = session.trust_score;
+        self.persist(session_id);
+        Some(trust)
+    }
+
+    /// Update session context only if the state is still at `expected_version`.
+    ///
+    /// Returns the new version on success, or `SessionError::VersionConflict`
+    /// if another update has advanced the session in the meantime.
+    pub fn update_context_checked(
+        &mut self,
+        session_id: &ContentHash,
+        expected_version: u64,
+        updates: HashMap<String, serde_json::Value>,
+    ) -> Result<u64, SessionError> {
+        let session = self.sessions.get_mut(session_id)
+            .ok_or(SessionError::NotFound)?;
+
+        if session.state.version != expected_version {
+            return Err(SessionError::VersionConflict {
+                expected: expected_version,
+                actual: session.state.version,
+            });
+        }
+
+        session.state.context.extend(updates);
+        session.state.version += 1;
+        session.state.hash = ContentHash::from_bytes(
+            &session.state.version.to_le_bytes()
+        );
+        session.touch();
+        let version = session.state.version;
+        self.persist(session_id);
+
+        Ok(version)
+    }
+
     /// Calculate new trust score using the 0-lang graph.
     async fn calculate_trust(&self, current: Confidence, action_confidence: Confidence) -> Confidence {
         // If we have a graph, use it
@@ -362,11 +556,37 @@ impl SessionManager {
         Confidence::new(new_value)
     }
 
+    /// Apply an event shared by a peer gateway.
+    ///
+    /// `TrustChanged` sets the trust of the user's existing session; events
+    /// for users without one are dropped. Returns whether the event changed
+    /// any session.
+    pub fn apply_peer_event(&mut self, event: &GatewayEvent) -> bool {
+        match event {
+            GatewayEvent::TrustChanged { channel_id, user_id, trust_score } => {
+                // Peers only update sessions this gateway already has
+                let Some(session_id) = self.session_id_for(channel_id, user_id) else {
+                    return false;
+                };
+                match self.sessions.get_mut(&session_id) {
+                    Some(session) => {
+                        session.trust_score = Confidence::new(*trust_score);
+                        self.persist(&session_id);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
     /// Remove a session.
     pub fn remove(&mut self, session_id: &ContentHash) -> Option<Session> {
         if let Some(session) = self.sessions.remove(session_id) {
-            let key = (session.channel_id.clone(), session.user_id.clone());
+            let key = UserRef::new(&session.channel_id, &session.user_id);
             self.user_sessions.remove(&key);
+            self.store.remove(session_id);
             Some(session)
         } else {
             None
@@ -406,6 +626,19 @@ impl SessionManager {
             .collect()
     }
 
+    /// Mark or clear the stale-channel flag on every session of a channel.
+    ///
+    /// Returns the number of sessions updated.
+    pub fn set_channel_stale(&mut self, channel_id: &str, stale: bool) -> usize {
+        let mut updated = 0;
+        for session in self.sessions.values_mut().filter(|s| s.channel_id == channel_id) {
+            session.channel_stale = stale;
+            self.store.save(session);
+            updated += 1;
+        }
+        updated
+    }
+
     /// Get sessions for a specific user.
     pub fn sessions_for_user(&self, user_id: &str) -> Vec<&Session> {
         self.sessions
@@ -457,6 +690,7 @@ impl From<&Session> for SessionInfo {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::super::session_store::JsonFileSessionStore;
 
     #[test]
     fn test_session_creation() {
@@ -517,6 +751,19 @@ mod tests {
         assert!(updated.value() < 0.9);
     }
 
+    #[tokio::test]
+    async fn test_graph_trust_matches_fallback() {
+        let manager = SessionManager::new();
+        assert!(manager.session_graph.is_some());
+
+        for (current, action) in [(0.5, 0.9), (0.0, 1.0), (1.0, 0.0), (0.73, 0.21)] {
+            let (current, action) = (Confidence::new(current), Confidence::new(action));
+            let graph = manager.calculate_trust(current, action).await;
+            let fallback = SessionManager::update_trust_fallback(current, action);
+            assert!((graph.value() - fallback.value()).abs() < 1e-5);
+        }
+    }
+
     #[test]
     fn test_session_context() {
         let mut session = Session::new("test", "user", 0.5);
@@ -527,4 +774,115 @@ mod tests {
             Some(&serde_json::json!("value"))
         );
     }
+
+    #[test]
+    fn test_stale_version_update_rejected() {
+        let mut manager = SessionManager::new();
+        let session_id = manager.get_or_create("test", "user").unwrap().id;
+        let version = manager.get(&session_id).unwrap().state.version;
+
+        // Two tasks read the same version
+        let mut first = HashMap::new();
+        first.insert("step".to_string(), serde_json::json!(1));
+        let mut second = HashMap::new();
+        second.insert("step".to_string(), serde_json::json!(2));
+
+        let new_version = manager.update_context_checked(&session_id, version, first).unwrap();
+        assert_eq!(new_version, version + 1);
+
+        let result = manager.update_context_checked(&session_id, version, second);
+        assert!(matches!(
+            result,
+            Err(SessionError::VersionConflict { expected, actual }) if expected == version && actual == new_version
+        ));
+
+        let session = manager.get(&session_id).unwrap();
+        assert_eq!(session.get_context("step"), Some(&serde_json::json!(1)));
+    }
+
+    #[test]
+    fn test_max_per_user_evicts_or_rejects() {
+        let config = SessionManagerConfig { max_per_user: 2, ..Default::default() };
+        let mut manager = SessionManager::with_config(config.clone());
+        let first = manager.get_or_create("telegram", "user1").unwrap().id;
+        manager.get_mut(&first).unwrap().last_activity = 0;
+        let second = manager.get_or_create("discord", "user1").unwrap().id;
+        manager.get_or_create("slack", "user2").unwrap();
+
+        let third = manager.get_or_create("slack", "user1").unwrap().id;
+        assert!(manager.get(&first).is_none());
+        assert!(manager.get(&second).is_some());
+        assert!(manager.get(&third).is_some());
+        assert_eq!(manager.count(), 3);
+        // The evicted channel gets a fresh session, evicting the next oldest
+        manager.get_mut(&second).unwrap().last_activity = 0;
+        manager.get_or_create("telegram", "user1").unwrap();
+        assert!(manager.get(&second).is_none());
+        assert_eq!(manager.count(), 3);
+
+        let mut strict = SessionManager::with_config(SessionManagerConfig { evict_oldest: false, ..config });
+        strict.get_or_create("telegram", "user1").unwrap();
+        strict.get_or_create("discord", "user1").unwrap();
+        let err = strict.get_or_create("slack", "user1").unwrap_err();
+        assert!(matches!(err, SessionError::TooManySessions { limit: 2 }));
+        assert!(strict.get_or_create("telegram", "user1").is_ok());
+        assert_eq!(strict.count(), 2);
+    }
+
+    #[test]
+    fn test_sessions_round_trip_through_store() {
+        let session = {
+            let mut session = Session::new("telegram", "alice", 0.5);
+            session.set_context("lang", serde_json::json!("en"));
+            session.add_to_history(ContentHash::from_string("action"));
+            session
+        };
+        let json = serde_json::to_string(&session).unwrap();
+        let restored: Session = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.id, session.id);
+        assert_eq!(restored.history, session.history);
+        assert_eq!(restored.state.version, session.state.version);
+        assert_eq!(restored.get_context("lang"), Some(&serde_json::json!("en")));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sessions.json");
+        let id = {
+            let mut manager = SessionManager::with_store(
+                SessionManagerConfig::default(),
+                Box::new(JsonFileSessionStore::new(&path)),
+            );
+            let id = manager.get_or_create("telegram", "alice").unwrap().id;
+            manager.record_outcome(&id, true, Confidence::full());
+            manager.get_or_create("discord", "bob").unwrap();
+            let bob = manager.session_id_for("discord", "bob").unwrap();
+            manager.remove(&bob);
+            id
+        };
+
+        let store = JsonFileSessionStore::new(&path);
+        assert_eq!(store.list().len(), 1);
+        let mut manager = SessionManager::with_store(SessionManagerConfig::default(), Box::new(store));
+        let session = manager.get_or_create("telegram", "alice").unwrap();
+        assert_eq!(session.id, id);
+        assert!(session.trust_score.value() > 0.5);
+        assert!(manager.session_id_for("discord", "bob").is_none());
+    }
+
+    #[test]
+    fn test_file_store_batches_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sessions.json");
+        let store = JsonFileSessionStore::with_write_delay(&path, std::time::Duration::from_secs(3600));
+        let mut manager = SessionManager::with_store(SessionManagerConfig::default(), Box::new(store));
+        for user in ["alice", "bob", "carol"] {
+            manager.get_or_create("slack", user).unwrap();
+        }
+        assert_eq!(manager.set_channel_stale("slack", true), 3);
+        assert!(!path.exists());
+
+        manager.flush();
+        let stored = JsonFileSessionStore::new(&path).list();
+        assert_eq!(stored.len(), 3);
+        assert!(stored.iter().all(|session| session.channel_stale));
+    }
 }
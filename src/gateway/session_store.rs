@@ -0,0 +1,236 @@
+//! Storage backends for sessions.
+//!
+//! [`SessionManager`](super::session::SessionManager) keeps active sessions
+//! in memory and hands every change to a [`SessionStore`], so trust scores
+//! and history survive a restart when the store is durable.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+use crate::types::ContentHash;
+use super::session::Session;
+
+/// Storage for sessions.
+pub trait SessionStore: Send + Sync {
+    /// Load a session by id.
+    fn load(&self, id: &ContentHash) -> Option<Session>;
+
+    /// Store a session, replacing any earlier version with the same id.
+    fn save(&mut self, session: &Session);
+
+    /// Remove a session by id.
+    fn remove(&mut self, id: &ContentHash);
+
+    /// All stored sessions.
+    fn list(&self) -> Vec<Session>;
+
+    /// Write any buffered changes through to durable storage.
+    fn flush(&mut self) {}
+}
+
+/// Sessions kept in memory only; lost on restart.
+#[derive(Debug, Default)]
+pub struct MemorySessionStore {
+    sessions: HashMap<ContentHash, Session>,
+}
+
+impl MemorySessionStore {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SessionStore for MemorySessionStore {
+    fn load(&self, id: &ContentHash) -> Option<Session> {
+        self.sessions.get(id).cloned()
+    }
+
+    fn save(&mut self, session: &Session) {
+        self.sessions.insert(session.id, session.clone());
+    }
+
+    fn remove(&mut self, id: &ContentHash) {
+        self.sessions.remove(id);
+    }
+
+    fn list(&self) -> Vec<Session> {
+        self.sessions.values().cloned().collect()
+    }
+}
+
+/// Sessions kept in memory and mirrored to a JSON file.
+///
+/// Changes only touch memory; a background thread rewrites the file once
+/// changes have settled for the write delay, so bursts of updates cost a
+/// single write and callers never block on disk. Writes go through a
+/// temporary file so a crash mid-write leaves the previous contents intact.
+/// Dropping the store writes any pending changes.
+#[derive(Debug)]
+pub struct JsonFileSessionStore {
+    shared: Arc<FileState>,
+    writer: Option<std::thread::JoinHandle<()>>,
+}
+
+/// Default time changes may accumulate before the file is rewritten.
+pub const DEFAULT_WRITE_DELAY: Duration = Duration::from_millis(200);
+
+#[derive(Debug)]
+struct FileState {
+    path: PathBuf,
+    delay: Duration,
+    state: Mutex<PendingState>,
+    changed: Condvar,
+    /// Held while snapshotting and writing, so writes land in order.
+    writing: Mutex<()>,
+}
+
+#[derive(Debug, Default)]
+struct PendingState {
+    sessions: MemorySessionStore,
+    dirty: bool,
+    closed: bool,
+}
+
+impl JsonFileSessionStore {
+    /// Use the JSON file at `path`, loading any sessions already stored there.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self::with_write_delay(path, DEFAULT_WRITE_DELAY)
+    }
+
+    /// Like [`Self::new`], batching changes for `delay` before each write.
+    pub fn with_write_delay<P: AsRef<Path>>(path: P, delay: Duration) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let mut sessions = MemorySessionStore::new();
+        if let Ok(contents) = std::fs::read_to_string(&path) {
+            match serde_json::from_str::<Vec<Session>>(&contents) {
+                Ok(stored) => stored.iter().for_each(|session| sessions.save(session)),
+                Err(e) => tracing::warn!("Ignoring unreadable session store {}: {}", path.display(), e),
+            }
+        }
+        let shared = Arc::new(FileState {
+            path,
+            delay,
+            state: Mutex::new(PendingState { sessions, ..Default::default() }),
+            changed: Condvar::new(),
+            writing: Mutex::new(()),
+        });
+        let writer = {
+            let shared = shared.clone();
+            std::thread::Builder::new()
+                .name("session-store".to_string())
+                .spawn(move || shared.run_writer())
+                .map_err(|e| tracing::warn!("Session store writes will block: {}", e))
+                .ok()
+        };
+        Self { shared, writer }
+    }
+
+    fn update(&mut self, change: impl FnOnce(&mut MemorySessionStore) -> bool) {
+        let mut state = self.shared.lock();
+        if change(&mut state.sessions) {
+            state.dirty = true;
+            drop(state);
+            if self.writer.is_some() {
+                self.shared.changed.notify_one();
+            } else {
+                self.shared.write_pending();
+            }
+        }
+    }
+}
+
+impl FileState {
+    fn lock(&self) -> std::sync::MutexGuard<'_, PendingState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn run_writer(&self) {
+        loop {
+            let state = self.lock();
+            let state = self
+                .changed
+                .wait_while(state, |state| !state.dirty && !state.closed)
+                .unwrap_or_else(|e| e.into_inner());
+            if !state.dirty {
+                return;
+            }
+            // Let further changes accumulate, unless the store is closing
+            let (state, _) = self
+                .changed
+                .wait_timeout_while(state, self.delay, |state| !state.closed)
+                .unwrap_or_else(|e| e.into_inner());
+            drop(state);
+            self.write_pending();
+        }
+    }
+
+    /// Write the sessions to the file if they changed since the last write.
+    fn write_pending(&self) {
+        let _writing = self.writing.lock().unwrap_or_else(|e| e.into_inner());
+        let sessions = {
+            let mut state = self.lock();
+            if !state.dirty {
+                return;
+            }
+            state.dirty = false;
+            state.sessions.list()
+        };
+        let result = serde_json::to_vec(&sessions)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| {
+                if let Some(parent) = self.path.parent() {
+                    std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+                let tmp = self.path.with_extension("json.tmp");
+                std::fs::write(&tmp, contents)
+                    .and_then(|_| std::fs::rename(&tmp, &self.path))
+                    .map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            tracing::warn!("Failed to persist sessions to {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+impl SessionStore for JsonFileSessionStore {
+    fn load(&self, id: &ContentHash) -> Option<Session> {
+        self.shared.lock().sessions.load(id)
+    }
+
+    fn save(&mut self, session: &Session) {
+        self.update(|sessions| {
+            sessions.save(session);
+            true
+        });
+    }
+
+    fn remove(&mut self, id: &ContentHash) {
+        self.update(|sessions| {
+            let present = sessions.load(id).is_some();
+            sessions.remove(id);
+            present
+        });
+    }
+
+    fn list(&self) -> Vec<Session> {
+        self.shared.lock().sessions.list()
+    }
+
+    fn flush(&mut self) {
+        self.shared.write_pending();
+    }
+}
+
+impl Drop for JsonFileSessionStore {
+    fn drop(&mut self) {
+        self.shared.lock().closed = true;
+        self.shared.changed.notify_one();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+        self.shared.write_pending();
+    }
+}
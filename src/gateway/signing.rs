@@ -0,0 +1,156 @@
+//! Pluggable signature schemes for Proof-Carrying Actions.
+//!
+//! Signing and verification are abstracted behind the [`Signer`] and
+//! [`Verifier`] traits so that deployments can plug in other algorithms
+//! (e.g. HSM-backed keys). Every PCA records the [`SigScheme`] it was
+//! signed with, and verification dispatches on that recorded scheme.
+
+use ed25519_dalek::{Signature, SigningKey, VerifyingKey};
+use rand::rngs::OsRng;
+
+use crate::error::ProofError;
+use crate::types::SigScheme;
+
+/// Produces signatures for a single scheme.
+pub trait Signer: Send + Sync {
+    /// The scheme this signer produces.
+    fn scheme(&self) -> SigScheme;
+
+    /// Sign a message.
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, ProofError>;
+
+    /// The verifier matching this signer's public key.
+    fn verifier(&self) -> Box<dyn Verifier>;
+
+    /// Export the secret key bytes, if the backend allows it.
+    fn export_key(&self) -> Option<Vec<u8>> {
+        None
+    }
+}
+
+/// Verifies signatures for a single scheme.
+pub trait Verifier: Send + Sync {
+    /// The scheme this verifier accepts.
+    fn scheme(&self) -> SigScheme;
+
+    /// Verify a signature over a message.
+    fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), ProofError>;
+
+    /// The public key bytes.
+    fn public_key_bytes(&self) -> Vec<u8>;
+}
+
+/// Ed25519 signer backed by an in-memory key.
+pub struct Ed25519Signer {
+    signing_key: SigningKey,
+}
+
+impl Ed25519Signer {
+    /// Create a signer with a random key.
+    pub fn generate() -> Self {
+        Self {
+            signing_key: SigningKey::generate(&mut OsRng),
+        }
+    }
+
+    /// Create a signer from 32 secret key bytes.
+    pub fn from_bytes(bytes: &[u8; 32]) -> Self {
+        Self {
+            signing_key: SigningKey::from_bytes(bytes),
+        }
+    }
+
+    /// Get the Ed25519 verifying key.
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.signing_key.verifying_key()
+    }
+}
+
+impl Signer for Ed25519Signer {
+    fn scheme(&self) -> SigScheme {
+        SigScheme::Ed25519
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, ProofError> {
+        use ed25519_dalek::Signer as _;
+        Ok(self.signing_key.sign(message).to_bytes().to_vec())
+    }
+
+    fn verifier(&self) -> Box<dyn Verifier> {
+        Box::new(Ed25519Verifier::new(self.verifying_key()))
+    }
+
+    fn export_key(&self) -> Option<Vec<u8>> {
+        Some(self.signing_key.to_bytes().to_vec())
+    }
+}
+
+/// Ed25519 verifier for a single public key.
+pub struct Ed25519Verifier {
+    verifying_key: VerifyingKey,
+}
+
+impl Ed25519Verifier {
+    /// Create a verifier for the given public key.
+    pub fn new(verifying_key: VerifyingKey) -> Self {
+        Self { verifying_key }
+    }
+}
+
+impl Verifier for Ed25519Verifier {
+    fn scheme(&self) -> SigScheme {
+        SigScheme::Ed25519
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), ProofError> {
+        use ed25519_dalek::Verifier as _;
+        let signature = Signature::from_slice(signature)
+            .map_err(|_| ProofError::InvalidSignature)?;
+        self.verifying_key
+            .verify(message, &signature)
+            .map_err(|e| ProofError::VerificationFailed(e.to_string()))
+    }
+
+    fn public_key_bytes(&self) -> Vec<u8> {
+        self.verifying_key.to_bytes().to_vec()
+    }
+}
+
+/// Build a verifier for a scheme from raw public key bytes.
+pub fn verifier_for(scheme: SigScheme, public_key: &[u8]) -> Result<Box<dyn Verifier>, ProofError> {
+    match scheme {
+        SigScheme::Ed25519 => {
+            let bytes: [u8; 32] = public_key
+                .try_into()
+                .map_err(|_| ProofError::VerificationFailed("Invalid Ed25519 public key length".to_string()))?;
+            let key = VerifyingKey::from_bytes(&bytes)
+                .map_err(|e| ProofError::VerificationFailed(e.to_string()))?;
+            Ok(Box::new(Ed25519Verifier::new(key)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ed25519_roundtrip() {
+        let signer = Ed25519Signer::generate();
+        let signature = signer.sign(b"message").unwrap();
+
+        let verifier = verifier_for(signer.scheme(), &signer.verifier().public_key_bytes()).unwrap();
+        assert_eq!(verifier.scheme(), SigScheme::Ed25519);
+        assert!(verifier.verify(b"message", &signature).is_ok());
+        assert!(verifier.verify(b"tampered", &signature).is_err());
+    }
+
+    #[test]
+    fn test_invalid_signature_length() {
+        let signer = Ed25519Signer::generate();
+        assert!(matches!(
+            signer.verifier().verify(b"message", &[1, 2, 3]),
+            Err(ProofError::InvalidSignature)
+        ));
+    }
+}
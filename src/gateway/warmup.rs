@@ -0,0 +1,90 @@
+//! Startup warm-up of core graphs and installed skills.
+//!
+//! Core components read their graphs from fixed files (the router from its
+//! configured path) and silently use built-in logic when a file is missing
+//! or malformed. [`WarmUpReport`]
+//! records which graphs loaded from file, so a broken graph shows up at
+//! startup instead of as a quiet change in behavior.
+
+use std::path::{Path, PathBuf};
+
+/// Core graphs loaded from the core graph directory.
+pub const CORE_GRAPHS: [&str; 4] = ["router", "session", "proof", "verifier"];
+
+/// Where a core graph came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphSource {
+    /// Parsed from its graph file
+    File,
+    /// The file could not be used; the built-in logic applies
+    BuiltIn {
+        /// Why the file was not used
+        reason: String,
+    },
+}
+
+/// Load status of one core graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoreGraphStatus {
+    /// Graph name, e.g. `router`
+    pub name: String,
+
+    /// File the graph was read from
+    pub path: PathBuf,
+
+    /// Where the graph came from
+    pub source: GraphSource,
+}
+
+impl CoreGraphStatus {
+    /// Read and parse the graph at `path`.
+    pub fn load(name: &str, path: &Path) -> Self {
+        let source = match std::fs::read_to_string(path) {
+            Ok(content) => match crate::runtime::parse_graph(&content) {
+                Ok(_) => GraphSource::File,
+                Err(e) => GraphSource::BuiltIn { reason: e.to_string() },
+            },
+            Err(e) => GraphSource::BuiltIn { reason: e.to_string() },
+        };
+        Self {
+            name: name.to_string(),
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    /// Whether the built-in logic is used instead of the file.
+    pub fn is_fallback(&self) -> bool {
+        matches!(self.source, GraphSource::BuiltIn { .. })
+    }
+}
+
+/// Outcome of [`Gateway::warm_up`](super::Gateway::warm_up).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WarmUpReport {
+    /// Status of each core graph
+    pub graphs: Vec<CoreGraphStatus>,
+
+    /// Number of installed skills re-verified
+    pub skills_verified: usize,
+
+    /// Installed skills that failed re-verification, with the reason
+    pub skill_failures: Vec<(String, String)>,
+}
+
+impl WarmUpReport {
+    /// Status of the core graph called `name`.
+    pub fn graph(&self, name: &str) -> Option<&CoreGraphStatus> {
+        self.graphs.iter().find(|status| status.name == name)
+    }
+
+    /// Core graphs that fell back to built-in logic.
+    pub fn fallbacks(&self) -> Vec<&CoreGraphStatus> {
+        self.graphs.iter().filter(|status| status.is_fallback()).collect()
+    }
+
+    /// Whether every core graph loaded from file and every skill verified.
+    pub fn is_clean(&self) -> bool {
+        self.fallbacks().is_empty() && self.skill_failures.is_empty()
+    }
+}
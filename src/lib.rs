@@ -31,6 +31,7 @@
 
 pub mod types;
 pub mod error;
+pub mod clock;
 pub mod runtime;
 pub mod gateway;
 pub mod channels;
@@ -42,8 +43,14 @@ pub use types::{
     ContentHash,
     Confidence,
     IncomingMessage,
+    Attachment,
+    UserRef,
     OutgoingMessage,
+    OutgoingAttachment,
+    AttachmentSource,
     ProofCarryingAction,
+    RedactedPca,
+    SigScheme,
     Action,
 };
 pub use error::{Error, Result};
@@ -4,6 +4,7 @@
 
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
+use zero_openclaw::cli::commands::{self, OutputFormat};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 /// Proof-carrying AI assistant built with 0-lang.
@@ -72,6 +73,25 @@ enum Commands {
     Verify {
         /// Path to PCA file
         pca_file: PathBuf,
+
+        /// Public key file (defaults to the configured keypair; a key in
+        /// <pca_file>.pub or embedded in the PCA is reported but never PASSes)
+        #[arg(long)]
+        public_key: Option<PathBuf>,
+
+        /// Hex-encoded public key
+        #[arg(long, conflicts_with = "public_key")]
+        pubkey: Option<String>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Process a single message and print the reply
+    Ask {
+        /// Message to send
+        message: String,
     },
 }
 
@@ -101,8 +121,11 @@ enum ChannelCommands {
 
 #[derive(Subcommand)]
 enum SkillCommands {
-    /// List installed skills
-    List,
+    /// List installed skills, or search them
+    List {
+        /// Fuzzy search over names, descriptions and tags
+        query: Option<String>,
+    },
 
     /// Install a skill
     Install {
@@ -118,14 +141,50 @@ enum SkillCommands {
 
     /// Verify a skill
     Verify {
-        /// Skill name or hash
+        /// Skill file, name, or hash
         skill: String,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Check that a skill would install, without installing it
+    Check {
+        /// Skill file path
+        source: String,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
     },
 
     /// Show skill info
     Info {
         /// Skill name or hash
         skill: String,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        output: OutputFormat,
+    },
+
+    /// Scaffold a new skill
+    New {
+        /// Skill name
+        name: String,
+
+        /// Input as name or name:type (repeatable)
+        #[arg(long = "input")]
+        inputs: Vec<String>,
+
+        /// Output name (repeatable)
+        #[arg(long = "output")]
+        outputs: Vec<String>,
+
+        /// Directory to write the skill to (defaults to the skills directory)
+        #[arg(long)]
+        dir: Option<PathBuf>,
     },
 }
 
@@ -163,7 +222,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| log_level.into()),
         )
-        .with(tracing_subscriber::fmt::layer())
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
         .init();
 
     match cli.command {
@@ -184,23 +243,47 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
         Commands::Channel { action } => match action {
             ChannelCommands::List => {
-                println!("Channel list implementation pending (Agent #8)");
+                match commands::channel_list(&cli.config) {
+                    Ok(out) => print!("{}", out),
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
             }
             ChannelCommands::Connect { channel_type } => {
-                println!("Connecting channel: {}", channel_type);
-                println!("Channel implementation pending (Agent #8)");
+                match commands::channel_connect(&cli.config, &channel_type).await {
+                    Ok(out) => print!("{}", out),
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
             }
             ChannelCommands::Disconnect { name } => {
-                println!("Disconnecting channel: {}", name);
+                match commands::channel_disconnect(&cli.config, &name).await {
+                    Ok(out) => print!("{}", out),
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
             }
             ChannelCommands::Status { name } => {
-                println!("Status for channel: {}", name);
+                match commands::channel_status(&cli.config, &name).await {
+                    Ok(out) => print!("{}", out),
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
             }
         },
 
         Commands::Skill { action } => match action {
-            SkillCommands::List => {
-                println!("Skill list implementation pending (Agent #9)");
+            SkillCommands::List { query } => {
+                let registry = commands::open_registry(&cli.config)?;
+                print!("{}", commands::skill_list(&registry, query.as_deref()));
             }
             SkillCommands::Install { source } => {
                 println!("Installing skill from: {}", source);
@@ -209,11 +292,64 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             SkillCommands::Uninstall { skill } => {
                 println!("Uninstalling skill: {}", skill);
             }
-            SkillCommands::Verify { skill } => {
-                println!("Verifying skill: {}", skill);
+            SkillCommands::Verify { skill, format } => {
+                let registry = commands::open_registry(&cli.config)?;
+                let rendered = commands::skill_verify(&registry, &skill).and_then(|result| {
+                    commands::render_verification(&result, format).map(|out| (out, result.safe))
+                });
+                match rendered {
+                    Ok((out, safe)) => {
+                        print!("{}", out);
+                        if !safe {
+                            std::process::exit(1);
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            SkillCommands::Check { source, format } => {
+                let registry = commands::open_registry(&cli.config)?;
+                let rendered = commands::skill_check(&registry, &source).and_then(|result| {
+                    commands::render_verification(&result, format).map(|out| (out, result.safe))
+                });
+                match rendered {
+                    Ok((out, safe)) => {
+                        print!("{}", out);
+                        if !safe {
+                            std::process::exit(1);
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
             }
-            SkillCommands::Info { skill } => {
-                println!("Info for skill: {}", skill);
+            SkillCommands::Info { skill, output } => {
+                let registry = commands::open_registry(&cli.config)?;
+                match commands::skill_info(&registry, &skill, output) {
+                    Ok(info) => print!("{}", info),
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            SkillCommands::New { name, inputs, outputs, dir } => {
+                let dir = dir.unwrap_or_else(|| commands::skills_dir(&cli.config));
+                match commands::skill_new(&dir, &name, &inputs, &outputs) {
+                    Ok(path) => {
+                        println!("Created skill '{}' at {}", name, path.display());
+                        println!("Verification: safe");
+                    }
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
             }
         },
 
@@ -285,11 +421,36 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             println!("  3. Run: zero-openclaw gateway");
         }
 
-        Commands::Verify { pca_file } => {
-            println!("Verifying proof-carrying action: {:?}", pca_file);
-            println!();
-            println!("PCA verification implementation pending (Agent #7)");
+        Commands::Verify { pca_file, public_key, pubkey, format } => {
+            let key = match (&public_key, &pubkey) {
+                (Some(path), _) => Some(commands::PublicKeyArg::File(path)),
+                (None, Some(hex)) => Some(commands::PublicKeyArg::Hex(hex)),
+                (None, None) => None,
+            };
+            let rendered = commands::pca_verify(&pca_file, key, Some(&cli.config)).and_then(|result| {
+                commands::render_pca_verification(&result, format).map(|out| (out, result.passed()))
+            });
+            match rendered {
+                Ok((out, passed)) => {
+                    print!("{}", out);
+                    if !passed {
+                        std::process::exit(1);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
         }
+
+        Commands::Ask { message } => match commands::ask(&message).await {
+            Ok(reply) => println!("{}", reply),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        },
     }
 
     Ok(())
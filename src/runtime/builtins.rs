@@ -3,9 +3,11 @@
 //! These are the primitive operations that can be used in 0-lang graphs.
 //! All complex logic should be built by composing these primitives.
 
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
 use async_trait::async_trait;
+use ed25519_dalek::{Signature, Signer as _, SigningKey, Verifier as _, VerifyingKey};
+use super::state::{MemoryStateBackend, StateBackend};
 use super::types::Value;
 use crate::error::GatewayError;
 
@@ -26,6 +28,135 @@ pub trait BuiltinOp: Send + Sync {
     fn description(&self) -> &str {
         ""
     }
+
+    /// Steps of the execution budget this call consumes.
+    ///
+    /// Defaults to one; operations whose work grows with their inputs
+    /// charge more.
+    fn step_cost(&self, _inputs: &[Value]) -> u64 {
+        1
+    }
+
+    /// Whether the result depends only on the inputs and parameters.
+    ///
+    /// Graphs using impure operations are never served from the result cache.
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    /// Whether the operation reads or writes interpreter state.
+    ///
+    /// The interpreter passes such operations the execution's namespace
+    /// as the `state_namespace` param, replacing any value from the graph.
+    fn uses_state(&self) -> bool {
+        false
+    }
+
+    /// Names of the outputs of a multi-output operation.
+    ///
+    /// Such operations return a map with an entry per name, and downstream
+    /// nodes read each one as `node_id.name`. Empty for single-output ops.
+    fn outputs(&self) -> &[&str] {
+        &[]
+    }
+}
+
+/// Name of the param carrying an execution's state namespace.
+pub const STATE_NAMESPACE_PARAM: &str = "state_namespace";
+
+/// Separator between a state namespace and the keys within it.
+const STATE_NAMESPACE_SEPARATOR: char = '/';
+
+/// State key for `key` within the namespace in `params`, if any.
+///
+/// Outside a namespace, keys may not contain the separator, so they
+/// cannot address state saved within one.
+fn state_key(params: &serde_json::Value, key: &str) -> Result<String, GatewayError> {
+    match params.get(STATE_NAMESPACE_PARAM).and_then(|ns| ns.as_str()) {
+        Some(namespace) => Ok(format!("{}{}{}", namespace, STATE_NAMESPACE_SEPARATOR, key)),
+        None if key.contains(STATE_NAMESPACE_SEPARATOR) => Err(GatewayError::ExecutionError(format!(
+            "State key '{}' contains the reserved namespace separator",
+            key
+        ))),
+        None => Ok(key.to_string()),
+    }
+}
+
+/// One step plus one per KiB of input data.
+fn size_cost(inputs: &[Value]) -> u64 {
+    let bytes: usize = inputs
+        .iter()
+        .map(|input| match input {
+            Value::String(s) => s.len(),
+            Value::Bytes(b) => b.len(),
+            other => other.canonical_bytes().len(),
+        })
+        .sum();
+    1 + (bytes / 1024) as u64
+}
+
+/// Maximum number of compiled patterns a [`RegexCache`] keeps.
+pub(crate) const MAX_CACHED_REGEXES: usize = 256;
+
+/// Compiled regular expressions, keyed by pattern.
+///
+/// Holds at most `capacity` patterns, dropping the oldest compiled first.
+pub(crate) struct RegexCache {
+    compiled: RwLock<CompiledRegexes>,
+    capacity: usize,
+}
+
+#[derive(Default)]
+struct CompiledRegexes {
+    by_pattern: HashMap<String, Arc<regex::Regex>>,
+    /// Patterns in compilation order, oldest first
+    order: VecDeque<String>,
+}
+
+impl Default for RegexCache {
+    fn default() -> Self {
+        Self::with_capacity(MAX_CACHED_REGEXES)
+    }
+}
+
+impl RegexCache {
+    /// Create a cache keeping at most `capacity` patterns.
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self { compiled: RwLock::new(CompiledRegexes::default()), capacity }
+    }
+
+    /// Compile `pattern`, reusing an earlier compilation.
+    pub(crate) fn get(&self, pattern: &str) -> Result<Arc<regex::Regex>, GatewayError> {
+        if let Some(regex) = self.compiled.read().unwrap().by_pattern.get(pattern) {
+            return Ok(regex.clone());
+        }
+        let regex = Arc::new(regex::Regex::new(pattern).map_err(|e| {
+            GatewayError::ExecutionError(format!("Invalid regex {}: {}", pattern, e))
+        })?);
+        let mut compiled = self.compiled.write().unwrap();
+        if compiled.by_pattern.insert(pattern.to_string(), regex.clone()).is_none() {
+            compiled.order.push_back(pattern.to_string());
+        }
+        while compiled.order.len() > self.capacity {
+            let Some(oldest) = compiled.order.pop_front() else { break };
+            compiled.by_pattern.remove(&oldest);
+        }
+        Ok(regex)
+    }
+
+    /// Number of compiled patterns held.
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.compiled.read().unwrap().by_pattern.len()
+    }
+
+    /// Compile the regex in `params.pattern`.
+    fn get_param(&self, params: &serde_json::Value) -> Result<Arc<regex::Regex>, GatewayError> {
+        let pattern = params.get("pattern").and_then(|v| v.as_str()).ok_or_else(|| {
+            GatewayError::ExecutionError("Missing regex pattern param".to_string())
+        })?;
+        self.get(pattern)
+    }
 }
 
 /// Registry of built-in operations.
@@ -35,45 +166,67 @@ pub struct BuiltinRegistry {
 
 impl BuiltinRegistry {
     /// Create a new registry with all standard builtins.
+    ///
+    /// State ops use a private in-memory backend.
     pub fn new() -> Self {
+        Self::with_state_backend(Arc::new(MemoryStateBackend::new()))
+    }
+
+    /// Create a registry whose state ops use the given backend.
+    pub fn with_state_backend(state: Arc<dyn StateBackend>) -> Self {
         let mut registry = Self {
             ops: HashMap::new(),
         };
         
         // Register all builtins
         registry.register(Arc::new(IdentityOp));
+        registry.register(Arc::new(HttpOp { name: HTTP_GET_OP }));
+        registry.register(Arc::new(HttpOp { name: HTTP_POST_OP }));
+        registry.register(Arc::new(WaitOp));
         registry.register(Arc::new(StartsWithOp));
         registry.register(Arc::new(EndsWithOp));
         registry.register(Arc::new(ContainsOp));
+        let regexes = Arc::new(RegexCache::default());
+        registry.register(Arc::new(RegexMatchOp { regexes: regexes.clone() }));
+        registry.register(Arc::new(RegexCaptureOp { regexes }));
         registry.register(Arc::new(ExtractFirstWordOp));
         registry.register(Arc::new(ExtractParamsOp));
+        registry.register(Arc::new(ParseCommandOp));
         registry.register(Arc::new(ConcatOp));
         registry.register(Arc::new(SplitOp));
+        registry.register(Arc::new(JoinOp));
         registry.register(Arc::new(TrimOp));
         registry.register(Arc::new(ToLowerOp));
         registry.register(Arc::new(ToUpperOp));
         registry.register(Arc::new(LengthOp));
+        registry.register(Arc::new(ToIntOp));
+        registry.register(Arc::new(ToFloatOp));
+        registry.register(Arc::new(ToBoolOp));
+        registry.register(Arc::new(ToStringOp));
         registry.register(Arc::new(GetFieldOp));
         registry.register(Arc::new(SetFieldOp));
         registry.register(Arc::new(MultiplyOp));
         registry.register(Arc::new(AddOp));
         registry.register(Arc::new(SubtractOp));
         registry.register(Arc::new(DivideOp));
+        registry.register(Arc::new(SafeDivideOp));
         registry.register(Arc::new(EqualsOp));
         registry.register(Arc::new(NotEqualsOp));
         registry.register(Arc::new(GreaterThanOp));
         registry.register(Arc::new(LessThanOp));
+        registry.register(Arc::new(SimilarityOp));
         registry.register(Arc::new(AndOp));
         registry.register(Arc::new(OrOp));
         registry.register(Arc::new(NotOp));
         registry.register(Arc::new(IfOp));
         registry.register(Arc::new(HashOp));
-        registry.register(Arc::new(SignOp));
+        registry.register(Arc::new(SignOp { keystore: Arc::new(Keystore::new()), dev_mode: false }));
         registry.register(Arc::new(VerifyOp));
         registry.register(Arc::new(TimestampOp));
+        registry.register(Arc::new(ParseDurationOp));
         registry.register(Arc::new(ClassifyIntentOp));
-        registry.register(Arc::new(LoadStateOp));
-        registry.register(Arc::new(SaveStateOp));
+        registry.register(Arc::new(LoadStateOp { state: state.clone() }));
+        registry.register(Arc::new(SaveStateOp { state }));
         registry.register(Arc::new(CreateMapOp));
         registry.register(Arc::new(MergeMapOp));
         registry.register(Arc::new(ArrayPushOp));
@@ -82,6 +235,15 @@ impl BuiltinRegistry {
         registry
     }
     
+    /// Make the keys in `keystore` available to `Sign` by name.
+    ///
+    /// With `dev_mode`, `Sign` also accepts a raw `seed` param. It comes
+    /// from the runtime's configuration, never from the graph being run.
+    pub fn with_keystore(mut self, keystore: Keystore, dev_mode: bool) -> Self {
+        self.register(Arc::new(SignOp { keystore: Arc::new(keystore), dev_mode }));
+        self
+    }
+
     /// Register a builtin operation.
     pub fn register(&mut self, op: Arc<dyn BuiltinOp>) {
         self.ops.insert(op.name().to_string(), op);
@@ -124,6 +286,42 @@ impl BuiltinOp for IdentityOp {
     fn description(&self) -> &str { "Returns the input unchanged" }
 }
 
+/// Name of the HTTP GET operation; its first input is the URL.
+pub const HTTP_GET_OP: &str = "HttpGet";
+/// Name of the HTTP POST operation; its inputs are the URL and the body.
+pub const HTTP_POST_OP: &str = "HttpPost";
+
+/// HTTP operation without a resolver to serve it.
+///
+/// The interpreter performs the request when it has an external resolver
+/// for the URL; otherwise the URL passes through, as for `Identity`.
+struct HttpOp {
+    name: &'static str,
+}
+
+#[async_trait]
+impl BuiltinOp for HttpOp {
+    async fn execute(&self, inputs: Vec<Value>, _params: &serde_json::Value) -> Result<Value, GatewayError> {
+        Ok(inputs.into_iter().next().unwrap_or(Value::Null))
+    }
+    fn name(&self) -> &str { self.name }
+    fn description(&self) -> &str { "Performs an HTTP request through the external resolver" }
+    fn is_pure(&self) -> bool { false }
+}
+
+struct WaitOp;
+
+#[async_trait]
+impl BuiltinOp for WaitOp {
+    async fn execute(&self, inputs: Vec<Value>, params: &serde_json::Value) -> Result<Value, GatewayError> {
+        let ms = params.get("wait_ms").and_then(|v| v.as_u64()).unwrap_or(0);
+        tokio::time::sleep(std::time::Duration::from_millis(ms)).await;
+        Ok(inputs.into_iter().next().unwrap_or(Value::Null))
+    }
+    fn name(&self) -> &str { "Wait" }
+    fn description(&self) -> &str { "Waits for `wait_ms` milliseconds, then returns the input unchanged" }
+}
+
 struct StartsWithOp;
 
 #[async_trait]
@@ -160,6 +358,52 @@ impl BuiltinOp for ContainsOp {
     fn name(&self) -> &str { "Contains" }
 }
 
+/// Whether the input matches `params.pattern`.
+struct RegexMatchOp {
+    regexes: Arc<RegexCache>,
+}
+
+#[async_trait]
+impl BuiltinOp for RegexMatchOp {
+    async fn execute(&self, inputs: Vec<Value>, params: &serde_json::Value) -> Result<Value, GatewayError> {
+        let input = inputs.first().and_then(|v| v.as_string()).unwrap_or("");
+        Ok(Value::Bool(self.regexes.get_param(params)?.is_match(input)))
+    }
+    fn name(&self) -> &str { "RegexMatch" }
+    fn step_cost(&self, inputs: &[Value]) -> u64 { size_cost(inputs) }
+}
+
+/// Capture groups of the first match of `params.pattern`.
+///
+/// Yields a map of the named groups if the pattern has any, else an array
+/// of the positional groups, else null when the input does not match.
+/// Groups that did not participate in the match are null.
+struct RegexCaptureOp {
+    regexes: Arc<RegexCache>,
+}
+
+#[async_trait]
+impl BuiltinOp for RegexCaptureOp {
+    async fn execute(&self, inputs: Vec<Value>, params: &serde_json::Value) -> Result<Value, GatewayError> {
+        let input = inputs.first().and_then(|v| v.as_string()).unwrap_or("");
+        let regex = self.regexes.get_param(params)?;
+        let Some(captures) = regex.captures(input) else {
+            return Ok(Value::Null);
+        };
+        let group = |m: Option<regex::Match>| m.map_or(Value::Null, |m| Value::String(m.as_str().into()));
+        let names: Vec<&str> = regex.capture_names().flatten().collect();
+        if names.is_empty() {
+            Ok(Value::Array(captures.iter().skip(1).map(group).collect()))
+        } else {
+            Ok(Value::Map(
+                names.into_iter().map(|name| (name.to_string(), group(captures.name(name)))).collect(),
+            ))
+        }
+    }
+    fn name(&self) -> &str { "RegexCapture" }
+    fn step_cost(&self, inputs: &[Value]) -> u64 { size_cost(inputs) }
+}
+
 struct ExtractFirstWordOp;
 
 #[async_trait]
@@ -167,11 +411,30 @@ impl BuiltinOp for ExtractFirstWordOp {
     async fn execute(&self, inputs: Vec<Value>, _params: &serde_json::Value) -> Result<Value, GatewayError> {
         let input = inputs.first().and_then(|v| v.as_string()).unwrap_or("");
         let first_word = input.split_whitespace().next().unwrap_or("");
-        Ok(Value::String(first_word.to_string()))
+        Ok(Value::String(first_word.into()))
     }
     fn name(&self) -> &str { "ExtractFirstWord" }
 }
 
+/// Splits a command line into its `command` and the array of its `args`.
+struct ParseCommandOp;
+
+#[async_trait]
+impl BuiltinOp for ParseCommandOp {
+    async fn execute(&self, inputs: Vec<Value>, _params: &serde_json::Value) -> Result<Value, GatewayError> {
+        let input = inputs.first().and_then(|v| v.as_string()).unwrap_or("");
+        let mut words = input.split_whitespace();
+        let command = words.next().unwrap_or("");
+        let args: Vec<Value> = words.map(|s| Value::String(s.into())).collect();
+        let mut outputs = HashMap::new();
+        outputs.insert("command".to_string(), Value::String(command.into()));
+        outputs.insert("args".to_string(), Value::Array(args));
+        Ok(Value::Map(outputs))
+    }
+    fn name(&self) -> &str { "ParseCommand" }
+    fn outputs(&self) -> &[&str] { &["command", "args"] }
+}
+
 struct ExtractParamsOp;
 
 #[async_trait]
@@ -179,7 +442,7 @@ impl BuiltinOp for ExtractParamsOp {
     async fn execute(&self, inputs: Vec<Value>, _params: &serde_json::Value) -> Result<Value, GatewayError> {
         let input = inputs.first().and_then(|v| v.as_string()).unwrap_or("");
         let words: Vec<&str> = input.split_whitespace().skip(1).collect();
-        let params: Vec<Value> = words.iter().map(|w| Value::String(w.to_string())).collect();
+        let params: Vec<Value> = words.iter().map(|w| Value::String((*w).into())).collect();
         Ok(Value::Array(params))
     }
     fn name(&self) -> &str { "ExtractParams" }
@@ -194,7 +457,7 @@ impl BuiltinOp for ConcatOp {
         let strings: Vec<String> = inputs.iter()
             .filter_map(|v| v.as_string().map(|s| s.to_string()))
             .collect();
-        Ok(Value::String(strings.join(separator)))
+        Ok(Value::String(strings.join(separator).into()))
     }
     fn name(&self) -> &str { "Concat" }
 }
@@ -206,21 +469,42 @@ impl BuiltinOp for SplitOp {
     async fn execute(&self, inputs: Vec<Value>, params: &serde_json::Value) -> Result<Value, GatewayError> {
         let input = inputs.first().and_then(|v| v.as_string()).unwrap_or("");
         let separator = params.get("separator").and_then(|v| v.as_str()).unwrap_or(" ");
-        let parts: Vec<Value> = input.split(separator)
-            .map(|s| Value::String(s.to_string()))
-            .collect();
+        // A limit of 0 (or none) splits on every separator
+        let limit = params.get("limit").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+        let parts: Vec<Value> = if limit == 0 {
+            input.split(separator).map(|s| Value::String(s.into())).collect()
+        } else {
+            input.splitn(limit, separator).map(|s| Value::String(s.into())).collect()
+        };
         Ok(Value::Array(parts))
     }
     fn name(&self) -> &str { "Split" }
 }
 
+struct JoinOp;
+
+#[async_trait]
+impl BuiltinOp for JoinOp {
+    async fn execute(&self, inputs: Vec<Value>, params: &serde_json::Value) -> Result<Value, GatewayError> {
+        let separator = params.get("separator").and_then(|v| v.as_str()).unwrap_or("");
+        // Join the elements of an array input, or the inputs themselves
+        let items = match inputs.first() {
+            Some(Value::Array(items)) => items.as_slice(),
+            _ => inputs.as_slice(),
+        };
+        let strings: Vec<&str> = items.iter().filter_map(|v| v.as_string()).collect();
+        Ok(Value::String(strings.join(separator).into()))
+    }
+    fn name(&self) -> &str { "Join" }
+}
+
 struct TrimOp;
 
 #[async_trait]
 impl BuiltinOp for TrimOp {
     async fn execute(&self, inputs: Vec<Value>, _params: &serde_json::Value) -> Result<Value, GatewayError> {
         let input = inputs.first().and_then(|v| v.as_string()).unwrap_or("");
-        Ok(Value::String(input.trim().to_string()))
+        Ok(Value::String(input.trim().into()))
     }
     fn name(&self) -> &str { "Trim" }
 }
@@ -231,7 +515,7 @@ struct ToLowerOp;
 impl BuiltinOp for ToLowerOp {
     async fn execute(&self, inputs: Vec<Value>, _params: &serde_json::Value) -> Result<Value, GatewayError> {
         let input = inputs.first().and_then(|v| v.as_string()).unwrap_or("");
-        Ok(Value::String(input.to_lowercase()))
+        Ok(Value::String(input.to_lowercase().into()))
     }
     fn name(&self) -> &str { "ToLower" }
 }
@@ -242,7 +526,7 @@ struct ToUpperOp;
 impl BuiltinOp for ToUpperOp {
     async fn execute(&self, inputs: Vec<Value>, _params: &serde_json::Value) -> Result<Value, GatewayError> {
         let input = inputs.first().and_then(|v| v.as_string()).unwrap_or("");
-        Ok(Value::String(input.to_uppercase()))
+        Ok(Value::String(input.to_uppercase().into()))
     }
     fn name(&self) -> &str { "ToUpper" }
 }
@@ -308,7 +592,7 @@ impl BuiltinOp for CreateMapOp {
         let mut map = HashMap::new();
         if let Some(obj) = params.as_object() {
             for (k, v) in obj {
-                map.insert(k.clone(), json_to_value(v));
+                map.insert(k.clone(), Value::from_json(v));
             }
         }
         Ok(Value::Map(map))
@@ -367,9 +651,118 @@ impl BuiltinOp for ArrayGetOp {
     fn name(&self) -> &str { "ArrayGet" }
 }
 
+// ============================================================================
+// Coercion Operations
+// ============================================================================
+
+/// Result for an input that cannot be converted.
+///
+/// With `strict` (the default) this is an error; otherwise the value is `Null`.
+fn coercion_failure(input: &Value, target: &str, params: &serde_json::Value) -> Result<Value, GatewayError> {
+    if params.get("strict").and_then(|v| v.as_bool()).unwrap_or(true) {
+        Err(GatewayError::ExecutionError(format!(
+            "Cannot convert {} to {}",
+            input.to_canonical_json(),
+            target
+        )))
+    } else {
+        Ok(Value::Null)
+    }
+}
+
+struct ToIntOp;
+
+#[async_trait]
+impl BuiltinOp for ToIntOp {
+    async fn execute(&self, inputs: Vec<Value>, params: &serde_json::Value) -> Result<Value, GatewayError> {
+        let input = inputs.into_iter().next().unwrap_or(Value::Null);
+        // Floats are accepted only when integral, so "5.0" and 5.0 convert but 5.5 does not
+        let integral = |f: f64| (f.is_finite() && f.fract() == 0.0).then_some(f as i64);
+        let converted = match &input {
+            Value::Int(i) => Some(*i),
+            Value::Bool(b) => Some(*b as i64),
+            Value::Float(f) | Value::Confidence(f) => integral(*f),
+            Value::String(s) => {
+                let s = s.trim();
+                s.parse().ok().or_else(|| s.parse().ok().and_then(integral))
+            }
+            _ => None,
+        };
+        match converted {
+            Some(i) => Ok(Value::Int(i)),
+            None => coercion_failure(&input, "Int", params),
+        }
+    }
+    fn name(&self) -> &str { "ToInt" }
+}
+
+struct ToFloatOp;
+
+#[async_trait]
+impl BuiltinOp for ToFloatOp {
+    async fn execute(&self, inputs: Vec<Value>, params: &serde_json::Value) -> Result<Value, GatewayError> {
+        let input = inputs.into_iter().next().unwrap_or(Value::Null);
+        let converted = match &input {
+            Value::Bool(b) => Some(*b as i64 as f64),
+            Value::String(s) => s.trim().parse::<f64>().ok().filter(|f| f.is_finite()),
+            other => other.as_float(),
+        };
+        match converted {
+            Some(f) => Ok(Value::Float(f)),
+            None => coercion_failure(&input, "Float", params),
+        }
+    }
+    fn name(&self) -> &str { "ToFloat" }
+}
+
+struct ToBoolOp;
+
+#[async_trait]
+impl BuiltinOp for ToBoolOp {
+    async fn execute(&self, inputs: Vec<Value>, params: &serde_json::Value) -> Result<Value, GatewayError> {
+        let input = inputs.into_iter().next().unwrap_or(Value::Null);
+        let converted = match &input {
+            Value::Bool(b) => Some(*b),
+            Value::Int(i) => Some(*i != 0),
+            Value::Float(f) => Some(*f != 0.0),
+            Value::String(s) => match s.trim().to_lowercase().as_str() {
+                "true" | "yes" | "on" | "1" => Some(true),
+                "false" | "no" | "off" | "0" => Some(false),
+                _ => None,
+            },
+            _ => None,
+        };
+        match converted {
+            Some(b) => Ok(Value::Bool(b)),
+            None => coercion_failure(&input, "Bool", params),
+        }
+    }
+    fn name(&self) -> &str { "ToBool" }
+}
+
+struct ToStringOp;
+
+#[async_trait]
+impl BuiltinOp for ToStringOp {
+    async fn execute(&self, inputs: Vec<Value>, _params: &serde_json::Value) -> Result<Value, GatewayError> {
+        // Every value has a string form, so this never fails
+        let string: Arc<str> = match inputs.first() {
+            None | Some(Value::Null) => "".into(),
+            Some(Value::String(s)) => s.clone(),
+            Some(other) => other.to_canonical_json().to_string().into(),
+        };
+        Ok(Value::String(string))
+    }
+    fn name(&self) -> &str { "ToString" }
+}
+
 // ============================================================================
 // Math Operations
 // ============================================================================
+//
+// Math and comparison ops require numeric inputs: strings are not parsed, and
+// non-numeric inputs fall back to the op's default operand. Convert channel
+// input with ToInt/ToFloat first.
 
 struct MultiplyOp;
 
@@ -430,6 +823,22 @@ impl BuiltinOp for DivideOp {
     fn name(&self) -> &str { "Divide" }
 }
 
+/// Division that yields the `default` param (0.0 when unset) on a zero denominator.
+struct SafeDivideOp;
+
+#[async_trait]
+impl BuiltinOp for SafeDivideOp {
+    async fn execute(&self, inputs: Vec<Value>, params: &serde_json::Value) -> Result<Value, GatewayError> {
+        let first = inputs.first().and_then(|v| v.as_float()).unwrap_or(0.0);
+        let second = inputs.get(1).and_then(|v| v.as_float()).unwrap_or(1.0);
+        if second == 0.0 {
+            return Ok(params.get("default").map(Value::from_json).unwrap_or(Value::Float(0.0)));
+        }
+        Ok(Value::Float(first / second))
+    }
+    fn name(&self) -> &str { "SafeDivide" }
+}
+
 // ============================================================================
 // Comparison Operations
 // ============================================================================
@@ -482,6 +891,72 @@ impl BuiltinOp for LessThanOp {
     fn name(&self) -> &str { "LessThan" }
 }
 
+/// Levenshtein edit distance between two strings, counted in characters.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut curr = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == cb { 0 } else { 1 };
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        prev = curr;
+    }
+    prev[b.len()]
+}
+
+struct SimilarityOp;
+
+impl SimilarityOp {
+    /// Levenshtein distance normalized to a 0.0-1.0 similarity.
+    fn levenshtein(a: &str, b: &str) -> f64 {
+        let max_len = a.chars().count().max(b.chars().count());
+        if max_len == 0 {
+            return 1.0;
+        }
+        1.0 - edit_distance(a, b) as f64 / max_len as f64
+    }
+
+    /// Jaccard similarity of the whitespace-separated word sets.
+    fn jaccard(a: &str, b: &str) -> f64 {
+        let a: std::collections::HashSet<&str> = a.split_whitespace().collect();
+        let b: std::collections::HashSet<&str> = b.split_whitespace().collect();
+        let union = a.union(&b).count();
+        if union == 0 {
+            return 1.0;
+        }
+        a.intersection(&b).count() as f64 / union as f64
+    }
+}
+
+#[async_trait]
+impl BuiltinOp for SimilarityOp {
+    async fn execute(&self, inputs: Vec<Value>, params: &serde_json::Value) -> Result<Value, GatewayError> {
+        let first = inputs.first().and_then(|v| v.as_string()).unwrap_or("").trim().to_lowercase();
+        let second = inputs.get(1).and_then(|v| v.as_string()).unwrap_or("").trim().to_lowercase();
+        let threshold = params.get("threshold").and_then(|v| v.as_f64()).unwrap_or(0.9);
+
+        let similarity = match params.get("metric").and_then(|v| v.as_str()).unwrap_or("levenshtein") {
+            "levenshtein" => Self::levenshtein(&first, &second),
+            "jaccard" => Self::jaccard(&first, &second),
+            other => {
+                return Err(GatewayError::ExecutionError(format!(
+                    "Unknown similarity metric: {}",
+                    other
+                )));
+            }
+        };
+
+        Ok(Value::Bool(similarity >= threshold))
+    }
+    fn name(&self) -> &str { "Similarity" }
+    fn step_cost(&self, inputs: &[Value]) -> u64 { size_cost(inputs) }
+    fn description(&self) -> &str { "Returns whether two strings are at least `threshold` similar" }
+}
+
 // ============================================================================
 // Logic Operations
 // ============================================================================
@@ -536,6 +1011,8 @@ impl BuiltinOp for IfOp {
 // Crypto Operations
 // ============================================================================
 
+/// SHA-256 over the canonical encoding of every input, so inputs of
+/// different types, or split differently, never hash alike.
 struct HashOp;
 
 #[async_trait]
@@ -544,12 +1021,7 @@ impl BuiltinOp for HashOp {
         use sha2::{Sha256, Digest};
         let mut hasher = Sha256::new();
         for input in inputs {
-            let bytes = match input {
-                Value::String(s) => s.into_bytes(),
-                Value::Bytes(b) => b,
-                other => serde_json::to_vec(&other).unwrap_or_default(),
-            };
-            hasher.update(&bytes);
+            hasher.update(input.canonical_bytes());
         }
         let result = hasher.finalize();
         let mut hash = [0u8; 32];
@@ -557,42 +1029,133 @@ impl BuiltinOp for HashOp {
         Ok(Value::Hash(hash))
     }
     fn name(&self) -> &str { "Hash" }
+    fn step_cost(&self, inputs: &[Value]) -> u64 { size_cost(inputs) }
+}
+
+/// Named ed25519 signing keys available to the `Sign` op.
+#[derive(Default)]
+pub struct Keystore {
+    keys: HashMap<String, SigningKey>,
 }
 
-struct SignOp;
+impl Keystore {
+    /// Create an empty keystore.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a key under `name`, built from a 32-byte seed.
+    pub fn with_key(mut self, name: &str, seed: [u8; 32]) -> Self {
+        self.keys.insert(name.to_string(), SigningKey::from_bytes(&seed));
+        self
+    }
+
+    /// Load a keystore from a JSON file mapping key names to hex-encoded
+    /// 32-byte seeds.
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, GatewayError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            GatewayError::ConfigError(format!("Cannot read keystore {}: {}", path.display(), e))
+        })?;
+        let seeds: HashMap<String, String> = serde_json::from_str(&content).map_err(|e| {
+            GatewayError::ConfigError(format!("Malformed keystore {}: {}", path.display(), e))
+        })?;
+        seeds.into_iter().try_fold(Self::new(), |keystore, (name, seed)| {
+            let seed: [u8; 32] = hex::decode(seed.trim())
+                .ok()
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or_else(|| GatewayError::ConfigError(format!(
+                    "Keystore key '{}' must be 32 hex-encoded bytes",
+                    name
+                )))?;
+            Ok(keystore.with_key(&name, seed))
+        })
+    }
+}
+
+/// Raw bytes of a `Bytes` value, or of a hex-encoded `String`.
+fn key_bytes(value: &Value) -> Option<Vec<u8>> {
+    match value {
+        Value::Bytes(bytes) => Some(bytes.clone()),
+        Value::String(s) => hex::decode(s.as_bytes()).ok(),
+        _ => None,
+    }
+}
+
+/// Signs the canonical bytes of its input with ed25519.
+///
+/// The key is given by the `key` param naming a keystore entry. A raw
+/// `seed` param (hex-encoded 32 bytes) is only accepted when the runtime
+/// runs in dev mode, since skill files are shared and show up in traces.
+struct SignOp {
+    keystore: Arc<Keystore>,
+    dev_mode: bool,
+}
+
+impl SignOp {
+    fn signing_key(&self, params: &serde_json::Value) -> Result<SigningKey, GatewayError> {
+        if let Some(seed) = params.get("seed").and_then(|v| v.as_str()) {
+            if !self.dev_mode {
+                return Err(GatewayError::ExecutionError(
+                    "Sign `seed` is only accepted when the runtime's dev_mode is on; use a keystore `key`"
+                        .to_string(),
+                ));
+            }
+            let seed: [u8; 32] = hex::decode(seed)
+                .ok()
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or_else(|| GatewayError::ExecutionError(
+                    "Sign `seed` must be 32 hex-encoded bytes".to_string(),
+                ))?;
+            return Ok(SigningKey::from_bytes(&seed));
+        }
+        if let Some(name) = params.get("key").and_then(|v| v.as_str()) {
+            return self.keystore.keys.get(name).cloned().ok_or_else(|| {
+                GatewayError::ExecutionError(format!("Sign key '{}' not in keystore", name))
+            });
+        }
+        Err(GatewayError::ExecutionError(
+            "Sign requires a `key` parameter".to_string(),
+        ))
+    }
+}
 
 #[async_trait]
 impl BuiltinOp for SignOp {
     async fn execute(&self, inputs: Vec<Value>, params: &serde_json::Value) -> Result<Value, GatewayError> {
-        // Simplified signing - in production, use proper key management
+        let key = self.signing_key(params)?;
         let message = inputs.first().cloned().unwrap_or(Value::Null);
-        let message_bytes = serde_json::to_vec(&message).unwrap_or_default();
-        
-        // For now, return a placeholder signature
-        // Real implementation would use ed25519-dalek
-        let mut signature = [0u8; 64];
-        use sha2::{Sha256, Digest};
-        let hash = Sha256::digest(&message_bytes);
-        signature[..32].copy_from_slice(&hash);
-        
-        Ok(Value::Bytes(signature.to_vec()))
+        let signature = key.sign(&message.canonical_bytes());
+        Ok(Value::Bytes(signature.to_bytes().to_vec()))
     }
     fn name(&self) -> &str { "Sign" }
+    fn step_cost(&self, inputs: &[Value]) -> u64 { size_cost(inputs) }
 }
 
+/// Checks an ed25519 signature: inputs are `(message, signature, public_key)`.
+///
+/// Malformed signatures or keys verify as `false`.
 struct VerifyOp;
 
 #[async_trait]
 impl BuiltinOp for VerifyOp {
     async fn execute(&self, inputs: Vec<Value>, _params: &serde_json::Value) -> Result<Value, GatewayError> {
-        // Simplified verification
-        let _message = inputs.first().cloned().unwrap_or(Value::Null);
-        let _signature = inputs.get(1).cloned().unwrap_or(Value::Null);
-        
-        // Real implementation would verify ed25519 signature
-        Ok(Value::Bool(true))
+        let message = inputs.first().cloned().unwrap_or(Value::Null);
+        let signature = inputs.get(1).and_then(key_bytes)
+            .and_then(|bytes| <[u8; 64]>::try_from(bytes).ok())
+            .map(|bytes| Signature::from_bytes(&bytes));
+        let public_key = inputs.get(2).and_then(key_bytes)
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok());
+
+        let valid = match (signature, public_key) {
+            (Some(signature), Some(key)) => key.verify(&message.canonical_bytes(), &signature).is_ok(),
+            _ => false,
+        };
+        Ok(Value::Bool(valid))
     }
     fn name(&self) -> &str { "Verify" }
+    fn step_cost(&self, inputs: &[Value]) -> u64 { size_cost(inputs) }
 }
 
 // ============================================================================
@@ -612,6 +1175,52 @@ impl BuiltinOp for TimestampOp {
         Ok(Value::Int(timestamp))
     }
     fn name(&self) -> &str { "Timestamp" }
+    fn is_pure(&self) -> bool { false }
+}
+
+/// Parse a duration such as `90s`, `10m`, `2h` or `1h30m` into milliseconds.
+///
+/// Units are `ms`, `s`, `m`, `h`, `d` and `w`. Returns `None` for malformed
+/// or empty input.
+pub fn parse_duration_ms(input: &str) -> Option<u64> {
+    let input = input.trim().to_ascii_lowercase();
+    if input.is_empty() {
+        return None;
+    }
+    let mut total: u64 = 0;
+    let mut rest = input.as_str();
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        let amount: u64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let unit_len = rest.find(|c: char| c.is_ascii_digit()).unwrap_or(rest.len());
+        let unit_ms = match &rest[..unit_len] {
+            "ms" => 1,
+            "s" => 1_000,
+            "m" => 60_000,
+            "h" => 3_600_000,
+            "d" => 86_400_000,
+            "w" => 604_800_000,
+            _ => return None,
+        };
+        rest = &rest[unit_len..];
+        total = total.checked_add(amount.checked_mul(unit_ms)?)?;
+    }
+    Some(total)
+}
+
+/// Parses a duration string into milliseconds; null when malformed.
+struct ParseDurationOp;
+
+#[async_trait]
+impl BuiltinOp for ParseDurationOp {
+    async fn execute(&self, inputs: Vec<Value>, _params: &serde_json::Value) -> Result<Value, GatewayError> {
+        let input = inputs.first().and_then(|v| v.as_string()).unwrap_or("");
+        Ok(parse_duration_ms(input)
+            .and_then(|ms| i64::try_from(ms).ok())
+            .map_or(Value::Null, Value::Int))
+    }
+    fn name(&self) -> &str { "ParseDuration" }
 }
 
 // ============================================================================
@@ -640,7 +1249,7 @@ impl BuiltinOp for ClassifyIntentOp {
             "statement"
         };
         
-        Ok(Value::String(intent.to_string()))
+        Ok(Value::String(intent.into()))
     }
     fn name(&self) -> &str { "ClassifyIntent" }
 }
@@ -649,62 +1258,44 @@ impl BuiltinOp for ClassifyIntentOp {
 // State Operations
 // ============================================================================
 
-struct LoadStateOp;
+struct LoadStateOp {
+    state: Arc<dyn StateBackend>,
+}
 
 #[async_trait]
 impl BuiltinOp for LoadStateOp {
-    async fn execute(&self, inputs: Vec<Value>, _params: &serde_json::Value) -> Result<Value, GatewayError> {
+    async fn execute(&self, inputs: Vec<Value>, params: &serde_json::Value) -> Result<Value, GatewayError> {
         let session_id = inputs.first().and_then(|v| v.as_string()).unwrap_or("");
-        // In production, this would load from a state store
-        // For now, return an empty map
+        if let Some(state) = self.state.get(&state_key(params, session_id)?).await? {
+            return Ok(state);
+        }
+        // Fresh sessions start from a neutral default
         let mut state = HashMap::new();
-        state.insert("session_id".to_string(), Value::String(session_id.to_string()));
+        state.insert("session_id".to_string(), Value::String(session_id.into()));
         state.insert("trust_score".to_string(), Value::Confidence(0.5));
         state.insert("message_count".to_string(), Value::Int(0));
         Ok(Value::Map(state))
     }
     fn name(&self) -> &str { "LoadState" }
+    fn is_pure(&self) -> bool { false }
+    fn uses_state(&self) -> bool { true }
 }
 
-struct SaveStateOp;
+struct SaveStateOp {
+    state: Arc<dyn StateBackend>,
+}
 
 #[async_trait]
 impl BuiltinOp for SaveStateOp {
-    async fn execute(&self, inputs: Vec<Value>, _params: &serde_json::Value) -> Result<Value, GatewayError> {
-        let _session_id = inputs.first().and_then(|v| v.as_string()).unwrap_or("");
+    async fn execute(&self, inputs: Vec<Value>, params: &serde_json::Value) -> Result<Value, GatewayError> {
+        let session_id = inputs.first().and_then(|v| v.as_string()).unwrap_or("");
         let state = inputs.get(1).cloned().unwrap_or(Value::Null);
-        // In production, this would save to a state store
+        self.state.set(&state_key(params, session_id)?, state.clone()).await?;
         Ok(state)
     }
     fn name(&self) -> &str { "SaveState" }
-}
-
-// ============================================================================
-// Helpers
-// ============================================================================
-
-fn json_to_value(json: &serde_json::Value) -> Value {
-    match json {
-        serde_json::Value::Null => Value::Null,
-        serde_json::Value::Bool(b) => Value::Bool(*b),
-        serde_json::Value::Number(n) => {
-            if let Some(i) = n.as_i64() {
-                Value::Int(i)
-            } else if let Some(f) = n.as_f64() {
-                Value::Float(f)
-            } else {
-                Value::Null
-            }
-        }
-        serde_json::Value::String(s) => Value::String(s.clone()),
-        serde_json::Value::Array(a) => Value::Array(a.iter().map(json_to_value).collect()),
-        serde_json::Value::Object(o) => {
-            let map: HashMap<String, Value> = o.iter()
-                .map(|(k, v)| (k.clone(), json_to_value(v)))
-                .collect();
-            Value::Map(map)
-        }
-    }
+    fn is_pure(&self) -> bool { false }
+    fn uses_state(&self) -> bool { true }
 }
 
 #[cfg(test)]
@@ -714,15 +1305,15 @@ mod tests {
     #[tokio::test]
     async fn test_identity() {
         let op = IdentityOp;
-        let result = op.execute(vec![Value::String("hello".to_string())], &serde_json::json!({})).await.unwrap();
-        assert_eq!(result, Value::String("hello".to_string()));
+        let result = op.execute(vec![Value::String("hello".into())], &serde_json::json!({})).await.unwrap();
+        assert_eq!(result, Value::String("hello".into()));
     }
 
     #[tokio::test]
     async fn test_starts_with() {
         let op = StartsWithOp;
         let result = op.execute(
-            vec![Value::String("/help".to_string())],
+            vec![Value::String("/help".into())],
             &serde_json::json!({"prefix": "/"}),
         ).await.unwrap();
         assert_eq!(result, Value::Bool(true));
@@ -732,10 +1323,31 @@ mod tests {
     async fn test_extract_first_word() {
         let op = ExtractFirstWordOp;
         let result = op.execute(
-            vec![Value::String("/help me please".to_string())],
+            vec![Value::String("/help me please".into())],
             &serde_json::json!({}),
         ).await.unwrap();
-        assert_eq!(result, Value::String("/help".to_string()));
+        assert_eq!(result, Value::String("/help".into()));
+    }
+
+    #[tokio::test]
+    async fn test_coercion() {
+        let string = |s: &str| vec![Value::String(s.into())];
+        let params = serde_json::json!({});
+
+        assert_eq!(ToIntOp.execute(string("5"), &params).await.unwrap(), Value::Int(5));
+        assert_eq!(ToFloatOp.execute(string("2.75"), &params).await.unwrap(), Value::Float(2.75));
+        assert_eq!(ToBoolOp.execute(string("yes"), &params).await.unwrap(), Value::Bool(true));
+        assert_eq!(
+            ToStringOp.execute(vec![Value::Int(5)], &params).await.unwrap(),
+            Value::String("5".into())
+        );
+
+        assert!(ToIntOp.execute(string("five"), &params).await.is_err());
+        assert!(ToIntOp.execute(string("5.5"), &params).await.is_err());
+        assert_eq!(
+            ToFloatOp.execute(string("abc"), &serde_json::json!({"strict": false})).await.unwrap(),
+            Value::Null
+        );
     }
 
     #[tokio::test]
@@ -748,16 +1360,123 @@ mod tests {
         assert!((result.as_float().unwrap() - 0.72).abs() < 0.001);
     }
 
+    #[tokio::test]
+    async fn test_safe_divide() {
+        let params = serde_json::json!({"default": -1});
+
+        let ratio = SafeDivideOp.execute(vec![Value::Float(3.0), Value::Float(4.0)], &params).await.unwrap();
+        assert_eq!(ratio, Value::Float(0.75));
+
+        let zero = SafeDivideOp.execute(vec![Value::Float(3.0), Value::Int(0)], &params).await.unwrap();
+        assert_eq!(zero, Value::Int(-1));
+
+        assert!(DivideOp.execute(vec![Value::Float(3.0), Value::Int(0)], &params).await.is_err());
+    }
+
     #[tokio::test]
     async fn test_hash() {
         let op = HashOp;
         let result = op.execute(
-            vec![Value::String("hello".to_string())],
+            vec![Value::String("hello".into())],
             &serde_json::json!({}),
         ).await.unwrap();
         matches!(result, Value::Hash(_));
     }
 
+    #[tokio::test]
+    async fn test_split_limit_and_join() {
+        let input = vec![Value::String("a,b,c".into())];
+        let parts = SplitOp.execute(input.clone(), &serde_json::json!({"separator": ","})).await.unwrap();
+        assert!(matches!(&parts, Value::Array(items) if items.len() == 3));
+
+        let limited = SplitOp
+            .execute(input, &serde_json::json!({"separator": ",", "limit": 2}))
+            .await
+            .unwrap();
+        assert_eq!(
+            limited,
+            Value::Array(vec![Value::String("a".into()), Value::String("b,c".into())])
+        );
+
+        let joined = JoinOp.execute(vec![parts], &serde_json::json!({"separator": "-"})).await.unwrap();
+        assert_eq!(joined, Value::String("a-b-c".into()));
+    }
+
+    #[tokio::test]
+    async fn test_hash_distinguishes_int_and_float() {
+        async fn hash(value: Value) -> Value {
+            HashOp.execute(vec![value], &serde_json::json!({})).await.unwrap()
+        }
+
+        assert_eq!(hash(Value::Int(2)).await, hash(Value::Int(2)).await);
+        assert_eq!(hash(Value::Float(2.0)).await, hash(Value::Float(2.0)).await);
+        assert_ne!(hash(Value::Int(2)).await, hash(Value::Float(2.0)).await);
+        assert_ne!(hash(Value::Int(1)).await, hash(Value::String("1".into())).await);
+
+        let split = |parts: &[&str]| parts.iter().map(|p| Value::String((*p).into())).collect::<Vec<_>>();
+        let params = serde_json::json!({});
+        assert_ne!(
+            HashOp.execute(split(&["ab", "c"]), &params).await.unwrap(),
+            HashOp.execute(split(&["a", "bc"]), &params).await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_similarity() {
+        async fn similar(a: &str, b: &str, params: serde_json::Value) -> Value {
+            let inputs = vec![Value::String(a.into()), Value::String(b.into())];
+            SimilarityOp.execute(inputs, &params).await.unwrap()
+        }
+
+        // Identical strings match
+        assert_eq!(similar("buy now!!", "Buy now!!", serde_json::json!({})).await, Value::Bool(true));
+
+        // Wholly different strings do not
+        assert_eq!(similar("hello there", "quarterly report", serde_json::json!({})).await, Value::Bool(false));
+        assert_eq!(
+            similar("hello there", "quarterly report", serde_json::json!({"metric": "jaccard", "threshold": 0.1})).await,
+            Value::Bool(false)
+        );
+
+        // One edit in four characters is exactly 0.75 similar
+        assert_eq!(similar("abcd", "abce", serde_json::json!({"threshold": 0.75})).await, Value::Bool(true));
+        assert_eq!(similar("abcd", "abce", serde_json::json!({"threshold": 0.76})).await, Value::Bool(false));
+    }
+
+    #[tokio::test]
+    async fn test_state_ops_share_backend() {
+        let backend: Arc<dyn StateBackend> = Arc::new(MemoryStateBackend::new());
+        let registry = BuiltinRegistry::with_state_backend(backend.clone());
+        let session = Value::String("s1".into());
+
+        let fresh = registry.get("LoadState").unwrap()
+            .execute(vec![session.clone()], &serde_json::json!({})).await.unwrap();
+        assert_eq!(fresh.as_map().and_then(|m| m.get("message_count")), Some(&Value::Int(0)));
+
+        registry.get("SaveState").unwrap()
+            .execute(vec![session.clone(), Value::Int(7)], &serde_json::json!({})).await.unwrap();
+        assert_eq!(backend.get("s1").await.unwrap(), Some(Value::Int(7)));
+
+        let loaded = registry.get("LoadState").unwrap()
+            .execute(vec![session], &serde_json::json!({})).await.unwrap();
+        assert_eq!(loaded, Value::Int(7));
+    }
+
+    #[tokio::test]
+    async fn test_parse_duration() {
+        assert_eq!(parse_duration_ms("10m"), Some(600_000));
+        assert_eq!(parse_duration_ms("1h30m"), Some(5_400_000));
+        assert_eq!(parse_duration_ms(" 2D "), Some(172_800_000));
+        assert_eq!(parse_duration_ms("500ms"), Some(500));
+        for bad in ["", "10", "m", "10x", "1.5h"] {
+            assert_eq!(parse_duration_ms(bad), None, "{}", bad);
+        }
+        let registry = BuiltinRegistry::new();
+        let op = registry.get("ParseDuration").unwrap();
+        assert_eq!(op.execute(vec![Value::from("45s")], &serde_json::json!({})).await.unwrap(), Value::Int(45_000));
+        assert_eq!(op.execute(vec![Value::from("soon")], &serde_json::json!({})).await.unwrap(), Value::Null);
+    }
+
     #[tokio::test]
     async fn test_registry() {
         let registry = BuiltinRegistry::new();
@@ -766,4 +1485,103 @@ mod tests {
         assert!(registry.get("StartsWith").is_some());
         assert!(registry.get("Hash").is_some());
     }
+
+    #[tokio::test]
+    async fn test_sign_and_verify_ed25519() {
+        let seed = [7u8; 32];
+        let public_key = SigningKey::from_bytes(&seed).verifying_key().to_bytes().to_vec();
+        let registry = BuiltinRegistry::new().with_keystore(Keystore::new().with_key("gateway", seed), false);
+        let sign = registry.get("Sign").unwrap();
+        let message = Value::String("hello".into());
+
+        // Raw seeds need the runtime's dev mode; a graph cannot turn it on
+        let seed_params = serde_json::json!({"seed": hex::encode(seed)});
+        let err = sign.execute(vec![message.clone()], &seed_params).await.unwrap_err();
+        assert!(err.to_string().contains("dev_mode"), "{}", err);
+        let graph_dev_mode = serde_json::json!({"seed": hex::encode(seed), "dev_mode": true});
+        assert!(sign.execute(vec![message.clone()], &graph_dev_mode).await.is_err());
+        let dev_registry = BuiltinRegistry::new().with_keystore(Keystore::new(), true);
+        let by_seed = dev_registry
+            .get("Sign")
+            .unwrap()
+            .execute(vec![message.clone()], &seed_params)
+            .await
+            .unwrap();
+        let by_name = sign.execute(vec![message.clone()], &serde_json::json!({"key": "gateway"})).await.unwrap();
+        assert_eq!(by_seed, by_name);
+
+        let verify = |inputs| async { VerifyOp.execute(inputs, &serde_json::json!({})).await.unwrap() };
+        let key = Value::Bytes(public_key);
+        assert_eq!(verify(vec![message.clone(), by_seed.clone(), key.clone()]).await, Value::Bool(true));
+        assert_eq!(
+            verify(vec![Value::String("other".into()), by_seed, key.clone()]).await,
+            Value::Bool(false)
+        );
+        assert_eq!(verify(vec![message, Value::Bytes(vec![1, 2, 3]), key]).await, Value::Bool(false));
+
+        let err = sign.execute(vec![Value::String("x".into())], &serde_json::json!({})).await.unwrap_err();
+        assert!(matches!(err, GatewayError::ExecutionError(_)));
+    }
+
+    #[tokio::test]
+    async fn test_verify_rejects_tampered_signature() {
+        let seed = [9u8; 32];
+        let key = Value::Bytes(SigningKey::from_bytes(&seed).verifying_key().to_bytes().to_vec());
+        let message = Value::String("transfer 10".into());
+        let signature = SignOp { keystore: Arc::new(Keystore::new().with_key("test", seed)), dev_mode: false }
+            .execute(vec![message.clone()], &serde_json::json!({"key": "test"}))
+            .await
+            .unwrap();
+        let Value::Bytes(mut tampered) = signature.clone() else {
+            panic!("Sign should return bytes");
+        };
+        tampered[0] ^= 0x01;
+
+        let params = serde_json::json!({});
+        let valid = VerifyOp.execute(vec![message.clone(), signature, key.clone()], &params).await.unwrap();
+        assert_eq!(valid, Value::Bool(true));
+        let flipped = VerifyOp.execute(vec![message, Value::Bytes(tampered), key], &params).await.unwrap();
+        assert_eq!(flipped, Value::Bool(false));
+    }
+
+    #[tokio::test]
+    async fn test_regex_match_and_capture() {
+        let registry = BuiltinRegistry::new();
+        let matcher = registry.get("RegexMatch").unwrap();
+        let capture = registry.get("RegexCapture").unwrap();
+        let message = || vec![Value::String("/remind 10m stretch your legs".into())];
+        let named = serde_json::json!({"pattern": r"/remind (?P<when>\w+) (?P<what>.+)"});
+
+        assert_eq!(matcher.execute(message(), &named).await.unwrap(), Value::Bool(true));
+        let groups = capture.execute(message(), &named).await.unwrap();
+        let groups = groups.as_map().unwrap();
+        assert_eq!(groups["when"], Value::String("10m".into()));
+        assert_eq!(groups["what"], Value::String("stretch your legs".into()));
+
+        let positional = serde_json::json!({"pattern": r"/remind (\w+) (.+)"});
+        assert_eq!(
+            capture.execute(message(), &positional).await.unwrap(),
+            Value::Array(vec![Value::String("10m".into()), Value::String("stretch your legs".into())])
+        );
+
+        let other = vec![Value::String("/help".into())];
+        assert_eq!(matcher.execute(other.clone(), &named).await.unwrap(), Value::Bool(false));
+        assert_eq!(capture.execute(other, &named).await.unwrap(), Value::Null);
+
+        let invalid = serde_json::json!({"pattern": "(unclosed"});
+        let err = matcher.execute(message(), &invalid).await.unwrap_err();
+        assert!(matches!(err, GatewayError::ExecutionError(ref msg) if msg.contains("Invalid regex")));
+    }
+
+    #[test]
+    fn test_regex_cache_bounded() {
+        let cache = RegexCache::with_capacity(2);
+        for pattern in ["a+", "b+", "c+"] {
+            assert!(cache.get(pattern).unwrap().is_match(&pattern[..1]));
+        }
+        assert_eq!(cache.len(), 2);
+        assert!(!cache.compiled.read().unwrap().by_pattern.contains_key("a+"));
+        assert!(cache.get("a+").unwrap().is_match("aa"));
+        assert_eq!(cache.len(), 2);
+    }
 }
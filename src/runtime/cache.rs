@@ -0,0 +1,184 @@
+//! Result cache for deterministic graphs.
+//!
+//! Results are keyed by the graph's content hash and its canonicalized
+//! inputs, and expire `ttl_ms` after they were stored. The cache holds at
+//! most a fixed number of results: a full cache first drops expired ones,
+//! then the least recently used. The interpreter only consults the cache
+//! for graphs without side-effecting nodes.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::clock::{Clock, SystemClock};
+use crate::types::ContentHash;
+use super::interpreter::ExecutionResult;
+use super::types::{Graph, Value};
+
+/// Cache hit/miss counters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Executions served from the cache
+    pub hits: u64,
+    /// Cacheable executions that had to run
+    pub misses: u64,
+    /// Results currently stored, including expired ones not yet evicted
+    pub entries: usize,
+}
+
+/// Default maximum number of cached results.
+pub const DEFAULT_MAX_RESULT_ENTRIES: usize = 1024;
+
+/// Execution results keyed by graph and inputs, with a time to live.
+pub struct ResultCache {
+    ttl: Duration,
+    clock: Arc<dyn Clock>,
+    entries: Mutex<Entries>,
+    stats: Mutex<CacheStats>,
+}
+
+/// Stored results with least-recently-used bookkeeping.
+#[derive(Default)]
+struct Entries {
+    /// Results with their storage time and the tick of their last access
+    results: HashMap<ContentHash, (DateTime<Utc>, ExecutionResult, u64)>,
+
+    /// Access order, oldest tick first
+    order: BTreeMap<u64, ContentHash>,
+
+    /// Monotonic access counter
+    tick: u64,
+
+    /// Maximum number of results kept
+    capacity: usize,
+}
+
+impl Entries {
+    fn touch(&mut self, key: &ContentHash) {
+        self.tick += 1;
+        let tick = self.tick;
+        if let Some((_, _, last)) = self.results.get_mut(key) {
+            self.order.remove(last);
+            *last = tick;
+            self.order.insert(tick, *key);
+        }
+    }
+
+    fn remove(&mut self, key: &ContentHash) {
+        if let Some((_, _, last)) = self.results.remove(key) {
+            self.order.remove(&last);
+        }
+    }
+
+    /// Make room for `incoming` new results, dropping expired results
+    /// before live ones.
+    fn evict(&mut self, now: DateTime<Utc>, ttl: Duration, incoming: usize) {
+        if self.results.len() + incoming <= self.capacity {
+            return;
+        }
+        let expired: Vec<ContentHash> = self
+            .results
+            .iter()
+            .filter(|(_, (stored_at, _, _))| now - *stored_at >= ttl)
+            .map(|(key, _)| *key)
+            .collect();
+        expired.iter().for_each(|key| self.remove(key));
+        while self.results.len() + incoming > self.capacity {
+            let Some((_, oldest)) = self.order.pop_first() else { break };
+            self.results.remove(&oldest);
+        }
+    }
+}
+
+impl ResultCache {
+    /// Create a cache whose entries live for `ttl_ms` milliseconds.
+    pub fn new(ttl_ms: u64) -> Self {
+        Self {
+            ttl: Duration::milliseconds(i64::try_from(ttl_ms).unwrap_or(i64::MAX)),
+            clock: Arc::new(SystemClock),
+            entries: Mutex::new(Entries {
+                capacity: DEFAULT_MAX_RESULT_ENTRIES,
+                ..Entries::default()
+            }),
+            stats: Mutex::new(CacheStats::default()),
+        }
+    }
+
+    /// Use a custom time source.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Keep at most `max_entries` results.
+    pub fn with_max_entries(self, max_entries: usize) -> Self {
+        self.entries.lock().unwrap().capacity = max_entries;
+        self
+    }
+
+    /// Cache key for executing `graph` with `inputs`.
+    pub fn key_for(graph: &Graph, inputs: &HashMap<String, Value>) -> ContentHash {
+        let mut bytes = serde_json::to_vec(graph).unwrap_or_default();
+        let sorted: BTreeMap<&String, &Value> = inputs.iter().collect();
+        for (name, value) in sorted {
+            bytes.extend_from_slice(name.as_bytes());
+            bytes.push(0);
+            bytes.extend_from_slice(&value.canonical_bytes());
+            bytes.push(0);
+        }
+        ContentHash::from_bytes(&bytes)
+    }
+
+    /// Get an unexpired result, counting a hit or miss.
+    pub fn get(&self, key: &ContentHash) -> Option<ExecutionResult> {
+        let now = self.clock.now();
+        let mut entries = self.entries.lock().unwrap();
+        let result = match entries.results.get(key) {
+            Some((stored_at, result, _)) if now - *stored_at < self.ttl => Some(result.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        };
+        if result.is_some() {
+            entries.touch(key);
+        }
+
+        let mut stats = self.stats.lock().unwrap();
+        match result {
+            Some(_) => stats.hits += 1,
+            None => stats.misses += 1,
+        }
+        result
+    }
+
+    /// Store a result, evicting others if the cache is full.
+    pub fn insert(&self, key: ContentHash, result: ExecutionResult) {
+        let now = self.clock.now();
+        let mut entries = self.entries.lock().unwrap();
+        entries.remove(&key);
+        entries.evict(now, self.ttl, 1);
+        if entries.capacity == 0 {
+            return;
+        }
+        entries.results.insert(key, (now, result, 0));
+        entries.touch(&key);
+    }
+
+    /// Drop all stored results, keeping the counters.
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.results.clear();
+        entries.order.clear();
+    }
+
+    /// Current hit/miss counters.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.entries.lock().unwrap().results.len(),
+            ..*self.stats.lock().unwrap()
+        }
+    }
+}
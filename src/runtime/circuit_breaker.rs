@@ -0,0 +1,271 @@
+//! Circuit breaker for external calls.
+//!
+//! After `failure_threshold` failures within `window_ms`, calls to the same
+//! host fail immediately until `cooldown_ms` has passed. The next call is
+//! then let through as a trial: success closes the circuit, failure opens it
+//! for another cooldown. A trial whose call is abandoned (for example by a
+//! timeout) frees the slot for the next one.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::error::GatewayError;
+use crate::clock::{Clock, SystemClock};
+
+/// Circuit breaker thresholds.
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerConfig {
+    /// Failures within the window that open the circuit
+    pub failure_threshold: usize,
+    /// Window over which failures are counted, in milliseconds
+    pub window_ms: u64,
+    /// Time the circuit stays open before a trial call, in milliseconds
+    pub cooldown_ms: u64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            window_ms: 60_000,
+            cooldown_ms: 30_000,
+        }
+    }
+}
+
+/// Observable state of a circuit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls pass through.
+    Closed,
+    /// Calls fail immediately.
+    Open,
+    /// A single trial call is allowed.
+    HalfOpen,
+}
+
+#[derive(Debug)]
+enum Circuit {
+    Closed { failures: VecDeque<DateTime<Utc>> },
+    Open { until: DateTime<Utc> },
+    HalfOpen { trial_in_flight: bool },
+}
+
+impl Default for Circuit {
+    fn default() -> Self {
+        Circuit::Closed { failures: VecDeque::new() }
+    }
+}
+
+/// Per-host circuit breaker.
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    clock: Arc<dyn Clock>,
+    circuits: Mutex<HashMap<String, Circuit>>,
+}
+
+impl CircuitBreaker {
+    /// Create a breaker using the system clock.
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            clock: Arc::new(SystemClock),
+            circuits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Use a custom time source.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Breaker key for a URI: its host (and port), or the whole URI if it has none.
+    pub fn key_for(uri: &str) -> String {
+        let rest = uri.split_once("://").map(|(_, rest)| rest).unwrap_or(uri);
+        let authority = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+        let host = authority.rsplit('@').next().unwrap_or(authority);
+        if host.is_empty() {
+            uri.to_string()
+        } else {
+            host.to_lowercase()
+        }
+    }
+
+    /// Check whether a call to `key` may proceed.
+    ///
+    /// Report the call's outcome through the returned permit. Dropping it
+    /// unreported releases a trial slot without changing the circuit.
+    pub fn check(&self, key: &str) -> Result<CallPermit<'_>, GatewayError> {
+        let now = self.clock.now();
+        let mut circuits = self.circuits.lock().unwrap();
+        let circuit = circuits.entry(key.to_string()).or_default();
+
+        let trial = match circuit {
+            Circuit::Closed { .. } => false,
+            Circuit::Open { until } if now < *until => {
+                return Err(GatewayError::CircuitOpen { key: key.to_string() });
+            }
+            Circuit::Open { .. } => {
+                *circuit = Circuit::HalfOpen { trial_in_flight: true };
+                true
+            }
+            Circuit::HalfOpen { trial_in_flight } if *trial_in_flight => {
+                return Err(GatewayError::CircuitOpen { key: key.to_string() });
+            }
+            Circuit::HalfOpen { trial_in_flight } => {
+                *trial_in_flight = true;
+                true
+            }
+        };
+        Ok(CallPermit { breaker: self, key: key.to_string(), trial, reported: false })
+    }
+
+    /// Free the trial slot of an abandoned half-open call.
+    fn release_trial(&self, key: &str) {
+        if let Some(Circuit::HalfOpen { trial_in_flight }) = self.circuits.lock().unwrap().get_mut(key) {
+            *trial_in_flight = false;
+        }
+    }
+
+    /// Record a successful call, closing the circuit.
+    pub fn record_success(&self, key: &str) {
+        self.circuits.lock().unwrap().insert(key.to_string(), Circuit::default());
+    }
+
+    /// Record a failed call.
+    pub fn record_failure(&self, key: &str) {
+        let now = self.clock.now();
+        let cooldown = Duration::milliseconds(self.config.cooldown_ms as i64);
+        let window = Duration::milliseconds(self.config.window_ms as i64);
+        let mut circuits = self.circuits.lock().unwrap();
+        let circuit = circuits.entry(key.to_string()).or_default();
+
+        match circuit {
+            Circuit::Closed { failures } => {
+                failures.push_back(now);
+                while failures.front().is_some_and(|t| now - *t > window) {
+                    failures.pop_front();
+                }
+                if failures.len() >= self.config.failure_threshold {
+                    tracing::warn!("Circuit for {} opened after {} failures", key, failures.len());
+                    *circuit = Circuit::Open { until: now + cooldown };
+                }
+            }
+            Circuit::HalfOpen { .. } | Circuit::Open { .. } => {
+                *circuit = Circuit::Open { until: now + cooldown };
+            }
+        }
+    }
+
+    /// Current state of the circuit for `key`.
+    pub fn state(&self, key: &str) -> CircuitState {
+        let now = self.clock.now();
+        match self.circuits.lock().unwrap().get(key) {
+            None | Some(Circuit::Closed { .. }) => CircuitState::Closed,
+            Some(Circuit::Open { until }) if now < *until => CircuitState::Open,
+            Some(Circuit::Open { .. }) | Some(Circuit::HalfOpen { .. }) => CircuitState::HalfOpen,
+        }
+    }
+}
+
+/// Permission for one call, returned by [`CircuitBreaker::check`].
+#[must_use = "report the outcome with record_success or record_failure"]
+pub struct CallPermit<'a> {
+    breaker: &'a CircuitBreaker,
+    key: String,
+    trial: bool,
+    reported: bool,
+}
+
+impl CallPermit<'_> {
+    /// Record that the call succeeded.
+    pub fn record_success(mut self) {
+        self.reported = true;
+        self.breaker.record_success(&self.key);
+    }
+
+    /// Record that the call failed.
+    pub fn record_failure(mut self) {
+        self.reported = true;
+        self.breaker.record_failure(&self.key);
+    }
+}
+
+impl Drop for CallPermit<'_> {
+    fn drop(&mut self) {
+        if self.trial && !self.reported {
+            self.breaker.release_trial(&self.key);
+        }
+    }
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(CircuitBreakerConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use chrono::TimeZone;
+
+    fn breaker() -> (CircuitBreaker, MockClock) {
+        let clock = MockClock::new(Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap());
+        let config = CircuitBreakerConfig { failure_threshold: 3, window_ms: 10_000, cooldown_ms: 5_000 };
+        (CircuitBreaker::new(config).with_clock(Arc::new(clock.clone())), clock)
+    }
+
+    #[test]
+    fn test_key_for_uri() {
+        assert_eq!(CircuitBreaker::key_for("https://API.example.com:8443/v1?q=1"), "api.example.com:8443");
+        assert_eq!(CircuitBreaker::key_for("http://user@host/path"), "host");
+        assert_eq!(CircuitBreaker::key_for("weather"), "weather");
+    }
+
+    #[test]
+    fn test_failures_outside_window_do_not_open() {
+        let (breaker, clock) = breaker();
+        breaker.record_failure("h");
+        breaker.record_failure("h");
+        clock.advance(Duration::seconds(11));
+        breaker.record_failure("h");
+        assert_eq!(breaker.state("h"), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_half_open_failure_reopens() {
+        let (breaker, clock) = breaker();
+        for _ in 0..3 {
+            breaker.record_failure("h");
+        }
+        assert!(breaker.check("h").is_err());
+
+        clock.advance(Duration::seconds(5));
+        let trial = breaker.check("h").unwrap();
+        // Only one trial call at a time
+        assert!(breaker.check("h").is_err());
+
+        trial.record_failure();
+        assert_eq!(breaker.state("h"), CircuitState::Open);
+    }
+
+    #[test]
+    fn test_abandoned_trial_frees_the_slot() {
+        let (breaker, clock) = breaker();
+        for _ in 0..3 {
+            breaker.record_failure("h");
+        }
+        clock.advance(Duration::seconds(5));
+
+        // A trial cancelled before reporting does not wedge the circuit
+        drop(breaker.check("h").unwrap());
+        let trial = breaker.check("h").unwrap();
+        trial.record_success();
+        assert_eq!(breaker.state("h"), CircuitState::Closed);
+    }
+}
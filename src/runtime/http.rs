@@ -0,0 +1,244 @@
+//! HTTP resolver for external calls.
+//!
+//! Resolves `http://` and `https://` URIs of `External` nodes and the
+//! `HttpGet`/`HttpPost` operations. A call without inputs is a GET; otherwise
+//! the first input is POSTed as JSON. JSON responses are parsed, anything
+//! else is returned as a string.
+//!
+//! Only hosts on the resolver's allowlist are called, and never at a
+//! loopback, private or link-local address: host names are resolved by the
+//! client itself, so a name that resolves to such an address is refused at
+//! connect time, not just when checked.
+
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::error::GatewayError;
+use super::interpreter::ExternalResolver;
+use super::types::Value;
+
+/// Resolver performing HTTP requests with `reqwest`.
+///
+/// The client is built on first use, so gateways that never make an HTTP
+/// call don't pay for loading TLS roots.
+pub struct HttpResolver {
+    timeout: Duration,
+    allowed_hosts: Arc<Vec<String>>,
+    client: OnceLock<reqwest::Client>,
+}
+
+impl HttpResolver {
+    /// Create a resolver whose requests time out after `timeout`.
+    ///
+    /// It calls no host until some are allowed with [`Self::with_allowed_hosts`].
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout, allowed_hosts: Arc::new(Vec::new()), client: OnceLock::new() }
+    }
+
+    /// Allow calls to `hosts`. `*.example.com` matches any subdomain of
+    /// `example.com`.
+    pub fn with_allowed_hosts(mut self, hosts: Vec<String>) -> Self {
+        self.allowed_hosts = Arc::new(hosts.into_iter().map(|host| host.to_ascii_lowercase()).collect());
+        self
+    }
+
+    fn client(&self) -> &reqwest::Client {
+        self.client.get_or_init(|| {
+            let allowed_hosts = self.allowed_hosts.clone();
+            public_client(self.timeout, move |url| check_url(&allowed_hosts, url))
+        })
+    }
+}
+
+/// Client connecting only to public addresses, following up to 5
+/// redirects that pass `check`.
+pub(crate) fn public_client(
+    timeout: Duration,
+    check: impl Fn(&reqwest::Url) -> Result<(), String> + Send + Sync + 'static,
+) -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .dns_resolver(Arc::new(PublicDnsResolver))
+        .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+            match check(attempt.url()) {
+                Ok(()) if attempt.previous().len() < 5 => attempt.follow(),
+                Ok(()) => attempt.error("too many redirects"),
+                Err(e) => attempt.error(e),
+            }
+        }))
+        .build()
+        .unwrap_or_default()
+}
+
+/// Check that `url` is an http(s) URL whose host is not a non-public IP
+/// literal. Names are checked when a [`public_client`] resolves them.
+pub(crate) fn check_public_url(url: &reqwest::Url) -> Result<(), String> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("{}: only http and https are allowed", url));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| format!("{}: no host", url))?
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        if !is_public_ip(ip) {
+            return Err(format!("{}: {} is not a public address", url, ip));
+        }
+    }
+    Ok(())
+}
+
+/// Check that `url` passes [`check_public_url`] and is on an allowed host.
+fn check_url(allowed_hosts: &[String], url: &reqwest::Url) -> Result<(), String> {
+    check_public_url(url)?;
+    let host = url
+        .host_str()
+        .unwrap_or_default()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_ascii_lowercase();
+    let allowed = allowed_hosts.iter().any(|allowed| match allowed.strip_prefix("*.") {
+        Some(domain) => host.strip_suffix(domain).is_some_and(|sub| sub.ends_with('.')),
+        None => *allowed == host,
+    });
+    if !allowed {
+        return Err(format!("{}: host '{}' is not in the HTTP allowlist", url, host));
+    }
+    Ok(())
+}
+
+/// Whether `ip` is a globally routable address, i.e. not loopback,
+/// private, link-local, shared, unspecified, broadcast or documentation.
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || a == 0
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_public_ip(IpAddr::V4(v4)),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || (first & 0xfe00) == 0xfc00
+                    || (first & 0xffc0) == 0xfe80)
+            }
+        },
+    }
+}
+
+/// DNS resolver that drops non-public addresses, failing if none are left.
+struct PublicDnsResolver;
+
+impl reqwest::dns::Resolve for PublicDnsResolver {
+    fn resolve(&self, name: hyper::client::connect::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} does not resolve to a public address", host).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+#[async_trait]
+impl ExternalResolver for HttpResolver {
+    fn handles(&self, uri: &str) -> bool {
+        uri.starts_with("http://") || uri.starts_with("https://")
+    }
+
+    async fn resolve(&self, uri: &str, inputs: Vec<Value>) -> Result<Value, GatewayError> {
+        let url = reqwest::Url::parse(uri).map_err(|e| GatewayError::ExecutionError(format!("{}: {}", uri, e)))?;
+        check_url(&self.allowed_hosts, &url).map_err(GatewayError::ExecutionError)?;
+
+        let client = self.client();
+        let request = match inputs.first() {
+            None => client.get(url),
+            Some(body) => client.post(url).json(&body.to_canonical_json()),
+        };
+        let response = request
+            .send()
+            .await
+            .map_err(|e| GatewayError::ExecutionError(format!("{}: {}", uri, e)))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(GatewayError::ExecutionError(format!("{}: HTTP {}", uri, status)));
+        }
+        let body = response
+            .text()
+            .await
+            .map_err(|e| GatewayError::ExecutionError(format!("{}: {}", uri, e)))?;
+        Ok(match serde_json::from_str::<serde_json::Value>(&body) {
+            Ok(json) => Value::from_json(&json),
+            Err(_) => Value::String(body.into()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_private_addresses_are_not_public() {
+        for ip in ["127.0.0.1", "10.1.2.3", "192.168.0.1", "172.16.5.4", "169.254.169.254", "100.64.0.1", "0.0.0.0", "::1", "fd00::1", "fe80::1", "::ffff:127.0.0.1"] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in ["93.184.216.34", "1.1.1.1", "2606:4700:4700::1111"] {
+            assert!(is_public_ip(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn test_only_allowed_hosts_pass() {
+        let allowed = vec!["api.example.com".to_string(), "*.search.example".to_string()];
+        let check = |url: &str| check_url(&allowed, &reqwest::Url::parse(url).unwrap());
+        assert!(check("https://api.example.com/v1").is_ok());
+        assert!(check("https://API.example.com/v1").is_ok());
+        assert!(check("https://eu.search.example/q").is_ok());
+        assert!(check("https://search.example/q").is_err());
+        assert!(check("https://evilsearch.example/q").is_err());
+        assert!(check("https://example.com/").is_err());
+        assert!(check("file:///etc/passwd").is_err());
+
+        let all_ips = vec!["127.0.0.1".to_string(), "[::1]".to_string(), "::1".to_string()];
+        assert!(check_url(&all_ips, &reqwest::Url::parse("http://127.0.0.1:8080/").unwrap()).is_err());
+        assert!(check_url(&all_ips, &reqwest::Url::parse("http://[::1]/").unwrap()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resolver_refuses_unlisted_and_private_hosts() {
+        let resolver = HttpResolver::new(Duration::from_secs(1)).with_allowed_hosts(vec!["localhost".to_string()]);
+        let err = resolver.resolve("http://169.254.169.254/latest", Vec::new()).await.unwrap_err();
+        assert!(err.to_string().contains("not a public address"), "{}", err);
+
+        // Allowed by name, but the name resolves to loopback
+        let err = resolver.resolve("http://localhost:9/", Vec::new()).await.unwrap_err();
+        assert!(err.to_string().contains("public address"), "{}", err);
+
+        let err = HttpResolver::new(Duration::from_secs(1))
+            .resolve("https://example.com/", Vec::new())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("allowlist"), "{}", err);
+    }
+}
@@ -4,15 +4,20 @@
 //! All business logic should be expressed as graphs; this interpreter
 //! provides the minimal runtime to execute them.
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
-use tokio::sync::RwLock;
 
-use super::builtins::BuiltinRegistry;
-use super::types::{Graph, GraphNode, NodeType, Value, RouteCondition};
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+
+use super::builtins::{BuiltinRegistry, Keystore, RegexCache, HTTP_GET_OP, HTTP_POST_OP, STATE_NAMESPACE_PARAM};
+use super::cache::{CacheStats, ResultCache};
+use super::circuit_breaker::CircuitBreaker;
+use super::state::{MemoryStateBackend, StateBackend};
+use super::types::{Graph, GraphNode, NodeType, Value, RouteCondition, StringInterner};
 use super::RuntimeConfig;
 use crate::error::GatewayError;
-use crate::types::ContentHash;
+use crate::types::{ContentHash, TraceNode};
 
 /// Result of graph execution.
 #[derive(Debug, Clone)]
@@ -21,34 +26,79 @@ pub struct ExecutionResult {
     pub outputs: HashMap<String, Value>,
     /// Execution trace (node IDs in execution order).
     pub trace: Vec<String>,
+    /// Executed nodes with their inputs and outputs, in execution order.
+    ///
+    /// Only populated when `RuntimeConfig::trace_enabled` is set.
+    pub nodes: Vec<TraceNode>,
     /// Content hash of the execution.
     pub hash: ContentHash,
     /// Final confidence score.
     pub confidence: f64,
+    /// External/input node IDs each output depends on (sorted).
+    ///
+    /// Only populated when `RuntimeConfig::track_provenance` is enabled.
+    pub provenance: HashMap<String, Vec<String>>,
+    /// Steps of the execution budget consumed.
+    pub steps: usize,
 }
 
 /// Execution context for a graph.
 #[derive(Debug)]
 pub struct ExecutionContext {
-    /// Node outputs computed so far.
+    /// Node outputs computed so far in the current scope.
     pub node_values: HashMap<String, Value>,
+    /// Node outputs of enclosing scopes, innermost last.
+    scopes: Vec<HashMap<String, Value>>,
     /// Execution trace.
     pub trace: Vec<String>,
+    /// Executed nodes with input and output hashes.
+    pub nodes: Vec<TraceNode>,
     /// Current confidence score.
     pub confidence: f64,
-    /// Step counter.
+    /// Steps of the budget consumed so far.
     pub steps: usize,
+    /// Shared storage for string values produced during the execution.
+    pub strings: StringInterner,
+    /// When the execution started, for the `timeout_ms` budget.
+    pub started_at: std::time::Instant,
+    /// Namespace confining the state operations of this execution.
+    pub state_namespace: Option<String>,
 }
 
 impl ExecutionContext {
     pub fn new() -> Self {
         Self {
             node_values: HashMap::new(),
+            scopes: Vec::new(),
             trace: Vec::new(),
+            nodes: Vec::new(),
             confidence: 1.0,
             steps: 0,
+            strings: StringInterner::new(),
+            started_at: std::time::Instant::now(),
+            state_namespace: None,
         }
     }
+
+    /// Look up a node output, innermost scope first.
+    pub fn get(&self, id: &str) -> Option<&Value> {
+        self.node_values
+            .get(id)
+            .or_else(|| self.scopes.iter().rev().find_map(|scope| scope.get(id)))
+    }
+
+    /// Start a nested scope. Its nodes can read, but not overwrite,
+    /// outputs of the enclosing scopes.
+    pub fn push_scope(&mut self) {
+        let outer = std::mem::take(&mut self.node_values);
+        self.scopes.push(outer);
+    }
+
+    /// End the innermost scope, returning the outputs computed in it.
+    pub fn pop_scope(&mut self) -> HashMap<String, Value> {
+        let outer = self.scopes.pop().unwrap_or_default();
+        std::mem::replace(&mut self.node_values, outer)
+    }
 }
 
 impl Default for ExecutionContext {
@@ -57,6 +107,18 @@ impl Default for ExecutionContext {
     }
 }
 
+/// Resolver for external nodes whose URI is not an `input://` reference.
+#[async_trait]
+pub trait ExternalResolver: Send + Sync {
+    /// Whether this resolver serves `uri`; others read graph inputs.
+    fn handles(&self, _uri: &str) -> bool {
+        true
+    }
+
+    /// Call the external service at `uri` with the node's input values.
+    async fn resolve(&self, uri: &str, inputs: Vec<Value>) -> Result<Value, GatewayError>;
+}
+
 /// The 0-lang graph interpreter.
 pub struct GraphInterpreter {
     /// Built-in operations.
@@ -64,19 +126,123 @@ pub struct GraphInterpreter {
     /// Runtime configuration.
     config: RuntimeConfig,
     /// State store for cross-execution state.
-    state_store: Arc<RwLock<HashMap<String, Value>>>,
+    state_store: Arc<dyn StateBackend>,
+    /// Resolver for external calls (none: external URIs read graph inputs).
+    external: Option<Arc<dyn ExternalResolver>>,
+    /// Circuit breaker guarding external calls, keyed by host.
+    breaker: CircuitBreaker,
+    /// Results of side-effect-free graphs (none: caching disabled).
+    cache: Option<ResultCache>,
+    /// Compiled patterns of `Regex` nodes, keyed by pattern.
+    regexes: RegexCache,
 }
 
 impl GraphInterpreter {
     /// Create a new interpreter with the given configuration.
     pub fn new(config: RuntimeConfig) -> Self {
+        Self::with_state_backend(config, Arc::new(MemoryStateBackend::new()))
+    }
+
+    /// Create an interpreter whose state lives in the given backend.
+    ///
+    /// `LoadState`/`SaveState` ops share the same backend.
+    pub fn with_state_backend(config: RuntimeConfig, backend: Arc<dyn StateBackend>) -> Self {
         Self {
-            builtins: BuiltinRegistry::new(),
+            builtins: BuiltinRegistry::with_state_backend(backend.clone()).with_keystore(Keystore::new(), config.dev_mode),
+            breaker: CircuitBreaker::new(config.circuit_breaker.clone()),
+            cache: (config.result_cache_ttl_ms > 0).then(|| {
+                ResultCache::new(config.result_cache_ttl_ms).with_max_entries(config.result_cache_max_entries)
+            }),
             config,
-            state_store: Arc::new(RwLock::new(HashMap::new())),
+            state_store: backend,
+            external: None,
+            regexes: RegexCache::default(),
+        }
+    }
+
+    /// Make the keys in `keystore` available to `Sign` by name.
+    pub fn with_keystore(self, keystore: Keystore) -> Self {
+        Self {
+            builtins: self.builtins.with_keystore(keystore, self.config.dev_mode),
+            ..self
         }
     }
 
+    /// Resolve non-`input://` external nodes through `resolver`.
+    pub fn with_external_resolver(mut self, resolver: Arc<dyn ExternalResolver>) -> Self {
+        self.external = Some(resolver);
+        self
+    }
+
+    /// Replace the circuit breaker guarding external calls.
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.breaker = breaker;
+        self
+    }
+
+    /// Cache results of side-effect-free graphs in `cache`.
+    pub fn with_result_cache(mut self, cache: ResultCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Hit/miss counters of the result cache, if enabled.
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(ResultCache::stats)
+    }
+
+    /// Whether results of `graph` may be cached.
+    ///
+    /// False if any node calls an external service, uses an impure
+    /// operation or an unknown one, directly or in a subgraph.
+    pub fn is_cacheable(&self, graph: &Graph) -> bool {
+        graph.nodes.iter().all(|node| match &node.node_type {
+            NodeType::External { uri } => self.resolver_for(uri).is_none(),
+            NodeType::Operation { op } => self.builtins.get(op).is_some_and(|b| b.is_pure()),
+            NodeType::SubGraph { graph, .. }
+            | NodeType::Map { body: graph }
+            | NodeType::Filter { predicate: graph }
+            | NodeType::Reduce { body: graph, .. } => self.is_cacheable(graph),
+            _ => true,
+        })
+    }
+
+    /// The resolver serving `uri`, if any.
+    fn resolver_for(&self, uri: &str) -> Option<&Arc<dyn ExternalResolver>> {
+        if uri.starts_with("input://") {
+            return None;
+        }
+        self.external.as_ref().filter(|resolver| resolver.handles(uri))
+    }
+
+    /// Call `uri` through `resolver`, guarded by the circuit breaker.
+    async fn call_external(
+        &self,
+        resolver: &Arc<dyn ExternalResolver>,
+        uri: &str,
+        args: Vec<Value>,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value, GatewayError> {
+        self.charge_steps(ctx, self.config.external_step_cost)?;
+        let permit = self.breaker.check(&CircuitBreaker::key_for(uri))?;
+        let result = resolver.resolve(uri, args).await;
+        match &result {
+            Ok(_) => permit.record_success(),
+            Err(_) => permit.record_failure(),
+        }
+        result
+    }
+
+    /// Get the circuit breaker guarding external calls.
+    pub fn circuit_breaker(&self) -> &CircuitBreaker {
+        &self.breaker
+    }
+
+    /// Get the state backend.
+    pub fn state_backend(&self) -> &Arc<dyn StateBackend> {
+        &self.state_store
+    }
+
     /// Get the builtin registry.
     pub fn builtins(&self) -> &BuiltinRegistry {
         &self.builtins
@@ -88,24 +254,41 @@ impl GraphInterpreter {
         graph: &Graph,
         inputs: HashMap<String, Value>,
     ) -> Result<ExecutionResult, GatewayError> {
-        let mut ctx = ExecutionContext::new();
+        self.execute_with_namespace(graph, inputs, None).await
+    }
 
-        // Topologically sort nodes
-        let sorted_nodes = graph.topo_sort()?;
+    /// Execute a graph whose `LoadState`/`SaveState` keys are confined to
+    /// `namespace`, so it cannot reach state saved under another one.
+    pub async fn execute_in_namespace(
+        &self,
+        graph: &Graph,
+        inputs: HashMap<String, Value>,
+        namespace: &str,
+    ) -> Result<ExecutionResult, GatewayError> {
+        self.execute_with_namespace(graph, inputs, Some(namespace)).await
+    }
 
-        // Execute nodes in order
-        for node in sorted_nodes {
-            if ctx.steps >= self.config.max_steps {
-                return Err(GatewayError::ExecutionError(
-                    "Maximum execution steps exceeded".to_string(),
-                ));
+    async fn execute_with_namespace(
+        &self,
+        graph: &Graph,
+        inputs: HashMap<String, Value>,
+        namespace: Option<&str>,
+    ) -> Result<ExecutionResult, GatewayError> {
+        let cache_key = match &self.cache {
+            Some(cache) if self.is_cacheable(graph) => {
+                let key = ResultCache::key_for(graph, &inputs);
+                if let Some(result) = cache.get(&key) {
+                    return Ok(result);
+                }
+                Some(key)
             }
+            _ => None,
+        };
 
-            let value = self.execute_node(node, &inputs, &mut ctx).await?;
-            ctx.node_values.insert(node.id.clone(), value);
-            ctx.trace.push(node.id.clone());
-            ctx.steps += 1;
-        }
+        graph.validate()?;
+        let mut ctx = ExecutionContext::new();
+        ctx.state_namespace = namespace.map(str::to_string);
+        self.run_graph(graph, &inputs, &mut ctx).await?;
 
         // Collect outputs
         let mut outputs = HashMap::new();
@@ -115,18 +298,160 @@ impl GraphInterpreter {
             }
         }
 
+        let provenance = if self.config.track_provenance {
+            Self::compute_provenance(graph)?
+        } else {
+            HashMap::new()
+        };
+
         // Compute execution hash
         let hash = self.compute_execution_hash(&ctx)?;
 
-        Ok(ExecutionResult {
+        let result = ExecutionResult {
             outputs,
+            steps: ctx.steps,
             trace: ctx.trace,
+            nodes: ctx.nodes,
             hash,
             confidence: ctx.confidence,
+            provenance,
+        };
+        if let (Some(cache), Some(key)) = (&self.cache, cache_key) {
+            cache.insert(key, result.clone());
+        }
+        Ok(result)
+    }
+
+    /// Execute every node of `graph` in topological order within `ctx`.
+    fn run_graph<'a>(
+        &'a self,
+        graph: &'a Graph,
+        inputs: &'a HashMap<String, Value>,
+        ctx: &'a mut ExecutionContext,
+    ) -> BoxFuture<'a, Result<(), GatewayError>> {
+        Box::pin(async move {
+            // Topologically sort nodes
+            let sorted_nodes = graph.topo_sort()?;
+            self.check_output_refs(graph)?;
+
+            // Execute nodes in order
+            for node in sorted_nodes {
+                let input_hash = self
+                    .config
+                    .trace_enabled
+                    .then(|| Self::hash_inputs(&node.inputs, ctx));
+                let value = self.execute_node_within_budget(node, inputs, ctx).await?;
+                if let Some(input_hash) = input_hash {
+                    ctx.nodes.push(TraceNode {
+                        node_id: node.id.clone(),
+                        op: node.node_type.op_name().to_string(),
+                        input_hash,
+                        output_hash: ContentHash::from_bytes(&value.canonical_bytes()),
+                    });
+                }
+                let value = ctx.strings.intern(value);
+                ctx.node_values.insert(node.id.clone(), value);
+                ctx.trace.push(node.id.clone());
+            }
+            Ok(())
         })
     }
 
-    /// Execute a single node.
+    /// Execute a node, failing once the run exceeds `timeout_ms`.
+    ///
+    /// A node still running at the deadline is cancelled. The error
+    /// carries the trace of the nodes completed so far.
+    async fn execute_node_within_budget(
+        &self,
+        node: &GraphNode,
+        inputs: &HashMap<String, Value>,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value, GatewayError> {
+        if self.config.timeout_ms == 0 {
+            return self.execute_node(node, inputs, ctx).await;
+        }
+        let budget = std::time::Duration::from_millis(self.config.timeout_ms);
+        let remaining = budget.saturating_sub(ctx.started_at.elapsed());
+        let started_at = ctx.started_at;
+        let result = tokio::time::timeout(remaining, self.execute_node(node, inputs, ctx)).await;
+        match result {
+            Ok(value) if started_at.elapsed() <= budget => value,
+            _ => Err(GatewayError::ExecutionTimeout {
+                elapsed_ms: started_at.elapsed().as_millis() as u64,
+                trace: ctx.trace.clone(),
+            }),
+        }
+    }
+
+    /// Execute a nested graph in its own scope, sharing the step budget.
+    ///
+    /// Only the mapped outputs reach the parent; inner nodes stay out of
+    /// the parent's values and trace.
+    async fn execute_subgraph(
+        &self,
+        node: &GraphNode,
+        graph: &Graph,
+        input_map: &HashMap<String, String>,
+        output_map: &HashMap<String, String>,
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value, GatewayError> {
+        let mut sub_inputs = HashMap::new();
+        for (name, input_ref) in input_map {
+            if !node.inputs.contains(input_ref) {
+                return Err(GatewayError::ExecutionError(format!(
+                    "Subgraph node '{}' maps '{}' to '{}', which is not one of its inputs",
+                    node.id, name, input_ref
+                )));
+            }
+            let value = self
+                .gather_inputs(std::slice::from_ref(input_ref), ctx)?
+                .pop()
+                .unwrap_or(Value::Null);
+            sub_inputs.insert(name.clone(), value);
+        }
+
+        let (trace_len, nodes_len) = (ctx.trace.len(), ctx.nodes.len());
+        ctx.push_scope();
+        let result = self.run_graph(graph, &sub_inputs, ctx).await;
+        let mut scope = ctx.pop_scope();
+        ctx.trace.truncate(trace_len);
+        ctx.nodes.truncate(nodes_len);
+        result?;
+
+        let mut outputs = HashMap::new();
+        for output_id in &graph.outputs {
+            let key = output_map.get(output_id).unwrap_or(output_id);
+            let value = scope.remove(output_id).unwrap_or(Value::Null);
+            outputs.insert(key.clone(), value);
+        }
+        Ok(Value::Map(outputs))
+    }
+
+    /// Hash the values of a node's inputs, in order.
+    fn hash_inputs(input_ids: &[String], ctx: &ExecutionContext) -> ContentHash {
+        let mut bytes = Vec::new();
+        for id in input_ids {
+            let value = ctx.get(id).unwrap_or(&Value::Null);
+            bytes.extend_from_slice(ContentHash::from_bytes(&value.canonical_bytes()).as_bytes());
+        }
+        ContentHash::from_bytes(&bytes)
+    }
+
+    /// Consume `cost` steps of the execution budget.
+    fn charge_steps(&self, ctx: &mut ExecutionContext, cost: u64) -> Result<(), GatewayError> {
+        let cost = usize::try_from(cost).unwrap_or(usize::MAX);
+        match ctx.steps.checked_add(cost) {
+            Some(steps) if steps <= self.config.max_steps => {
+                ctx.steps = steps;
+                Ok(())
+            }
+            _ => Err(GatewayError::ExecutionError(
+                "Maximum execution steps exceeded".to_string(),
+            )),
+        }
+    }
+
+    /// Execute a single node, charging its step cost.
     async fn execute_node(
         &self,
         node: &GraphNode,
@@ -135,12 +460,18 @@ impl GraphInterpreter {
     ) -> Result<Value, GatewayError> {
         match &node.node_type {
             NodeType::External { uri } => {
-                // Extract input from provided inputs
-                let key = uri.strip_prefix("input://").unwrap_or(uri);
-                Ok(inputs.get(key).cloned().unwrap_or(Value::Null))
+                let Some(resolver) = self.resolver_for(uri) else {
+                    // Extract input from provided inputs
+                    self.charge_steps(ctx, 1)?;
+                    let key = uri.strip_prefix("input://").unwrap_or(uri);
+                    return Ok(inputs.get(key).cloned().unwrap_or(Value::Null));
+                };
+                let args = self.gather_inputs(&node.inputs, ctx)?;
+                self.call_external(resolver, uri, args, ctx).await
             }
 
             NodeType::Constant { value } => {
+                self.charge_steps(ctx, 1)?;
                 Ok(value.clone())
             }
 
@@ -148,9 +479,36 @@ impl GraphInterpreter {
                 // Gather inputs
                 let input_values = self.gather_inputs(&node.inputs, ctx)?;
 
+                // HTTP operations call out when a resolver serves the URL
+                if matches!(op.as_str(), HTTP_GET_OP | HTTP_POST_OP) {
+                    let url = input_values.first().and_then(|v| v.as_string()).unwrap_or_default().to_string();
+                    if let Some(resolver) = self.resolver_for(&url) {
+                        let body = match op.as_str() {
+                            HTTP_POST_OP => input_values.into_iter().skip(1).take(1).collect(),
+                            _ => Vec::new(),
+                        };
+                        return self.call_external(resolver, &url, body, ctx).await;
+                    }
+                }
+
                 // Execute builtin
                 if let Some(builtin) = self.builtins.get(op) {
-                    builtin.execute(input_values, &node.params).await
+                    self.charge_steps(ctx, builtin.step_cost(&input_values))?;
+                    if builtin.uses_state() {
+                        // Graphs cannot choose their own namespace
+                        let mut params = match &node.params {
+                            serde_json::Value::Object(map) => map.clone(),
+                            _ => serde_json::Map::new(),
+                        };
+                        params.remove(STATE_NAMESPACE_PARAM);
+                        if let Some(namespace) = &ctx.state_namespace {
+                            params.insert(STATE_NAMESPACE_PARAM.to_string(), namespace.clone().into());
+                        }
+                        return builtin.execute(input_values, &serde_json::Value::Object(params)).await;
+                    }
+                    let value = builtin.execute(input_values, &node.params).await?;
+                    Self::check_outputs(op, builtin.outputs(), &value)?;
+                    Ok(value)
                 } else {
                     Err(GatewayError::ExecutionError(format!(
                         "Unknown operation: {}",
@@ -160,6 +518,8 @@ impl GraphInterpreter {
             }
 
             NodeType::Lookup { table, default } => {
+                self.charge_steps(ctx, 1)?;
+
                 // Get lookup key from first input
                 let key = self.gather_inputs(&node.inputs, ctx)?
                     .first()
@@ -172,16 +532,31 @@ impl GraphInterpreter {
                     .cloned()
                     .unwrap_or_default();
 
-                Ok(Value::String(result))
+                Ok(Value::String(result.into()))
             }
 
             NodeType::Route { conditions } => {
+                self.charge_steps(ctx, 1)?;
                 self.execute_route(conditions, ctx).await
             }
 
+            NodeType::Regex { pattern, target } => {
+                self.charge_steps(ctx, 1)?;
+                let input = self.gather_inputs(&node.inputs[..node.inputs.len().min(1)], ctx)?
+                    .pop()
+                    .unwrap_or(Value::Null);
+                let matched = match input.as_string() {
+                    Some(text) => self.regexes.get(pattern)?.is_match(text),
+                    None => false,
+                };
+                Ok(if matched { Value::String(target.as_str().into()) } else { Value::Null })
+            }
+
             NodeType::Permission { action, min_confidence } => {
+                self.charge_steps(ctx, 1)?;
+
                 // Check permission based on sender context
-                let sender_confidence = ctx.node_values
+                let sender_confidence = ctx
                     .get("sender_confidence")
                     .and_then(|v| v.as_float())
                     .unwrap_or(0.5);
@@ -190,7 +565,7 @@ impl GraphInterpreter {
                 let mut result = HashMap::new();
                 result.insert("granted".to_string(), Value::Bool(granted));
                 result.insert("confidence".to_string(), Value::Confidence(sender_confidence));
-                result.insert("action".to_string(), Value::String(action.clone()));
+                result.insert("action".to_string(), Value::String(action.as_str().into()));
 
                 // Update context confidence
                 if granted {
@@ -201,9 +576,101 @@ impl GraphInterpreter {
 
                 Ok(Value::Map(result))
             }
+
+            NodeType::Switch { cases, default } => {
+                self.charge_steps(ctx, 1)?;
+                let input = self.gather_inputs(&node.inputs[..node.inputs.len().min(1)], ctx)?
+                    .pop()
+                    .unwrap_or(Value::Null);
+                let target = cases
+                    .iter()
+                    .find(|(value, _)| *value == input)
+                    .map(|(_, target)| target)
+                    .or(default.as_ref());
+                Ok(target
+                    .and_then(|target| ctx.get(target).cloned())
+                    .unwrap_or(Value::Null))
+            }
+
+            NodeType::SubGraph { graph, input_map, output_map } => {
+                self.charge_steps(ctx, 1)?;
+                self.execute_subgraph(node, graph, input_map, output_map, ctx).await
+            }
+
+            NodeType::Map { body } => {
+                self.charge_steps(ctx, 1)?;
+                let mut results = Vec::new();
+                for item in self.array_input(node, ctx)? {
+                    results.push(self.run_body(body, &[("item", item)], ctx).await?);
+                }
+                Ok(Value::Array(results))
+            }
+
+            NodeType::Filter { predicate } => {
+                self.charge_steps(ctx, 1)?;
+                let mut kept = Vec::new();
+                for item in self.array_input(node, ctx)? {
+                    if self.run_body(predicate, &[("item", item.clone())], ctx).await?.is_truthy() {
+                        kept.push(item);
+                    }
+                }
+                Ok(Value::Array(kept))
+            }
+
+            NodeType::Reduce { body, initial } => {
+                self.charge_steps(ctx, 1)?;
+                let mut acc = initial.clone();
+                for item in self.array_input(node, ctx)? {
+                    acc = self.run_body(body, &[("acc", acc), ("item", item)], ctx).await?;
+                }
+                Ok(acc)
+            }
+        }
+    }
+
+    /// Elements of a node's first input; null counts as empty.
+    fn array_input(&self, node: &GraphNode, ctx: &ExecutionContext) -> Result<Vec<Value>, GatewayError> {
+        let input = self.gather_inputs(&node.inputs[..node.inputs.len().min(1)], ctx)?
+            .pop()
+            .unwrap_or(Value::Null);
+        match input {
+            Value::Array(items) => Ok(items),
+            Value::Null => Ok(Vec::new()),
+            other => Err(GatewayError::ExecutionError(format!(
+                "'{}' expects an array input, got {:?}",
+                node.id, other
+            ))),
         }
     }
 
+    /// Run a loop body in a nested scope and return its first output.
+    ///
+    /// Steps count against the enclosing execution's budget.
+    async fn run_body(
+        &self,
+        body: &Graph,
+        inputs: &[(&str, Value)],
+        ctx: &mut ExecutionContext,
+    ) -> Result<Value, GatewayError> {
+        let inputs: HashMap<String, Value> = inputs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect();
+        let (trace_len, nodes_len) = (ctx.trace.len(), ctx.nodes.len());
+        ctx.push_scope();
+        let result = self.run_graph(body, &inputs, ctx).await;
+        let mut scope = ctx.pop_scope();
+        ctx.trace.truncate(trace_len);
+        ctx.nodes.truncate(nodes_len);
+        result?;
+
+        Ok(body
+            .outputs
+            .first()
+            .and_then(|output| scope.remove(output))
+            .unwrap_or(Value::Null))
+    }
+
     /// Execute a routing decision.
     async fn execute_route(
         &self,
@@ -211,7 +678,7 @@ impl GraphInterpreter {
         ctx: &mut ExecutionContext,
     ) -> Result<Value, GatewayError> {
         for condition in conditions {
-            let input_value = ctx.node_values
+            let input_value = ctx
                 .get(&condition.input)
                 .cloned()
                 .unwrap_or(Value::Null);
@@ -231,9 +698,9 @@ impl GraphInterpreter {
                 ctx.confidence *= condition.confidence;
                 
                 let mut result = HashMap::new();
-                result.insert("target".to_string(), Value::String(condition.target.clone()));
+                result.insert("target".to_string(), Value::String(condition.target.as_str().into()));
                 result.insert("confidence".to_string(), Value::Confidence(condition.confidence));
-                result.insert("matched_input".to_string(), Value::String(condition.input.clone()));
+                result.insert("matched_input".to_string(), Value::String(condition.input.as_str().into()));
                 
                 return Ok(Value::Map(result));
             }
@@ -243,6 +710,41 @@ impl GraphInterpreter {
         Ok(Value::Null)
     }
 
+    /// Check that `node.output` references to multi-output operations name
+    /// one of the outputs the operation declares.
+    fn check_output_refs(&self, graph: &Graph) -> Result<(), GatewayError> {
+        for node in &graph.nodes {
+            for input in &node.inputs {
+                let Some((source, output)) = input.split_once('.') else { continue };
+                let Some(NodeType::Operation { op }) = graph.get_node(source).map(|n| &n.node_type) else {
+                    continue;
+                };
+                let declared = self.builtins.get(op).map(|b| b.outputs()).unwrap_or_default();
+                if !declared.is_empty() && !declared.contains(&output) {
+                    return Err(GatewayError::ExecutionError(format!(
+                        "Node {} reads unknown output {} of {} ({} outputs: {})",
+                        node.id, output, source, op, declared.join(", ")
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that a multi-output operation produced every declared output.
+    fn check_outputs(op: &str, declared: &[&str], value: &Value) -> Result<(), GatewayError> {
+        if declared.is_empty() {
+            return Ok(());
+        }
+        let produced = value.as_map();
+        match declared.iter().find(|name| !produced.is_some_and(|map| map.contains_key(**name))) {
+            Some(missing) => Err(GatewayError::ExecutionError(format!(
+                "Operation {} did not produce output {}", op, missing
+            ))),
+            None => Ok(()),
+        }
+    }
+
     /// Gather input values for a node.
     fn gather_inputs(
         &self,
@@ -258,7 +760,7 @@ impl GraphInterpreter {
                 let node_id = parts[0];
                 let field = parts[1];
                 
-                ctx.node_values
+                ctx
                     .get(node_id)
                     .and_then(|v| {
                         if let Value::Map(m) = v {
@@ -269,7 +771,7 @@ impl GraphInterpreter {
                     })
                     .unwrap_or(Value::Null)
             } else {
-                ctx.node_values.get(input_ref).cloned().unwrap_or(Value::Null)
+                ctx.get(input_ref).cloned().unwrap_or(Value::Null)
             };
             
             values.push(value);
@@ -278,19 +780,55 @@ impl GraphInterpreter {
         Ok(values)
     }
 
+    /// Compute, for each output, the external/input nodes in its dependency cone.
+    fn compute_provenance(graph: &Graph) -> Result<HashMap<String, Vec<String>>, GatewayError> {
+        let mut cones: HashMap<&str, BTreeSet<String>> = HashMap::new();
+
+        for node in graph.topo_sort()? {
+            let mut cone = BTreeSet::new();
+            if matches!(node.node_type, NodeType::External { .. }) {
+                cone.insert(node.id.clone());
+            }
+
+            let mut deps: Vec<&str> = node.inputs.iter().map(|i| i.as_str()).collect();
+            if let NodeType::Route { conditions } = &node.node_type {
+                deps.extend(conditions.iter().map(|c| c.input.as_str()));
+            }
+            for dep in deps {
+                // Field references like "node.field" depend on "node"
+                let dep = dep.split('.').next().unwrap_or(dep);
+                if let Some(upstream) = cones.get(dep) {
+                    cone.extend(upstream.iter().cloned());
+                }
+            }
+
+            cones.insert(node.id.as_str(), cone);
+        }
+
+        Ok(graph
+            .outputs
+            .iter()
+            .map(|id| {
+                let cone = cones.get(id.as_str()).cloned().unwrap_or_default();
+                (id.clone(), cone.into_iter().collect())
+            })
+            .collect())
+    }
+
     /// Compute content hash of the execution.
     fn compute_execution_hash(&self, ctx: &ExecutionContext) -> Result<ContentHash, GatewayError> {
         use sha2::{Sha256, Digest};
         
         let mut hasher = Sha256::new();
         
-        // Hash the trace
+        // Hash the trace; maps are hashed in canonical key order so equal
+        // executions hash equally regardless of HashMap iteration order
         for node_id in &ctx.trace {
+            hasher.update((node_id.len() as u64).to_be_bytes());
             hasher.update(node_id.as_bytes());
-            if let Some(value) = ctx.node_values.get(node_id) {
-                let value_bytes = serde_json::to_vec(value).unwrap_or_default();
-                hasher.update(&value_bytes);
-            }
+            let bytes = ctx.node_values.get(node_id).map(Value::canonical_bytes).unwrap_or_default();
+            hasher.update((bytes.len() as u64).to_be_bytes());
+            hasher.update(&bytes);
         }
         
         let result = hasher.finalize();
@@ -299,14 +837,27 @@ impl GraphInterpreter {
 
     /// Load state for a session.
     pub async fn load_state(&self, session_id: &str) -> Value {
-        let store = self.state_store.read().await;
-        store.get(session_id).cloned().unwrap_or(Value::Null)
+        match self.state_store.get(session_id).await {
+            Ok(state) => state.unwrap_or(Value::Null),
+            Err(e) => {
+                tracing::warn!("Failed to load state for {}: {}", session_id, e);
+                Value::Null
+            }
+        }
     }
 
     /// Save state for a session.
     pub async fn save_state(&self, session_id: &str, state: Value) {
-        let mut store = self.state_store.write().await;
-        store.insert(session_id.to_string(), state);
+        if let Err(e) = self.state_store.set(session_id, state).await {
+            tracing::warn!("Failed to save state for {}: {}", session_id, e);
+        }
+    }
+
+    /// Remove state for a session.
+    pub async fn clear_state(&self, session_id: &str) {
+        if let Err(e) = self.state_store.delete(session_id).await {
+            tracing::warn!("Failed to clear state for {}: {}", session_id, e);
+        }
     }
 }
 
@@ -364,7 +915,7 @@ mod tests {
         let graph = create_test_graph();
         
         let mut inputs = HashMap::new();
-        inputs.insert("message".to_string(), Value::String("/help".to_string()));
+        inputs.insert("message".to_string(), Value::String("/help".into()));
         
         let result = interp.execute(&graph, inputs).await.unwrap();
         
@@ -379,7 +930,7 @@ mod tests {
         let graph = create_test_graph();
         
         let mut inputs = HashMap::new();
-        inputs.insert("message".to_string(), Value::String("hello".to_string()));
+        inputs.insert("message".to_string(), Value::String("hello".into()));
         
         let result = interp.execute(&graph, inputs).await.unwrap();
         
@@ -423,12 +974,12 @@ mod tests {
         };
         
         let mut inputs = HashMap::new();
-        inputs.insert("command".to_string(), Value::String("/help".to_string()));
+        inputs.insert("command".to_string(), Value::String("/help".into()));
         
         let result = interp.execute(&graph, inputs).await.unwrap();
         assert_eq!(
             result.outputs.get("lookup"),
-            Some(&Value::String("skill:help".to_string()))
+            Some(&Value::String("skill:help".into()))
         );
     }
 
@@ -456,7 +1007,7 @@ mod tests {
         let graph = create_test_graph();
         
         let mut inputs = HashMap::new();
-        inputs.insert("message".to_string(), Value::String("/help".to_string()));
+        inputs.insert("message".to_string(), Value::String("/help".into()));
         
         let result1 = interp.execute(&graph, inputs.clone()).await.unwrap();
         let result2 = interp.execute(&graph, inputs).await.unwrap();
@@ -464,4 +1015,661 @@ mod tests {
         // Same inputs should produce same hash
         assert_eq!(result1.hash, result2.hash);
     }
+
+    fn node(id: &str, node_type: NodeType, inputs: &[&str]) -> GraphNode {
+        GraphNode {
+            id: id.to_string(),
+            node_type,
+            inputs: inputs.iter().map(|s| s.to_string()).collect(),
+            params: serde_json::json!({}),
+        }
+    }
+
+    fn single_op_graph(op: &str) -> Graph {
+        Graph {
+            name: "single_op".to_string(),
+            version: 1,
+            description: String::new(),
+            nodes: vec![
+                node("data", NodeType::External { uri: "input://data".to_string() }, &[]),
+                node("out", NodeType::Operation { op: op.to_string() }, &["data"]),
+            ],
+            outputs: vec!["out".to_string()],
+            entry_point: "data".to_string(),
+            metadata: serde_json::json!({}),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_step_cost_scales_with_input() {
+        let interp = GraphInterpreter::new(RuntimeConfig { max_steps: 100, ..Default::default() });
+        let mut inputs = HashMap::new();
+        inputs.insert("data".to_string(), Value::String("x".repeat(64 * 1024).into()));
+
+        let identity = interp.execute(&single_op_graph("Identity"), inputs.clone()).await.unwrap();
+        let hash = interp.execute(&single_op_graph("Hash"), inputs.clone()).await.unwrap();
+        assert_eq!(identity.steps, 2);
+        assert_eq!(hash.steps, 66);
+
+        // A megabyte of hashing does not fit the same budget
+        inputs.insert("data".to_string(), Value::String("x".repeat(1024 * 1024).into()));
+        assert!(interp.execute(&single_op_graph("Identity"), inputs.clone()).await.is_ok());
+        assert!(interp.execute(&single_op_graph("Hash"), inputs).await.is_err());
+    }
+
+    fn create_provenance_graph() -> Graph {
+        let external = |uri: &str| NodeType::External { uri: uri.to_string() };
+        let op = |name: &str| NodeType::Operation { op: name.to_string() };
+        Graph {
+            name: "provenance".to_string(),
+            version: 1,
+            description: String::new(),
+            nodes: vec![
+                node("message", external("input://message"), &[]),
+                node("sender", external("input://sender"), &[]),
+                node("channel", external("input://channel"), &[]),
+                node("suffix", NodeType::Constant { value: Value::String("!".into()) }, &[]),
+                node("greeting", op("Concat"), &["message", "suffix"]),
+                node("signed", op("Concat"), &["greeting", "sender"]),
+                node("where", op("Identity"), &["channel"]),
+            ],
+            outputs: vec!["greeting".to_string(), "signed".to_string(), "where".to_string()],
+            entry_point: "message".to_string(),
+            metadata: serde_json::json!({}),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_provenance_tracks_upstream_inputs() {
+        let interp = GraphInterpreter::new(RuntimeConfig {
+            track_provenance: true,
+            ..Default::default()
+        });
+        let result = interp.execute(&create_provenance_graph(), HashMap::new()).await.unwrap();
+
+        assert_eq!(result.provenance["greeting"], vec!["message"]);
+        assert_eq!(result.provenance["signed"], vec!["message", "sender"]);
+        assert_eq!(result.provenance["where"], vec!["channel"]);
+    }
+
+    #[tokio::test]
+    async fn test_provenance_disabled_by_default() {
+        let interp = GraphInterpreter::default();
+        let result = interp.execute(&create_provenance_graph(), HashMap::new()).await.unwrap();
+        assert!(result.provenance.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_repeated_strings_share_storage() {
+        let op = |name: &str| NodeType::Operation { op: name.to_string() };
+        let graph = Graph {
+            name: "words".to_string(),
+            version: 1,
+            description: String::new(),
+            nodes: vec![
+                node("message", NodeType::External { uri: "input://message".to_string() }, &[]),
+                node("words", op("Split"), &["message"]),
+                node("joined", op("Join"), &["words"]),
+            ],
+            outputs: vec!["words".to_string(), "joined".to_string()],
+            entry_point: "message".to_string(),
+            metadata: serde_json::json!({}),
+        };
+        let message = ["yes", "no"].repeat(5_000).join(" ");
+        let inputs = HashMap::from([("message".to_string(), Value::from(message.as_str()))]);
+
+        let result = GraphInterpreter::default().execute(&graph, inputs).await.unwrap();
+
+        let Value::Array(words) = &result.outputs["words"] else { panic!("expected array") };
+        assert_eq!(words.len(), 10_000);
+        let (Value::String(first), Value::String(third)) = (&words[0], &words[2]) else {
+            panic!("expected strings")
+        };
+        assert!(Arc::ptr_eq(first, third));
+        assert_eq!(result.outputs["joined"], Value::from(message.replace(' ', "")));
+    }
+
+    /// Resolver that fails while `down` is set and counts calls.
+    struct FlakyResolver {
+        down: std::sync::atomic::AtomicBool,
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl ExternalResolver for FlakyResolver {
+        async fn resolve(&self, uri: &str, _inputs: Vec<Value>) -> Result<Value, GatewayError> {
+            use std::sync::atomic::Ordering;
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.down.load(Ordering::SeqCst) {
+                Err(GatewayError::ExecutionError(format!("{} unreachable", uri)))
+            } else {
+                Ok(Value::String("sunny".into()))
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_on_external_calls() {
+        use std::sync::atomic::Ordering;
+        use chrono::TimeZone;
+        use crate::clock::MockClock;
+        use crate::runtime::circuit_breaker::{CircuitBreakerConfig, CircuitState};
+
+        let clock = MockClock::new(chrono::Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap());
+        let resolver = Arc::new(FlakyResolver {
+            down: std::sync::atomic::AtomicBool::new(true),
+            calls: std::sync::atomic::AtomicUsize::new(0),
+        });
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold: 2,
+            window_ms: 60_000,
+            cooldown_ms: 30_000,
+        })
+        .with_clock(Arc::new(clock.clone()));
+        let interp = GraphInterpreter::default()
+            .with_external_resolver(resolver.clone())
+            .with_circuit_breaker(breaker);
+
+        let graph = Graph {
+            name: "weather".to_string(),
+            version: 1,
+            description: String::new(),
+            nodes: vec![GraphNode {
+                id: "forecast".to_string(),
+                node_type: NodeType::External { uri: "https://weather.example.com/today".to_string() },
+                inputs: vec![],
+                params: serde_json::json!({}),
+            }],
+            outputs: vec!["forecast".to_string()],
+            entry_point: "forecast".to_string(),
+            metadata: serde_json::json!({}),
+        };
+
+        // Two failures open the circuit; the third call never reaches the resolver
+        assert!(interp.execute(&graph, HashMap::new()).await.is_err());
+        assert!(interp.execute(&graph, HashMap::new()).await.is_err());
+        assert!(matches!(
+            interp.execute(&graph, HashMap::new()).await,
+            Err(GatewayError::CircuitOpen { .. })
+        ));
+        assert_eq!(resolver.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(interp.circuit_breaker().state("weather.example.com"), CircuitState::Open);
+
+        // After the cooldown a successful trial closes it again
+        resolver.down.store(false, Ordering::SeqCst);
+        clock.advance(chrono::Duration::seconds(30));
+        let result = interp.execute(&graph, HashMap::new()).await.unwrap();
+        assert_eq!(result.outputs["forecast"], Value::String("sunny".into()));
+        assert_eq!(interp.circuit_breaker().state("weather.example.com"), CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_http_get_op_goes_through_breaker() {
+        use std::sync::atomic::Ordering;
+        use crate::runtime::circuit_breaker::{CircuitBreakerConfig, CircuitState};
+
+        let resolver = Arc::new(FlakyResolver {
+            down: std::sync::atomic::AtomicBool::new(true),
+            calls: std::sync::atomic::AtomicUsize::new(0),
+        });
+        let interp = GraphInterpreter::default()
+            .with_external_resolver(resolver.clone())
+            .with_circuit_breaker(CircuitBreaker::new(CircuitBreakerConfig {
+                failure_threshold: 1,
+                window_ms: 60_000,
+                cooldown_ms: 30_000,
+            }));
+
+        let graph = Graph {
+            name: "fetch".to_string(),
+            version: 1,
+            description: String::new(),
+            nodes: vec![
+                GraphNode {
+                    id: "url".to_string(),
+                    node_type: NodeType::Constant {
+                        value: Value::String("https://weather.example.com/today".into()),
+                    },
+                    inputs: vec![],
+                    params: serde_json::json!({}),
+                },
+                GraphNode {
+                    id: "fetch".to_string(),
+                    node_type: NodeType::Operation { op: HTTP_GET_OP.to_string() },
+                    inputs: vec!["url".to_string()],
+                    params: serde_json::json!({}),
+                },
+            ],
+            outputs: vec!["fetch".to_string()],
+            entry_point: "url".to_string(),
+            metadata: serde_json::json!({}),
+        };
+
+        assert!(interp.execute(&graph, HashMap::new()).await.is_err());
+        assert!(matches!(
+            interp.execute(&graph, HashMap::new()).await,
+            Err(GatewayError::CircuitOpen { .. })
+        ));
+        assert_eq!(resolver.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(interp.circuit_breaker().state("weather.example.com"), CircuitState::Open);
+    }
+
+    fn normalize_subgraph(input: &str) -> NodeType {
+        let op = |name: &str| NodeType::Operation { op: name.to_string() };
+        NodeType::SubGraph {
+            graph: Graph {
+                name: "normalize".to_string(),
+                version: 1,
+                description: String::new(),
+                nodes: vec![
+                    node("raw", NodeType::External { uri: "input://text".to_string() }, &[]),
+                    node("trimmed", op("Trim"), &["raw"]),
+                    node("lower", op("ToLower"), &["trimmed"]),
+                ],
+                outputs: vec!["lower".to_string()],
+                entry_point: "raw".to_string(),
+                metadata: serde_json::json!({}),
+            },
+            input_map: HashMap::from([("text".to_string(), input.to_string())]),
+            output_map: HashMap::from([("lower".to_string(), "text".to_string())]),
+        }
+    }
+
+    fn create_subgraph_graph() -> Graph {
+        let external = |uri: &str| NodeType::External { uri: uri.to_string() };
+        Graph {
+            name: "greeting".to_string(),
+            version: 1,
+            description: String::new(),
+            nodes: vec![
+                node("first", external("input://first"), &[]),
+                node("last", external("input://last"), &[]),
+                node("norm_first", normalize_subgraph("first"), &["first"]),
+                node("norm_last", normalize_subgraph("last"), &["last"]),
+                node(
+                    "joined",
+                    NodeType::Operation { op: "Concat".to_string() },
+                    &["norm_first.text", "norm_last.text"],
+                ),
+            ],
+            outputs: vec!["joined".to_string()],
+            entry_point: "first".to_string(),
+            metadata: serde_json::json!({}),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subgraph_reused_in_isolation() {
+        let interp = GraphInterpreter::default();
+        let inputs = HashMap::from([
+            ("first".to_string(), Value::String("  Hello ".into())),
+            ("last".to_string(), Value::String(" WORLD".into())),
+        ]);
+        let result = interp.execute(&create_subgraph_graph(), inputs.clone()).await.unwrap();
+
+        assert_eq!(result.outputs["joined"], Value::String("helloworld".into()));
+        // Nested nodes stay inside the subgraph
+        assert!(!result.trace.contains(&"trimmed".to_string()));
+        assert_eq!(result.trace.len(), 5);
+
+        // The step budget spans the subgraph boundary
+        let tight = GraphInterpreter::new(RuntimeConfig {
+            max_steps: result.steps - 1,
+            ..Default::default()
+        });
+        assert!(tight.execute(&create_subgraph_graph(), inputs).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_result_cache_serves_pure_graphs_only() {
+        use chrono::TimeZone;
+        use crate::clock::MockClock;
+
+        let clock = MockClock::new(chrono::Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap());
+        let interp = GraphInterpreter::default()
+            .with_result_cache(ResultCache::new(60_000).with_clock(Arc::new(clock.clone())));
+        let inputs = HashMap::from([("data".to_string(), Value::String("hello".into()))]);
+
+        let pure = single_op_graph("ToUpper");
+        let first = interp.execute(&pure, inputs.clone()).await.unwrap();
+        let second = interp.execute(&pure, inputs.clone()).await.unwrap();
+        assert_eq!(second.outputs, first.outputs);
+        assert_eq!(second.hash, first.hash);
+        assert_eq!(interp.cache_stats(), Some(CacheStats { hits: 1, misses: 1, entries: 1 }));
+
+        // Expired entries run again
+        clock.advance(chrono::Duration::seconds(61));
+        interp.execute(&pure, inputs.clone()).await.unwrap();
+        assert_eq!(interp.cache_stats().unwrap().misses, 2);
+
+        // Side-effecting graphs never touch the cache
+        let clock_graph = single_op_graph("Timestamp");
+        assert!(!interp.is_cacheable(&clock_graph));
+        interp.execute(&clock_graph, inputs.clone()).await.unwrap();
+        interp.execute(&clock_graph, inputs).await.unwrap();
+        assert_eq!(interp.cache_stats(), Some(CacheStats { hits: 1, misses: 2, entries: 1 }));
+    }
+
+    #[tokio::test]
+    async fn test_result_cache_bounded() {
+        use chrono::TimeZone;
+        use crate::clock::MockClock;
+
+        let clock = MockClock::new(chrono::Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap());
+        let cache = ResultCache::new(60_000).with_clock(Arc::new(clock.clone())).with_max_entries(2);
+        let result = GraphInterpreter::default()
+            .execute(&single_op_graph("ToUpper"), HashMap::from([("data".to_string(), Value::String("a".into()))]))
+            .await
+            .unwrap();
+        let key = |name: &str| ContentHash::from_string(name);
+
+        // The least recently used result makes room
+        cache.insert(key("a"), result.clone());
+        cache.insert(key("b"), result.clone());
+        assert!(cache.get(&key("a")).is_some());
+        cache.insert(key("c"), result.clone());
+        assert!(cache.get(&key("b")).is_none());
+        assert!(cache.get(&key("a")).is_some());
+        assert_eq!(cache.stats().entries, 2);
+
+        // Expired results are swept before live ones are evicted
+        clock.advance(chrono::Duration::seconds(61));
+        cache.insert(key("d"), result);
+        assert_eq!(cache.stats().entries, 1);
+        assert!(cache.get(&key("d")).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_subgraph_scope_shadows_without_clobbering() {
+        let op = |name: &str| NodeType::Operation { op: name.to_string() };
+        // The inner "text" shadows the outer one; "suffix" is read from the parent
+        let shout = NodeType::SubGraph {
+            graph: Graph {
+                name: "shout".to_string(),
+                version: 1,
+                description: String::new(),
+                nodes: vec![
+                    node("raw", NodeType::External { uri: "input://raw".to_string() }, &[]),
+                    node("text", op("ToUpper"), &["raw"]),
+                    node("out", op("Concat"), &["text", "suffix"]),
+                ],
+                outputs: vec!["out".to_string()],
+                entry_point: "raw".to_string(),
+                metadata: serde_json::json!({}),
+            },
+            input_map: HashMap::from([("raw".to_string(), "text".to_string())]),
+            output_map: HashMap::new(),
+        };
+        let graph = Graph {
+            name: "scoped".to_string(),
+            version: 1,
+            description: String::new(),
+            nodes: vec![
+                node("text", NodeType::External { uri: "input://text".to_string() }, &[]),
+                node("suffix", NodeType::Constant { value: Value::String("!".into()) }, &[]),
+                node("shout", shout, &["text", "suffix"]),
+                node("joined", op("Concat"), &["text", "shout.out"]),
+            ],
+            outputs: vec!["text".to_string(), "joined".to_string()],
+            entry_point: "text".to_string(),
+            metadata: serde_json::json!({}),
+        };
+
+        let inputs = HashMap::from([("text".to_string(), Value::String("Hey".into()))]);
+        let result = GraphInterpreter::default().execute(&graph, inputs).await.unwrap();
+
+        assert_eq!(result.outputs["text"], Value::String("Hey".into()));
+        assert_eq!(result.outputs["joined"], Value::String("HeyHEY!".into()));
+        assert_eq!(result.trace.len(), 4);
+        assert!(!result.trace.iter().any(|id| id == "raw" || id == "out"));
+    }
+
+    struct SlowResolver;
+
+    #[async_trait]
+    impl ExternalResolver for SlowResolver {
+        async fn resolve(&self, _uri: &str, _inputs: Vec<Value>) -> Result<Value, GatewayError> {
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+            Ok(Value::Null)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timeout_stops_slow_external_node() {
+        let interp = GraphInterpreter::new(RuntimeConfig { timeout_ms: 50, ..Default::default() })
+            .with_external_resolver(Arc::new(SlowResolver));
+        let graph = Graph {
+            name: "slow".to_string(),
+            version: 1,
+            description: String::new(),
+            nodes: vec![
+                node("query", NodeType::Constant { value: Value::from("weather") }, &[]),
+                node("fetch", NodeType::External { uri: "https://slow.example.com".to_string() }, &["query"]),
+            ],
+            outputs: vec!["fetch".to_string()],
+            entry_point: "query".to_string(),
+            metadata: serde_json::json!({}),
+        };
+
+        let started = std::time::Instant::now();
+        match interp.execute(&graph, HashMap::new()).await {
+            Err(GatewayError::ExecutionTimeout { elapsed_ms, trace }) => {
+                assert!(elapsed_ms >= 50);
+                assert_eq!(trace, vec!["query".to_string()]);
+            }
+            other => panic!("expected timeout, got {:?}", other),
+        }
+        assert!(started.elapsed() < std::time::Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_timeout_stops_slow_operation() {
+        let interp = GraphInterpreter::new(RuntimeConfig { timeout_ms: 50, ..Default::default() });
+        let mut graph = single_op_graph("Wait");
+        graph.nodes[1].params = serde_json::json!({"wait_ms": 5_000});
+        let mut inputs = HashMap::new();
+        inputs.insert("data".to_string(), Value::from("x"));
+
+        let result = interp.execute(&graph, inputs).await;
+        assert!(
+            matches!(&result, Err(GatewayError::ExecutionTimeout { trace, .. }) if trace == &["data".to_string()]),
+            "{:?}",
+            result
+        );
+    }
+
+    fn body_graph(name: &str, nodes: Vec<GraphNode>, output: &str) -> Graph {
+        Graph {
+            name: name.to_string(),
+            version: 1,
+            description: String::new(),
+            nodes,
+            outputs: vec![output.to_string()],
+            entry_point: String::new(),
+            metadata: serde_json::json!({}),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_map_filter_reduce_run_bodies() {
+        let item = || node("item", NodeType::External { uri: "input://item".to_string() }, &[]);
+        let upper = body_graph(
+            "upper",
+            vec![item(), node("up", NodeType::Operation { op: "ToUpper".to_string() }, &["item"])],
+            "up",
+        );
+        // The predicate reads `limit` from the enclosing graph
+        let above = body_graph(
+            "above",
+            vec![item(), node("gt", NodeType::Operation { op: "GreaterThan".to_string() }, &["item", "limit"])],
+            "gt",
+        );
+        let sum = body_graph(
+            "sum",
+            vec![
+                item(),
+                node("acc", NodeType::External { uri: "input://acc".to_string() }, &[]),
+                node("add", NodeType::Operation { op: "Add".to_string() }, &["acc", "item"]),
+            ],
+            "add",
+        );
+        let graph = Graph {
+            name: "loops".to_string(),
+            version: 1,
+            description: String::new(),
+            nodes: vec![
+                node("words", NodeType::Constant { value: Value::from_json(&serde_json::json!(["a", "b"])) }, &[]),
+                node("numbers", NodeType::Constant { value: Value::from_json(&serde_json::json!([1, 5, 3, 8])) }, &[]),
+                node("limit", NodeType::Constant { value: Value::Int(3) }, &[]),
+                node("shouted", NodeType::Map { body: upper }, &["words"]),
+                node("big", NodeType::Filter { predicate: above }, &["numbers", "limit"]),
+                node("total", NodeType::Reduce { body: sum, initial: Value::Int(0) }, &["numbers"]),
+            ],
+            outputs: vec!["shouted".to_string(), "big".to_string(), "total".to_string()],
+            entry_point: "words".to_string(),
+            metadata: serde_json::json!({}),
+        };
+
+        let result = GraphInterpreter::default().execute(&graph, HashMap::new()).await.unwrap();
+        assert_eq!(result.outputs["shouted"], Value::from_json(&serde_json::json!(["A", "B"])));
+        assert_eq!(result.outputs["big"], Value::from_json(&serde_json::json!([5, 8])));
+        assert_eq!(result.outputs["total"].as_float(), Some(17.0));
+        assert!(!result.trace.contains(&"up".to_string()));
+
+        // Every element's body counts against the parent budget
+        let limited = GraphInterpreter::new(RuntimeConfig { max_steps: 10, ..Default::default() });
+        assert!(limited.execute(&graph, HashMap::new()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_loops_over_empty_and_non_array_inputs() {
+        let identity = || body_graph(
+            "identity",
+            vec![
+                node("item", NodeType::External { uri: "input://item".to_string() }, &[]),
+                node("same", NodeType::Operation { op: "Identity".to_string() }, &["item"]),
+            ],
+            "same",
+        );
+        let loops = |data: Value| Graph {
+            name: "loops".to_string(),
+            version: 1,
+            description: String::new(),
+            nodes: vec![
+                node("data", NodeType::Constant { value: data }, &[]),
+                node("mapped", NodeType::Map { body: identity() }, &["data"]),
+                node("kept", NodeType::Filter { predicate: identity() }, &["data"]),
+                node("folded", NodeType::Reduce { body: identity(), initial: Value::Int(7) }, &["data"]),
+            ],
+            outputs: vec!["mapped".to_string(), "kept".to_string(), "folded".to_string()],
+            entry_point: "data".to_string(),
+            metadata: serde_json::json!({}),
+        };
+        let interp = GraphInterpreter::default();
+
+        for empty in [Value::Array(Vec::new()), Value::Null] {
+            let result = interp.execute(&loops(empty), HashMap::new()).await.unwrap();
+            assert_eq!(result.outputs["mapped"], Value::Array(Vec::new()));
+            assert_eq!(result.outputs["kept"], Value::Array(Vec::new()));
+            assert_eq!(result.outputs["folded"], Value::Int(7));
+        }
+
+        let err = interp.execute(&loops(Value::from("a,b")), HashMap::new()).await.unwrap_err();
+        assert!(err.to_string().contains("expects an array input"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_state_namespaces_isolate_skills() {
+        let save = Graph {
+            name: "save".to_string(),
+            version: 1,
+            description: String::new(),
+            nodes: vec![
+                node("key", NodeType::Constant { value: Value::from("prefs") }, &[]),
+                node("value", NodeType::Constant { value: Value::from("dark mode") }, &[]),
+                node("saved", NodeType::Operation { op: "SaveState".to_string() }, &["key", "value"]),
+            ],
+            outputs: vec!["saved".to_string()],
+            entry_point: "key".to_string(),
+            metadata: serde_json::json!({}),
+        };
+        let mut load = save.clone();
+        load.nodes = vec![
+            node("key", NodeType::Constant { value: Value::from("prefs") }, &[]),
+            node("loaded", NodeType::Operation { op: "LoadState".to_string() }, &["key"]),
+        ];
+        load.outputs = vec!["loaded".to_string()];
+        let mut forged = load.clone();
+        forged.nodes[1].params = serde_json::json!({"state_namespace": "skill-a/session-1"});
+
+        let interp = GraphInterpreter::default();
+        interp.execute_in_namespace(&save, HashMap::new(), "skill-a/session-1").await.unwrap();
+
+        let own = interp.execute_in_namespace(&load, HashMap::new(), "skill-a/session-1").await.unwrap();
+        assert_eq!(own.outputs["loaded"], Value::from("dark mode"));
+        for namespace in ["skill-b/session-1", "skill-a/session-2"] {
+            let other = interp.execute_in_namespace(&load, HashMap::new(), namespace).await.unwrap();
+            assert!(other.outputs["loaded"].as_map().is_some(), "{} read another namespace", namespace);
+        }
+        let other = interp.execute_in_namespace(&forged, HashMap::new(), "skill-b/session-1").await.unwrap();
+        assert!(other.outputs["loaded"].as_map().is_some());
+
+        // A raw key naming another skill's namespace reaches nothing
+        let mut raw = load.clone();
+        raw.nodes[0] = node("key", NodeType::Constant { value: Value::from("skill-a/session-1/prefs") }, &[]);
+        assert!(interp.execute(&raw, HashMap::new()).await.is_err());
+        let nested = interp.execute_in_namespace(&raw, HashMap::new(), "skill-b/session-1").await.unwrap();
+        assert!(nested.outputs["loaded"].as_map().is_some());
+    }
+
+    fn parse_command_graph(args_ref: &str) -> Graph {
+        let op = |name: &str| NodeType::Operation { op: name.to_string() };
+        Graph {
+            name: "parse_command".to_string(),
+            version: 1,
+            description: String::new(),
+            nodes: vec![
+                node("message", NodeType::External { uri: "input://message".to_string() }, &[]),
+                node("parsed", op("ParseCommand"), &["message"]),
+                node("command", op("ToUpper"), &["parsed.command"]),
+                node("args", op("Join"), &[args_ref]),
+            ],
+            outputs: vec!["command".to_string(), "args".to_string()],
+            entry_point: "message".to_string(),
+            metadata: serde_json::json!({}),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_multi_output_op() {
+        let interp = GraphInterpreter::default();
+        let mut inputs = HashMap::new();
+        inputs.insert("message".to_string(), Value::String("/search rust async".into()));
+
+        let result = interp.execute(&parse_command_graph("parsed.args"), inputs.clone()).await.unwrap();
+        assert_eq!(result.outputs["command"], Value::String("/SEARCH".into()));
+        assert_eq!(result.outputs["args"], Value::String("rustasync".into()));
+
+        // Outputs the op does not declare are rejected before execution
+        let err = interp.execute(&parse_command_graph("parsed.flags"), inputs).await.unwrap_err();
+        assert!(err.to_string().contains("unknown output flags of parsed"), "{}", err);
+        assert!(interp.builtins().get("ParseCommand").unwrap().outputs().contains(&"args"));
+    }
+
+    #[tokio::test]
+    async fn test_execution_hash_ignores_map_order() {
+        let interp = GraphInterpreter::default();
+        let graph = single_op_graph("Identity");
+        let run = || {
+            let data: HashMap<String, Value> =
+                (0..32).map(|i| (format!("key{}", i), Value::Int(i))).collect();
+            let mut inputs = HashMap::new();
+            inputs.insert("data".to_string(), Value::Map(data));
+            interp.execute(&graph, inputs)
+        };
+
+        let first = run().await.unwrap();
+        let second = run().await.unwrap();
+        assert_eq!(first.hash, second.hash);
+    }
 }
@@ -6,11 +6,20 @@
 
 mod interpreter;
 mod builtins;
+pub mod cache;
+pub mod circuit_breaker;
+pub mod http;
+pub mod state;
 pub mod types;
 
-pub use interpreter::{GraphInterpreter, ExecutionContext, ExecutionResult};
-pub use builtins::{BuiltinOp, BuiltinRegistry};
-pub use types::{Value, GraphNode, Graph, NodeType, Edge};
+pub use interpreter::{GraphInterpreter, ExecutionContext, ExecutionResult, ExternalResolver};
+pub use cache::{CacheStats, ResultCache, DEFAULT_MAX_RESULT_ENTRIES};
+pub use circuit_breaker::{CallPermit, CircuitBreaker, CircuitBreakerConfig, CircuitState};
+pub use http::HttpResolver;
+pub use builtins::{BuiltinOp, BuiltinRegistry, Keystore, parse_duration_ms};
+pub(crate) use builtins::edit_distance;
+pub use state::{StateBackend, MemoryStateBackend};
+pub use types::{Value, GraphNode, Graph, NodeType, Edge, StringInterner};
 
 use crate::error::GatewayError;
 
@@ -18,19 +27,40 @@ use crate::error::GatewayError;
 #[derive(Debug, Clone)]
 pub struct RuntimeConfig {
     /// Maximum execution steps (prevents infinite loops)
+    ///
+    /// Each node consumes its operation's step cost, so expensive
+    /// operations use more of the budget.
     pub max_steps: usize,
+    /// Steps consumed by a call to an external resolver
+    pub external_step_cost: u64,
     /// Enable execution tracing
     pub trace_enabled: bool,
     /// Timeout in milliseconds
     pub timeout_ms: u64,
+    /// Record which external/input nodes each output depends on
+    pub track_provenance: bool,
+    /// Circuit breaker thresholds for external calls
+    pub circuit_breaker: CircuitBreakerConfig,
+    /// Time to live of cached results of side-effect-free graphs (0 disables)
+    pub result_cache_ttl_ms: u64,
+    /// Maximum number of cached results
+    pub result_cache_max_entries: usize,
+    /// Development mode: lets `Sign` take a raw `seed` param
+    pub dev_mode: bool,
 }
 
 impl Default for RuntimeConfig {
     fn default() -> Self {
         Self {
             max_steps: 10000,
+            external_step_cost: 10,
             trace_enabled: true,
             timeout_ms: 30000,
+            track_provenance: false,
+            circuit_breaker: CircuitBreakerConfig::default(),
+            result_cache_ttl_ms: 0,
+            result_cache_max_entries: cache::DEFAULT_MAX_RESULT_ENTRIES,
+            dev_mode: false,
         }
     }
 }
@@ -95,7 +125,7 @@ mod tests {
         };
 
         let mut inputs = std::collections::HashMap::new();
-        inputs.insert("message".to_string(), Value::String("hello".to_string()));
+        inputs.insert("message".to_string(), Value::String("hello".into()));
 
         let result = interp.execute(&graph, inputs).await.unwrap();
         assert!(result.outputs.contains_key("output"));
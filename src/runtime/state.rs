@@ -0,0 +1,253 @@
+//! Pluggable storage for cross-execution interpreter state.
+//!
+//! The default backend keeps state in process memory. With the `redis`
+//! feature, state can live in Redis so that several gateway instances share
+//! it and it survives restarts.
+
+use std::collections::HashMap;
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+
+use super::types::Value;
+use crate::error::GatewayError;
+
+/// Storage backend for interpreter state.
+#[async_trait]
+pub trait StateBackend: Send + Sync {
+    /// Get the value stored under `key`.
+    async fn get(&self, key: &str) -> Result<Option<Value>, GatewayError>;
+
+    /// Store `value` under `key`, replacing any previous value.
+    async fn set(&self, key: &str, value: Value) -> Result<(), GatewayError>;
+
+    /// Remove the value stored under `key`.
+    async fn delete(&self, key: &str) -> Result<(), GatewayError>;
+}
+
+/// In-process state backend (the default).
+#[derive(Debug, Default)]
+pub struct MemoryStateBackend {
+    store: RwLock<HashMap<String, Value>>,
+}
+
+impl MemoryStateBackend {
+    /// Create an empty in-memory backend.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl StateBackend for MemoryStateBackend {
+    async fn get(&self, key: &str) -> Result<Option<Value>, GatewayError> {
+        Ok(self.store.read().await.get(key).cloned())
+    }
+
+    async fn set(&self, key: &str, value: Value) -> Result<(), GatewayError> {
+        self.store.write().await.insert(key.to_string(), value);
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), GatewayError> {
+        self.store.write().await.remove(key);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "redis")]
+pub use self::redis::{RedisClient, RedisStateBackend, TcpRedisClient};
+
+#[cfg(feature = "redis")]
+mod redis {
+    use async_trait::async_trait;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpStream;
+    use tokio::sync::Mutex;
+
+    use super::StateBackend;
+    use crate::error::GatewayError;
+    use crate::runtime::types::Value;
+
+    /// Minimal string-keyed Redis command set used by [`RedisStateBackend`].
+    #[async_trait]
+    pub trait RedisClient: Send + Sync {
+        /// `GET key`
+        async fn get(&self, key: &str) -> Result<Option<String>, GatewayError>;
+        /// `SET key value`
+        async fn set(&self, key: &str, value: &str) -> Result<(), GatewayError>;
+        /// `DEL key`
+        async fn del(&self, key: &str) -> Result<(), GatewayError>;
+    }
+
+    /// Redis client speaking RESP over a single TCP connection.
+    pub struct TcpRedisClient {
+        stream: Mutex<BufReader<TcpStream>>,
+    }
+
+    impl TcpRedisClient {
+        /// Connect to a Redis server at `addr` (e.g. `127.0.0.1:6379`).
+        pub async fn connect(addr: &str) -> Result<Self, GatewayError> {
+            let stream = TcpStream::connect(addr)
+                .await
+                .map_err(|e| GatewayError::ServerError(format!("Redis connect failed: {}", e)))?;
+            Ok(Self { stream: Mutex::new(BufReader::new(stream)) })
+        }
+
+        async fn command(&self, args: &[&str]) -> Result<Option<String>, GatewayError> {
+            let mut request = format!("*{}\r\n", args.len());
+            for arg in args {
+                request.push_str(&format!("${}\r\n{}\r\n", arg.len(), arg));
+            }
+
+            let io_err = |e: std::io::Error| GatewayError::ServerError(format!("Redis I/O error: {}", e));
+            let mut stream = self.stream.lock().await;
+            stream.get_mut().write_all(request.as_bytes()).await.map_err(io_err)?;
+
+            let mut line = String::new();
+            stream.read_line(&mut line).await.map_err(io_err)?;
+            let line = line.trim_end();
+            let (kind, rest) = line.split_at(line.len().min(1));
+            match kind {
+                "+" | ":" => Ok(Some(rest.to_string())),
+                "-" => Err(GatewayError::ServerError(format!("Redis error: {}", rest))),
+                "$" => {
+                    let len: i64 = rest
+                        .parse()
+                        .map_err(|_| GatewayError::ServerError(format!("Bad Redis reply: {}", line)))?;
+                    if len < 0 {
+                        return Ok(None);
+                    }
+                    let mut buf = vec![0u8; len as usize + 2];
+                    stream.read_exact(&mut buf).await.map_err(io_err)?;
+                    buf.truncate(len as usize);
+                    String::from_utf8(buf)
+                        .map(Some)
+                        .map_err(|e| GatewayError::ServerError(format!("Bad Redis reply: {}", e)))
+                }
+                _ => Err(GatewayError::ServerError(format!("Unexpected Redis reply: {}", line))),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl RedisClient for TcpRedisClient {
+        async fn get(&self, key: &str) -> Result<Option<String>, GatewayError> {
+            self.command(&["GET", key]).await
+        }
+
+        async fn set(&self, key: &str, value: &str) -> Result<(), GatewayError> {
+            self.command(&["SET", key, value]).await.map(|_| ())
+        }
+
+        async fn del(&self, key: &str) -> Result<(), GatewayError> {
+            self.command(&["DEL", key]).await.map(|_| ())
+        }
+    }
+
+    /// State backend storing canonical JSON values in Redis.
+    pub struct RedisStateBackend<C: RedisClient = TcpRedisClient> {
+        client: C,
+        prefix: String,
+    }
+
+    impl RedisStateBackend<TcpRedisClient> {
+        /// Connect to a Redis server at `addr`.
+        pub async fn connect(addr: &str) -> Result<Self, GatewayError> {
+            Ok(Self::new(TcpRedisClient::connect(addr).await?))
+        }
+    }
+
+    impl<C: RedisClient> RedisStateBackend<C> {
+        /// Create a backend over an existing client.
+        pub fn new(client: C) -> Self {
+            Self {
+                client,
+                prefix: "openclaw:state:".to_string(),
+            }
+        }
+
+        /// Set the key prefix (default `openclaw:state:`).
+        pub fn with_prefix(mut self, prefix: &str) -> Self {
+            self.prefix = prefix.to_string();
+            self
+        }
+
+        fn key(&self, key: &str) -> String {
+            format!("{}{}", self.prefix, key)
+        }
+    }
+
+    #[async_trait]
+    impl<C: RedisClient> StateBackend for RedisStateBackend<C> {
+        async fn get(&self, key: &str) -> Result<Option<Value>, GatewayError> {
+            let Some(raw) = self.client.get(&self.key(key)).await? else {
+                return Ok(None);
+            };
+            let json: serde_json::Value = serde_json::from_str(&raw)
+                .map_err(|e| GatewayError::ExecutionError(format!("Corrupt state for {}: {}", key, e)))?;
+            Ok(Some(Value::from_json(&json)))
+        }
+
+        async fn set(&self, key: &str, value: Value) -> Result<(), GatewayError> {
+            let raw = value.to_canonical_json().to_string();
+            self.client.set(&self.key(key), &raw).await
+        }
+
+        async fn delete(&self, key: &str) -> Result<(), GatewayError> {
+            self.client.del(&self.key(key)).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_backend() {
+        let backend = MemoryStateBackend::new();
+        assert_eq!(backend.get("a").await.unwrap(), None);
+
+        backend.set("a", Value::Int(1)).await.unwrap();
+        assert_eq!(backend.get("a").await.unwrap(), Some(Value::Int(1)));
+
+        backend.delete("a").await.unwrap();
+        assert_eq!(backend.get("a").await.unwrap(), None);
+    }
+
+    #[cfg(feature = "redis")]
+    #[tokio::test]
+    async fn test_redis_backend_shared_across_instances() {
+        use std::sync::Arc;
+
+        /// In-memory stand-in for a Redis server shared by several clients.
+        #[derive(Clone, Default)]
+        struct MockRedis(Arc<std::sync::Mutex<HashMap<String, String>>>);
+
+        #[async_trait]
+        impl RedisClient for MockRedis {
+            async fn get(&self, key: &str) -> Result<Option<String>, GatewayError> {
+                Ok(self.0.lock().unwrap().get(key).cloned())
+            }
+            async fn set(&self, key: &str, value: &str) -> Result<(), GatewayError> {
+                self.0.lock().unwrap().insert(key.to_string(), value.to_string());
+                Ok(())
+            }
+            async fn del(&self, key: &str) -> Result<(), GatewayError> {
+                self.0.lock().unwrap().remove(key);
+                Ok(())
+            }
+        }
+
+        let server = MockRedis::default();
+        let first = RedisStateBackend::new(server.clone());
+        let second = RedisStateBackend::new(server.clone());
+
+        first.set("session", Value::Float(2.0)).await.unwrap();
+        assert_eq!(second.get("session").await.unwrap(), Some(Value::Float(2.0)));
+        assert!(server.0.lock().unwrap().contains_key("openclaw:state:session"));
+
+        second.delete("session").await.unwrap();
+        assert_eq!(first.get("session").await.unwrap(), None);
+    }
+}
@@ -1,7 +1,8 @@
 //! Core types for the 0-lang runtime.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, OnceLock};
 use crate::error::GatewayError;
 
 /// A value in the 0-lang runtime.
@@ -12,7 +13,8 @@ pub enum Value {
     Bool(bool),
     Int(i64),
     Float(f64),
-    String(String),
+    /// Shared string; clones and interned duplicates share storage
+    String(Arc<str>),
     Bytes(Vec<u8>),
     Array(Vec<Value>),
     Map(HashMap<String, Value>),
@@ -74,6 +76,170 @@ impl Value {
             Value::Confidence(c) => *c > 0.0,
         }
     }
+
+    /// Convert JSON into a runtime value.
+    ///
+    /// Integral JSON numbers (`2`) become `Int`; numbers with a fraction or
+    /// exponent (`2.0`, `2e0`) become `Float`.
+    pub fn from_json(json: &serde_json::Value) -> Value {
+        match json {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(b) => Value::Bool(*b),
+            serde_json::Value::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    Value::Int(i)
+                } else if let Some(f) = n.as_f64() {
+                    Value::Float(f)
+                } else {
+                    Value::Null
+                }
+            }
+            serde_json::Value::String(s) => Value::String(s.as_str().into()),
+            serde_json::Value::Array(a) => Value::Array(a.iter().map(Value::from_json).collect()),
+            serde_json::Value::Object(o) => {
+                Value::Map(o.iter().map(|(k, v)| (k.clone(), Value::from_json(v))).collect())
+            }
+        }
+    }
+
+    /// Canonical JSON form, for handing values to JSON consumers.
+    ///
+    /// `Int` and `Float` are deliberately distinct: `Int(2)` encodes as `2`
+    /// and `Float(2.0)` as `2.0`, so each round-trips through
+    /// [`Value::from_json`] to the same variant. Map keys are sorted so equal
+    /// maps always encode identically. Non-finite floats have no JSON form
+    /// and encode as `null`. JSON loses some types (`Bytes` and `Array` look
+    /// alike), so hashing and signing use [`Value::canonical_bytes`].
+    pub fn to_canonical_json(&self) -> serde_json::Value {
+        match self {
+            Value::Null => serde_json::Value::Null,
+            Value::Bool(b) => serde_json::Value::Bool(*b),
+            Value::Int(i) => serde_json::Value::from(*i),
+            Value::Float(f) | Value::Confidence(f) => serde_json::Number::from_f64(*f)
+                .map(serde_json::Value::Number)
+                .unwrap_or(serde_json::Value::Null),
+            Value::String(s) => serde_json::Value::String(s.to_string()),
+            Value::Bytes(b) => serde_json::Value::from(b.clone()),
+            Value::Hash(h) => serde_json::Value::from(h.to_vec()),
+            Value::Array(a) => serde_json::Value::Array(a.iter().map(Value::to_canonical_json).collect()),
+            Value::Map(m) => {
+                let mut keys: Vec<&String> = m.keys().collect();
+                keys.sort();
+                let mut object = serde_json::Map::new();
+                for key in keys {
+                    object.insert(key.clone(), m[key].to_canonical_json());
+                }
+                serde_json::Value::Object(object)
+            }
+        }
+    }
+
+    /// Canonical byte encoding used for hashing and signing.
+    ///
+    /// Every value starts with a tag naming its variant, and strings, bytes
+    /// and collections carry their length, so values of different types or
+    /// shapes never encode alike: `String("1")`, `Int(1)` and `Float(1.0)`
+    /// all differ. Map entries are sorted by key.
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.write_canonical(&mut out);
+        out
+    }
+
+    fn write_canonical(&self, out: &mut Vec<u8>) {
+        let write_len = |out: &mut Vec<u8>, len: usize| out.extend_from_slice(&(len as u64).to_be_bytes());
+        match self {
+            Value::Null => out.push(b'n'),
+            Value::Bool(b) => out.extend_from_slice(&[b'b', u8::from(*b)]),
+            Value::Int(i) => {
+                out.push(b'i');
+                out.extend_from_slice(&i.to_be_bytes());
+            }
+            Value::Float(f) => {
+                out.push(b'f');
+                out.extend_from_slice(&f.to_bits().to_be_bytes());
+            }
+            Value::Confidence(c) => {
+                out.push(b'c');
+                out.extend_from_slice(&c.to_bits().to_be_bytes());
+            }
+            Value::String(s) => {
+                out.push(b's');
+                write_len(out, s.len());
+                out.extend_from_slice(s.as_bytes());
+            }
+            Value::Bytes(b) => {
+                out.push(b'y');
+                write_len(out, b.len());
+                out.extend_from_slice(b);
+            }
+            Value::Hash(h) => {
+                out.push(b'h');
+                out.extend_from_slice(h);
+            }
+            Value::Array(items) => {
+                out.push(b'a');
+                write_len(out, items.len());
+                items.iter().for_each(|item| item.write_canonical(out));
+            }
+            Value::Map(m) => {
+                out.push(b'm');
+                write_len(out, m.len());
+                let mut keys: Vec<&String> = m.keys().collect();
+                keys.sort();
+                for key in keys {
+                    write_len(out, key.len());
+                    out.extend_from_slice(key.as_bytes());
+                    m[key].write_canonical(out);
+                }
+            }
+        }
+    }
+}
+
+/// Pool that makes equal strings share one allocation.
+///
+/// The interpreter keeps one per execution, so repeated strings in large
+/// arrays are stored once and cloning them only bumps a reference count.
+#[derive(Debug, Default)]
+pub struct StringInterner {
+    pool: HashSet<Arc<str>>,
+}
+
+impl StringInterner {
+    /// Create an empty pool.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the pooled copy of `s`, adding it if absent.
+    pub fn intern_str(&mut self, s: Arc<str>) -> Arc<str> {
+        if let Some(existing) = self.pool.get(&s) {
+            return existing.clone();
+        }
+        self.pool.insert(s.clone());
+        s
+    }
+
+    /// Intern every string inside `value`.
+    pub fn intern(&mut self, value: Value) -> Value {
+        match value {
+            Value::String(s) => Value::String(self.intern_str(s)),
+            Value::Array(items) => Value::Array(items.into_iter().map(|v| self.intern(v)).collect()),
+            Value::Map(map) => Value::Map(map.into_iter().map(|(k, v)| (k, self.intern(v))).collect()),
+            other => other,
+        }
+    }
+
+    /// Number of distinct strings in the pool.
+    pub fn len(&self) -> usize {
+        self.pool.len()
+    }
+
+    /// Whether the pool is empty.
+    pub fn is_empty(&self) -> bool {
+        self.pool.is_empty()
+    }
 }
 
 impl Default for Value {
@@ -84,13 +250,13 @@ impl Default for Value {
 
 impl From<String> for Value {
     fn from(s: String) -> Self {
-        Value::String(s)
+        Value::String(s.into())
     }
 }
 
 impl From<&str> for Value {
     fn from(s: &str) -> Self {
-        Value::String(s.to_string())
+        Value::String(s.into())
     }
 }
 
@@ -128,6 +294,63 @@ pub enum NodeType {
     Permission { action: String, min_confidence: f64 },
     /// Constant value node
     Constant { value: Value },
+    /// Multi-way branch on the first input.
+    ///
+    /// Yields the value of the target node of the first case equal to the
+    /// input, else of `default`, else null.
+    Switch {
+        cases: Vec<(Value, String)>,
+        #[serde(default)]
+        default: Option<String>,
+    },
+    /// Nested graph executed in isolation.
+    ///
+    /// `input_map` binds each subgraph input name to one of this node's
+    /// `inputs` references; `output_map` renames subgraph outputs to the
+    /// keys of the resulting map.
+    SubGraph {
+        graph: Graph,
+        #[serde(default)]
+        input_map: HashMap<String, String>,
+        #[serde(default)]
+        output_map: HashMap<String, String>,
+    },
+    /// Runs `body` on each element of the first input, an array.
+    ///
+    /// The body reads the element as its `item` input; its first output
+    /// becomes the element of the result.
+    Map { body: Graph },
+    /// Keeps the elements of the first input for which the first output
+    /// of `predicate`, reading `item`, is truthy.
+    Filter { predicate: Graph },
+    /// Folds the first input into an accumulator starting at `initial`.
+    ///
+    /// The body reads `acc` and `item`; its first output is the next
+    /// accumulator.
+    Reduce { body: Graph, initial: Value },
+    /// Yields `target` if the first input, a string, matches `pattern`,
+    /// else null.
+    Regex { pattern: String, target: String },
+}
+
+impl NodeType {
+    /// Operation name for operation nodes, otherwise the node kind.
+    pub fn op_name(&self) -> &str {
+        match self {
+            NodeType::External { .. } => "External",
+            NodeType::Operation { op } => op,
+            NodeType::Lookup { .. } => "Lookup",
+            NodeType::Route { .. } => "Route",
+            NodeType::Permission { .. } => "Permission",
+            NodeType::Constant { .. } => "Constant",
+            NodeType::Switch { .. } => "Switch",
+            NodeType::SubGraph { .. } => "SubGraph",
+            NodeType::Map { .. } => "Map",
+            NodeType::Filter { .. } => "Filter",
+            NodeType::Reduce { .. } => "Reduce",
+            NodeType::Regex { .. } => "Regex",
+        }
+    }
 }
 
 /// A condition for routing.
@@ -193,6 +416,92 @@ impl Graph {
         self.nodes.iter().find(|n| n.id == id)
     }
 
+    /// Names of the inputs the graph reads, from its `input://` external nodes.
+    pub fn input_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for node in &self.nodes {
+            if let NodeType::External { uri } = &node.node_type {
+                if let Some(name) = uri.strip_prefix("input://") {
+                    if !names.iter().any(|n| n == name) {
+                        names.push(name.to_string());
+                    }
+                }
+            }
+        }
+        names
+    }
+
+    /// Check the graph is well-formed: its regex patterns compile and its
+    /// nodes read only nodes that exist.
+    pub fn validate(&self) -> Result<(), GatewayError> {
+        self.validate_patterns()?;
+        self.validate_references()
+    }
+
+    /// Check that every `Regex` node and `RegexMatch`/`RegexCapture`
+    /// operation, including those in nested graphs, has a valid pattern.
+    pub fn validate_patterns(&self) -> Result<(), GatewayError> {
+        for node in &self.nodes {
+            let pattern = match &node.node_type {
+                NodeType::Regex { pattern, .. } => Some(pattern.as_str()),
+                NodeType::Operation { op } if op == "RegexMatch" || op == "RegexCapture" => {
+                    node.params.get("pattern").and_then(|v| v.as_str())
+                }
+                _ => None,
+            };
+            if let Some(pattern) = pattern {
+                regex::Regex::new(pattern).map_err(|e| {
+                    GatewayError::ConfigError(format!("Invalid regex in node {}: {}", node.id, e))
+                })?;
+            }
+            match &node.node_type {
+                NodeType::SubGraph { graph, .. }
+                | NodeType::Map { body: graph }
+                | NodeType::Filter { predicate: graph }
+                | NodeType::Reduce { body: graph, .. } => graph.validate_patterns()?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that every node input names a node of the graph.
+    ///
+    /// Nested graphs may also read the nodes of their enclosing graphs,
+    /// which stay visible to them at execution time.
+    pub fn validate_references(&self) -> Result<(), GatewayError> {
+        self.validate_references_within(&HashSet::new())
+    }
+
+    fn validate_references_within(&self, enclosing: &HashSet<&str>) -> Result<(), GatewayError> {
+        let mut visible = enclosing.clone();
+        visible.extend(self.nodes.iter().map(|node| node.id.as_str()));
+        for node in &self.nodes {
+            for input in &node.inputs {
+                let source = input.split('.').next().unwrap_or(input);
+                if !visible.contains(source) {
+                    return Err(GatewayError::ConfigError(format!(
+                        "Node {} of graph {} reads unknown node {}",
+                        node.id, self.name, source
+                    )));
+                }
+            }
+            match &node.node_type {
+                NodeType::SubGraph { graph, .. }
+                | NodeType::Map { body: graph }
+                | NodeType::Filter { predicate: graph }
+                | NodeType::Reduce { body: graph, .. } => graph.validate_references_within(&visible)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// IDs of the nodes whose values the graph returns.
+    pub fn output_names(&self) -> &[String] {
+        &self.outputs
+    }
+
     /// Get all edges in the graph.
     pub fn edges(&self) -> Vec<Edge> {
         let mut edges = Vec::new();
@@ -226,10 +535,15 @@ impl Graph {
             adj.entry(&node.id).or_insert_with(Vec::new);
         }
 
-        // Build adjacency and in-degree
+        // Build adjacency and in-degree. Inputs naming no node of this graph
+        // come from an enclosing scope and impose no order; graphs reading
+        // nodes that exist nowhere are rejected by `validate_references`.
         for node in &self.nodes {
             for input in &node.inputs {
                 let from_node = input.split('.').next().unwrap();
+                if !in_degree.contains_key(from_node) {
+                    continue;
+                }
                 if let Some(degree) = in_degree.get_mut(node.id.as_str()) {
                     *degree += 1;
                 }
@@ -282,11 +596,10 @@ pub fn parse_graph_from_source(source: &str) -> Result<Graph, GatewayError> {
         if trimmed.starts_with('#') {
             continue; // Skip comment lines
         }
-        // Remove inline comments
-        let line_without_comment = if let Some(idx) = line.find('#') {
-            &line[..idx]
-        } else {
-            line
+        // Remove inline comments, keeping `#` inside strings
+        let line_without_comment = match comment_start(line) {
+            Some(idx) => &line[..idx],
+            None => line,
         };
         cleaned.push_str(line_without_comment);
         cleaned.push('\n');
@@ -326,21 +639,47 @@ pub fn parse_graph_from_source(source: &str) -> Result<Graph, GatewayError> {
     // Parse as JSON
     let graph: Graph = serde_json::from_str(&json_content)
         .map_err(|e| GatewayError::ConfigError(format!("Failed to parse graph: {}", e)))?;
-    
+    graph.validate()?;
+
     Ok(graph)
 }
 
+/// Byte index of the `#` starting a comment on `line`, ignoring string literals.
+fn comment_start(line: &str) -> Option<usize> {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '#' if !in_string => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
 /// Convert 0-lang format to JSON.
 fn convert_to_json(source: &str) -> Result<String, GatewayError> {
     let mut result = source.to_string();
     
-    // Replace unquoted keys with quoted keys
+    // Replace unquoted keys with quoted keys, leaving string literals such
+    // as "input://message" alone
     // This is a simplified conversion
-    let key_pattern = regex::Regex::new(r"(\s*)(\w+)(\s*):").unwrap();
-    result = key_pattern.replace_all(&result, r#"$1"$2"$3:"#).to_string();
+    static KEY_PATTERN: OnceLock<regex::Regex> = OnceLock::new();
+    let key_pattern = KEY_PATTERN
+        .get_or_init(|| regex::Regex::new(r#""(?:[^"\\]|\\.)*"|(\w+)(\s*):"#).unwrap());
+    result = key_pattern
+        .replace_all(&result, |caps: &regex::Captures| match caps.get(1) {
+            Some(key) => format!("\"{}\"{}:", key.as_str(), &caps[2]),
+            None => caps[0].to_string(),
+        })
+        .to_string();
     
     // Handle trailing commas (remove them)
-    let trailing_comma = regex::Regex::new(r",(\s*[}\]])").unwrap();
+    static TRAILING_COMMA: OnceLock<regex::Regex> = OnceLock::new();
+    let trailing_comma = TRAILING_COMMA.get_or_init(|| regex::Regex::new(r",(\s*[}\]])").unwrap());
     result = trailing_comma.replace_all(&result, "$1").to_string();
     
     // Handle unquoted string values for known fields
@@ -365,13 +704,77 @@ mod tests {
         assert_eq!(v.as_int(), Some(42));
     }
 
+    #[test]
+    fn test_canonical_numbers() {
+        let int = Value::Int(2);
+        let float = Value::Float(2.0);
+
+        assert_eq!(int.canonical_bytes(), Value::Int(2).canonical_bytes());
+        assert_ne!(int.canonical_bytes(), float.canonical_bytes());
+        assert_eq!(int.to_canonical_json().to_string(), "2");
+        assert_eq!(float.to_canonical_json().to_string(), "2.0");
+
+        // Values of different types never share an encoding
+        let distinct = [
+            Value::String("1".into()),
+            Value::Int(1),
+            Value::Float(1.0),
+            Value::Confidence(1.0),
+            Value::Bytes(vec![1]),
+            Value::Array(vec![Value::Int(1)]),
+            Value::Null,
+            Value::String("null".into()),
+        ];
+        for (i, a) in distinct.iter().enumerate() {
+            for b in &distinct[i + 1..] {
+                assert_ne!(a.canonical_bytes(), b.canonical_bytes(), "{:?} vs {:?}", a, b);
+            }
+        }
+
+        // Each variant survives a JSON round trip
+        assert_eq!(Value::from_json(&int.to_canonical_json()), int);
+        assert_eq!(Value::from_json(&float.to_canonical_json()), float);
+    }
+
+    #[test]
+    fn test_canonical_map_is_stable() {
+        let mut a = HashMap::new();
+        let mut b = HashMap::new();
+        for i in 0..32 {
+            a.insert(format!("key{}", i), Value::Int(i));
+        }
+        for i in (0..32).rev() {
+            b.insert(format!("key{}", i), Value::Int(i));
+        }
+        assert_eq!(Value::Map(a).canonical_bytes(), Value::Map(b).canonical_bytes());
+    }
+
+    #[test]
+    fn test_parse_keeps_string_literals() {
+        let source = r#"
+            Graph {
+                name: "literals", # trailing comment
+                version: 1,
+                description: "Uses input://message and issue #7",
+                nodes: [
+                    { id: "message", type: "External", uri: "input://message" }
+                ],
+                entry_point: "message",
+                outputs: ["message"],
+            }
+        "#;
+        let graph = parse_graph_from_source(source).unwrap();
+        assert_eq!(graph.description, "Uses input://message and issue #7");
+        assert!(matches!(&graph.nodes[0].node_type, NodeType::External { uri } if uri == "input://message"));
+    }
+
     #[test]
     fn test_value_truthy() {
         assert!(!Value::Null.is_truthy());
         assert!(Value::Bool(true).is_truthy());
         assert!(!Value::Bool(false).is_truthy());
-        assert!(Value::String("hello".to_string()).is_truthy());
-        assert!(!Value::String("".to_string()).is_truthy());
+        assert!(Value::String("hello".into()).is_truthy());
+        assert!(!Value::String("".into()).is_truthy());
     }
 
     #[test]
@@ -412,4 +815,72 @@ mod tests {
         assert!(ids.iter().position(|&x| x == "a") < ids.iter().position(|&x| x == "b"));
         assert!(ids.iter().position(|&x| x == "b") < ids.iter().position(|&x| x == "c"));
     }
+
+    #[test]
+    fn test_dangling_references_rejected() {
+        let node = |id: &str, inputs: &[&str], node_type: NodeType| GraphNode {
+            id: id.to_string(),
+            node_type,
+            inputs: inputs.iter().map(|s| s.to_string()).collect(),
+            params: serde_json::json!({}),
+        };
+        let graph = |name: &str, nodes: Vec<GraphNode>| Graph {
+            name: name.to_string(),
+            version: 1,
+            description: String::new(),
+            nodes,
+            outputs: vec![],
+            entry_point: String::new(),
+            metadata: serde_json::json!({}),
+        };
+        let identity = || NodeType::Operation { op: "Identity".to_string() };
+        let input = || NodeType::External { uri: "input://a".to_string() };
+
+        let dangling = graph("top", vec![node("a", &[], input()), node("b", &["missing.field"], identity())]);
+        let err = dangling.validate_references().unwrap_err();
+        assert!(err.to_string().contains("missing"), "{}", err);
+        assert!(dangling.topo_sort().is_ok());
+
+        // Nested graphs may read their enclosing graph's nodes, but nothing else
+        let nested = |reads: &str| graph("outer", vec![
+            node("a", &[], input()),
+            node("sub", &["a"], NodeType::SubGraph {
+                graph: graph("inner", vec![node("b", &[reads], identity())]),
+                input_map: HashMap::new(),
+                output_map: HashMap::new(),
+            }),
+        ]);
+        assert!(nested("a").validate_references().is_ok());
+        assert!(nested("missing").validate_references().is_err());
+    }
+
+    #[test]
+    fn test_invalid_regex_rejected_at_parse_time() {
+        let source = |pattern: &str| format!(
+            r#"Graph {{
+                name: "regex",
+                nodes: [
+                    {{ id: "message", type: "External", uri: "input" }},
+                    {{ id: "match", type: "Regex", pattern: "{}", target: "weather", inputs: ["message"] }},
+                ],
+                outputs: ["match"],
+            }}"#,
+            pattern
+        );
+
+        assert!(parse_graph_from_source(&source("(weather|forecast)")).is_ok());
+        let err = parse_graph_from_source(&source("(weather")).unwrap_err();
+        assert!(err.to_string().contains("Invalid regex in node match"), "{}", err);
+
+        // Graphs built in code are checked too, including regex operations
+        let mut graph = parse_graph_from_source(&source("(weather|forecast)")).unwrap();
+        graph.nodes.push(GraphNode {
+            id: "capture".to_string(),
+            node_type: NodeType::Operation { op: "RegexCapture".to_string() },
+            inputs: vec!["message".to_string()],
+            params: serde_json::json!({"pattern": "[unclosed"}),
+        });
+        let err = graph.validate().unwrap_err();
+        assert!(err.to_string().contains("Invalid regex in node capture"), "{}", err);
+    }
 }
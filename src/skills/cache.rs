@@ -0,0 +1,76 @@
+//! Verified Skill Cache - remember which skill hashes passed verification.
+//!
+//! Skills are content-addressed, so a hash that verified once verifies
+//! again. The cache records each verified hash as `<dir>/<hash>.verified`
+//! holding the verifier version, letting startup skip re-verification of
+//! unchanged skills until the verifier itself changes.
+
+use std::path::{Path, PathBuf};
+use crate::types::ContentHash;
+use super::verifier::VERIFIER_VERSION;
+
+/// On-disk record of skill hashes that passed verification.
+#[derive(Debug, Clone)]
+pub struct VerificationCache {
+    /// Directory holding the `.verified` markers.
+    dir: PathBuf,
+    /// Verifier version markers must match to count as verified.
+    verifier_version: u32,
+}
+
+impl VerificationCache {
+    /// Create a cache in `dir` for the current verifier version.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            verifier_version: VERIFIER_VERSION,
+        }
+    }
+
+    /// Use a specific verifier version instead of the current one.
+    pub fn with_verifier_version(mut self, version: u32) -> Self {
+        self.verifier_version = version;
+        self
+    }
+
+    /// Directory holding the markers.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Whether `hash` passed verification with this verifier version.
+    pub fn is_verified(&self, hash: &ContentHash) -> bool {
+        std::fs::read_to_string(self.marker_path(hash))
+            .ok()
+            .and_then(|contents| contents.trim().parse::<u32>().ok())
+            == Some(self.verifier_version)
+    }
+
+    /// Record that `hash` passed verification with this verifier version.
+    pub fn record(&self, hash: &ContentHash) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.marker_path(hash), self.verifier_version.to_string())
+    }
+
+    fn marker_path(&self, hash: &ContentHash) -> PathBuf {
+        self.dir.join(format!("{}.verified", hash.to_hex()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_marker_tied_to_verifier_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let hash = ContentHash::from_string("skill");
+        let cache = VerificationCache::new(dir.path());
+        assert!(!cache.is_verified(&hash));
+
+        cache.record(&hash).unwrap();
+        assert!(cache.is_verified(&hash));
+        assert!(dir.path().join(format!("{}.verified", hash.to_hex())).exists());
+        assert!(!cache.clone().with_verifier_version(VERIFIER_VERSION + 1).is_verified(&hash));
+    }
+}
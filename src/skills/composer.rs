@@ -7,6 +7,7 @@ use std::collections::{HashMap, HashSet};
 use crate::types::ContentHash;
 use crate::error::SkillError;
 use super::graph::{SkillGraph, SkillNode, Op, SafetyProof};
+use super::verifier::{SkillVerifier, VerificationError};
 
 /// A connection between two skills.
 #[derive(Debug, Clone)]
@@ -55,6 +56,13 @@ pub enum ComposerError {
     
     #[error("Empty composition - no skills added")]
     EmptyComposition,
+
+    #[error("Composed graph failed verification: {}", format_errors(.0))]
+    VerificationFailed(Vec<VerificationError>),
+}
+
+fn format_errors(errors: &[VerificationError]) -> String {
+    errors.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ")
 }
 
 impl From<ComposerError> for SkillError {
@@ -70,6 +78,8 @@ pub struct SkillComposer {
     skills: HashMap<ContentHash, SkillGraph>,
     /// Connection definitions.
     connections: Vec<SkillConnection>,
+    /// Skip verifying the composed graph.
+    skip_verification: bool,
 }
 
 impl SkillComposer {
@@ -78,6 +88,12 @@ impl SkillComposer {
         Self::default()
     }
 
+    /// Return the composed graph without verifying it.
+    pub fn without_verification(mut self) -> Self {
+        self.skip_verification = true;
+        self
+    }
+
     /// Add a skill to the composition.
     ///
     /// Returns the content hash of the added skill.
@@ -111,6 +127,9 @@ impl SkillComposer {
     }
 
     /// Compose all skills into a single unified graph.
+    ///
+    /// The composed graph is verified unless [`Self::without_verification`]
+    /// was used, so composition cannot yield a graph that will not run.
     pub fn compose(&self, name: &str) -> Result<ComposedSkill, ComposerError> {
         if self.skills.is_empty() {
             return Err(ComposerError::EmptyComposition);
@@ -192,6 +211,13 @@ impl SkillComposer {
         builder = builder.proof(combined_proof);
         
         let graph = builder.build();
+        if !self.skip_verification {
+            let result = SkillVerifier::verify(&graph)
+                .map_err(|e| ComposerError::GraphBuildError(e.to_string()))?;
+            if !result.safe {
+                return Err(ComposerError::VerificationFailed(result.errors));
+            }
+        }
         let composition_hash = graph.content_hash();
         let source_skills: Vec<ContentHash> = self.skills.keys().copied().collect();
         
@@ -296,9 +322,10 @@ impl SkillComposer {
         let prefix = &skill_hash.to_hex()[..8];
         
         match node {
-            SkillNode::Input { name, tensor_type } => SkillNode::Input {
+            SkillNode::Input { name, tensor_type, doc } => SkillNode::Input {
                 name: format!("{}_{}", prefix, name),
                 tensor_type: tensor_type.clone(),
+                doc: doc.clone(),
             },
             SkillNode::Operation { id, op, inputs } => {
                 let new_inputs: Vec<String> = inputs
@@ -475,4 +502,41 @@ mod tests {
         let result = composer.compose("cyclic");
         assert!(matches!(result, Err(ComposerError::CycleDetected)));
     }
+
+    #[test]
+    fn test_compose_rejects_unverifiable_graph() {
+        let greet = SkillGraph::builder("greet")
+            .add_input("name", "string")
+            .add_operation("greeting", Op::StringFormat { template: "Hi {}".to_string() }, vec!["name"])
+            .output("greeting")
+            .build();
+        // Calls the network without declaring the permission
+        let shout = SkillGraph::builder("shout")
+            .add_input("text", "string")
+            .add_node(SkillNode::External {
+                id: "loud".to_string(),
+                uri: "http://example.com/shout".to_string(),
+                inputs: vec!["text".to_string()],
+            })
+            .output("loud")
+            .build();
+
+        let mut composer = SkillComposer::new();
+        let greet = composer.add_skill(greet);
+        let shout = composer.add_skill(shout);
+        composer.connect(greet, "greeting", shout, "text");
+
+        let err = composer.compose("greet_loudly").unwrap_err();
+        let ComposerError::VerificationFailed(errors) = &err else {
+            panic!("Expected VerificationFailed, got {:?}", err);
+        };
+        assert!(errors.iter().any(|e| matches!(e, VerificationError::MissingPermission { .. })));
+        assert!(err.to_string().starts_with("Composed graph failed verification"));
+
+        let mut unchecked = SkillComposer::new().without_verification();
+        let greet = unchecked.add_skill(composer.skills[&greet].clone());
+        let shout = unchecked.add_skill(composer.skills[&shout].clone());
+        unchecked.connect(greet, "greeting", shout, "text");
+        assert!(unchecked.compose("greet_loudly").is_ok());
+    }
 }
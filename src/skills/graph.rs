@@ -3,6 +3,8 @@
 //! A SkillGraph represents a directed acyclic graph (DAG) of operations
 //! that define skill behavior and can be compiled into runtime graphs.
 
+use std::collections::BTreeMap;
+
 use serde::{Serialize, Deserialize};
 use crate::types::ContentHash;
 use crate::error::SkillError;
@@ -14,6 +16,9 @@ pub enum SkillNode {
     Input {
         name: String,
         tensor_type: String,
+        /// What the input means, shown by `skill info`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        doc: Option<String>,
     },
     /// Operation node - performs computation.
     Operation {
@@ -54,6 +59,19 @@ impl SkillNode {
             SkillNode::Constant { .. } => &[],
         }
     }
+
+    /// Get the permission implied by this node's operation, if any.
+    pub fn required_permission(&self) -> Option<&'static str> {
+        match self {
+            SkillNode::External { uri, .. }
+                if uri.starts_with("http://") || uri.starts_with("https://") =>
+            {
+                Some("network")
+            }
+            SkillNode::Operation { op: Op::HttpGet | Op::HttpPost, .. } => Some("network"),
+            _ => None,
+        }
+    }
 }
 
 /// Operations available in skill graphs.
@@ -65,6 +83,18 @@ pub enum Op {
     StringFormat { template: String },
     /// String concatenation.
     StringConcat,
+    /// Split a string into an array, at most `limit` parts (0 for no limit).
+    StringSplit { separator: String, limit: usize },
+    /// Join an array of strings.
+    StringJoin { separator: String },
+    /// Parse into an integer; unparseable input errors when `strict`, else yields null.
+    ToInt { strict: bool },
+    /// Parse into a float; unparseable input errors when `strict`, else yields null.
+    ToFloat { strict: bool },
+    /// Parse into a boolean; unparseable input errors when `strict`, else yields null.
+    ToBool { strict: bool },
+    /// Render any value as a string.
+    ToString,
     /// JSON parsing.
     JsonParse,
     /// JSON field extraction.
@@ -73,12 +103,21 @@ pub enum Op {
     JsonStringify,
     /// Conditional branch.
     Conditional,
+    /// Multi-way branch on the first input.
+    ///
+    /// Yields the value of the node named by the first case equal to the
+    /// input, else of `default`, else null. Every branch target is
+    /// evaluated; the match only selects which value flows on.
+    Match {
+        cases: Vec<(serde_json::Value, String)>,
+        default: Option<String>,
+    },
     /// Map over array.
     Map { body: Box<SkillGraph> },
     /// Filter array.
     Filter { predicate: Box<SkillGraph> },
-    /// Reduce array.
-    Reduce { initial: serde_json::Value },
+    /// Reduce array: `body` reads `acc` and `item` and outputs the next accumulator.
+    Reduce { initial: serde_json::Value, body: Box<SkillGraph> },
     /// HTTP GET request.
     HttpGet,
     /// HTTP POST request.
@@ -113,6 +152,23 @@ impl Default for SafetyProof {
     }
 }
 
+/// Structural complexity summary of a skill graph.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ComplexityReport {
+    /// Total number of nodes.
+    pub node_count: usize,
+    /// Number of input nodes.
+    pub input_count: usize,
+    /// Number of operation nodes.
+    pub operation_count: usize,
+    /// Number of external call nodes.
+    pub external_count: usize,
+    /// Number of data dependencies between nodes.
+    pub edge_count: usize,
+    /// Length of the longest dependency chain.
+    pub max_depth: usize,
+}
+
 /// A skill graph - the core execution unit.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SkillGraph {
@@ -128,6 +184,9 @@ pub struct SkillGraph {
     pub entry_point: Option<String>,
     /// Output node IDs.
     pub outputs: Vec<String>,
+    /// Documentation for outputs, keyed by output node ID.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub output_docs: BTreeMap<String, String>,
     /// Required permissions.
     pub permissions: Vec<String>,
     /// Safety proofs.
@@ -158,6 +217,30 @@ impl SkillGraph {
         })
     }
 
+    /// Names of the graph's inputs: its input nodes and `input://` externals.
+    pub fn input_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for node in &self.nodes {
+            let name = match node {
+                SkillNode::Input { name, .. } => name.as_str(),
+                SkillNode::External { uri, .. } => match uri.strip_prefix("input://") {
+                    Some(name) => name,
+                    None => continue,
+                },
+                _ => continue,
+            };
+            if !names.iter().any(|n| n == name) {
+                names.push(name.to_string());
+            }
+        }
+        names
+    }
+
+    /// IDs of the nodes whose values the graph returns.
+    pub fn output_names(&self) -> &[String] {
+        &self.outputs
+    }
+
     /// Get a node by ID.
     pub fn get_node(&self, id: &str) -> Option<&SkillNode> {
         self.nodes.iter().find(|n| n.id() == id)
@@ -177,6 +260,31 @@ impl SkillGraph {
             .collect()
     }
 
+    /// Infer the permissions implied by the graph's node operations.
+    ///
+    /// Nested `Map`, `Filter` and `Reduce` bodies are included.
+    pub fn infer_permissions(&self) -> Vec<String> {
+        let mut permissions: Vec<String> = Vec::new();
+        for node in &self.nodes {
+            let mut implied: Vec<String> = node.required_permission()
+                .map(|p| vec![p.to_string()])
+                .unwrap_or_default();
+            if let SkillNode::Operation {
+                op: Op::Map { body: inner } | Op::Filter { predicate: inner } | Op::Reduce { body: inner, .. },
+                ..
+            } = node
+            {
+                implied.extend(inner.infer_permissions());
+            }
+            for permission in implied {
+                if !permissions.contains(&permission) {
+                    permissions.push(permission);
+                }
+            }
+        }
+        permissions
+    }
+
     /// Check if graph has any external calls.
     pub fn has_external_calls(&self) -> bool {
         self.nodes.iter().any(|n| matches!(n, SkillNode::External { .. }))
@@ -187,6 +295,44 @@ impl SkillGraph {
         self.nodes.len()
     }
 
+    /// Summarize the structural complexity of the graph.
+    pub fn complexity(&self) -> ComplexityReport {
+        let mut depths: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+        let mut max_depth = 0;
+
+        // Nodes are usually declared in dependency order; iterate until stable
+        // so out-of-order declarations are still measured correctly.
+        for _ in 0..self.nodes.len() {
+            let mut changed = false;
+            for node in &self.nodes {
+                let depth = node
+                    .inputs()
+                    .iter()
+                    .filter_map(|input| depths.get(input.as_str()))
+                    .max()
+                    .map(|d| d + 1)
+                    .unwrap_or(0);
+                if depths.get(node.id()) != Some(&depth) {
+                    depths.insert(node.id(), depth);
+                    changed = true;
+                }
+                max_depth = max_depth.max(depth);
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        ComplexityReport {
+            node_count: self.nodes.len(),
+            input_count: self.nodes.iter().filter(|n| matches!(n, SkillNode::Input { .. })).count(),
+            operation_count: self.nodes.iter().filter(|n| matches!(n, SkillNode::Operation { .. })).count(),
+            external_count: self.nodes.iter().filter(|n| matches!(n, SkillNode::External { .. })).count(),
+            edge_count: self.nodes.iter().map(|n| n.inputs().len()).sum(),
+            max_depth,
+        }
+    }
+
     /// Serialize to bytes.
     pub fn serialize(&self) -> Vec<u8> {
         serde_json::to_vec(self).unwrap_or_default()
@@ -212,6 +358,46 @@ impl SkillGraph {
                     inputs: Vec::new(),
                     params: serde_json::json!({}),
                 }),
+                SkillNode::Operation { id, op: Op::Match { cases, default }, inputs } => {
+                    // Branch targets must run before the switch selects one
+                    let mut inputs = inputs.clone();
+                    for target in op_branch_targets(cases, default) {
+                        if !inputs.iter().any(|i| i == target) {
+                            inputs.push(target.to_string());
+                        }
+                    }
+                    nodes.push(GraphNode {
+                        id: id.clone(),
+                        node_type: NodeType::Switch {
+                            cases: cases
+                                .iter()
+                                .map(|(value, target)| {
+                                    (crate::runtime::Value::from_json(value), target.clone())
+                                })
+                                .collect(),
+                            default: default.clone(),
+                        },
+                        inputs,
+                        params: serde_json::json!({}),
+                    });
+                }
+                SkillNode::Operation { id, op: op @ (Op::Map { .. } | Op::Filter { .. } | Op::Reduce { .. }), inputs } => {
+                    let node_type = match op {
+                        Op::Map { body } => NodeType::Map { body: body.to_runtime_graph()? },
+                        Op::Filter { predicate } => NodeType::Filter { predicate: predicate.to_runtime_graph()? },
+                        Op::Reduce { initial, body } => NodeType::Reduce {
+                            body: body.to_runtime_graph()?,
+                            initial: crate::runtime::Value::from_json(initial),
+                        },
+                        _ => unreachable!("matched loop ops only"),
+                    };
+                    nodes.push(GraphNode {
+                        id: id.clone(),
+                        node_type,
+                        inputs: inputs.clone(),
+                        params: serde_json::json!({}),
+                    });
+                }
                 SkillNode::Operation { id, op, inputs } => {
                     nodes.push(GraphNode {
                         id: id.clone(),
@@ -231,7 +417,7 @@ impl SkillGraph {
                 SkillNode::Constant { id, value } => nodes.push(GraphNode {
                     id: id.clone(),
                     node_type: NodeType::Constant {
-                        value: json_to_runtime_value(value),
+                        value: crate::runtime::Value::from_json(value),
                     },
                     inputs: Vec::new(),
                     params: serde_json::json!({}),
@@ -255,27 +441,192 @@ impl SkillGraph {
             }),
         })
     }
+    /// Render the graph in `.0` format.
+    ///
+    /// Templates containing newlines, commas or quotes are written as
+    /// triple-quoted blocks. Fails for nodes and operations the `.0` parser
+    /// cannot read back, such as constants and nested graphs.
+    pub fn to_zero_source(&self) -> Result<String, SkillError> {
+        let mut out = String::from("Graph {\n");
+        out.push_str(&format!("    name: {},\n", zero_string("name", &self.name)?));
+        out.push_str(&format!("    version: {},\n", self.version));
+        if let Some(description) = &self.description {
+            out.push_str(&format!("    description: {},\n", zero_string("description", description)?));
+        }
+
+        out.push_str("\n    nodes: [\n");
+        for node in &self.nodes {
+            let fields = match node {
+                SkillNode::Input { name, tensor_type, .. } => format!(
+                    "id: {}, type: Input, tensor_type: {}",
+                    zero_string("id", name)?,
+                    zero_string("tensor_type", tensor_type)?
+                ),
+                SkillNode::External { id, uri, inputs } => format!(
+                    "id: {}, type: External, uri: {}, inputs: {}",
+                    zero_string("id", id)?,
+                    zero_string("uri", uri)?,
+                    zero_list(inputs)?
+                ),
+                SkillNode::Operation { id, op, inputs } => format!(
+                    "id: {}, type: Operation, op: {}, inputs: {}",
+                    zero_string("id", id)?,
+                    zero_op(op)?,
+                    zero_list(inputs)?
+                ),
+                SkillNode::Constant { id, .. } => {
+                    return Err(SkillError::InvalidGraph(format!(
+                        "Constant node '{}' cannot be written in .0 format",
+                        id
+                    )));
+                }
+            };
+            let doc = match node {
+                SkillNode::Input { doc, .. } => doc.as_ref(),
+                other => self.output_docs.get(other.id()),
+            };
+            match doc {
+                Some(doc) => out.push_str(&format!(
+                    "        {{ {}, doc: {} }},\n",
+                    fields,
+                    zero_string("doc", doc)?
+                )),
+                None => out.push_str(&format!("        {{ {} }},\n", fields)),
+            }
+        }
+        out.push_str("    ],\n\n");
+
+        if let Some(entry_point) = &self.entry_point {
+            out.push_str(&format!("    entry_point: {},\n", zero_string("entry_point", entry_point)?));
+        }
+        if !self.permissions.is_empty() {
+            out.push_str(&format!("    permissions: {},\n", zero_list(&self.permissions)?));
+        }
+        out.push_str(&format!("    outputs: {},\n", zero_list(&self.outputs)?));
+        out.push_str("}\n");
+        Ok(out)
+    }
+}
+
+fn op_branch_targets<'a>(
+    cases: &'a [(serde_json::Value, String)],
+    default: &'a Option<String>,
+) -> impl Iterator<Item = &'a str> {
+    cases.iter().map(|(_, target)| target.as_str()).chain(default.as_deref())
+}
+
+/// Quote a single-line `.0` value, rejecting characters the parser splits on.
+fn zero_string(key: &str, value: &str) -> Result<String, SkillError> {
+    if value.contains([',', '"', '\n']) {
+        return Err(SkillError::InvalidGraph(format!(
+            "Value of '{}' cannot be written in .0 format: {:?}",
+            key, value
+        )));
+    }
+    Ok(format!("\"{}\"", value))
+}
+
+fn zero_list(items: &[String]) -> Result<String, SkillError> {
+    let quoted = items
+        .iter()
+        .map(|item| zero_string("list item", item))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(format!("[{}]", quoted.join(", ")))
+}
+
+/// Write a template, as a triple-quoted block when it would not survive a plain string.
+fn zero_template(template: &str) -> Result<String, SkillError> {
+    if template.contains("\"\"\"") {
+        return Err(SkillError::InvalidGraph(
+            "Templates containing '\"\"\"' cannot be written in .0 format".to_string(),
+        ));
+    }
+    if template.contains([',', '"', '\n']) {
+        Ok(format!("\"\"\"\n{}\n        \"\"\"", template))
+    } else {
+        Ok(format!("\"{}\"", template))
+    }
+}
+
+fn zero_op(op: &Op) -> Result<String, SkillError> {
+    let rendered = match op {
+        Op::Identity => "Identity".to_string(),
+        Op::StringFormat { template } => format!("StringFormat, template: {}", zero_template(template)?),
+        Op::StringConcat => "StringConcat".to_string(),
+        Op::StringSplit { separator, limit } => format!(
+            "StringSplit, separator: {}, limit: {}",
+            zero_string("separator", separator)?,
+            limit
+        ),
+        Op::StringJoin { separator } => {
+            format!("StringJoin, separator: {}", zero_string("separator", separator)?)
+        }
+        Op::ToInt { strict } => format!("ToInt, strict: {}", strict),
+        Op::ToFloat { strict } => format!("ToFloat, strict: {}", strict),
+        Op::ToBool { strict } => format!("ToBool, strict: {}", strict),
+        Op::ToString => "ToString".to_string(),
+        Op::JsonParse => "JsonParse".to_string(),
+        Op::JsonGet { path } => format!("JsonGet, path: {}", zero_string("path", path)?),
+        Op::JsonStringify => "JsonStringify".to_string(),
+        Op::HttpGet => "HttpGet".to_string(),
+        Op::HttpPost => "HttpPost".to_string(),
+        Op::Match { cases, default } => {
+            let cases = cases
+                .iter()
+                .map(|(value, target)| match value {
+                    serde_json::Value::String(value) => format!("{}=>{}", value, target),
+                    value => format!("{}=>{}", value, target),
+                })
+                .collect::<Vec<_>>()
+                .join("; ");
+            let mut rendered = format!("Match, cases: {}", zero_string("cases", &cases)?);
+            if let Some(default) = default {
+                rendered.push_str(&format!(", default: {}", zero_string("default", default)?));
+            }
+            rendered
+        }
+        other => {
+            return Err(SkillError::InvalidGraph(format!(
+                "Operation {:?} cannot be written in .0 format",
+                other
+            )));
+        }
+    };
+    Ok(rendered)
 }
 
 impl Op {
+    /// Node IDs a `Match` op can select, in case order then the default.
+    pub fn branch_targets(&self) -> Vec<&str> {
+        match self {
+            Op::Match { cases, default } => op_branch_targets(cases, default).collect(),
+            _ => Vec::new(),
+        }
+    }
+
     pub fn runtime_name(&self) -> Result<&'static str, SkillError> {
         let name = match self {
             Op::Identity => "Identity",
             Op::StringFormat { .. } => "Concat",
             Op::StringConcat => "Concat",
+            Op::StringSplit { .. } => "Split",
+            Op::StringJoin { .. } => "Join",
+            Op::ToInt { .. } => "ToInt",
+            Op::ToFloat { .. } => "ToFloat",
+            Op::ToBool { .. } => "ToBool",
+            Op::ToString => "ToString",
             Op::JsonParse => "Identity",
             Op::JsonGet { .. } => "GetField",
             Op::JsonStringify => "Identity",
             Op::Conditional => "If",
-            Op::HttpGet => "Identity",
-            Op::HttpPost => "Identity",
-            Op::Wait { .. } => "Identity",
+            Op::Match { .. } => "Switch",
+            Op::HttpGet => "HttpGet",
+            Op::HttpPost => "HttpPost",
+            Op::Wait { .. } => "Wait",
             Op::Log { .. } => "Identity",
-            Op::Map { .. } | Op::Filter { .. } | Op::Reduce { .. } => {
-                return Err(SkillError::InvalidGraph(
-                    "Nested skill graph ops are not runtime-compatible yet".to_string(),
-                ));
-            }
+            Op::Map { .. } => "Map",
+            Op::Filter { .. } => "Filter",
+            Op::Reduce { .. } => "Reduce",
         };
         Ok(name)
     }
@@ -284,6 +635,13 @@ impl Op {
         match self {
             Op::StringFormat { template } => serde_json::json!({ "separator": "", "template": template }),
             Op::JsonGet { path } => serde_json::json!({ "field": path }),
+            Op::StringSplit { separator, limit } => {
+                serde_json::json!({ "separator": separator, "limit": limit })
+            }
+            Op::StringJoin { separator } => serde_json::json!({ "separator": separator }),
+            Op::ToInt { strict } | Op::ToFloat { strict } | Op::ToBool { strict } => {
+                serde_json::json!({ "strict": strict })
+            }
             Op::Wait { ms } => serde_json::json!({ "wait_ms": ms }),
             Op::Log { level } => serde_json::json!({ "level": level }),
             _ => serde_json::json!({}),
@@ -291,33 +649,6 @@ impl Op {
     }
 }
 
-fn json_to_runtime_value(value: &serde_json::Value) -> crate::runtime::Value {
-    use crate::runtime::Value;
-
-    match value {
-        serde_json::Value::Null => Value::Null,
-        serde_json::Value::Bool(v) => Value::Bool(*v),
-        serde_json::Value::Number(v) => {
-            if let Some(i) = v.as_i64() {
-                Value::Int(i)
-            } else if let Some(f) = v.as_f64() {
-                Value::Float(f)
-            } else {
-                Value::Null
-            }
-        }
-        serde_json::Value::String(v) => Value::String(v.clone()),
-        serde_json::Value::Array(v) => Value::Array(v.iter().map(json_to_runtime_value).collect()),
-        serde_json::Value::Object(v) => {
-            let mut map = std::collections::HashMap::new();
-            for (k, val) in v {
-                map.insert(k.clone(), json_to_runtime_value(val));
-            }
-            Value::Map(map)
-        }
-    }
-}
-
 /// Builder for SkillGraph.
 #[derive(Debug)]
 pub struct SkillGraphBuilder {
@@ -327,7 +658,9 @@ pub struct SkillGraphBuilder {
     nodes: Vec<SkillNode>,
     entry_point: Option<String>,
     outputs: Vec<String>,
+    output_docs: BTreeMap<String, String>,
     permissions: Vec<String>,
+    auto_permissions: bool,
     proofs: Vec<SafetyProof>,
 }
 
@@ -341,7 +674,9 @@ impl SkillGraphBuilder {
             nodes: Vec::new(),
             entry_point: None,
             outputs: Vec::new(),
+            output_docs: BTreeMap::new(),
             permissions: Vec::new(),
+            auto_permissions: false,
             proofs: Vec::new(),
         }
     }
@@ -363,6 +698,7 @@ impl SkillGraphBuilder {
         self.nodes.push(SkillNode::Input {
             name: name.to_string(),
             tensor_type: tensor_type.to_string(),
+            doc: None,
         });
         if self.entry_point.is_none() {
             self.entry_point = Some(name.to_string());
@@ -370,6 +706,18 @@ impl SkillGraphBuilder {
         self
     }
 
+    /// Document an input added earlier.
+    pub fn input_doc(mut self, name: &str, doc: &str) -> Self {
+        for node in &mut self.nodes {
+            if let SkillNode::Input { name: input, doc: slot, .. } = node {
+                if input == name {
+                    *slot = Some(doc.to_string());
+                }
+            }
+        }
+        self
+    }
+
     /// Add an operation node.
     pub fn add_operation(mut self, id: &str, op: Op, inputs: Vec<&str>) -> Self {
         self.nodes.push(SkillNode::Operation {
@@ -417,6 +765,12 @@ impl SkillGraphBuilder {
         self
     }
 
+    /// Document an output.
+    pub fn output_doc(mut self, id: &str, doc: &str) -> Self {
+        self.output_docs.insert(id.to_string(), doc.to_string());
+        self
+    }
+
     /// Add multiple outputs.
     pub fn outputs(mut self, ids: Vec<&str>) -> Self {
         self.outputs.extend(ids.iter().map(|s| s.to_string()));
@@ -429,6 +783,12 @@ impl SkillGraphBuilder {
         self
     }
 
+    /// Declare every permission implied by the graph's operations on build.
+    pub fn auto_permissions(mut self) -> Self {
+        self.auto_permissions = true;
+        self
+    }
+
     /// Add a safety proof.
     pub fn proof(mut self, proof: SafetyProof) -> Self {
         self.proofs.push(proof);
@@ -444,16 +804,27 @@ impl SkillGraphBuilder {
             self.outputs
         };
 
-        SkillGraph {
+        let mut graph = SkillGraph {
             name: self.name,
             version: self.version,
             description: self.description,
             nodes: self.nodes,
             entry_point: self.entry_point,
             outputs,
+            output_docs: self.output_docs,
             permissions: self.permissions,
             proofs: self.proofs,
+        };
+
+        if self.auto_permissions {
+            for permission in graph.infer_permissions() {
+                if !graph.permissions.contains(&permission) {
+                    graph.permissions.push(permission);
+                }
+            }
         }
+
+        graph
     }
 }
 
@@ -504,4 +875,104 @@ mod tests {
 
         assert_ne!(graph1.content_hash(), graph2.content_hash());
     }
+
+    #[test]
+    fn test_complexity_report() {
+        let graph = SkillGraph::builder("complex")
+            .add_input("query", "string")
+            .add_external("api", "https://api.example/search", vec!["query"])
+            .add_operation("parse", Op::JsonParse, vec!["api"])
+            .add_operation("format", Op::StringFormat { template: "{}".to_string() }, vec!["parse"])
+            .output("format")
+            .build();
+
+        let report = graph.complexity();
+        assert_eq!(report.node_count, 4);
+        assert_eq!(report.input_count, 1);
+        assert_eq!(report.operation_count, 2);
+        assert_eq!(report.external_count, 1);
+        assert_eq!(report.edge_count, 3);
+        assert_eq!(report.max_depth, 3);
+    }
+
+    #[test]
+    fn test_infer_permissions() {
+        let http = SkillGraph::builder("fetch")
+            .add_input("url", "string")
+            .add_operation("get", Op::HttpGet, vec!["url"])
+            .output("get")
+            .build();
+        assert_eq!(http.infer_permissions(), vec!["network".to_string()]);
+        assert!(http.permissions.is_empty());
+
+        let declared = SkillGraph::builder("fetch")
+            .add_input("url", "string")
+            .add_operation("get", Op::HttpGet, vec!["url"])
+            .output("get")
+            .auto_permissions()
+            .build();
+        assert_eq!(declared.permissions, vec!["network".to_string()]);
+
+        let pure = SkillGraph::builder("echo")
+            .add_input("text", "string")
+            .add_operation("out", Op::Identity, vec!["text"])
+            .add_external("cal", "calendar://today", vec!["text"])
+            .output("out")
+            .build();
+        assert!(pure.infer_permissions().is_empty());
+    }
+
+    fn match_graph(default: Option<&str>) -> SkillGraph {
+        SkillGraph::builder("greeter")
+            .add_input("command", "string")
+            .add_constant("greeting", serde_json::json!("Hello!"))
+            .add_constant("farewell", serde_json::json!("Goodbye!"))
+            .add_constant("fallback", serde_json::json!("Unknown command"))
+            .add_operation(
+                "reply",
+                Op::Match {
+                    cases: vec![
+                        (serde_json::json!("hi"), "greeting".to_string()),
+                        (serde_json::json!("bye"), "farewell".to_string()),
+                    ],
+                    default: default.map(|d| d.to_string()),
+                },
+                vec!["command"],
+            )
+            .output("reply")
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_match_selects_branch() {
+        use crate::runtime::{GraphInterpreter, Value};
+
+        let runtime_graph = match_graph(Some("fallback")).to_runtime_graph().unwrap();
+        let interpreter = GraphInterpreter::default();
+        for (command, expected) in [("hi", "Hello!"), ("bye", "Goodbye!"), ("help", "Unknown command")] {
+            let inputs = [("command".to_string(), Value::String(command.into()))].into();
+            let result = interpreter.execute(&runtime_graph, inputs).await.unwrap();
+            assert_eq!(result.outputs["reply"], Value::String(expected.into()));
+        }
+
+        let verified = crate::skills::SkillVerifier::verify(&match_graph(Some("missing"))).unwrap();
+        assert!(verified.errors.iter().any(|e| matches!(
+            e,
+            crate::skills::VerificationError::InvalidReference { to_node, .. } if to_node == "missing"
+        )));
+    }
+
+    #[test]
+    fn test_input_and_output_names() {
+        let graph = SkillGraph::builder("lookup")
+            .add_input("query", "string")
+            .add_external("locale", "input://locale", vec![])
+            .add_external("fetch", "https://example.com/search", vec!["query"])
+            .add_operation("result", Op::Identity, vec!["fetch"])
+            .outputs(vec!["result", "locale"])
+            .build();
+        assert_eq!(graph.input_names(), vec!["query", "locale"]);
+        assert_eq!(graph.output_names(), ["result", "locale"]);
+        assert_eq!(graph.to_runtime_graph().unwrap().input_names(), vec!["query", "locale"]);
+    }
 }
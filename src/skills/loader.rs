@@ -3,6 +3,7 @@
 //! The SkillLoader provides functionality to load skill graphs from
 //! various sources including local files and remote URLs.
 
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use crate::error::SkillError;
 use super::graph::{SkillGraph, SkillNode};
@@ -14,6 +15,8 @@ pub struct SkillLoader {
     base_dir: PathBuf,
     /// Whether to verify skills on load.
     verify_on_load: bool,
+    /// Whether unknown node types and ops are errors.
+    strict: bool,
     /// Cache of loaded skills.
     cache: std::collections::HashMap<PathBuf, SkillGraph>,
 }
@@ -24,6 +27,7 @@ impl SkillLoader {
         Self {
             base_dir: base_dir.into(),
             verify_on_load: true,
+            strict: true,
             cache: std::collections::HashMap::new(),
         }
     }
@@ -34,6 +38,16 @@ impl SkillLoader {
         self
     }
 
+    /// Set whether unknown node types and ops are errors.
+    ///
+    /// Strict mode (the default) rejects them with the offending token and
+    /// line. Lenient mode drops unknown node types and treats unknown ops as
+    /// `Identity`, logging a warning for each.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
     /// Load a skill from a file.
     ///
     /// Supports `.0` (custom format) and `.json` files.
@@ -174,13 +188,15 @@ impl SkillLoader {
         let mut description = None;
         let mut nodes = Vec::new();
         let mut outputs = Vec::new();
+        let mut node_docs = BTreeMap::new();
         let mut permissions = Vec::new();
         let proofs = Vec::new();
         
         let mut in_graph = false;
         let mut current_section = "";
         
-        for line in content.lines() {
+        let mut lines = content.lines().enumerate();
+        while let Some((index, line)) = lines.next() {
             let line = line.trim();
             
             // Skip empty lines and comments
@@ -255,11 +271,33 @@ impl SkillLoader {
             
             // Parse node definitions
             if line.starts_with('{') && current_section == "nodes" {
-                let node = self.parse_node_definition(line)?;
-                nodes.push(node);
+                // A triple-quoted value continues until its closing delimiter
+                let mut definition = line.to_string();
+                while definition.matches(TRIPLE_QUOTE).count() % 2 == 1 {
+                    let (_, next) = lines.next().ok_or_else(|| {
+                        SkillError::InvalidGraph(format!(
+                            "Unterminated triple-quoted value (line {})",
+                            index + 1
+                        ))
+                    })?;
+                    definition.push('\n');
+                    definition.push_str(next);
+                }
+                if let Some((node, doc)) = self.parse_node_definition(&definition, index + 1)? {
+                    if let Some(doc) = doc {
+                        node_docs.insert(node.id().to_string(), doc);
+                    }
+                    nodes.push(node);
+                }
             }
         }
         
+        // Docs on input nodes live on the node itself
+        let output_docs = node_docs
+            .into_iter()
+            .filter(|(id, _)| outputs.contains(id))
+            .collect();
+
         Ok(SkillGraph {
             name,
             version,
@@ -267,15 +305,25 @@ impl SkillLoader {
             nodes,
             entry_point: None,
             outputs,
+            output_docs,
             permissions,
             proofs,
         })
     }
 
     /// Parse a single node definition.
-    fn parse_node_definition(&self, line: &str) -> Result<SkillNode, SkillError> {
+    ///
+    /// Returns the node with its `doc` value, or `None` for a node of
+    /// unknown type in lenient mode.
+    fn parse_node_definition(
+        &self,
+        line: &str,
+        line_no: usize,
+    ) -> Result<Option<(SkillNode, Option<String>)>, SkillError> {
         use super::graph::Op;
         
+        let (line, mut block) = split_triple_quoted(line);
+
         // Extract key-value pairs from the node definition
         let content = line.trim_matches(|c| c == '{' || c == '}' || c == ',');
         
@@ -286,8 +334,15 @@ impl SkillLoader {
         let mut inputs = Vec::new();
         let mut template = String::new();
         let mut path = String::new();
-        
-        for part in content.split(',') {
+        let mut tensor_type = String::new();
+        let mut separator = None;
+        let mut limit = 0;
+        let mut strict = true;
+        let mut cases = Vec::new();
+        let mut default = None;
+        let mut doc = None;
+        
+        for part in split_params(content) {
             let part = part.trim();
             if let Some(pos) = part.find(':') {
                 let key = part[..pos].trim().to_lowercase();
@@ -306,44 +361,102 @@ impl SkillLoader {
                             .filter(|s| !s.is_empty())
                             .collect();
                     }
-                    "template" => template = value.to_string(),
+                    "template" => template = block.take().unwrap_or_else(|| value.to_string()),
                     "path" => path = value.to_string(),
+                    "tensor_type" => tensor_type = value.to_string(),
+                    "separator" => separator = Some(value.to_string()),
+                    "limit" => {
+                        limit = value.parse().map_err(|_| {
+                            SkillError::InvalidGraph(format!("Invalid limit '{}' in skill node", value))
+                        })?;
+                    }
+                    "cases" => {
+                        // `value=>target; value=>target`, values as JSON or bare strings
+                        for case in value.split(';').map(str::trim).filter(|c| !c.is_empty()) {
+                            let (value, target) = case.split_once("=>").ok_or_else(|| {
+                                SkillError::InvalidGraph(format!("Invalid match case '{}' in skill node", case))
+                            })?;
+                            let value = value.trim();
+                            let value = serde_json::from_str(value)
+                                .unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+                            cases.push((value, target.trim().to_string()));
+                        }
+                    }
+                    "default" => default = Some(value.to_string()),
+                    "doc" => doc = Some(block.take().map_or_else(|| value.to_string(), |b| b.trim().to_string())),
+                    "strict" => {
+                        strict = value.parse().map_err(|_| {
+                            SkillError::InvalidGraph(format!("Invalid strict '{}' in skill node", value))
+                        })?;
+                    }
                     _ => {}
                 }
             }
         }
         
         // Create node based on type
-        match node_type.as_str() {
-            "External" => Ok(SkillNode::External { id, uri, inputs }),
+        let node = match node_type.as_str() {
+            "External" => SkillNode::External { id, uri, inputs },
             "Operation" => {
                 let operation = match op.as_str() {
                     "Identity" => Op::Identity,
                     "StringFormat" => Op::StringFormat { template },
                     "StringConcat" => Op::StringConcat,
+                    "StringSplit" => Op::StringSplit {
+                        separator: separator.unwrap_or_else(|| " ".to_string()),
+                        limit,
+                    },
+                    "StringJoin" => Op::StringJoin {
+                        separator: separator.unwrap_or_default(),
+                    },
+                    "ToInt" => Op::ToInt { strict },
+                    "ToFloat" => Op::ToFloat { strict },
+                    "ToBool" => Op::ToBool { strict },
+                    "ToString" => Op::ToString,
                     "JsonParse" => Op::JsonParse,
                     "JsonGet" => Op::JsonGet { path },
                     "JsonStringify" => Op::JsonStringify,
                     "HttpGet" => Op::HttpGet,
                     "HttpPost" => Op::HttpPost,
-                    unknown => {
+                    "Match" => Op::Match { cases, default },
+                    unknown if self.strict => {
                         return Err(SkillError::InvalidGraph(format!(
-                            "Unknown operation '{}' in skill node '{}'",
-                            unknown, id
+                            "Unknown operation '{}' in skill node '{}' (line {})",
+                            unknown, id, line_no
                         )));
                     }
+                    unknown => {
+                        tracing::warn!(
+                            "Unknown operation '{}' in skill node '{}' (line {}), using Identity",
+                            unknown, id, line_no
+                        );
+                        Op::Identity
+                    }
                 };
-                Ok(SkillNode::Operation { id, op: operation, inputs })
+                SkillNode::Operation { id, op: operation, inputs }
             }
-            "Input" => Ok(SkillNode::Input { 
-                name: id, 
-                tensor_type: "string".to_string() 
-            }),
-            other => Err(SkillError::InvalidGraph(format!(
-                "Unknown node type '{}'",
-                other
-            ))),
-        }
+            "Input" => {
+                return Ok(Some((
+                    SkillNode::Input {
+                        name: id,
+                        tensor_type: if tensor_type.is_empty() { "string".to_string() } else { tensor_type },
+                        doc,
+                    },
+                    None,
+                )));
+            }
+            other if self.strict => {
+                return Err(SkillError::InvalidGraph(format!(
+                    "Unknown node type '{}' (line {})",
+                    other, line_no
+                )));
+            }
+            other => {
+                tracing::warn!("Skipping node '{}' of unknown type '{}' (line {})", id, other, line_no);
+                return Ok(None);
+            }
+        };
+        Ok(Some((node, doc)))
     }
 
     /// Auto-detect format and parse.
@@ -370,6 +483,73 @@ impl SkillLoader {
     }
 }
 
+/// Split a node definition's `key: value` pairs on commas that are not
+/// inside a quoted string or an `[...]` list.
+fn split_params(content: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut depth = 0usize;
+    for (i, c) in content.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '[' => depth += 1,
+            ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(&content[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&content[start..]);
+    parts
+}
+
+/// Delimiter for values spanning several lines in `.0` files.
+const TRIPLE_QUOTE: &str = "\"\"\"";
+
+/// Pull the first triple-quoted block out of a node definition.
+///
+/// Returns the definition with the block replaced by an empty string, and the
+/// block's text. A newline straight after the opening delimiter, and a final
+/// line holding only indentation before the closing one, are not part of the
+/// value; every other newline is kept.
+fn split_triple_quoted(definition: &str) -> (String, Option<String>) {
+    let Some(start) = definition.find(TRIPLE_QUOTE) else {
+        return (definition.to_string(), None);
+    };
+    let body_start = start + TRIPLE_QUOTE.len();
+    let Some(len) = definition[body_start..].find(TRIPLE_QUOTE) else {
+        return (definition.to_string(), None);
+    };
+
+    let mut body = &definition[body_start..body_start + len];
+    body = body.strip_prefix('\n').unwrap_or(body);
+    if let Some((rest, last)) = body.rsplit_once('\n') {
+        if last.trim().is_empty() {
+            body = rest;
+        }
+    }
+
+    let rest = format!(
+        "{}\"\"{}",
+        &definition[..start],
+        &definition[body_start + len + TRIPLE_QUOTE.len()..]
+    );
+    (rest, Some(body.to_string()))
+}
+
 impl Default for SkillLoader {
     fn default() -> Self {
         Self::new("graphs/skills")
@@ -439,6 +619,170 @@ mod tests {
         assert_eq!(graph.description, Some("A test skill".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_parse_and_execute_split_join() {
+        let content = r#"
+            Graph {
+                name: "reverse_words",
+                version: 1,
+
+                nodes: [
+                    { id: "message", type: Input },
+                    { id: "words", type: Operation, op: StringSplit, separator: " ", limit: 3, inputs: ["message"] },
+                    { id: "joined", type: Operation, op: StringJoin, separator: "_", inputs: ["words"] },
+                ],
+
+                outputs: ["joined"],
+            }
+        "#;
+
+        let loader = SkillLoader::new("/tmp");
+        let graph = loader.parse_zero_format(content).unwrap();
+        assert!(graph.nodes.iter().any(|node| matches!(
+            node,
+            SkillNode::Operation { op: crate::skills::Op::StringSplit { limit: 3, .. }, .. }
+        )));
+
+        let runtime_graph = graph.to_runtime_graph().unwrap();
+        let mut inputs = std::collections::HashMap::new();
+        inputs.insert(
+            "message".to_string(),
+            crate::runtime::Value::String("one two three four".into()),
+        );
+        let result = crate::runtime::GraphInterpreter::default()
+            .execute(&runtime_graph, inputs)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result.outputs.get("joined"),
+            Some(&crate::runtime::Value::String("one_two_three four".into()))
+        );
+    }
+
+    const MISSPELLED_OP: &str = r#"
+        Graph {
+            name: "typo",
+            nodes: [
+                { id: "message", type: Input },
+                { id: "out", type: Operation, op: Identiy, inputs: ["message"] },
+            ],
+            outputs: ["out"],
+        }
+    "#;
+
+    #[test]
+    fn test_strict_rejects_misspelled_op() {
+        let loader = SkillLoader::new("/tmp").with_strict(true);
+        match loader.parse_zero_format(MISSPELLED_OP) {
+            Err(SkillError::InvalidGraph(msg)) => {
+                assert!(msg.contains("'Identiy'"));
+                assert!(msg.contains("line 6"));
+            }
+            other => panic!("Expected InvalidGraph, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_lenient_tolerates_misspelled_op() {
+        let loader = SkillLoader::new("/tmp").with_strict(false);
+        let graph = loader.parse_zero_format(MISSPELLED_OP).unwrap();
+        assert!(graph.nodes.iter().any(|node| matches!(
+            node,
+            SkillNode::Operation { id, op: crate::skills::Op::Identity, .. } if id == "out"
+        )));
+
+        let unknown_type = MISSPELLED_OP.replace("type: Operation, op: Identiy", "type: Operaton");
+        let graph = loader.parse_zero_format(&unknown_type).unwrap();
+        assert_eq!(graph.nodes.len(), 1);
+        assert!(SkillLoader::new("/tmp").parse_zero_format(&unknown_type).is_err());
+    }
+
+    const MULTILINE_TEMPLATE: &str = r#"
+        Graph {
+            name: "greet",
+            nodes: [
+                { id: "name", type: Input },
+                { id: "out", type: Operation, op: StringFormat, template: """
+Hello, {}!
+
+  Have a "great" day.
+                """, inputs: ["name"] },
+            ],
+            outputs: ["out"],
+        }
+    "#;
+
+    fn template_of(graph: &SkillGraph) -> String {
+        graph
+            .nodes
+            .iter()
+            .find_map(|node| match node {
+                SkillNode::Operation { op: crate::skills::Op::StringFormat { template }, .. } => {
+                    Some(template.clone())
+                }
+                _ => None,
+            })
+            .expect("StringFormat node")
+    }
+
+    #[test]
+    fn test_parse_multiline_template() {
+        let loader = SkillLoader::new("/tmp");
+        let graph = loader.parse_zero_format(MULTILINE_TEMPLATE).unwrap();
+
+        assert_eq!(template_of(&graph), "Hello, {}!\n\n  Have a \"great\" day.");
+        assert!(graph.get_node("out").is_some_and(|node| node.inputs() == ["name"]));
+        assert_eq!(graph.outputs, vec!["out"]);
+
+        let unterminated = MULTILINE_TEMPLATE.replace("                \"\"\", inputs", "inputs");
+        assert!(loader.parse_zero_format(&unterminated).is_err());
+    }
+
+    #[test]
+    fn test_multiline_template_round_trip() {
+        let loader = SkillLoader::new("/tmp");
+        let graph = loader.parse_zero_format(MULTILINE_TEMPLATE).unwrap();
+
+        let source = graph.to_zero_source().unwrap();
+        let reparsed = loader.parse_zero_format(&source).unwrap();
+        assert_eq!(template_of(&reparsed), template_of(&graph));
+        assert_eq!(reparsed.nodes, graph.nodes);
+    }
+
+    #[test]
+    fn test_parse_match_round_trip() {
+        let content = r#"
+            Graph {
+                name: "dispatch",
+                version: 1,
+
+                nodes: [
+                    { id: "command", type: Input },
+                    { id: "help", type: Operation, op: Identity, inputs: ["command"] },
+                    { id: "stop", type: Operation, op: Identity, inputs: ["command"] },
+                    { id: "reply", type: Operation, op: Match, cases: "help=>help; 0=>stop", default: "help", inputs: ["command"] },
+                ],
+
+                outputs: ["reply"],
+            }
+        "#;
+
+        let loader = SkillLoader::new("/tmp");
+        let graph = loader.parse_zero_format(content).unwrap();
+        let expected = crate::skills::Op::Match {
+            cases: vec![
+                (serde_json::json!("help"), "help".to_string()),
+                (serde_json::json!(0), "stop".to_string()),
+            ],
+            default: Some("help".to_string()),
+        };
+        assert!(matches!(graph.get_node("reply"), Some(SkillNode::Operation { op, .. }) if *op == expected));
+
+        let reparsed = loader.parse_zero_format(&graph.to_zero_source().unwrap()).unwrap();
+        assert_eq!(reparsed.nodes, graph.nodes);
+    }
+
     #[test]
     fn test_cache() {
         let dir = tempdir().unwrap();
@@ -469,4 +813,40 @@ mod tests {
         loader.clear_cache();
         assert_eq!(loader.cache.len(), 0);
     }
+
+    #[tokio::test]
+    async fn test_quoted_comma_in_params() {
+        let content = r#"
+            Graph {
+                name: "csv",
+                version: 1,
+
+                nodes: [
+                    { id: "message", type: Input },
+                    { id: "fields", type: Operation, op: StringSplit, separator: ",", inputs: ["message"] },
+                    { id: "joined", type: Operation, op: StringJoin, separator: ", ", inputs: ["fields"] },
+                ],
+
+                outputs: ["joined"],
+            }
+        "#;
+
+        let graph = SkillLoader::new("/tmp").parse_zero_format(content).unwrap();
+        assert!(graph.nodes.iter().any(|node| matches!(
+            node,
+            SkillNode::Operation { op: crate::skills::Op::StringSplit { separator, .. }, .. } if separator == ","
+        )));
+        assert!(graph.get_node("joined").is_some_and(|node| node.inputs() == ["fields"]));
+
+        let mut inputs = std::collections::HashMap::new();
+        inputs.insert("message".to_string(), crate::runtime::Value::String("a,b,c".into()));
+        let result = crate::runtime::GraphInterpreter::default()
+            .execute(&graph.to_runtime_graph().unwrap(), inputs)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.outputs.get("joined"),
+            Some(&crate::runtime::Value::String("a, b, c".into()))
+        );
+    }
 }
@@ -83,12 +83,20 @@ pub mod loader;
 // Built-in skills
 pub mod builtin;
 
+// Signed version history
+pub mod provenance;
+
+// Verified skill cache
+pub mod cache;
+
 // Re-export main types
-pub use graph::{SkillGraph, SkillNode, Op, SafetyProof, SkillGraphBuilder};
-pub use registry::{SkillRegistry, SkillEntry, SkillMetadata, SkillInput, SkillOutput};
+pub use graph::{SkillGraph, SkillNode, Op, SafetyProof, SkillGraphBuilder, ComplexityReport};
+pub use registry::{SkillRegistry, SkillEntry, SkillMetadata, SkillInput, SkillOutput, SkillLoadMode, SkillQuota, SkillUsage, LOCAL_INSTALLER};
 pub use composer::{SkillComposer, SkillConnection, ComposedSkill, ComposerError};
-pub use verifier::{SkillVerifier, VerificationResult, VerificationWarning, VerificationError};
+pub use verifier::{SkillVerifier, VerificationResult, VerificationWarning, VerificationError, VERIFIER_VERSION, VERIFIER_GRAPH_PATH};
 pub use loader::SkillLoader;
+pub use provenance::{SkillProvenance, ProvenanceEntry};
+pub use cache::VerificationCache;
 
 use crate::error::SkillError;
 
@@ -0,0 +1,150 @@
+//! Tamper-evident history of skill versions.
+//!
+//! Every install or upgrade appends a signed [`ProvenanceEntry`] linking the
+//! previous content hash to the new one. Altering, removing or reordering an
+//! entry breaks either a hash link or a signature.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::SkillError;
+use crate::gateway::signing::{verifier_for, Signer};
+use crate::types::{ContentHash, SigScheme};
+
+/// One signed step in a skill's history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProvenanceEntry {
+    /// Hash of the version this entry replaces (`None` for the first install).
+    pub prev_hash: Option<ContentHash>,
+    /// Hash of the version this entry introduces.
+    pub new_hash: ContentHash,
+    /// Public key of the author who signed the entry.
+    pub author_pubkey: Vec<u8>,
+    /// Signature over the skill name, both hashes and the timestamp.
+    pub signature: Vec<u8>,
+    /// When the entry was created (Unix timestamp ms).
+    pub timestamp: u64,
+}
+
+impl ProvenanceEntry {
+    /// Create an entry for `skill` signed by `signer`.
+    pub fn signed(
+        signer: &dyn Signer,
+        skill: &str,
+        prev_hash: Option<ContentHash>,
+        new_hash: ContentHash,
+        timestamp: u64,
+    ) -> Result<Self, SkillError> {
+        let message = Self::signing_bytes(skill, prev_hash.as_ref(), &new_hash, timestamp);
+        let signature = signer
+            .sign(&message)
+            .map_err(|e| SkillError::VerificationFailed(format!("Cannot sign provenance: {}", e)))?;
+        Ok(Self {
+            prev_hash,
+            new_hash,
+            author_pubkey: signer.verifier().public_key_bytes(),
+            signature,
+            timestamp,
+        })
+    }
+
+    /// Check the signature against the entry's author key.
+    pub fn verify_signature(&self, skill: &str) -> bool {
+        let message = Self::signing_bytes(skill, self.prev_hash.as_ref(), &self.new_hash, self.timestamp);
+        verifier_for(SigScheme::Ed25519, &self.author_pubkey)
+            .and_then(|verifier| verifier.verify(&message, &self.signature))
+            .is_ok()
+    }
+
+    fn signing_bytes(
+        skill: &str,
+        prev_hash: Option<&ContentHash>,
+        new_hash: &ContentHash,
+        timestamp: u64,
+    ) -> Vec<u8> {
+        let mut bytes = b"openclaw:skill-provenance:".to_vec();
+        bytes.extend_from_slice(skill.as_bytes());
+        bytes.push(0);
+        bytes.extend_from_slice(&prev_hash.copied().unwrap_or_default().0);
+        bytes.extend_from_slice(&new_hash.0);
+        bytes.extend_from_slice(&timestamp.to_be_bytes());
+        bytes
+    }
+}
+
+/// Ordered chain of provenance entries for one skill.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkillProvenance {
+    /// Entries from the first install to the current version.
+    pub entries: Vec<ProvenanceEntry>,
+}
+
+impl SkillProvenance {
+    /// Create an empty chain.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hash of the latest version recorded in the chain.
+    pub fn head(&self) -> Option<ContentHash> {
+        self.entries.last().map(|entry| entry.new_hash)
+    }
+
+    /// Append an entry, which must link to the current head.
+    pub fn append(&mut self, entry: ProvenanceEntry) -> Result<(), SkillError> {
+        if !links_to(entry.prev_hash, self.head()) {
+            return Err(SkillError::VerificationFailed(
+                "Provenance entry does not link to the current version".to_string(),
+            ));
+        }
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Check every hash link and signature in the chain.
+    pub fn verify(&self, skill: &str) -> bool {
+        let mut prev = None;
+        for entry in &self.entries {
+            if !links_to(entry.prev_hash, prev) || !entry.verify_signature(skill) {
+                return false;
+            }
+            prev = Some(entry.new_hash);
+        }
+        true
+    }
+}
+
+/// Compare chain links in constant time; `None` only links to `None`.
+fn links_to(prev: Option<ContentHash>, expected: Option<ContentHash>) -> bool {
+    match (prev, expected) {
+        (Some(prev), Some(expected)) => prev.ct_eq(&expected),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gateway::signing::Ed25519Signer;
+
+    #[test]
+    fn test_signature_binds_skill_name() {
+        let signer = Ed25519Signer::generate();
+        let entry = ProvenanceEntry::signed(&signer, "weather", None, ContentHash::from_string("v1"), 1).unwrap();
+
+        assert!(entry.verify_signature("weather"));
+        assert!(!entry.verify_signature("calendar"));
+    }
+
+    #[test]
+    fn test_append_requires_link() {
+        let signer = Ed25519Signer::generate();
+        let v1 = ContentHash::from_string("v1");
+        let mut chain = SkillProvenance::new();
+        chain.append(ProvenanceEntry::signed(&signer, "s", None, v1, 1).unwrap()).unwrap();
+
+        let unlinked = ProvenanceEntry::signed(&signer, "s", None, ContentHash::from_string("v2"), 2).unwrap();
+        assert!(chain.append(unlinked).is_err());
+        assert_eq!(chain.head(), Some(v1));
+    }
+}
@@ -8,8 +8,13 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use crate::types::ContentHash;
 use crate::error::SkillError;
+use crate::channels::ChannelFeature;
+use crate::gateway::signing::Signer;
+use super::cache::VerificationCache;
 use super::graph::{SkillGraph, SkillNode};
-use super::verifier::SkillVerifier;
+use super::loader::SkillLoader;
+use super::provenance::{ProvenanceEntry, SkillProvenance};
+use super::verifier::{SkillVerifier, VerificationError, VerificationResult};
 
 /// Metadata about a skill.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -28,6 +33,15 @@ pub struct SkillMetadata {
     pub inputs: Vec<SkillInput>,
     /// Output definitions.
     pub outputs: Vec<SkillOutput>,
+    /// Execution timeout in milliseconds, overriding the global runtime timeout.
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Channel features the skill needs (e.g., file attachments).
+    #[serde(default)]
+    pub required_features: Vec<ChannelFeature>,
+    /// Free-form keywords used by search.
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 /// Input definition for a skill.
@@ -65,6 +79,9 @@ impl SkillMetadata {
             permissions: Vec::new(),
             inputs: Vec::new(),
             outputs: Vec::new(),
+            timeout_ms: None,
+            required_features: Vec::new(),
+            tags: Vec::new(),
         }
     }
 
@@ -74,6 +91,26 @@ impl SkillMetadata {
         self
     }
 
+    /// Set the execution timeout in milliseconds.
+    pub fn with_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = Some(timeout_ms);
+        self
+    }
+
+    /// Add a required channel feature.
+    pub fn with_required_feature(mut self, feature: ChannelFeature) -> Self {
+        if !self.required_features.contains(&feature) {
+            self.required_features.push(feature);
+        }
+        self
+    }
+
+    /// Add a search tag.
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
     /// Set the author.
     pub fn with_author(mut self, author: &str) -> Self {
         self.author = Some(author.to_string());
@@ -108,6 +145,37 @@ impl SkillMetadata {
     }
 }
 
+/// Per-installer limits on custom skill storage.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SkillQuota {
+    /// Maximum number of skills per installer
+    #[serde(default)]
+    pub max_skills: Option<usize>,
+    /// Maximum serialized size of an installer's skills, in bytes
+    #[serde(default)]
+    pub max_bytes: Option<u64>,
+}
+
+/// How to handle skill files that fail to load or verify at startup.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SkillLoadMode {
+    /// Skip rejected skills, logging each one
+    #[default]
+    Safe,
+    /// Abort on the first rejected skill
+    Strict,
+}
+
+/// Approximate storage used by one installer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SkillUsage {
+    /// Number of installed skills
+    pub skills: usize,
+    /// Serialized size of the installed graphs, in bytes
+    pub bytes: u64,
+}
+
 /// A registered skill entry.
 #[derive(Debug, Clone)]
 pub struct SkillEntry {
@@ -123,6 +191,24 @@ pub struct SkillEntry {
     pub builtin: bool,
     /// When the skill was installed (Unix timestamp ms).
     pub installed_at: u64,
+    /// Signed history of the skill's versions.
+    pub provenance: SkillProvenance,
+    /// Identity that installed the skill, counted against its quota.
+    pub installer: Option<String>,
+}
+
+impl SkillEntry {
+    /// Approximate storage size of the skill graph, in bytes.
+    pub fn size_bytes(&self) -> u64 {
+        graph_size(&self.graph)
+    }
+}
+
+/// Installer that skills installed without an explicit identity count against.
+pub const LOCAL_INSTALLER: &str = "local";
+
+fn graph_size(graph: &SkillGraph) -> u64 {
+    serde_json::to_vec(graph).map(|bytes| bytes.len() as u64).unwrap_or(0)
 }
 
 /// Registry for managing skill graphs.
@@ -137,6 +223,12 @@ pub struct SkillRegistry {
     name_index: HashMap<String, ContentHash>,
     /// Directory for skill graph files.
     skills_dir: PathBuf,
+    /// Storage limits per installer.
+    quota: SkillQuota,
+    /// Hashes known to have passed verification.
+    verification_cache: Option<VerificationCache>,
+    /// Number of verifier runs performed for installs.
+    verifications: usize,
 }
 
 impl SkillRegistry {
@@ -146,9 +238,40 @@ impl SkillRegistry {
             skills: HashMap::new(),
             name_index: HashMap::new(),
             skills_dir: skills_dir.into(),
+            quota: SkillQuota::default(),
+            verification_cache: None,
+            verifications: 0,
         }
     }
 
+    /// Limit the skills each installer may install.
+    pub fn with_quota(mut self, quota: SkillQuota) -> Self {
+        self.quota = quota;
+        self
+    }
+
+    /// Skip re-verifying skills the cache records as verified.
+    pub fn with_verification_cache(mut self, cache: VerificationCache) -> Self {
+        self.verification_cache = Some(cache);
+        self
+    }
+
+    /// Number of times the verifier ran for an install.
+    pub fn verification_count(&self) -> usize {
+        self.verifications
+    }
+
+    /// Storage used by the skills `installer` installed.
+    pub fn usage(&self, installer: &str) -> SkillUsage {
+        self.skills
+            .values()
+            .filter(|entry| !entry.builtin && entry.installer.as_deref() == Some(installer))
+            .fold(SkillUsage::default(), |usage, entry| SkillUsage {
+                skills: usage.skills + 1,
+                bytes: usage.bytes + entry.size_bytes(),
+            })
+    }
+
     /// Load built-in skills into the registry.
     pub fn load_builtin(&mut self) -> Result<(), SkillError> {
         use super::builtin;
@@ -177,6 +300,47 @@ impl SkillRegistry {
         Ok(())
     }
 
+    /// Load and install the custom skills in the skills directory.
+    ///
+    /// Files are loaded in name order and verified before install, unless
+    /// the verification cache already vouches for them. A stored provenance
+    /// chain is restored and must end at the loaded version. A missing
+    /// directory loads nothing. Returns the number of skills installed.
+    pub fn load_installed(&mut self, mode: SkillLoadMode) -> Result<usize, SkillError> {
+        let mut paths: Vec<PathBuf> = match std::fs::read_dir(&self.skills_dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| matches!(path.extension().and_then(|e| e.to_str()), Some("0") | Some("json")))
+                .collect(),
+            Err(_) => return Ok(0),
+        };
+        paths.sort();
+
+        // Verification happens on install, where the cache is consulted.
+        let mut loader = SkillLoader::new(&self.skills_dir).with_verify(false);
+        let mut installed = 0;
+        for path in paths {
+            let result = loader.load_file(&path).and_then(|graph| {
+                let name = graph.name.clone();
+                let hash = self.install_graph(&name, graph, false)?;
+                self.restore_provenance(&name, &hash).inspect_err(|_| {
+                    let _ = self.uninstall(&hash);
+                })
+            });
+            match result {
+                Ok(_) => installed += 1,
+                Err(e) if mode == SkillLoadMode::Safe => {
+                    tracing::warn!("Rejected skill {}: {}", path.display(), e);
+                }
+                Err(e) => {
+                    return Err(SkillError::VerificationFailed(format!("{}: {}", path.display(), e)));
+                }
+            }
+        }
+        tracing::info!("Loaded {} skills from {}", installed, self.skills_dir.display());
+        Ok(installed)
+    }
+
     /// Install a skill from a graph.
     ///
     /// # Arguments
@@ -192,6 +356,47 @@ impl SkillRegistry {
         graph: SkillGraph,
         builtin: bool,
     ) -> Result<ContentHash, SkillError> {
+        let metadata = Self::extract_metadata(&graph, name);
+        self.install_with_metadata(graph, metadata, builtin)
+    }
+
+    /// Install a custom skill on behalf of `installer`, enforcing its quota.
+    ///
+    /// Custom skills installed through the other install paths count
+    /// against [`LOCAL_INSTALLER`].
+    pub fn install_graph_as(
+        &mut self,
+        installer: &str,
+        name: &str,
+        graph: SkillGraph,
+    ) -> Result<ContentHash, SkillError> {
+        let metadata = Self::extract_metadata(&graph, name);
+        self.insert(graph, metadata, false, Some(installer))
+    }
+
+    /// Install a skill from a graph with explicit metadata.
+    ///
+    /// Use this when richer metadata (author, input descriptions, ...) is
+    /// available than what can be extracted from the graph itself. The skill
+    /// is registered under `metadata.name`.
+    pub fn install_with_metadata(
+        &mut self,
+        graph: SkillGraph,
+        metadata: SkillMetadata,
+        builtin: bool,
+    ) -> Result<ContentHash, SkillError> {
+        self.insert(graph, metadata, builtin, None)
+    }
+
+    fn insert(
+        &mut self,
+        graph: SkillGraph,
+        metadata: SkillMetadata,
+        builtin: bool,
+        installer: Option<&str>,
+    ) -> Result<ContentHash, SkillError> {
+        let name = metadata.name.clone();
+        let name = name.as_str();
         let hash = graph.content_hash();
         
         // Check if already installed
@@ -200,27 +405,14 @@ impl SkillRegistry {
             return Ok(hash);
         }
 
-        // Hard gate: installed skills must compile to runtime graph.
-        graph.to_runtime_graph()?;
-        
-        // Verify skill unless it's built-in
-        let verified = if builtin {
-            true
-        } else {
-            let result = SkillVerifier::verify(&graph)?;
-            if !result.safe {
-                let error_msgs: Vec<String> = result.errors.iter()
-                    .map(|e| e.to_string())
-                    .collect();
-                return Err(SkillError::VerificationFailed(
-                    error_msgs.join("; ")
-                ));
-            }
-            true
+        let verified = self.verify_for_install(&graph, &hash, builtin)?;
+        let installer = match builtin {
+            true => None,
+            false => Some(installer.unwrap_or(LOCAL_INSTALLER)),
         };
-        
-        // Extract metadata
-        let metadata = Self::extract_metadata(&graph, name);
+        if let Some(installer) = installer {
+            self.check_quota(installer, &graph, None)?;
+        }
         
         let entry = SkillEntry {
             hash,
@@ -229,6 +421,8 @@ impl SkillRegistry {
             verified,
             builtin,
             installed_at: chrono::Utc::now().timestamp_millis() as u64,
+            provenance: SkillProvenance::new(),
+            installer: installer.map(str::to_string),
         };
         
         // Check for name conflicts
@@ -248,6 +442,258 @@ impl SkillRegistry {
         Ok(hash)
     }
 
+    /// Check that installing `graph` keeps `installer` within its quota.
+    ///
+    /// When `replacing` is set, that entry's storage is released by the
+    /// install, as on upgrade.
+    fn check_quota(
+        &self,
+        installer: &str,
+        graph: &SkillGraph,
+        replacing: Option<&SkillEntry>,
+    ) -> Result<(), SkillError> {
+        let mut usage = self.usage(installer);
+        if let Some(old) = replacing.filter(|old| !old.builtin && old.installer.as_deref() == Some(installer)) {
+            usage.skills -= 1;
+            usage.bytes -= old.size_bytes();
+        }
+        if let Some(limit) = self.quota.max_skills {
+            if usage.skills + 1 > limit {
+                return Err(SkillError::QuotaExceeded {
+                    used: usage.skills as u64 + 1,
+                    limit: limit as u64,
+                });
+            }
+        }
+        if let Some(limit) = self.quota.max_bytes {
+            let used = usage.bytes + graph_size(graph);
+            if used > limit {
+                return Err(SkillError::QuotaExceeded { used, limit });
+            }
+        }
+        Ok(())
+    }
+
+    /// Check a graph is installable, trusting the verification cache.
+    fn verify_for_install(
+        &mut self,
+        graph: &SkillGraph,
+        hash: &ContentHash,
+        builtin: bool,
+    ) -> Result<bool, SkillError> {
+        if builtin {
+            return Self::check_installable(graph, true);
+        }
+        if let Some(cache) = &self.verification_cache {
+            if cache.is_verified(hash) {
+                graph.to_runtime_graph()?;
+                return Ok(true);
+            }
+        }
+        self.verifications += 1;
+        let verified = Self::check_installable(graph, false)?;
+        if let Some(cache) = &self.verification_cache {
+            if let Err(e) = cache.record(hash) {
+                tracing::warn!("Failed to cache verification of {:?}: {}", hash, e);
+            }
+        }
+        Ok(verified)
+    }
+
+    /// Compile and (unless built-in) verify a graph before installing it.
+    fn check_installable(graph: &SkillGraph, builtin: bool) -> Result<bool, SkillError> {
+        // Hard gate: installed skills must compile to runtime graph.
+        graph.to_runtime_graph()?;
+        
+        // Verify skill unless it's built-in
+        if !builtin {
+            let result = SkillVerifier::verify(graph)?;
+            if !result.safe {
+                let error_msgs: Vec<String> = result.errors.iter()
+                    .map(|e| e.to_string())
+                    .collect();
+                return Err(SkillError::VerificationFailed(
+                    error_msgs.join("; ")
+                ));
+            }
+        }
+        Ok(true)
+    }
+
+    /// Check whether `graph` could be installed as `name`, without installing it.
+    ///
+    /// Runs the same compilation, verification and name-conflict checks as
+    /// [`Self::install_with_metadata`] and reports them as a verification result.
+    pub fn check_install(&self, name: &str, graph: &SkillGraph) -> Result<VerificationResult, SkillError> {
+        let mut result = SkillVerifier::verify(graph)?;
+
+        if let Err(e) = graph.to_runtime_graph() {
+            result = result.with_error(VerificationError::NotCompilable { reason: e.to_string() });
+        }
+        if self.name_index.get(name).is_some_and(|hash| *hash != graph.content_hash()) {
+            result = result.with_error(VerificationError::NameConflict { name: name.to_string() });
+        }
+        Ok(result)
+    }
+
+    /// Install a skill and start its provenance chain signed by `signer`.
+    pub fn install_signed(
+        &mut self,
+        graph: SkillGraph,
+        metadata: SkillMetadata,
+        signer: &dyn Signer,
+    ) -> Result<ContentHash, SkillError> {
+        let name = metadata.name.clone();
+        let hash = self.install_with_metadata(graph, metadata, false)?;
+        let entry = self.skills.get_mut(&hash).ok_or_else(|| SkillError::NotFound(name.clone()))?;
+        if entry.provenance.head() != Some(hash) {
+            let record = ProvenanceEntry::signed(
+                signer,
+                &name,
+                entry.provenance.head(),
+                hash,
+                chrono::Utc::now().timestamp_millis() as u64,
+            )?;
+            entry.provenance.append(record)?;
+            let provenance = entry.provenance.clone();
+            if let Err(e) = self.store_provenance(&hash, &provenance) {
+                let _ = self.uninstall(&hash);
+                return Err(e);
+            }
+        }
+        Ok(hash)
+    }
+
+    /// Replace the skill `name` with a new graph, extending its provenance chain.
+    ///
+    /// The new version keeps the existing metadata and is verified like any
+    /// other custom install. `signer` must hold the key that signed the
+    /// installed version; use [`upgrade_with_new_signer`](Self::upgrade_with_new_signer)
+    /// to hand the skill to a different key.
+    pub fn upgrade(
+        &mut self,
+        name: &str,
+        graph: SkillGraph,
+        signer: &dyn Signer,
+    ) -> Result<ContentHash, SkillError> {
+        self.upgrade_signed(name, graph, signer, false)
+    }
+
+    /// Upgrade the skill `name` like [`upgrade`](Self::upgrade), accepting a
+    /// `signer` other than the one that signed the installed version.
+    pub fn upgrade_with_new_signer(
+        &mut self,
+        name: &str,
+        graph: SkillGraph,
+        signer: &dyn Signer,
+    ) -> Result<ContentHash, SkillError> {
+        self.upgrade_signed(name, graph, signer, true)
+    }
+
+    fn upgrade_signed(
+        &mut self,
+        name: &str,
+        graph: SkillGraph,
+        signer: &dyn Signer,
+        allow_new_signer: bool,
+    ) -> Result<ContentHash, SkillError> {
+        let old_hash = *self.name_index
+            .get(name)
+            .ok_or_else(|| SkillError::NotFound(name.to_string()))?;
+        let new_hash = graph.content_hash();
+        if new_hash == old_hash {
+            return Ok(old_hash);
+        }
+        if self.skills.contains_key(&new_hash) {
+            return Err(SkillError::AlreadyInstalled(format!(
+                "Another skill already has hash {:?}",
+                new_hash
+            )));
+        }
+        let verified = self.verify_for_install(&graph, &new_hash, false)?;
+
+        let old = self.skills.get(&old_hash).ok_or_else(|| SkillError::NotFound(name.to_string()))?;
+        let same_signer = old
+            .provenance
+            .entries
+            .last()
+            .is_some_and(|last| last.author_pubkey == signer.verifier().public_key_bytes());
+        if !same_signer && !allow_new_signer {
+            return Err(SkillError::VerificationFailed(format!(
+                "Upgrade of '{}' is not signed by the key that signed the installed version",
+                name
+            )));
+        }
+        let installer = old.installer.clone().unwrap_or_else(|| LOCAL_INSTALLER.to_string());
+        self.check_quota(&installer, &graph, Some(old))?;
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        let mut provenance = old.provenance.clone();
+        provenance.append(ProvenanceEntry::signed(signer, name, Some(old_hash), new_hash, now)?)?;
+
+        let entry = SkillEntry {
+            hash: new_hash,
+            metadata: old.metadata.clone(),
+            graph,
+            verified,
+            builtin: false,
+            installed_at: now,
+            provenance,
+            installer: Some(installer),
+        };
+        self.store_provenance(&new_hash, &entry.provenance)?;
+        let _ = std::fs::remove_file(self.provenance_path(&old_hash));
+        self.skills.remove(&old_hash);
+        self.skills.insert(new_hash, entry);
+        self.name_index.insert(name.to_string(), new_hash);
+
+        tracing::info!("Upgraded skill '{}' from {:?} to {:?}", name, old_hash, new_hash);
+        Ok(new_hash)
+    }
+
+    fn provenance_path(&self, hash: &ContentHash) -> PathBuf {
+        self.skills_dir.join(format!("{}.provenance", hash.to_hex()))
+    }
+
+    /// Write the provenance chain of the version `hash` next to the skill files.
+    fn store_provenance(&self, hash: &ContentHash, provenance: &SkillProvenance) -> Result<(), SkillError> {
+        let json = serde_json::to_vec(provenance)
+            .map_err(|e| SkillError::VerificationFailed(format!("Cannot encode provenance: {}", e)))?;
+        std::fs::create_dir_all(&self.skills_dir)
+            .and_then(|_| std::fs::write(self.provenance_path(hash), json))
+            .map_err(|e| SkillError::VerificationFailed(format!("Cannot store provenance: {}", e)))
+    }
+
+    /// Attach the stored provenance chain, if any, to the installed skill `name`.
+    fn restore_provenance(&mut self, name: &str, hash: &ContentHash) -> Result<(), SkillError> {
+        let json = match std::fs::read(self.provenance_path(hash)) {
+            Ok(json) => json,
+            Err(_) => return Ok(()),
+        };
+        let provenance: SkillProvenance = serde_json::from_slice(&json)
+            .map_err(|e| SkillError::VerificationFailed(format!("Invalid provenance for '{}': {}", name, e)))?;
+        if provenance.head() != Some(*hash) || !provenance.verify(name) {
+            return Err(SkillError::VerificationFailed(format!(
+                "Provenance of '{}' does not verify",
+                name
+            )));
+        }
+        if let Some(entry) = self.skills.get_mut(hash) {
+            entry.provenance = provenance;
+        }
+        Ok(())
+    }
+
+    /// Check the provenance chain of the skill `name`.
+    ///
+    /// Returns `false` if a hash link or signature is broken, or if the chain
+    /// does not end at the installed version.
+    pub fn verify_provenance(&self, name: &str) -> Result<bool, SkillError> {
+        let entry = self
+            .get_by_name(name)
+            .ok_or_else(|| SkillError::NotFound(name.to_string()))?;
+        Ok(entry.provenance.head() == Some(entry.hash) && entry.provenance.verify(name))
+    }
+
     /// Get a skill by its content hash.
     pub fn get(&self, hash: &ContentHash) -> Option<&SkillEntry> {
         self.skills.get(hash)
@@ -260,6 +706,104 @@ impl SkillRegistry {
             .and_then(|hash| self.skills.get(hash))
     }
 
+    /// Resolve a skill by name, full content hash, or unambiguous hash prefix.
+    pub fn resolve(&self, name_or_hash: &str) -> Option<&SkillEntry> {
+        if let Some(entry) = self.get_by_name(name_or_hash) {
+            return Some(entry);
+        }
+
+        if let Ok(hash) = ContentHash::from_hex(name_or_hash) {
+            return self.get(&hash);
+        }
+
+        let prefix = name_or_hash.to_lowercase();
+        if prefix.is_empty() || !prefix.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        let mut matches = self.skills.values().filter(|e| e.hash.to_hex().starts_with(&prefix));
+        match (matches.next(), matches.next()) {
+            (Some(entry), None) => Some(entry),
+            _ => None,
+        }
+    }
+
+    /// Describe a skill's inputs as a JSON schema object, for external callers.
+    ///
+    /// Tensor types map to JSON types (`i64` to `integer`, `f32` to
+    /// `number`, ...); the original type is kept under `x-tensor-type`.
+    pub fn input_schema(&self, name: &str) -> Option<serde_json::Value> {
+        let entry = self.get_by_name(name)?;
+        let mut properties = serde_json::Map::new();
+        let mut required = Vec::new();
+        for input in &entry.metadata.inputs {
+            properties.insert(
+                input.name.clone(),
+                serde_json::json!({
+                    "type": json_schema_type(&input.tensor_type),
+                    "description": input.description,
+                    "x-tensor-type": input.tensor_type,
+                }),
+            );
+            if input.required {
+                required.push(input.name.clone());
+            }
+        }
+
+        Some(serde_json::json!({
+            "title": entry.metadata.name,
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        }))
+    }
+
+    /// Search skills by fuzzy match over name, description and tags.
+    ///
+    /// Each whitespace-separated query term is scored against every field
+    /// (name weighs most, then tags, then description) and the term scores
+    /// are averaged. Results are sorted by descending score; skills that
+    /// match no term are omitted.
+    pub fn search(&self, query: &str) -> Vec<(&SkillEntry, f32)> {
+        let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        if terms.is_empty() {
+            return Vec::new();
+        }
+
+        let mut results: Vec<(&SkillEntry, f32)> = self
+            .skills
+            .values()
+            .filter_map(|entry| {
+                let metadata = &entry.metadata;
+                let total: f32 = terms
+                    .iter()
+                    .map(|term| {
+                        let name = fuzzy_score(term, &metadata.name);
+                        let tags = metadata
+                            .tags
+                            .iter()
+                            .map(|tag| fuzzy_score(term, tag))
+                            .fold(0.0, f32::max);
+                        let description = metadata
+                            .description
+                            .split_whitespace()
+                            .map(|word| fuzzy_score(term, word))
+                            .fold(0.0, f32::max);
+                        name.max(tags * 0.8).max(description * 0.6)
+                    })
+                    .sum();
+                let score = total / terms.len() as f32;
+                (score > 0.0).then_some((entry, score))
+            })
+            .collect();
+
+        results.sort_by(|a, b| {
+            b.1.total_cmp(&a.1)
+                .then_with(|| a.0.metadata.name.cmp(&b.0.metadata.name))
+        });
+        results
+    }
+
     /// List all installed skills.
     pub fn list(&self) -> Vec<&SkillEntry> {
         self.skills.values().collect()
@@ -341,10 +885,10 @@ impl SkillRegistry {
         
         // Extract inputs from graph
         for node in &graph.nodes {
-            if let SkillNode::Input { name, tensor_type } = node {
+            if let SkillNode::Input { name, tensor_type, doc } = node {
                 metadata.inputs.push(SkillInput {
                     name: name.clone(),
-                    description: String::new(),
+                    description: doc.clone().unwrap_or_default(),
                     tensor_type: tensor_type.clone(),
                     required: true,
                 });
@@ -355,7 +899,7 @@ impl SkillRegistry {
         for output_name in &graph.outputs {
             metadata.outputs.push(SkillOutput {
                 name: output_name.clone(),
-                description: String::new(),
+                description: graph.output_docs.get(output_name).cloned().unwrap_or_default(),
                 tensor_type: "any".to_string(),
             });
         }
@@ -367,12 +911,51 @@ impl SkillRegistry {
     }
 }
 
+/// Score how well a lowercase `term` matches `text`, from 0.0 to 1.0.
+///
+/// Exact matches score highest, then prefixes, then substrings. Otherwise
+/// the term's characters must appear in order in `text`, and the score
+/// falls with the number of skipped characters.
+fn fuzzy_score(term: &str, text: &str) -> f32 {
+    let text = text.to_lowercase();
+    if text == term {
+        return 1.0;
+    }
+    if text.starts_with(term) {
+        return 0.9;
+    }
+    if text.contains(term) {
+        return 0.75;
+    }
+
+    let mut chars = text.chars();
+    let matched = term.chars().all(|c| chars.any(|t| t == c));
+    if !matched {
+        return 0.0;
+    }
+    let term_len = term.chars().count() as f32;
+    0.5 * term_len / text.chars().count() as f32
+}
+
 impl Default for SkillRegistry {
     fn default() -> Self {
         Self::new("graphs/skills")
     }
 }
 
+/// JSON schema type for a tensor type name.
+fn json_schema_type(tensor_type: &str) -> &'static str {
+    match tensor_type {
+        "string" | "str" => "string",
+        "bool" | "boolean" => "boolean",
+        "int" | "integer" | "i32" | "i64" | "u32" | "u64" => "integer",
+        "float" | "number" | "f32" | "f64" => "number",
+        t if t.starts_with('[') || t.starts_with("array") => "array",
+        "map" | "object" | "json" => "object",
+        _ => "string",
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -440,4 +1023,324 @@ mod tests {
         assert_eq!(hash1, hash2);
         assert_eq!(registry.count(), 1);
     }
+
+    fn echo_graph(description: &str) -> SkillGraph {
+        SkillGraph::builder("echo_custom")
+            .description(description)
+            .add_input("message", "string")
+            .add_operation("output", crate::skills::Op::Identity, vec!["message"])
+            .output("output")
+            .build()
+    }
+
+    #[test]
+    fn test_provenance_chain_across_upgrade() {
+        use crate::gateway::signing::Ed25519Signer;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut registry = SkillRegistry::new(dir.path());
+        let author = Ed25519Signer::generate();
+        let metadata = SkillMetadata::new("echo_custom", "Echo");
+
+        let v1 = registry.install_signed(echo_graph("v1"), metadata, &author).unwrap();
+        let v2 = registry.upgrade("echo_custom", echo_graph("v2"), &author).unwrap();
+
+        assert!(!registry.is_installed(&v1));
+        let entry = registry.get_by_name("echo_custom").unwrap();
+        assert_eq!(entry.hash, v2);
+        assert_eq!(entry.provenance.entries.len(), 2);
+        assert_eq!(entry.provenance.entries[1].prev_hash, Some(v1));
+        assert!(registry.verify_provenance("echo_custom").unwrap());
+    }
+
+    #[test]
+    fn test_provenance_broken_link_detected() {
+        use crate::gateway::signing::Ed25519Signer;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut registry = SkillRegistry::new(dir.path());
+        let author = Ed25519Signer::generate();
+        let metadata = SkillMetadata::new("echo_custom", "Echo");
+        registry.install_signed(echo_graph("v1"), metadata, &author).unwrap();
+        let v2 = registry.upgrade("echo_custom", echo_graph("v2"), &author).unwrap();
+
+        // Rewrite history so the upgrade claims a different predecessor
+        let entry = registry.skills.get_mut(&v2).unwrap();
+        entry.provenance.entries[1].prev_hash = Some(ContentHash::from_string("forged"));
+
+        assert!(!registry.verify_provenance("echo_custom").unwrap());
+        assert!(registry.verify_provenance("missing").is_err());
+    }
+
+    #[test]
+    fn test_upgrade_requires_same_signer() {
+        use crate::gateway::signing::Ed25519Signer;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut registry = SkillRegistry::new(dir.path());
+        let author = Ed25519Signer::generate();
+        let stranger = Ed25519Signer::generate();
+        let v1 = registry
+            .install_signed(echo_graph("v1"), SkillMetadata::new("echo_custom", "Echo"), &author)
+            .unwrap();
+
+        let err = registry.upgrade("echo_custom", echo_graph("v2"), &stranger).unwrap_err();
+        assert!(matches!(err, SkillError::VerificationFailed(_)), "{:?}", err);
+        assert_eq!(registry.get_by_name("echo_custom").unwrap().hash, v1);
+
+        registry.upgrade_with_new_signer("echo_custom", echo_graph("v2"), &stranger).unwrap();
+        registry.upgrade("echo_custom", echo_graph("v3"), &stranger).unwrap();
+        assert!(registry.upgrade("echo_custom", echo_graph("v4"), &author).is_err());
+        assert!(registry.verify_provenance("echo_custom").unwrap());
+    }
+
+    #[test]
+    fn test_provenance_survives_reload() {
+        use crate::gateway::signing::Ed25519Signer;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut registry = SkillRegistry::new(dir.path());
+        let author = Ed25519Signer::generate();
+        registry
+            .install_signed(echo_graph("v1"), SkillMetadata::new("echo_custom", "Echo"), &author)
+            .unwrap();
+        let v2 = registry.upgrade("echo_custom", echo_graph("v2"), &author).unwrap();
+        std::fs::write(dir.path().join("echo.json"), serde_json::to_string(&echo_graph("v2")).unwrap()).unwrap();
+
+        let mut reloaded = SkillRegistry::new(dir.path());
+        assert_eq!(reloaded.load_installed(SkillLoadMode::Strict).unwrap(), 1);
+        assert_eq!(reloaded.get_by_name("echo_custom").unwrap().provenance.entries.len(), 2);
+        assert!(reloaded.verify_provenance("echo_custom").unwrap());
+        let stranger = Ed25519Signer::generate();
+        assert!(reloaded.upgrade("echo_custom", echo_graph("v3"), &stranger).is_err());
+
+        // A tampered chain rejects the skill instead of loading it without history
+        let path = dir.path().join(format!("{}.provenance", v2.to_hex()));
+        let mut provenance: SkillProvenance = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        provenance.entries[0].timestamp += 1;
+        std::fs::write(&path, serde_json::to_vec(&provenance).unwrap()).unwrap();
+        let mut tampered = SkillRegistry::new(dir.path());
+        assert_eq!(tampered.load_installed(SkillLoadMode::Safe).unwrap(), 0);
+        assert!(!tampered.is_installed_by_name("echo_custom"));
+    }
+
+    #[test]
+    fn test_input_schema() {
+        let mut registry = SkillRegistry::new("/tmp/skills");
+        let graph = SkillGraph::builder("search")
+            .add_input("query", "string")
+            .add_input("limit", "i64")
+            .add_operation("result", crate::skills::Op::Identity, vec!["query"])
+            .output("result")
+            .build();
+        let metadata = SkillMetadata::new("search", "Search things")
+            .with_input("query", "Search terms", "string", true)
+            .with_input("limit", "Result limit", "i64", false);
+        registry.install_with_metadata(graph, metadata, false).unwrap();
+
+        let schema = registry.input_schema("search").unwrap();
+        assert_eq!(schema["properties"]["query"]["type"], "string");
+        assert_eq!(schema["properties"]["limit"]["type"], "integer");
+        assert_eq!(schema["required"], serde_json::json!(["query"]));
+        assert!(registry.input_schema("missing").is_none());
+    }
+
+    #[test]
+    fn test_search_ranks_best_match_first() {
+        let mut registry = SkillRegistry::new("/tmp/skills");
+        let skills = [
+            ("weather", "Look up the forecast", "climate"),
+            ("calendar", "Show upcoming events", "schedule"),
+            ("news", "Latest headlines, weather included", "media"),
+        ];
+        for (name, description, tag) in skills {
+            let graph = SkillGraph::builder(name)
+                .description(description)
+                .add_input("query", "string")
+                .add_operation("output", crate::skills::Op::Identity, vec!["query"])
+                .output("output")
+                .build();
+            let metadata = SkillMetadata::new(name, description).with_tag(tag);
+            registry.install_with_metadata(graph, metadata, false).unwrap();
+        }
+
+        let results = registry.search("weath");
+        assert_eq!(results[0].0.metadata.name, "weather");
+        assert_eq!(results[1].0.metadata.name, "news");
+        assert!(results[0].1 > results[1].1);
+
+        let results = registry.search("sched");
+        assert_eq!(results[0].0.metadata.name, "calendar");
+        assert!(registry.search("zzz").is_empty());
+    }
+
+    #[test]
+    fn test_check_install_does_not_mutate() {
+        let mut registry = SkillRegistry::new("/tmp/skills");
+
+        // Network access without the permission declared
+        let unsafe_graph = SkillGraph::builder("fetcher")
+            .add_input("url", "string")
+            .add_operation("fetch", crate::skills::Op::HttpGet, vec!["url"])
+            .output("fetch")
+            .build();
+        let result = registry.check_install("fetcher", &unsafe_graph).unwrap();
+        assert!(!result.safe);
+        assert!(!result.errors.is_empty());
+        assert_eq!(registry.count(), 0);
+
+        registry.install_graph("echo_custom", echo_graph("v1"), false).unwrap();
+        let result = registry.check_install("echo_custom", &echo_graph("v2")).unwrap();
+        assert!(result.errors.iter().any(|e| matches!(e, VerificationError::NameConflict { .. })));
+        assert!(registry.check_install("echo_custom", &echo_graph("v1")).unwrap().safe);
+        assert_eq!(registry.count(), 1);
+    }
+
+    #[test]
+    fn test_install_quota_per_installer() {
+        let mut registry = SkillRegistry::new("/tmp/skills").with_quota(SkillQuota {
+            max_skills: Some(2),
+            max_bytes: None,
+        });
+        registry.load_builtin().unwrap();
+
+        registry.install_graph_as("alice", "a1", echo_graph("a1")).unwrap();
+        registry.install_graph_as("alice", "a2", echo_graph("a2")).unwrap();
+        let err = registry.install_graph_as("alice", "a3", echo_graph("a3")).unwrap_err();
+        assert!(matches!(err, SkillError::QuotaExceeded { used: 3, limit: 2 }), "{:?}", err);
+        assert!(!registry.is_installed_by_name("a3"));
+
+        registry.install_graph_as("bob", "b1", echo_graph("b1")).unwrap();
+        assert_eq!(registry.usage("alice").skills, 2);
+        assert_eq!(registry.usage("bob").skills, 1);
+        assert!(registry.usage("alice").bytes > 0);
+
+        let limit = registry.usage("bob").bytes;
+        let mut sized = SkillRegistry::new("/tmp/skills").with_quota(SkillQuota {
+            max_skills: None,
+            max_bytes: Some(limit),
+        });
+        sized.install_graph_as("bob", "b1", echo_graph("b1")).unwrap();
+        let err = sized.install_graph_as("bob", "b2", echo_graph("b2")).unwrap_err();
+        assert!(matches!(err, SkillError::QuotaExceeded { limit: l, .. } if l == limit));
+    }
+
+    #[test]
+    fn test_quota_covers_every_install_path() {
+        use crate::gateway::signing::Ed25519Signer;
+
+        let state = tempfile::tempdir().unwrap();
+        let mut registry = SkillRegistry::new(state.path()).with_quota(SkillQuota {
+            max_skills: Some(1),
+            max_bytes: Some(graph_size(&echo_graph("v1")) + 8),
+        });
+        registry.load_builtin().unwrap();
+        let author = Ed25519Signer::generate();
+        registry.install_signed(echo_graph("v1"), SkillMetadata::new("a1", "Echo"), &author).unwrap();
+        assert_eq!(registry.usage(LOCAL_INSTALLER).skills, 1);
+        let err = registry.install_graph("a2", echo_graph("v2"), false).unwrap_err();
+        assert!(matches!(err, SkillError::QuotaExceeded { used: 2, limit: 1 }), "{:?}", err);
+
+        // Upgrades replace the old version's storage rather than adding to it
+        registry.upgrade("a1", echo_graph("v2"), &author).unwrap();
+        let err = registry
+            .upgrade("a1", echo_graph("a much longer description"), &author)
+            .unwrap_err();
+        assert!(matches!(err, SkillError::QuotaExceeded { .. }), "{:?}", err);
+        assert_eq!(registry.get_by_name("a1").unwrap().hash, echo_graph("v2").content_hash());
+
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["one", "two"] {
+            let graph = SkillGraph::builder(name)
+                .add_input("message", "string")
+                .add_operation("output", crate::skills::Op::Identity, vec!["message"])
+                .output("output")
+                .build();
+            std::fs::write(dir.path().join(format!("{}.json", name)), serde_json::to_string(&graph).unwrap()).unwrap();
+        }
+        let mut loaded = SkillRegistry::new(dir.path()).with_quota(SkillQuota {
+            max_skills: Some(1),
+            max_bytes: None,
+        });
+        assert_eq!(loaded.load_installed(SkillLoadMode::Safe).unwrap(), 1);
+        assert!(loaded.load_installed(SkillLoadMode::Strict).is_err());
+    }
+
+    #[test]
+    fn test_load_installed_modes() {
+        let dir = tempfile::tempdir().unwrap();
+        let skill = |name: &str, outputs: &str| format!(
+            r#"{{"name": "{}", "version": "1", "description": "", "nodes": [
+                {{"Input": {{"name": "input", "tensor_type": "string"}}}}
+            ], "entry_point": null, "outputs": {}, "permissions": [], "proofs": []}}"#,
+            name, outputs
+        );
+        std::fs::write(dir.path().join("a_good.json"), skill("good", r#"["input"]"#)).unwrap();
+        std::fs::write(dir.path().join("b_bad.json"), skill("bad", "[]")).unwrap();
+        std::fs::write(dir.path().join("c_other.json"), skill("other", r#"["input"]"#)).unwrap();
+
+        let mut safe = SkillRegistry::new(dir.path());
+        assert_eq!(safe.load_installed(SkillLoadMode::Safe).unwrap(), 2);
+        assert!(safe.is_installed_by_name("good"));
+        assert!(safe.is_installed_by_name("other"));
+        assert!(!safe.is_installed_by_name("bad"));
+
+        let mut strict = SkillRegistry::new(dir.path());
+        let err = strict.load_installed(SkillLoadMode::Strict).unwrap_err();
+        assert!(err.to_string().contains("b_bad.json"), "{}", err);
+    }
+
+    #[test]
+    fn test_verification_cache_skips_unchanged_skills() {
+        let skills = tempfile::tempdir().unwrap();
+        let cache_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            skills.path().join("greet.json"),
+            serde_json::to_string(&echo_graph("greet")).unwrap(),
+        ).unwrap();
+        let load = |cache: VerificationCache| {
+            let mut registry = SkillRegistry::new(skills.path()).with_verification_cache(cache);
+            assert_eq!(registry.load_installed(SkillLoadMode::Strict).unwrap(), 1);
+            registry.verification_count()
+        };
+
+        assert_eq!(load(VerificationCache::new(cache_dir.path())), 1);
+        assert_eq!(load(VerificationCache::new(cache_dir.path())), 0);
+
+        let bumped = VerificationCache::new(cache_dir.path())
+            .with_verifier_version(super::super::VERIFIER_VERSION + 1);
+        assert_eq!(load(bumped.clone()), 1);
+        assert_eq!(load(bumped), 0);
+    }
+
+    #[test]
+    fn test_documented_inputs_in_metadata() {
+        use crate::skills::SkillLoader;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("greet.0");
+        std::fs::write(&path, r#"
+            Graph {
+                name: "greet",
+                version: 1,
+
+                nodes: [
+                    { id: "who", type: Input, doc: "Name of the person to greet" },
+                    { id: "greeting", type: Operation, op: Identity, inputs: ["who"], doc: """
+                        The greeting, ready to send
+                    """ },
+                ],
+
+                outputs: ["greeting"],
+            }
+        "#).unwrap();
+
+        let graph = SkillLoader::new(dir.path()).load_file(&path).unwrap();
+        let mut registry = SkillRegistry::new(dir.path());
+        let hash = registry.install_graph("greet", graph, true).unwrap();
+
+        let metadata = &registry.get(&hash).unwrap().metadata;
+        assert_eq!(metadata.inputs[0].description, "Name of the person to greet");
+        assert_eq!(metadata.outputs[0].description, "The greeting, ready to send");
+    }
 }
@@ -12,12 +12,24 @@
 
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use serde::Serialize;
 use crate::error::SkillError;
-use crate::runtime::{GraphInterpreter, Graph, Value};
+use crate::runtime::{BuiltinRegistry, GraphInterpreter, Graph, RuntimeConfig, Value};
 use super::graph::{SkillGraph, SkillNode, Op, SafetyProof};
 
+/// Version of the verification rules.
+///
+/// Bump whenever the verifier's checks change so cached verification
+/// results from older versions are discarded.
+pub const VERIFIER_VERSION: u32 = 1;
+
+/// File the verification graph is read from.
+pub const VERIFIER_GRAPH_PATH: &str = "graphs/core/verifier.0";
+
 /// Result of skill verification.
-#[derive(Debug, Clone)]
+///
+/// Serializes to JSON with each warning and error tagged by `kind`.
+#[derive(Debug, Clone, Serialize)]
 pub struct VerificationResult {
     /// Whether the skill passed verification.
     pub safe: bool,
@@ -74,7 +86,8 @@ impl VerificationResult {
 }
 
 /// Verification warnings (non-blocking).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum VerificationWarning {
     /// Graph has many nodes.
     LargeGraph { node_count: usize },
@@ -86,6 +99,8 @@ pub enum VerificationWarning {
     HighMemoryUsage { estimated_bytes: u64 },
     /// Deprecated operation used.
     DeprecatedOp { op: String, replacement: String },
+    /// Several nodes share the id of a declared output.
+    AmbiguousOutput { name: String, node_count: usize },
 }
 
 impl std::fmt::Display for VerificationWarning {
@@ -106,12 +121,16 @@ impl std::fmt::Display for VerificationWarning {
             Self::DeprecatedOp { op, replacement } => {
                 write!(f, "Deprecated operation '{}', use '{}' instead", op, replacement)
             }
+            Self::AmbiguousOutput { name, node_count } => {
+                write!(f, "Output '{}' is produced by {} nodes", name, node_count)
+            }
         }
     }
 }
 
 /// Verification errors (blocking).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum VerificationError {
     /// Infinite loop detected.
     InfiniteLoop { cycle: Vec<String> },
@@ -137,8 +156,14 @@ pub enum VerificationError {
     },
     /// Graph has no outputs.
     NoOutputs,
+    /// A declared output is not produced by any node.
+    UnboundOutput { name: String },
     /// Empty graph.
     EmptyGraph,
+    /// Graph cannot be compiled for the runtime.
+    NotCompilable { reason: String },
+    /// A different skill is already installed under this name.
+    NameConflict { name: String },
 }
 
 impl std::fmt::Display for VerificationError {
@@ -160,10 +185,21 @@ impl std::fmt::Display for VerificationError {
                 write!(f, "Invalid reference from '{}' to '{}'", from_node, to_node)
             }
             Self::MissingPermission { required, for_operation } => {
-                write!(f, "Missing permission '{}' for operation '{}'", required, for_operation)
+                write!(
+                    f,
+                    "Permission '{}' is used by {} but not declared; add it with auto_permissions()",
+                    required, for_operation
+                )
             }
             Self::NoOutputs => write!(f, "Graph has no outputs defined"),
+            Self::UnboundOutput { name } => {
+                write!(f, "Output '{}' is not produced by any node", name)
+            }
             Self::EmptyGraph => write!(f, "Graph is empty"),
+            Self::NotCompilable { reason } => write!(f, "Not runtime-compatible: {}", reason),
+            Self::NameConflict { name } => {
+                write!(f, "A different skill is already installed as '{}'", name)
+            }
         }
     }
 }
@@ -189,8 +225,7 @@ impl SkillVerifier {
     
     /// Load the verification graph.
     fn load_verifier_graph() -> Option<Graph> {
-        let graph_path = "graphs/core/verifier.0";
-        if let Ok(content) = std::fs::read_to_string(graph_path) {
+        if let Ok(content) = std::fs::read_to_string(VERIFIER_GRAPH_PATH) {
             if let Ok(graph) = crate::runtime::parse_graph(&content) {
                 return Some(graph);
             }
@@ -221,6 +256,23 @@ impl SkillVerifier {
             result = result.with_error(VerificationError::NoOutputs);
         }
         
+        // Check each output is produced by exactly one node
+        for output in &graph.outputs {
+            let producers = graph.nodes.iter().filter(|n| n.id() == output).count();
+            match producers {
+                0 => {
+                    result = result.with_error(VerificationError::UnboundOutput { name: output.clone() });
+                }
+                1 => {}
+                node_count => {
+                    result = result.with_warning(VerificationWarning::AmbiguousOutput {
+                        name: output.clone(),
+                        node_count,
+                    });
+                }
+            }
+        }
+
         // Check graph size
         if graph.node_count() > 1000 {
             result = result.with_warning(VerificationWarning::LargeGraph {
@@ -233,11 +285,11 @@ impl SkillVerifier {
             result = result.with_error(VerificationError::InfiniteLoop { cycle });
         }
         
-        // Check node safety
-        for node in &graph.nodes {
-            if let Some(error) = Self::check_node_safety(node, &graph.permissions) {
-                result = result.with_error(error);
-            }
+        // Check node safety, including nested Map/Filter/Reduce bodies
+        let mut unsafe_nodes = Vec::new();
+        Self::check_nodes_safety(graph, &graph.permissions, &mut unsafe_nodes);
+        for error in unsafe_nodes {
+            result = result.with_error(error);
         }
         
         // Validate references
@@ -251,6 +303,16 @@ impl SkillVerifier {
                     });
                 }
             }
+            if let SkillNode::Operation { op, .. } = node {
+                for target in op.branch_targets() {
+                    if !node_ids.contains(target) {
+                        result = result.with_error(VerificationError::InvalidReference {
+                            from_node: node.id().to_string(),
+                            to_node: target.to_string(),
+                        });
+                    }
+                }
+            }
         }
         
         // Check external calls
@@ -344,41 +406,41 @@ impl SkillVerifier {
         None
     }
 
+    /// Check every node of `graph` and of its nested bodies against the
+    /// permissions declared by the top-level skill.
+    fn check_nodes_safety(graph: &SkillGraph, declared_permissions: &[String], errors: &mut Vec<VerificationError>) {
+        for node in &graph.nodes {
+            errors.extend(Self::check_node_safety(node, declared_permissions));
+            if let SkillNode::Operation {
+                op: Op::Map { body: inner } | Op::Filter { predicate: inner } | Op::Reduce { body: inner, .. },
+                ..
+            } = node
+            {
+                Self::check_nodes_safety(inner, declared_permissions, errors);
+            }
+        }
+    }
+
     /// Check if a node operation is safe.
     fn check_node_safety(
         node: &SkillNode,
         declared_permissions: &[String],
     ) -> Option<VerificationError> {
-        match node {
-            SkillNode::External { uri, .. } => {
-                // Only require network permission for actual HTTP(S) calls
-                // Internal protocols (calendar://, input://, etc.) don't need it
-                let is_network_call = uri.starts_with("http://") || uri.starts_with("https://");
-                
-                if is_network_call && !declared_permissions.contains(&"network".to_string()) {
-                    return Some(VerificationError::MissingPermission {
-                        required: "network".to_string(),
-                        for_operation: format!("external call to {}", uri),
-                    });
-                }
-                None
-            }
-            SkillNode::Operation { id, op, .. } => {
-                match op {
-                    Op::HttpGet | Op::HttpPost => {
-                        if !declared_permissions.contains(&"network".to_string()) {
-                            return Some(VerificationError::MissingPermission {
-                                required: "network".to_string(),
-                                for_operation: format!("HTTP operation at {}", id),
-                            });
-                        }
-                    }
-                    _ => {}
-                }
-                None
-            }
-            _ => None,
+        // Only actual HTTP(S) calls imply a permission; internal protocols
+        // (calendar://, input://, etc.) don't need one
+        let required = node.required_permission()?;
+        if declared_permissions.iter().any(|p| p == required) {
+            return None;
         }
+
+        let for_operation = match node {
+            SkillNode::External { uri, .. } => format!("external call to {}", uri),
+            other => format!("HTTP operation at {}", other.id()),
+        };
+        Some(VerificationError::MissingPermission {
+            required: required.to_string(),
+            for_operation,
+        })
     }
 
     /// Build a safety proof for a verified graph.
@@ -396,9 +458,29 @@ impl SkillVerifier {
     }
 
     /// Estimate maximum execution steps.
+    ///
+    /// Uses the runtime's step cost model: each operation's base cost, and
+    /// the external call cost for external and HTTP nodes.
     fn estimate_max_steps(graph: &SkillGraph) -> u64 {
-        // Base estimate: 10 steps per node
-        let base = (graph.node_count() as u64) * 10;
+        let builtins = BuiltinRegistry::new();
+        let external_cost = RuntimeConfig::default().external_step_cost;
+        let cost: u64 = graph
+            .nodes
+            .iter()
+            .map(|node| match node {
+                SkillNode::External { .. }
+                | SkillNode::Operation { op: Op::HttpGet | Op::HttpPost, .. } => external_cost,
+                SkillNode::Operation { op, .. } => op
+                    .runtime_name()
+                    .ok()
+                    .and_then(|name| builtins.get(name))
+                    .map_or(1, |builtin| builtin.step_cost(&[])),
+                _ => 1,
+            })
+            .sum();
+
+        // Base estimate: 10x the base cost, leaving room for input-dependent costs
+        let base = cost.saturating_mul(10);
         
         // Add extra for operations that may loop
         let mut multiplier = 1u64;
@@ -436,20 +518,14 @@ impl SkillVerifier {
         // 2. All operations are bounded
         
         for node in &graph.nodes {
-            if let SkillNode::Operation { op, .. } = node {
-                match op {
-                    // These operations may not halt without bounds
-                    Op::Map { body } | Op::Filter { predicate: body } => {
-                        // Recursively check sub-graphs
-                        if !Self::prove_halting(body) {
-                            return false;
-                        }
-                    }
-                    Op::Reduce { .. } => {
-                        // Reduce on unbounded input may not halt
-                        // For now, assume bounded input
-                    }
-                    _ => {}
+            // Loop operations halt if their bodies do; inputs are assumed bounded
+            if let SkillNode::Operation {
+                op: Op::Map { body } | Op::Filter { predicate: body } | Op::Reduce { body, .. },
+                ..
+            } = node
+            {
+                if !Self::prove_halting(body) {
+                    return false;
                 }
             }
         }
@@ -484,6 +560,53 @@ mod tests {
         assert!(result.errors.is_empty());
     }
 
+    #[test]
+    fn test_estimate_uses_step_costs() {
+        let local = SkillGraph::builder("local")
+            .add_input("x", "string")
+            .add_operation("y", Op::Identity, vec!["x"])
+            .output("y")
+            .build();
+        let remote = SkillGraph::builder("remote")
+            .add_input("x", "string")
+            .add_operation("y", Op::HttpGet, vec!["x"])
+            .output("y")
+            .build();
+
+        assert_eq!(SkillVerifier::estimate_max_steps(&local), 20);
+        assert_eq!(SkillVerifier::estimate_max_steps(&remote), 110);
+    }
+
+    #[test]
+    fn test_result_serializes_tagged() {
+        let result = VerificationResult::pass()
+            .with_warning(VerificationWarning::ExternalCall { uri: "https://example.com".to_string() })
+            .with_error(VerificationError::MissingPermission {
+                required: "network".to_string(),
+                for_operation: "fetch".to_string(),
+            });
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["safe"], false);
+        assert_eq!(json["proof"], serde_json::Value::Null);
+        assert_eq!(
+            json["warnings"],
+            serde_json::json!([{ "kind": "external_call", "uri": "https://example.com" }])
+        );
+        assert_eq!(
+            json["errors"],
+            serde_json::json!([{
+                "kind": "missing_permission",
+                "required": "network",
+                "for_operation": "fetch"
+            }])
+        );
+        assert_eq!(
+            serde_json::to_value(VerificationError::NoOutputs).unwrap(),
+            serde_json::json!({ "kind": "no_outputs" })
+        );
+    }
+
     #[test]
     fn test_verify_empty_graph() {
         let graph = SkillGraph::builder("empty").build();
@@ -521,6 +644,33 @@ mod tests {
         assert!(result.safe);
     }
 
+    #[test]
+    fn test_verify_nested_http_needs_permission() {
+        let body = SkillGraph::builder("fetch_each")
+            .add_input("item", "string")
+            .add_operation("fetch", Op::HttpGet, vec!["item"])
+            .output("fetch")
+            .build();
+        let graph = |permission: Option<&str>| {
+            let builder = SkillGraph::builder("fetch_all")
+                .add_input("urls", "array")
+                .add_operation("pages", Op::Map { body: Box::new(body.clone()) }, vec!["urls"])
+                .output("pages");
+            match permission {
+                Some(permission) => builder.permission(permission).build(),
+                None => builder.build(),
+            }
+        };
+
+        let result = SkillVerifier::verify(&graph(None)).unwrap();
+        assert!(!result.safe);
+        assert!(result.errors.iter().any(|e| {
+            matches!(e, VerificationError::MissingPermission { required, .. } if required == "network")
+        }));
+
+        assert!(SkillVerifier::verify(&graph(Some("network"))).unwrap().safe);
+    }
+
     #[test]
     fn test_invalid_reference() {
         let graph = SkillGraph::builder("invalid")
@@ -547,4 +697,27 @@ mod tests {
         assert!(SkillVerifier::quick_check(&good_graph));
         assert!(!SkillVerifier::quick_check(&empty_graph));
     }
+
+    #[test]
+    fn test_verify_output_bindings() {
+        let orphaned = SkillGraph::builder("renamed")
+            .add_input("message", "string")
+            .add_operation("reply", Op::Identity, vec!["message"])
+            .output("response")
+            .build();
+        let result = SkillVerifier::verify(&orphaned).unwrap();
+        assert!(!result.safe);
+        assert!(result.errors.iter().any(|e| {
+            matches!(e, VerificationError::UnboundOutput { name } if name == "response")
+        }));
+
+        let bound = SkillGraph::builder("bound")
+            .add_input("message", "string")
+            .add_operation("reply", Op::Identity, vec!["message"])
+            .output("reply")
+            .build();
+        let result = SkillVerifier::verify(&bound).unwrap();
+        assert!(result.safe);
+        assert!(!result.warnings.iter().any(|w| matches!(w, VerificationWarning::AmbiguousOutput { .. })));
+    }
 }
@@ -6,11 +6,18 @@
 use serde::{Deserialize, Serialize};
 use sha2::{Sha256, Digest};
 use std::fmt;
+use subtle::ConstantTimeEq;
+
+use crate::error::ProofError;
 
 /// Unique identifier based on content hash (SHA-256).
 ///
 /// ContentHash provides content-addressed identification: the same content
 /// always produces the same hash, enabling deterministic behavior verification.
+///
+/// The derived `==` short-circuits and is meant for lookups (maps, caches,
+/// registries). Checks an attacker could probe by timing, such as proof trace
+/// roots, provenance chain links and session authentication, use [`ContentHash::ct_eq`].
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
 pub struct ContentHash(pub [u8; 32]);
 
@@ -57,6 +64,36 @@ impl ContentHash {
     pub fn as_bytes(&self) -> &[u8; 32] {
         &self.0
     }
+
+    /// Compare in constant time, for security-sensitive checks.
+    pub fn ct_eq(&self, other: &Self) -> bool {
+        self.0.ct_eq(&other.0).into()
+    }
+
+    /// Compute the Merkle root of a list of hashes.
+    ///
+    /// Follows RFC 6962: leaves hash as `H(0x00 || leaf)`, internal nodes as
+    /// `H(0x01 || left || right)`, and a list splits at the largest power of
+    /// two below its length. An empty list yields the zero hash.
+    pub fn merkle_root(hashes: &[ContentHash]) -> Self {
+        match hashes {
+            [] => Self::zero(),
+            [leaf] => {
+                let mut hasher = Sha256::new();
+                hasher.update([0x00]);
+                hasher.update(leaf.0);
+                Self(hasher.finalize().into())
+            }
+            _ => {
+                let split = 1 << (usize::BITS - 1 - (hashes.len() - 1).leading_zeros());
+                let mut hasher = Sha256::new();
+                hasher.update([0x01]);
+                hasher.update(Self::merkle_root(&hashes[..split]).0);
+                hasher.update(Self::merkle_root(&hashes[split..]).0);
+                Self(hasher.finalize().into())
+            }
+        }
+    }
 }
 
 impl fmt::Debug for ContentHash {
@@ -150,10 +187,43 @@ pub struct IncomingMessage {
     /// Unix timestamp in milliseconds.
     pub timestamp: u64,
     
+    /// Files sent with the message.
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    
     /// Channel-specific metadata.
     pub metadata: serde_json::Value,
 }
 
+/// A file attached to an incoming message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+    /// File name as sent.
+    pub file_name: String,
+    
+    /// MIME type reported by the channel (e.g. "image/png").
+    pub mime_type: String,
+    
+    /// Size in bytes.
+    pub size_bytes: u64,
+    
+    /// Where the channel serves the file, when known.
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+impl Attachment {
+    /// Create an attachment without a download URL.
+    pub fn new(file_name: &str, mime_type: &str, size_bytes: u64) -> Self {
+        Self {
+            file_name: file_name.to_string(),
+            mime_type: mime_type.to_string(),
+            size_bytes,
+            url: None,
+        }
+    }
+}
+
 impl IncomingMessage {
     /// Create a new incoming message with auto-generated ID.
     pub fn new(channel_id: &str, sender_id: &str, content: &str) -> Self {
@@ -166,10 +236,17 @@ impl IncomingMessage {
             sender_id: sender_id.to_string(),
             content: content.to_string(),
             timestamp,
+            attachments: Vec::new(),
             metadata: serde_json::Value::Null,
         }
     }
 
+    /// Attach a file to the message.
+    pub fn with_attachment(mut self, attachment: Attachment) -> Self {
+        self.attachments.push(attachment);
+        self
+    }
+
     /// Add metadata to the message.
     pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
         self.metadata = metadata;
@@ -177,6 +254,35 @@ impl IncomingMessage {
     }
 }
 
+/// Canonical identity of a user on a channel.
+///
+/// `id` is the sender id after normalization, so the same user maps to the
+/// same `UserRef` whatever format the channel reported.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct UserRef {
+    /// Channel the user is on.
+    pub channel: String,
+    
+    /// Normalized user id.
+    pub id: String,
+}
+
+impl UserRef {
+    /// Create a user reference.
+    pub fn new(channel: &str, id: &str) -> Self {
+        Self {
+            channel: channel.to_string(),
+            id: id.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for UserRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.channel, self.id)
+    }
+}
+
 /// Outgoing message to any channel.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OutgoingMessage {
@@ -191,6 +297,120 @@ pub struct OutgoingMessage {
     
     /// Optional: message this is replying to.
     pub reply_to: Option<ContentHash>,
+    
+    /// Optional: platform id of an earlier message this one replaces.
+    #[serde(default)]
+    pub edit_of: Option<String>,
+
+    /// Files sent with the message.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<OutgoingAttachment>,
+}
+
+/// A file attached to an outgoing message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutgoingAttachment {
+    /// File name shown to the recipient.
+    pub file_name: String,
+
+    /// MIME type of the file (e.g. "application/pdf").
+    pub mime_type: String,
+
+    /// File contents, or where the channel can fetch them.
+    pub source: AttachmentSource,
+
+    /// Hash of the file's bytes. It is part of the signed action, and
+    /// fetched contents that don't match it are refused.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<ContentHash>,
+}
+
+/// Largest attachment [`OutgoingAttachment::fetch`] downloads.
+pub const MAX_ATTACHMENT_BYTES: usize = 25 * 1024 * 1024;
+
+/// Time limit of an attachment download.
+const ATTACHMENT_FETCH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Contents of an outgoing attachment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttachmentSource {
+    /// The file's bytes (hex-encoded when serialized).
+    Bytes(#[serde(with = "hex_serde")] Vec<u8>),
+    /// A URL serving the file.
+    Url(String),
+}
+
+impl OutgoingAttachment {
+    /// Attach the given bytes.
+    pub fn bytes(file_name: &str, mime_type: &str, bytes: Vec<u8>) -> Self {
+        Self {
+            file_name: file_name.to_string(),
+            mime_type: mime_type.to_string(),
+            content_hash: Some(ContentHash::from_bytes(&bytes)),
+            source: AttachmentSource::Bytes(bytes),
+        }
+    }
+
+    /// Attach the file served at `url`.
+    pub fn url(file_name: &str, mime_type: &str, url: &str) -> Self {
+        Self {
+            file_name: file_name.to_string(),
+            mime_type: mime_type.to_string(),
+            source: AttachmentSource::Url(url.to_string()),
+            content_hash: None,
+        }
+    }
+
+    /// Require the file's bytes to hash to `hash`.
+    pub fn with_content_hash(mut self, hash: ContentHash) -> Self {
+        self.content_hash = Some(hash);
+        self
+    }
+
+    /// The file's bytes, downloading them if only a URL is known.
+    ///
+    /// Downloads only reach public addresses, and are limited to
+    /// [`MAX_ATTACHMENT_BYTES`] and 30 seconds. Bytes not matching
+    /// `content_hash` are refused.
+    pub async fn fetch(&self) -> Result<Vec<u8>, crate::error::ChannelError> {
+        use crate::error::ChannelError;
+        use crate::runtime::http::{check_public_url, public_client};
+
+        let bytes = match &self.source {
+            AttachmentSource::Bytes(bytes) => bytes.clone(),
+            AttachmentSource::Url(url) => {
+                let parsed = reqwest::Url::parse(url).map_err(|e| ChannelError::SendFailed(format!("{}: {}", url, e)))?;
+                check_public_url(&parsed).map_err(ChannelError::SendFailed)?;
+                let mut response = public_client(ATTACHMENT_FETCH_TIMEOUT, check_public_url)
+                    .get(parsed)
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .map_err(|e| ChannelError::SendFailed(e.to_string()))?;
+                let too_large = || ChannelError::SendFailed(format!(
+                    "attachment '{}' exceeds {} bytes", self.file_name, MAX_ATTACHMENT_BYTES
+                ));
+                if response.content_length().is_some_and(|len| len > MAX_ATTACHMENT_BYTES as u64) {
+                    return Err(too_large());
+                }
+                let mut bytes = Vec::new();
+                while let Some(chunk) = response.chunk().await.map_err(|e| ChannelError::SendFailed(e.to_string()))? {
+                    if bytes.len() + chunk.len() > MAX_ATTACHMENT_BYTES {
+                        return Err(too_large());
+                    }
+                    bytes.extend_from_slice(&chunk);
+                }
+                bytes
+            }
+        };
+        match self.content_hash {
+            Some(expected) if !ContentHash::from_bytes(&bytes).ct_eq(&expected) => Err(ChannelError::SendFailed(
+                format!("attachment '{}' does not match its content hash", self.file_name),
+            )),
+            _ => Ok(bytes),
+        }
+    }
 }
 
 impl OutgoingMessage {
@@ -201,14 +421,33 @@ impl OutgoingMessage {
             recipient_id: recipient_id.to_string(),
             content: content.to_string(),
             reply_to: None,
+            edit_of: None,
+            attachments: Vec::new(),
         }
     }
 
+    /// Attach a file.
+    pub fn with_attachment(mut self, attachment: OutgoingAttachment) -> Self {
+        self.attachments.push(attachment);
+        self
+    }
+
     /// Set the message this is replying to.
     pub fn reply_to(mut self, hash: ContentHash) -> Self {
         self.reply_to = Some(hash);
         self
     }
+
+    /// Deliver as an edit of an earlier message with this platform id.
+    pub fn edit_of(mut self, message_id: &str) -> Self {
+        self.edit_of = Some(message_id.to_string());
+        self
+    }
+
+    /// Hash identifying this message in delivery receipts.
+    pub fn receipt_id(&self) -> ContentHash {
+        ContentHash::from_string(&format!("{}:{}:{}", self.channel_id, self.recipient_id, self.content))
+    }
 }
 
 /// Actions the assistant can take.
@@ -233,6 +472,23 @@ pub enum Action {
     NoOp {
         reason: String,
     },
+
+    /// Route the sender's next messages straight to a skill, for at most
+    /// `max_turns` messages.
+    ClaimConversation {
+        skill_hash: ContentHash,
+        max_turns: u32,
+    },
+
+    /// End a conversation claim, returning to normal routing.
+    ReleaseConversation,
+
+    /// Action refused by permission or trust gating. `notice` is the
+    /// message delivered to the sender in its place.
+    Denied {
+        reason: String,
+        notice: OutgoingMessage,
+    },
 }
 
 impl Action {
@@ -248,8 +504,22 @@ impl Action {
             Action::ExecuteSkill { .. } => "ExecuteSkill",
             Action::UpdateSession { .. } => "UpdateSession",
             Action::NoOp { .. } => "NoOp",
+            Action::ClaimConversation { .. } => "ClaimConversation",
+            Action::ReleaseConversation => "ReleaseConversation",
+            Action::Denied { .. } => "Denied",
         }
     }
+
+    /// Hash of `nonce` followed by the serialized action, which is what a
+    /// PCA signature covers.
+    ///
+    /// The nonce keeps short or predictable actions from being recovered
+    /// from the hash by guessing.
+    pub fn salted_hash(&self, nonce: &[u8]) -> ContentHash {
+        let mut bytes = nonce.to_vec();
+        bytes.extend(serde_json::to_vec(self).unwrap_or_default());
+        ContentHash::from_bytes(&bytes)
+    }
 }
 
 /// Confidence-driven routing lanes used by gateway execution policy.
@@ -289,61 +559,271 @@ pub struct EffectReceipt {
     pub timestamp: u64,
 }
 
+/// One executed node in a decision trace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceNode {
+    /// ID of the node in its graph.
+    pub node_id: String,
+    /// Operation name, or the node kind for non-operation nodes.
+    pub op: String,
+    /// Hash of the node's input values, in input order.
+    pub input_hash: ContentHash,
+    /// Hash of the value the node produced.
+    pub output_hash: ContentHash,
+}
+
+impl TraceNode {
+    /// Hash committing to every field, as recorded in a PCA's decision trace.
+    pub fn hash(&self) -> ContentHash {
+        let mut bytes = Vec::with_capacity(self.node_id.len() + self.op.len() + 66);
+        bytes.extend_from_slice(self.node_id.as_bytes());
+        bytes.push(0);
+        bytes.extend_from_slice(self.op.as_bytes());
+        bytes.push(0);
+        bytes.extend_from_slice(&self.input_hash.0);
+        bytes.extend_from_slice(&self.output_hash.0);
+        ContentHash::from_bytes(&bytes)
+    }
+}
+
+/// Signature scheme used to sign a Proof-Carrying Action.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SigScheme {
+    /// Ed25519 (default).
+    #[default]
+    Ed25519,
+}
+
+impl SigScheme {
+    /// Get the scheme name.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SigScheme::Ed25519 => "ed25519",
+        }
+    }
+}
+
+impl fmt::Display for SigScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Current version of the serialized [`ProofCarryingAction`] format.
+///
+/// Version 2 changed the signed message and the trace Merkle root, and
+/// version 3 salts the signed action hash with `action_nonce`, and version 4
+/// signs `sig_scheme`, so PCAs of earlier versions no longer verify.
+pub const PCA_SCHEMA_VERSION: u32 = 4;
+
+/// Files written before the version was recorded are version 1.
+fn default_pca_schema_version() -> u32 {
+    1
+}
+
 /// Proof-Carrying Action - the core innovation of 0-openclaw.
 ///
 /// Every action includes cryptographic proof of the decision path,
 /// allowing verification without trusting the runtime.
+///
+/// Hashes serialize as hex strings; byte arrays written by earlier versions
+/// are still accepted. Effect receipts keep their encoding, since the
+/// signature covers it.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProofCarryingAction {
+    /// Version of the serialized format.
+    #[serde(default = "default_pca_schema_version")]
+    pub schema_version: u32,
+
     /// The action to perform.
     pub action: Action,
+
+    /// Random salt of the signed action hash (see [`Action::salted_hash`]).
+    /// Left out of [`RedactedPca`].
+    #[serde(default, with = "hex_serde")]
+    pub action_nonce: Vec<u8>,
     
     /// Hash of the session context.
+    #[serde(with = "hash_serde")]
     pub session_hash: ContentHash,
     
     /// Hash of the input that triggered this action.
+    #[serde(with = "hash_serde")]
     pub input_hash: ContentHash,
     
     /// Decision trace hashes (routing + skill decision graph execution).
+    #[serde(with = "hash_serde::vec")]
     pub decision_trace: Vec<ContentHash>,
     
+    /// Whether `decision_trace` was truncated to the configured maximum.
+    #[serde(default)]
+    pub truncated: bool,
+    
+    /// Merkle root of the full decision trace (set when truncated).
+    #[serde(default, with = "hash_serde::option")]
+    pub trace_root: Option<ContentHash>,
+    
     /// Effect trace receipts produced by side effects.
     pub effect_trace: Vec<EffectReceipt>,
     
     /// Confidence score for this action.
     pub confidence: Confidence,
     
-    /// Ed25519 signature over all fields (hex-encoded for serde compatibility).
-    #[serde(with = "signature_serde")]
-    pub signature: [u8; 64],
+    /// Scheme used to produce `signature` (PCAs without it are Ed25519).
+    #[serde(default)]
+    pub sig_scheme: SigScheme,
+    
+    /// Signature over all fields (hex-encoded for serde compatibility).
+    #[serde(with = "hex_serde")]
+    pub signature: Vec<u8>,
+    
+    /// Set when proof generation is disabled and the PCA was never signed.
+    #[serde(default)]
+    pub unsigned: bool,
     
     /// Unix timestamp in milliseconds.
     pub timestamp: u64,
 }
 
-/// Custom serde module for [u8; 64] signature.
-mod signature_serde {
+/// A Proof-Carrying Action with its action replaced by the action's hash.
+///
+/// Proves an action occurred without revealing its content: the signature
+/// commits to the action hash, so the original signature still verifies.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactedPca {
+    /// Type of the redacted action (see [`Action::action_type`]).
+    pub action_type: String,
+
+    /// Salted hash of the redacted action; the salt stays with the original.
+    pub action_hash: ContentHash,
+
+    /// Hash of the session context.
+    pub session_hash: ContentHash,
+
+    /// Hash of the input that triggered the action.
+    pub input_hash: ContentHash,
+
+    /// Decision trace hashes.
+    pub decision_trace: Vec<ContentHash>,
+
+    /// Whether `decision_trace` was truncated.
+    #[serde(default)]
+    pub truncated: bool,
+
+    /// Merkle root of the full decision trace (set when truncated).
+    #[serde(default)]
+    pub trace_root: Option<ContentHash>,
+
+    /// Effect trace receipts.
+    pub effect_trace: Vec<EffectReceipt>,
+
+    /// Confidence score for the action.
+    pub confidence: Confidence,
+
+    /// Scheme used to produce `signature`.
+    #[serde(default)]
+    pub sig_scheme: SigScheme,
+
+    /// Signature of the original PCA.
+    #[serde(with = "hex_serde")]
+    pub signature: Vec<u8>,
+
+    /// Unix timestamp in milliseconds.
+    pub timestamp: u64,
+}
+
+/// Custom serde module for hex-encoded bytes.
+mod hex_serde {
     use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-    pub fn serialize<S>(bytes: &[u8; 64], serializer: S) -> Result<S::Ok, S::Error>
+    pub fn serialize<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
         hex::encode(bytes).serialize(serializer)
     }
 
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<[u8; 64], D::Error>
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
     where
         D: Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
-        let bytes = hex::decode(&s).map_err(serde::de::Error::custom)?;
-        if bytes.len() != 64 {
-            return Err(serde::de::Error::custom("signature must be 64 bytes"));
+        hex::decode(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Custom serde module for hex-encoded content hashes.
+///
+/// Also accepts the 32-element byte arrays of the derived encoding.
+mod hash_serde {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::ContentHash;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Encoded {
+        Hex(String),
+        Bytes([u8; 32]),
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(transparent)]
+    struct Hex(#[serde(with = "self")] ContentHash);
+
+    pub fn serialize<S>(hash: &ContentHash, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        hash.to_hex().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<ContentHash, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Encoded::deserialize(deserializer)? {
+            Encoded::Hex(s) => ContentHash::from_hex(&s).map_err(serde::de::Error::custom),
+            Encoded::Bytes(bytes) => Ok(ContentHash(bytes)),
+        }
+    }
+
+    pub mod vec {
+        use super::*;
+
+        pub fn serialize<S>(hashes: &[ContentHash], serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.collect_seq(hashes.iter().map(|hash| Hex(*hash)))
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<ContentHash>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let hashes = Vec::<Hex>::deserialize(deserializer)?;
+            Ok(hashes.into_iter().map(|Hex(hash)| hash).collect())
+        }
+    }
+
+    pub mod option {
+        use super::*;
+
+        pub fn serialize<S>(hash: &Option<ContentHash>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            hash.map(Hex).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<ContentHash>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            Ok(Option::<Hex>::deserialize(deserializer)?.map(|Hex(hash)| hash))
         }
-        let mut arr = [0u8; 64];
-        arr.copy_from_slice(&bytes);
-        Ok(arr)
     }
 }
 
@@ -351,22 +831,82 @@ impl ProofCarryingAction {
     /// Create a pending PCA (used before signing).
     pub fn pending() -> Self {
         Self {
+            schema_version: PCA_SCHEMA_VERSION,
             action: Action::NoOp { reason: "pending".to_string() },
+            action_nonce: Self::new_action_nonce(),
             session_hash: ContentHash::zero(),
             input_hash: ContentHash::zero(),
             decision_trace: Vec::new(),
+            truncated: false,
+            trace_root: None,
             effect_trace: Vec::new(),
             confidence: Confidence::none(),
-            signature: [0u8; 64],
+            sig_scheme: SigScheme::default(),
+            signature: Vec::new(),
+            unsigned: false,
             timestamp: chrono::Utc::now().timestamp_millis() as u64,
         }
     }
 
+    /// Generate a random salt for the action hash.
+    pub fn new_action_nonce() -> Vec<u8> {
+        rand::random::<[u8; 32]>().to_vec()
+    }
+
+    /// Serialize to pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String, ProofError> {
+        serde_json::to_string_pretty(self).map_err(|e| ProofError::InvalidFormat(e.to_string()))
+    }
+
+    /// Parse from JSON, rejecting schema versions other than this build's.
+    pub fn from_json(json: &str) -> Result<Self, ProofError> {
+        let pca: Self = serde_json::from_str(json).map_err(|e| ProofError::InvalidFormat(e.to_string()))?;
+        if pca.schema_version > PCA_SCHEMA_VERSION {
+            return Err(ProofError::InvalidFormat(format!(
+                "Unsupported schema version {} (supported up to {})",
+                pca.schema_version, PCA_SCHEMA_VERSION
+            )));
+        }
+        if pca.schema_version < PCA_SCHEMA_VERSION {
+            return Err(ProofError::InvalidFormat(format!(
+                "Schema version {} predates the current signing format (version {}); the PCA must be re-issued",
+                pca.schema_version, PCA_SCHEMA_VERSION
+            )));
+        }
+        Ok(pca)
+    }
+
+    /// Write to a JSON file.
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<(), ProofError> {
+        std::fs::write(path, self.to_json()?).map_err(|e| ProofError::InvalidFormat(e.to_string()))
+    }
+
+    /// Read from a JSON file written by [`Self::save`].
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, ProofError> {
+        let json = std::fs::read_to_string(path).map_err(|e| ProofError::InvalidFormat(e.to_string()))?;
+        Self::from_json(&json)
+    }
+
     /// Get the number of nodes in the decision trace.
     pub fn decision_trace_length(&self) -> usize {
         self.decision_trace.len()
     }
 
+    /// Check that a full decision trace matches this PCA's trace root.
+    ///
+    /// For untruncated PCAs the full trace must equal `decision_trace`.
+    pub fn matches_full_trace(&self, full_trace: &[ContentHash]) -> bool {
+        match self.trace_root {
+            Some(root) => ContentHash::merkle_root(full_trace).ct_eq(&root),
+            None => {
+                // One comparison over all the bytes, so timing does not reveal
+                // where the traces first differ
+                let bytes = |trace: &[ContentHash]| trace.iter().flat_map(|hash| hash.0).collect::<Vec<u8>>();
+                bytes(&self.decision_trace).ct_eq(&bytes(full_trace)).into()
+            }
+        }
+    }
+
     /// Get the number of side-effect receipts.
     pub fn effect_count(&self) -> usize {
         self.effect_trace.len()
@@ -376,6 +916,24 @@ impl ProofCarryingAction {
     pub fn is_signed(&self) -> bool {
         self.signature.iter().any(|&b| b != 0)
     }
+
+    /// Replace the action with its hash for sharing without its content.
+    pub fn redact(&self) -> RedactedPca {
+        RedactedPca {
+            action_type: self.action.action_type().to_string(),
+            action_hash: self.action.salted_hash(&self.action_nonce),
+            session_hash: self.session_hash,
+            input_hash: self.input_hash,
+            decision_trace: self.decision_trace.clone(),
+            truncated: self.truncated,
+            trace_root: self.trace_root,
+            effect_trace: self.effect_trace.clone(),
+            confidence: self.confidence,
+            sig_scheme: self.sig_scheme,
+            signature: self.signature.clone(),
+            timestamp: self.timestamp,
+        }
+    }
 }
 
 impl fmt::Display for ProofCarryingAction {
@@ -406,6 +964,51 @@ mod tests {
         assert_ne!(hash1, hash3);
     }
 
+    #[test]
+    fn test_ct_eq_agrees_with_eq() {
+        let hashes = [
+            ContentHash::from_string("hello"),
+            ContentHash::from_string("hello"),
+            ContentHash::from_string("world"),
+            ContentHash::zero(),
+        ];
+        for a in &hashes {
+            for b in &hashes {
+                assert_eq!(a.ct_eq(b), a == b);
+            }
+        }
+
+        let trace: Vec<ContentHash> = ["a", "b", "c"].iter().map(|s| ContentHash::from_string(s)).collect();
+        let mut pca = ProofCarryingAction::pending();
+        pca.decision_trace = trace.clone();
+        pca.trace_root = None;
+        assert!(pca.matches_full_trace(&trace));
+        assert!(!pca.matches_full_trace(&trace[..2]));
+        assert!(!pca.matches_full_trace(&[trace[0], trace[1], trace[0]]));
+    }
+
+    #[test]
+    fn test_merkle_root() {
+        let a = ContentHash::from_string("a");
+        let b = ContentHash::from_string("b");
+        let c = ContentHash::from_string("c");
+
+        assert_eq!(ContentHash::merkle_root(&[]), ContentHash::zero());
+        assert_eq!(ContentHash::merkle_root(&[a, b, c]), ContentHash::merkle_root(&[a, b, c]));
+        assert_ne!(ContentHash::merkle_root(&[a, b, c]), ContentHash::merkle_root(&[b, a, c]));
+
+        // Leaves and internal nodes are domain-separated
+        assert_ne!(ContentHash::merkle_root(&[a]), a);
+        let ab = ContentHash::merkle_root(&[a, b]);
+        assert_ne!(ContentHash::merkle_root(&[ab]), ab);
+        assert_ne!(ContentHash::merkle_root(&[a, b, c]), ContentHash::merkle_root(&[ab, c]));
+        // An odd last node is not promoted as-is
+        assert_ne!(
+            ContentHash::merkle_root(&[a, b, c]),
+            ContentHash::merkle_root(&[a, b, ContentHash::merkle_root(&[c])])
+        );
+    }
+
     #[test]
     fn test_content_hash_hex() {
         let hash = ContentHash::from_string("test");
@@ -466,4 +1069,42 @@ mod tests {
         let noop = Action::NoOp { reason: "test".to_string() };
         assert!(noop.is_noop());
     }
+
+    #[test]
+    fn test_outgoing_attachment_serialization() {
+        let message = OutgoingMessage::new("telegram", "42", "Your report")
+            .with_attachment(OutgoingAttachment::bytes("report.pdf", "application/pdf", vec![0x25, 0x50, 0x44, 0x46]))
+            .with_attachment(OutgoingAttachment::url("chart.png", "image/png", "https://example.com/chart.png"));
+
+        let json = serde_json::to_value(&message).unwrap();
+        assert_eq!(json["attachments"][0]["source"]["bytes"], "25504446");
+        let decoded: OutgoingMessage = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.attachments, message.attachments);
+
+        // Messages without attachments serialize as before
+        let plain = serde_json::to_value(OutgoingMessage::new("telegram", "42", "hi")).unwrap();
+        assert!(plain.get("attachments").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_attachment_fetch_checks_hash_and_address() {
+        let bytes = OutgoingAttachment::bytes("notes.txt", "text/plain", b"hi".to_vec());
+        assert_eq!(bytes.fetch().await.unwrap(), b"hi");
+        let tampered = bytes.clone().with_content_hash(ContentHash::from_bytes(b"other"));
+        assert!(tampered.fetch().await.is_err());
+
+        for url in ["http://127.0.0.1:9/file", "http://169.254.169.254/latest", "http://localhost:9/file", "file:///etc/passwd"] {
+            let err = OutgoingAttachment::url("file", "text/plain", url).fetch().await.unwrap_err();
+            assert!(matches!(err, crate::error::ChannelError::SendFailed(_)), "{}: {}", url, err);
+        }
+
+        // The signed action hash covers the attachment's content hash
+        let message = |hash: &[u8]| {
+            Action::SendMessage(OutgoingMessage::new("slack", "C1", "chart").with_attachment(
+                OutgoingAttachment::url("chart.png", "image/png", "https://example.com/chart.png")
+                    .with_content_hash(ContentHash::from_bytes(hash)),
+            ))
+        };
+        assert_ne!(message(b"a").salted_hash(b"nonce"), message(b"b").salted_hash(b"nonce"));
+    }
 }